@@ -0,0 +1,63 @@
+//! Times `FlatTableData::from_rows` on generated rows.
+//!
+//! Run with `cargo bench --bench flat` (optionally `-- ROWS`).
+
+use jlcat::core::{ArrayExpansion, FlatConfig, FlatTableData};
+use serde_json::{json, Value};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ROWS: usize = 100_000;
+const ITERATIONS: u32 = 5;
+
+fn rows(n: usize) -> Vec<Value> {
+    (0..n)
+        .map(|i| {
+            let mut row = json!({
+                "id": i,
+                "user": {"name": format!("user{}", i), "profile": {"age": i % 90, "city": "Tokyo"}},
+                "tags": ["a", "b", "c"],
+                "score": i as f64 / 3.0,
+            });
+            // Sparse and conflicting keys exercise late columns and placeholders
+            if i % 7 == 0 {
+                row["extra"] = json!({"note": "sparse"});
+            }
+            if i % 11 == 0 {
+                row["user"] = json!("anonymous");
+            }
+            row
+        })
+        .collect()
+}
+
+fn time(label: &str, rows: &[Value], config: &FlatConfig) {
+    let mut best = Duration::MAX;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        black_box(FlatTableData::from_rows(black_box(rows), config.clone()));
+        best = best.min(start.elapsed());
+    }
+    println!(
+        "{:<16} {:>8} rows  best of {}: {:?}",
+        label,
+        rows.len(),
+        ITERATIONS,
+        best
+    );
+}
+
+fn main() {
+    let n = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_ROWS);
+    let rows = rows(n);
+
+    time("flat", &rows, &FlatConfig::default());
+    time(
+        "flat-arrays",
+        &rows,
+        &FlatConfig::default().with_arrays(ArrayExpansion::Index),
+    );
+}
@@ -0,0 +1,205 @@
+use crate::cli::TableStyle;
+use crate::core::{ColumnSelector, FilterExpr, FlatConfig, Formatter, Sorter, TableData};
+use crate::error::Result;
+use crate::pipeline::{self, DecodeOptions, Projection, View};
+use crate::render::CatRenderer;
+use serde_json::Value;
+use std::io::Read;
+
+/// Builds a table from JSON rows: filter, sort, select columns, then render.
+///
+/// This is the embedding entry point for the same steps the `jlcat` binary runs.
+///
+/// ```
+/// use jlcat::{TableBuilder, TableStyle};
+/// use serde_json::json;
+///
+/// let rows = vec![
+///     json!({"id": 1, "name": "Alice", "age": 30}),
+///     json!({"id": 2, "name": "Bob", "age": 25}),
+/// ];
+/// let table = TableBuilder::new(rows)
+///     .filter("age>26")?
+///     .columns(&["name"])?
+///     .style(TableStyle::Markdown)
+///     .render();
+/// assert!(table.contains("Alice"));
+/// assert!(!table.contains("Bob"));
+/// # Ok::<(), jlcat::JlcatError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct TableBuilder {
+    rows: Vec<Value>,
+    filters: Vec<FilterExpr>,
+    sorter: Option<Sorter>,
+    selector: Option<ColumnSelector>,
+    view: View,
+    style: TableStyle,
+    formatter: Formatter,
+}
+
+impl TableBuilder {
+    pub fn new(rows: Vec<Value>) -> Self {
+        Self {
+            rows,
+            filters: Vec::new(),
+            sorter: None,
+            selector: None,
+            view: View::Normal,
+            style: TableStyle::default(),
+            formatter: Formatter::default(),
+        }
+    }
+
+    /// Read JSONL or a JSON array of objects, failing on invalid lines
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let options = DecodeOptions {
+            strict: true,
+            ..Default::default()
+        };
+        Ok(Self::new(pipeline::decode(reader, &options)?.rows))
+    }
+
+    /// Keep only these columns, in this order (dot notation and `user.*` supported)
+    pub fn columns<S: AsRef<str>>(mut self, columns: &[S]) -> Result<Self> {
+        let columns = columns.iter().map(|c| c.as_ref().to_string()).collect();
+        self.selector = Some(ColumnSelector::new(columns)?);
+        Ok(self)
+    }
+
+    /// Keep rows matching a filter expression (e.g. `status=active age>30`).
+    /// Repeated calls must all match.
+    pub fn filter(mut self, expr: &str) -> Result<Self> {
+        self.filters.push(FilterExpr::parse(expr)?);
+        Ok(self)
+    }
+
+    /// Sort by keys, `-` prefix for descending (e.g. `["-score", "name"]`)
+    pub fn sort<S: AsRef<str>>(mut self, keys: &[S]) -> Result<Self> {
+        let keys: Vec<String> = keys.iter().map(|k| k.as_ref().to_string()).collect();
+        self.sorter = Some(Sorter::parse(&keys)?);
+        Ok(self)
+    }
+
+    /// Expand nested objects into dot-notation columns
+    pub fn flat(mut self, config: FlatConfig) -> Self {
+        self.view = View::Flat(config);
+        self
+    }
+
+    /// Render nested objects and arrays as child tables
+    pub fn recursive(mut self) -> Self {
+        self.view = View::Recursive {
+            summary: false,
+            child_rows: Default::default(),
+            extractor: Default::default(),
+        };
+        self
+    }
+
+    pub fn style(mut self, style: TableStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Conditional formatting, timestamp display and truncation of cells
+    pub fn formatter(mut self, formatter: Formatter) -> Self {
+        self.formatter = formatter;
+        self
+    }
+
+    /// The rows left after filtering and sorting
+    pub fn rows(&self) -> Vec<Value> {
+        let mut rows: Vec<Value> = self
+            .rows
+            .iter()
+            .filter(|row| self.filters.iter().all(|f| f.matches(row)))
+            .cloned()
+            .collect();
+        if let Some(ref sorter) = self.sorter {
+            sorter.sort(&mut rows);
+        }
+        rows
+    }
+
+    /// The table as cells, with the selected columns
+    pub fn table_data(&self) -> TableData {
+        TableData::from_rows(self.rows(), self.selector.clone())
+    }
+
+    /// Render the table as text, as the binary prints it to a non-terminal
+    pub fn render(&self) -> String {
+        let rows = self.rows();
+        if rows.is_empty() {
+            return String::new();
+        }
+        let renderer = CatRenderer::new(self.style.clone()).with_formatter(self.formatter.clone());
+        let projection = Projection {
+            selector: self.selector.clone(),
+            view: self.view.clone(),
+            auto_layout: false,
+            array_limit: 3,
+        };
+        pipeline::render_cat(&renderer, rows, projection, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rows() -> Vec<Value> {
+        vec![
+            json!({"id": 1, "name": "Alice", "user": {"city": "Tokyo"}, "orders": [{"sku": "a"}]}),
+            json!({"id": 2, "name": "Bob", "user": {"city": "Osaka"}, "orders": []}),
+            json!({"id": 3, "name": "Carol", "user": {"city": "Tokyo"}, "orders": []}),
+        ]
+    }
+
+    #[test]
+    fn test_filter_and_sort() {
+        let builder = TableBuilder::new(rows())
+            .filter("user.city=Tokyo")
+            .unwrap()
+            .sort(&["-id"])
+            .unwrap();
+
+        let ids: Vec<Value> = builder.rows().iter().map(|r| r["id"].clone()).collect();
+        assert_eq!(ids, vec![json!(3), json!(1)]);
+    }
+
+    #[test]
+    fn test_columns_select_table_data() {
+        let table = TableBuilder::new(rows())
+            .columns(&["name", "user.city"])
+            .unwrap()
+            .table_data();
+
+        assert_eq!(table.columns(), &["name", "user.city"]);
+        assert_eq!(table.row_count(), 3);
+    }
+
+    #[test]
+    fn test_render_views() {
+        let flat = TableBuilder::new(rows())
+            .flat(FlatConfig::default())
+            .style(TableStyle::Plain)
+            .render();
+        assert!(flat.contains("user.city"));
+
+        let recursive = TableBuilder::new(rows()).recursive().render();
+        assert!(recursive.contains("## orders"));
+    }
+
+    #[test]
+    fn test_from_reader_and_errors() {
+        let builder = TableBuilder::from_reader("{\"id\": 1}\n{\"id\": 2}\n".as_bytes()).unwrap();
+        assert_eq!(builder.rows().len(), 2);
+
+        assert!(TableBuilder::from_reader("oops\n".as_bytes()).is_err());
+        assert!(TableBuilder::new(rows()).sort(&[""]).is_err());
+        assert!(TableBuilder::new(rows()).render().contains("Alice"));
+        assert_eq!(TableBuilder::new(Vec::new()).render(), "");
+    }
+}
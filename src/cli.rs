@@ -1,13 +1,59 @@
-use clap::{Parser, ValueEnum};
+use crate::config::Config;
+use crate::core::ColumnWidths;
+use crate::error::{JlcatError, Result};
+use crate::input::parse_encoding;
+use clap::builder::BoolishValueParser;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum};
+use encoding_rs::Encoding;
+use serde::Deserialize;
+use std::ffi::OsString;
 use std::path::PathBuf;
 
+/// Environment variable for `--flat`, which clap's env support can't express
+/// because the flag takes an optional value
+const FLAT_ENV: &str = "JLCAT_FLAT";
+
 #[derive(Parser, Debug)]
 #[command(name = "jlcat")]
 #[command(about = "JSON/JSONL table viewer with TUI support")]
 #[command(version)]
 pub struct Cli {
-    /// JSON or JSONL file path (reads from stdin if omitted)
-    pub file: Option<PathBuf>,
+    /// JSON or JSONL files, shown as one table (reads from stdin if omitted)
+    #[arg(value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+
+    /// Add a `_source` column with the file each row came from
+    #[arg(long)]
+    pub tag_source: bool,
+
+    /// Label for a file's `_source` value instead of its path; repeatable,
+    /// in the order of the files
+    #[arg(long, value_name = "LABEL", requires = "tag_source", action = clap::ArgAction::Append)]
+    pub source_label: Vec<String>,
+
+    /// Pick rows by 1-based position after reading: numbers and inclusive
+    /// ranges, e.g. '10..20,50,100..'
+    #[arg(long, value_name = "RANGES")]
+    pub rows: Option<String>,
+
+    /// Reverse the row order, after sorting if combined with -s
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// Randomize the row order after reading, for a mix of rows from a
+    /// sorted or clustered file rather than just its head
+    #[arg(long, conflicts_with_all = ["sort", "reverse"])]
+    pub shuffle: bool,
+
+    /// Seed for --shuffle, to get the same order again
+    #[arg(long, value_name = "N", requires = "shuffle")]
+    pub seed: Option<u64>,
+
+    /// Add a `#` column with each row's position in its input, so rows can be
+    /// traced back after sorting and filtering
+    #[arg(long, conflicts_with = "interactive")]
+    pub row_numbers: bool,
 
     /// Launch in interactive TUI mode
     #[arg(short, long)]
@@ -17,58 +63,641 @@ pub struct Cli {
     #[arg(long, value_name = "N")]
     pub skip: Option<usize>,
 
-    /// Limit the number of rows read from input
-    #[arg(long, value_name = "N", alias = "head")]
-    pub limit: Option<usize>,
+    /// Limit the rows read from input: a count, or a percentage of the input
+    /// files' size such as 10%
+    #[arg(long, value_name = "N|N%", alias = "head", value_parser = parse_limit)]
+    pub limit: Option<Limit>,
+
+    /// Stop reading input after this many bytes, e.g. 50M; the row that
+    /// crosses the limit is still shown
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub limit_bytes: Option<u64>,
 
     /// Read only the last N rows (mutually exclusive with --skip/--limit)
-    #[arg(long, value_name = "N", conflicts_with_all = ["skip", "limit"])]
+    #[arg(long, value_name = "N", conflicts_with_all = ["skip", "limit", "limit_bytes"])]
     pub tail: Option<usize>,
 
+    /// Print an estimated row count and exit (files are sampled, stdin is counted)
+    #[arg(long)]
+    pub estimate: bool,
+
+    /// Validate the input and exit: report valid and invalid rows, the
+    /// inferred columns and columns whose values mix types, exiting non-zero
+    /// if there are problems
+    #[arg(long, conflicts_with_all = ["estimate", "interactive"])]
+    pub check: bool,
+
+    /// Print a draft JSON Schema describing every row, nested objects and
+    /// arrays included, and exit
+    #[arg(long, conflicts_with_all = ["estimate", "check", "interactive"])]
+    pub infer_schema: bool,
+
     /// Recursively expand nested structures as child tables
     #[arg(short, long)]
     pub recursive: bool,
 
+    /// In recursive mode, show one summary row per parent for each child table
+    /// (row count and min/max of numeric fields) instead of every child row
+    #[arg(long, requires = "recursive")]
+    pub children_summary: bool,
+
+    /// In recursive mode, show at most N rows per child table; TABLE=N entries
+    /// (comma-separated) set the limit of one table, e.g. '20,orders.items=5'
+    #[arg(long, value_name = "N|TABLE=N", requires = "recursive", value_parser = parse_child_limit)]
+    pub child_limit: Option<ChildLimit>,
+
+    /// In recursive mode, leave out the first N rows of every child table
+    #[arg(long, value_name = "N", requires = "recursive")]
+    pub child_skip: Option<usize>,
+
+    /// In recursive mode, identify parent rows in child tables by these fields
+    /// (comma-separated) instead of the row index
+    #[arg(
+        long,
+        value_name = "FIELDS",
+        value_delimiter = ',',
+        requires = "recursive"
+    )]
+    pub parent_key: Vec<String>,
+
+    /// In recursive mode, only extract these nested fields as child tables
+    /// (comma-separated; `orders.*` also extracts tables nested under `orders`)
+    #[arg(
+        long,
+        value_name = "FIELDS",
+        value_delimiter = ',',
+        requires = "recursive"
+    )]
+    pub children: Option<Vec<String>>,
+
+    /// Write the table (and in recursive mode every child table) to files in DIR
+    /// instead of printing, one file per table named after its path
+    #[arg(long, value_name = "DIR", conflicts_with = "interactive")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Print tables as csv, json, jsonl or markdown instead of a rendered table;
+    /// also the file format for --out-dir (default csv)
+    #[arg(long, value_enum, conflicts_with_all = ["interactive", "vertical", "transpose"])]
+    pub output: Option<OutputFormat>,
+
+    /// Write `--output json` arrays on one line instead of indented
+    #[arg(long, requires = "output")]
+    pub compact: bool,
+
     /// Columns to display (comma-separated, supports dot notation)
     #[arg(short, long, value_delimiter = ',')]
     pub columns: Option<Vec<String>>,
 
     /// Sort keys (comma-separated, prefix with - for descending)
-    #[arg(short, long, value_delimiter = ',')]
+    #[arg(short, long, value_delimiter = ',', allow_hyphen_values = true)]
     pub sort: Option<Vec<String>>,
 
+    /// Only show rows matching a filter expression (e.g., 'status=active age>30');
+    /// repeatable, all must match
+    #[arg(short, long, value_name = "EXPR", action = clap::ArgAction::Append)]
+    pub filter: Vec<String>,
+
+    /// Keep one row per distinct value of these columns (comma-separated),
+    /// after filtering and sorting
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub distinct_on: Option<Vec<String>>,
+
+    /// Which row --distinct-on keeps for each value: first (default) or last
+    #[arg(long, value_enum, value_name = "WHICH", requires = "distinct_on")]
+    pub keep: Option<KeepMode>,
+
+    /// Only show rows where any value contains TEXT (case-insensitive);
+    /// 'column:TEXT' searches a single column
+    #[arg(long, value_name = "TEXT")]
+    pub grep: Option<String>,
+
+    /// Make `~` and `!~` filters and --grep match whole words only
+    /// (`id` doesn't match `valid`)
+    #[arg(short, long)]
+    pub word: bool,
+
+    /// Reshape each record with a jq-like expression before building the table,
+    /// e.g. '.items[] | {id, name}' (paths, [], [n], {...} and | are supported)
+    #[arg(long, value_name = "EXPR")]
+    pub select: Option<String>,
+
+    /// Apply a named profile from the config file ([profiles.NAME]); flags override it
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
     /// Table style
-    #[arg(long, value_enum, default_value = "rounded")]
+    #[arg(long, value_enum, default_value = "rounded", env = "JLCAT_STYLE")]
     pub style: TableStyle,
 
+    /// When to use color: auto (terminals only, honoring NO_COLOR), always, or never
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        default_value = "auto",
+        env = "JLCAT_COLOR"
+    )]
+    pub color: ColorMode,
+
     /// Exit on invalid JSON line (default: true)
     #[arg(long, default_value = "true", action = clap::ArgAction::Set)]
     pub strict: bool,
 
     /// Skip invalid JSON lines with warning
-    #[arg(long)]
+    #[arg(long, env = "JLCAT_LENIENT", value_parser = BoolishValueParser::new())]
     pub lenient: bool,
 
+    /// In lenient mode, abort once more than N lines have been skipped
+    #[arg(long, value_name = "N", requires = "lenient")]
+    pub max_errors: Option<usize>,
+
+    /// In lenient mode, write every skipped line to PATH as JSONL records
+    /// with its line number, error and original text
+    #[arg(long, value_name = "PATH", requires = "lenient")]
+    pub rejects: Option<PathBuf>,
+
+    /// Don't warn about each skipped line in lenient mode (only the final
+    /// summary is printed) and don't show a progress bar for large files
+    #[arg(short, long, env = "JLCAT_QUIET", value_parser = BoolishValueParser::new())]
+    pub quiet: bool,
+
+    /// Encoding of input without a byte order mark, e.g. utf-16le or
+    /// utf-16be (default: utf-8; UTF-8 and UTF-16 marks are detected)
+    #[arg(long, value_name = "ENCODING", value_parser = parse_encoding)]
+    pub encoding: Option<&'static Encoding>,
+
+    /// Which value to keep when a key repeats within an object; `error`
+    /// treats the row as invalid JSON (default: last, with a warning)
+    #[arg(long, value_name = "POLICY")]
+    pub dup_keys: Option<DupKeysMode>,
+
+    /// Memory budget for loaded rows, e.g. 512M or 2G; larger inputs are
+    /// indexed on disk and read back as they are shown
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_memory: Option<u64>,
+
+    /// With --max-memory, save the row index of a file next to it
+    /// (FILE.jlcatidx) and reuse it while the file is unchanged
+    #[arg(long, requires = "max_memory")]
+    pub index_cache: bool,
+
     /// Flatten nested objects into dot-notation columns
-    /// Optional depth limit (e.g., --flat or --flat=3)
+    /// Optional depth limit (e.g., --flat or --flat=3) [env: JLCAT_FLAT]
     #[arg(long = "flat", value_name = "DEPTH", num_args = 0..=1, default_missing_value = "")]
     flat_raw: Option<String>,
 
+    /// Choose between normal, flat, and child-summary layouts from the shape of the data
+    #[arg(long, conflicts_with_all = ["flat_raw", "recursive"])]
+    pub auto_layout: bool,
+
     /// Maximum array elements to display in flat mode
-    #[arg(long, default_value = "3")]
+    #[arg(long, default_value = "3", env = "JLCAT_ARRAY_LIMIT")]
     pub array_limit: usize,
+
+    /// Duplicate each row once per element of this array field ("unnest"); repeatable.
+    /// Combine with --flat to spread the element's fields into columns
+    #[arg(long, value_name = "FIELD", action = clap::ArgAction::Append)]
+    pub explode: Vec<String>,
+
+    /// How flat mode shows arrays: join (one comma-separated cell) or index
+    /// (one column per element: tags[0], tags[1], ...)
+    #[arg(long, value_enum, value_name = "MODE", requires = "flat_raw")]
+    pub flat_arrays: Option<FlatArraysMode>,
+
+    /// Maximum displayed width of a cell; longer values are truncated with an ellipsis
+    #[arg(long, value_name = "N", env = "JLCAT_MAX_COL_WIDTH")]
+    pub max_col_width: Option<usize>,
+
+    /// Pin column widths, e.g. 'id=6,message=60,*=20' (`*` sets the rest);
+    /// pinned cells are truncated to fit
+    #[arg(long, value_name = "COL=N,...", value_parser = ColumnWidths::parse)]
+    pub widths: Option<ColumnWidths>,
+
+    /// Which part of a truncated cell to elide: end (default) or middle (keeps head and tail)
+    #[arg(long, value_enum, value_name = "MODE", requires = "max_col_width")]
+    pub elide: Option<ElideMode>,
+
+    /// How nested objects and arrays are shown in cells: placeholder (`{3 keys}`,
+    /// the default) or inline[:N], compact JSON cut at N characters (default 40)
+    #[arg(long, value_name = "MODE", value_parser = parse_nested)]
+    pub nested: Option<NestedMode>,
+
+    /// Text shown for null cells instead of "null" (e.g. '-' or '')
+    #[arg(long, value_name = "TEXT")]
+    pub null_label: Option<String>,
+
+    /// Text shown for true and false cells, comma-separated (e.g. '✓,✗')
+    #[arg(long, value_name = "TRUE,FALSE", value_parser = parse_bool_labels)]
+    pub bool_labels: Option<BoolLabels>,
+
+    /// Leave out the header row of printed tables
+    #[arg(long, conflicts_with = "interactive")]
+    pub no_header: bool,
+
+    /// Change the case of column headers
+    #[arg(long, value_enum, value_name = "CASE")]
+    pub header_case: Option<HeaderCaseMode>,
+
+    /// Follow each header with the type of its values, e.g. `age:number`, or
+    /// `name:string?` when some are null
+    #[arg(long)]
+    pub show_types: bool,
+
+    /// TOML file mapping column names to the labels shown for them, e.g.
+    /// `t_lat_ms = "Latency (ms)"`; applies to tables, the TUI and exports
+    #[arg(long, value_name = "PATH")]
+    pub header_map: Option<PathBuf>,
+
+    /// Style the cells a condition tests when it matches, as EXPR:STYLE
+    /// (e.g. 'latency_ms>1000:yellow'), or every cell of a JSON type
+    /// ('null:dim'); repeatable, later rules win
+    #[arg(long, value_name = "RULE", action = clap::ArgAction::Append)]
+    pub highlight: Vec<String>,
+
+    /// Style whole rows matching a condition, as EXPR:STYLE
+    /// (e.g. 'status=error:red'); repeatable
+    #[arg(long, value_name = "RULE", action = clap::ArgAction::Append)]
+    pub highlight_row: Vec<String>,
+
+    /// Print control characters and ANSI escapes in values as they are
+    /// (by default they are shown escaped, e.g. `\u{1b}`)
+    #[arg(long)]
+    pub raw_control: bool,
+
+    /// Swap rows and columns: one row per field, one column per record
+    #[arg(long, conflicts_with = "interactive")]
+    pub transpose: bool,
+
+    /// Print each row as a block of `field | value` lines (like psql's \x)
+    #[arg(long, conflicts_with_all = ["interactive", "transpose"])]
+    pub vertical: bool,
+
+    /// Skip tables and pretty-print each row as JSON (colored on terminals),
+    /// separated by record dividers
+    #[arg(long, conflicts_with_all = ["interactive", "transpose", "vertical", "output", "out_dir"])]
+    pub pretty: bool,
+
+    /// Print hints below the table on how to view nested values
+    #[arg(long, env = "JLCAT_HINTS", value_parser = BoolishValueParser::new())]
+    pub hints: bool,
+
+    /// Wrap long cell values onto multiple lines (at --max-col-width if set)
+    #[arg(long, env = "JLCAT_WRAP", value_parser = BoolishValueParser::new())]
+    pub wrap: bool,
+
+    /// Reformat timestamp values with a strftime pattern (e.g., "%Y-%m-%d %H:%M").
+    /// Use COLUMN=PATTERN to target a single column; repeatable
+    #[arg(long, value_name = "[COLUMN=]PATTERN", action = clap::ArgAction::Append)]
+    pub date_format: Vec<String>,
+
+    /// Timezone for displaying timestamps: UTC, local, or an IANA name (e.g., Asia/Tokyo)
+    #[arg(long, value_name = "TZ", env = "JLCAT_TZ")]
+    pub tz: Option<String>,
+
+    /// Unit of epoch timestamps: auto, s, ms, us, or ns (default: auto-detect by magnitude).
+    /// Use COLUMN=UNIT to target a single column; repeatable
+    #[arg(long, value_name = "[COLUMN=]UNIT", action = clap::ArgAction::Append)]
+    pub epoch_unit: Vec<String>,
+
+    /// Columns to render as relative time, e.g. "3m ago" (comma-separated)
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub relative_time: Vec<String>,
+
+    /// Filter by log level: `warn+` (and above), `warn-` (and below), or `warn` (exactly)
+    #[arg(long, value_name = "LEVEL")]
+    pub level: Option<String>,
+
+    /// Field holding the log level (auto-detected from level/severity/lvl if omitted)
+    #[arg(long, value_name = "FIELD", requires = "level")]
+    pub level_field: Option<String>,
+
+    /// Pipe rows (as JSONL) through an external command and display its output
+    #[arg(long, value_name = "CMD")]
+    pub transform_cmd: Option<String>,
+
+    /// Rows written to the transform command between flushes
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "1000",
+        requires = "transform_cmd"
+    )]
+    pub transform_batch: usize,
+
+    /// WebAssembly plugin providing a row transform and/or custom renderer
+    #[cfg(feature = "wasm")]
+    #[arg(long, value_name = "PATH")]
+    pub plugin: Option<PathBuf>,
+
+    /// Rhai script run against each row (bound as `row`); rows are dropped when it evaluates to false
+    #[cfg(feature = "script")]
+    #[arg(long, value_name = "PATH", conflicts_with = "eval")]
+    pub script: Option<PathBuf>,
+
+    /// Inline Rhai script, e.g. 'row.total = row.price * row.qty'
+    #[cfg(feature = "script")]
+    #[arg(long, value_name = "SCRIPT")]
+    pub eval: Option<String>,
+
+    /// SQL query over the input loaded as table `t`, e.g.
+    /// 'select status, count(*) as n from t group by 1'; its result rows are shown
+    #[cfg(feature = "sql")]
+    #[arg(long, value_name = "QUERY")]
+    pub sql: Option<String>,
+
+    /// Write the table (and in recursive mode every child table) to a SQLite
+    /// database as FILE[:TABLE] instead of printing; the table defaults to `t`
+    /// and is replaced if it exists
+    #[cfg(feature = "sql")]
+    #[arg(
+        long,
+        value_name = "FILE[:TABLE]",
+        conflicts_with_all = ["interactive", "out_dir", "output"]
+    )]
+    pub to_sqlite: Option<String>,
+
+    /// Config file path (default: ~/.config/jlcat/config.toml)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
 }
 
-#[derive(ValueEnum, Clone, Debug, Default)]
+#[derive(ValueEnum, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
 pub enum TableStyle {
     Ascii,
     #[default]
     Rounded,
     Markdown,
     Plain,
+    /// PostgreSQL `psql` output
+    Psql,
+    /// Box-drawing grid with a line between every row
+    Grid,
+    /// Emacs org-mode table
+    #[value(alias = "org-mode")]
+    #[serde(alias = "org-mode")]
+    Org,
+    /// Jira wiki markup
+    Jira,
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeepMode {
+    First,
+    Last,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DupKeysMode {
+    First,
+    Last,
+    Error,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeaderCaseMode {
+    Upper,
+    Lower,
+    Title,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElideMode {
+    End,
+    Middle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NestedMode {
+    Placeholder,
+    /// Compact JSON of at most this many characters
+    Inline(usize),
+}
+
+/// Width of `--nested inline` cells without `:N`
+const DEFAULT_INLINE_WIDTH: usize = 40;
+
+fn parse_nested(s: &str) -> std::result::Result<NestedMode, String> {
+    match s.split_once(':') {
+        None if s == "placeholder" => Ok(NestedMode::Placeholder),
+        None if s == "inline" => Ok(NestedMode::Inline(DEFAULT_INLINE_WIDTH)),
+        Some(("inline", n)) => match n.parse() {
+            Ok(0) | Err(_) => Err(format!(
+                "invalid width '{}' (expected a positive number)",
+                n
+            )),
+            Ok(n) => Ok(NestedMode::Inline(n)),
+        },
+        _ => Err("expected placeholder, inline or inline:N".to_string()),
+    }
+}
+
+/// `--child-limit`: rows per child table, with limits for particular tables
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChildLimit {
+    pub rows: Option<usize>,
+    pub tables: Vec<(String, usize)>,
+}
+
+fn parse_child_limit(s: &str) -> std::result::Result<ChildLimit, String> {
+    let mut limit = ChildLimit::default();
+    for part in s.split(',').map(str::trim) {
+        let count = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!("invalid row count '{}'", n))
+        };
+        match part.split_once('=') {
+            Some((table, n)) if !table.is_empty() => {
+                limit.tables.push((table.to_string(), count(n)?));
+            }
+            Some(_) => return Err(format!("missing table name in '{}'", part)),
+            None => limit.rows = Some(count(part)?),
+        }
+    }
+    Ok(limit)
+}
+
+/// `--limit`: a row count, or a share of the input size
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    Rows(usize),
+    /// Percentage of the input files' total size, in (0, 100]
+    Percent(f64),
+}
+
+fn parse_limit(s: &str) -> std::result::Result<Limit, String> {
+    match s.trim().strip_suffix('%') {
+        Some(p) => p
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|p| *p > 0.0 && *p <= 100.0)
+            .map(Limit::Percent)
+            .ok_or_else(|| format!("invalid percentage '{}', expected e.g. 10%", s)),
+        None => s
+            .trim()
+            .parse()
+            .map(Limit::Rows)
+            .map_err(|_| format!("invalid row count '{}'", s)),
+    }
+}
+
+/// Parse a byte count with an optional K, M, G or T suffix (powers of 1024)
+fn parse_size(s: &str) -> std::result::Result<u64, String> {
+    let upper = s.trim().to_ascii_uppercase();
+    let digits = upper
+        .strip_suffix("IB")
+        .or_else(|| upper.strip_suffix('B'))
+        .unwrap_or(&upper);
+    let (number, shift) = match digits.char_indices().last() {
+        Some((i, 'K')) => (&digits[..i], 10),
+        Some((i, 'M')) => (&digits[..i], 20),
+        Some((i, 'G')) => (&digits[..i], 30),
+        Some((i, 'T')) => (&digits[..i], 40),
+        _ => (digits, 0),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .filter(|&n| n > 0)
+        .ok_or_else(|| format!("invalid size '{}', expected e.g. 512M or 2G", s))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoolLabels {
+    pub true_label: String,
+    pub false_label: String,
+}
+
+fn parse_bool_labels(s: &str) -> std::result::Result<BoolLabels, String> {
+    match s.split_once(',') {
+        Some((t, f)) if !f.contains(',') => Ok(BoolLabels {
+            true_label: t.to_string(),
+            false_label: f.to_string(),
+        }),
+        _ => Err("expected two labels separated by a comma, e.g. 'yes,no'".to_string()),
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Comma-separated values with a header line
+    #[default]
+    Csv,
+    /// A single JSON array of objects
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// GitHub-flavored Markdown table
+    Markdown,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlatArraysMode {
+    Join,
+    Index,
 }
 
 impl Cli {
+    /// Parse the command line and load the config file, whose defaults fill
+    /// in options not given as flags
+    pub fn parse_with_config() -> Result<(Self, Config)> {
+        let matches = Self::command().get_matches();
+        let mut cli = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        cli.apply_flat_env(std::env::var_os(FLAT_ENV))?;
+        let config = Config::load(cli.config.as_deref())?;
+        cli.apply_config(&config, &matches)?;
+        Ok((cli, config))
+    }
+
+    /// Turn on flat mode from `$JLCAT_FLAT` unless the command line already picks a
+    /// layout: `true`, `yes`, `on` or empty for `--flat`, a number for `--flat=N`,
+    /// and `false`, `no`, `off` to leave it off
+    pub fn apply_flat_env(&mut self, value: Option<OsString>) -> Result<()> {
+        let Some(value) = value else {
+            return Ok(());
+        };
+        if self.flat_raw.is_some() || self.recursive || self.auto_layout {
+            return Ok(());
+        }
+        let text = value.to_string_lossy().trim().to_ascii_lowercase();
+        self.flat_raw = match text.as_str() {
+            "" | "true" | "yes" | "on" => Some(String::new()),
+            "false" | "no" | "off" => None,
+            depth if depth.parse::<usize>().is_ok() => Some(depth.to_string()),
+            _ => {
+                return Err(JlcatError::Config(format!(
+                    "invalid {} value '{}': expected true, false or a depth",
+                    FLAT_ENV,
+                    value.to_string_lossy()
+                )))
+            }
+        };
+        Ok(())
+    }
+
+    /// Fill options left at their built-in defaults from the selected profile,
+    /// then from the config file's defaults
+    pub fn apply_config(&mut self, config: &Config, matches: &ArgMatches) -> Result<()> {
+        let given = |id: &str| {
+            matches
+                .value_source(id)
+                .is_some_and(|source| source != ValueSource::DefaultValue)
+        };
+        let profile = match self.profile {
+            Some(ref name) => Some(config.profile(name)?),
+            None => None,
+        };
+        let defaults = &config.defaults;
+
+        let style = profile
+            .and_then(|p| p.style.clone())
+            .or_else(|| defaults.style.clone());
+        if let Some(style) = style.filter(|_| !given("style")) {
+            self.style = style;
+        }
+        if let Some(limit) = defaults.array_limit.filter(|_| !given("array_limit")) {
+            self.array_limit = limit;
+        }
+        if let Some(color) = defaults.color.filter(|_| !given("color")) {
+            self.color = color;
+        }
+        if let Some(profile) = profile {
+            if self.filter.is_empty() {
+                self.filter.extend(profile.filter.clone());
+            }
+            if self.sort.is_none() {
+                self.sort = profile.sort.clone();
+            }
+            if self.date_format.is_empty() {
+                self.date_format = profile.date_format.clone().unwrap_or_default();
+            }
+            if self.columns.is_none() {
+                self.columns = profile.columns.clone();
+            }
+        }
+        if self.columns.is_none() {
+            let file = match self.files.as_slice() {
+                [file] => Some(file.as_path()),
+                _ => None,
+            };
+            if let Some(columns) = file.and_then(|f| config.columns_for(f)) {
+                self.columns = Some(columns.to_vec());
+            }
+        }
+        Ok(())
+    }
+
     pub fn is_strict(&self) -> bool {
         // Honor both flags: strict mode requires --strict=true (default) AND no --lenient
         self.strict && !self.lenient
@@ -97,6 +726,116 @@ mod tests {
     use super::*;
     use clap::Parser;
 
+    fn with_config(args: &[&str], config: &str) -> Cli {
+        let matches = Cli::command().get_matches_from(args);
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        cli.apply_config(&Config::parse(config).unwrap(), &matches)
+            .unwrap();
+        cli
+    }
+
+    #[test]
+    fn test_config_defaults_under_flags() {
+        let config = r#"
+[defaults]
+style = "psql"
+array-limit = 5
+color = "never"
+
+[[columns]]
+pattern = "*.log.jsonl"
+columns = ["level", "msg"]
+"#;
+        let cli = with_config(&["jlcat", "app.log.jsonl"], config);
+        assert!(matches!(cli.style, TableStyle::Psql));
+        assert_eq!(cli.array_limit, 5);
+        assert_eq!(cli.color, ColorMode::Never);
+        assert_eq!(cli.columns, Some(vec!["level".into(), "msg".into()]));
+
+        let cli = with_config(
+            &[
+                "jlcat",
+                "--style=ascii",
+                "--array-limit=3",
+                "--color=always",
+                "-c",
+                "id",
+                "app.log.jsonl",
+            ],
+            config,
+        );
+        assert!(matches!(cli.style, TableStyle::Ascii));
+        assert_eq!(cli.array_limit, 3);
+        assert_eq!(cli.color, ColorMode::Always);
+        assert_eq!(cli.columns, Some(vec!["id".into()]));
+    }
+
+    #[test]
+    fn test_profile_under_flags() {
+        let config = r#"
+[defaults]
+style = "psql"
+
+[profiles.errors]
+columns = ["ts", "msg"]
+filter = "level=error"
+sort = ["-ts"]
+style = "plain"
+date-format = ["%H:%M"]
+"#;
+        let cli = with_config(&["jlcat", "--profile", "errors"], config);
+        assert!(matches!(cli.style, TableStyle::Plain));
+        assert_eq!(cli.columns, Some(vec!["ts".into(), "msg".into()]));
+        assert_eq!(cli.filter, vec!["level=error"]);
+        assert_eq!(cli.sort, Some(vec!["-ts".into()]));
+        assert_eq!(cli.date_format, vec!["%H:%M"]);
+
+        let cli = with_config(
+            &[
+                "jlcat",
+                "--profile",
+                "errors",
+                "--style=grid",
+                "-f",
+                "level=warn",
+            ],
+            config,
+        );
+        assert!(matches!(cli.style, TableStyle::Grid));
+        assert_eq!(cli.filter, vec!["level=warn"]);
+
+        let matches = Cli::command().get_matches_from(["jlcat", "--profile", "nope"]);
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        let config = Config::parse(config).unwrap();
+        assert!(cli.apply_config(&config, &matches).is_err());
+    }
+
+    #[test]
+    fn test_flat_env() {
+        let mut cli = Cli::parse_from(["jlcat"]);
+        cli.apply_flat_env(Some("".into())).unwrap();
+        assert_eq!(cli.flat(), Some(None));
+
+        let mut cli = Cli::parse_from(["jlcat"]);
+        cli.apply_flat_env(Some("2".into())).unwrap();
+        assert_eq!(cli.flat_depth(), Some(2));
+
+        let mut cli = Cli::parse_from(["jlcat"]);
+        cli.apply_flat_env(Some("off".into())).unwrap();
+        assert!(!cli.is_flat());
+
+        // Flags choosing a layout win over the environment
+        let mut cli = Cli::parse_from(["jlcat", "--flat=1"]);
+        cli.apply_flat_env(Some("3".into())).unwrap();
+        assert_eq!(cli.flat_depth(), Some(1));
+        let mut cli = Cli::parse_from(["jlcat", "-r"]);
+        cli.apply_flat_env(Some("true".into())).unwrap();
+        assert!(!cli.is_flat());
+
+        let mut cli = Cli::parse_from(["jlcat"]);
+        assert!(cli.apply_flat_env(Some("deep".into())).is_err());
+    }
+
     #[test]
     fn test_flat_flag_only() {
         let cli = Cli::parse_from(["jlcat", "--flat"]);
@@ -104,12 +843,74 @@ mod tests {
         assert_eq!(cli.flat(), Some(None)); // flat enabled, no depth
     }
 
+    #[test]
+    fn test_auto_layout_conflicts_with_flat() {
+        assert!(Cli::try_parse_from(["jlcat", "--auto-layout", "--flat"]).is_err());
+        let cli = Cli::parse_from(["jlcat", "--auto-layout"]);
+        assert!(cli.auto_layout);
+        assert!(!cli.is_flat());
+    }
+
     #[test]
     fn test_flat_with_depth() {
         let cli = Cli::parse_from(["jlcat", "--flat=3"]);
         assert_eq!(cli.flat(), Some(Some(3)));
     }
 
+    #[test]
+    fn test_estimate_flag() {
+        let cli = Cli::parse_from(["jlcat", "--estimate", "data.jsonl"]);
+        assert!(cli.estimate);
+    }
+
+    #[test]
+    fn test_highlight_rules_repeat() {
+        let cli = Cli::parse_from([
+            "jlcat",
+            "--highlight",
+            "latency_ms>1000:yellow",
+            "--highlight",
+            "null:dim",
+            "--highlight-row",
+            "status=error:red",
+        ]);
+        assert_eq!(cli.highlight, vec!["latency_ms>1000:yellow", "null:dim"]);
+        assert_eq!(cli.highlight_row, vec!["status=error:red"]);
+    }
+
+    #[test]
+    fn test_check_flag() {
+        assert!(Cli::parse_from(["jlcat", "--check", "data.jsonl"]).check);
+        assert!(Cli::try_parse_from(["jlcat", "--check", "--estimate"]).is_err());
+        assert!(Cli::try_parse_from(["jlcat", "--check", "--infer-schema"]).is_err());
+    }
+
+    #[test]
+    fn test_transpose_conflicts_with_interactive() {
+        assert!(Cli::parse_from(["jlcat", "--transpose"]).transpose);
+        assert!(Cli::try_parse_from(["jlcat", "--transpose", "-i"]).is_err());
+    }
+
+    #[test]
+    fn test_vertical_conflicts() {
+        assert!(Cli::parse_from(["jlcat", "--vertical"]).vertical);
+        assert!(Cli::try_parse_from(["jlcat", "--vertical", "-i"]).is_err());
+        assert!(Cli::try_parse_from(["jlcat", "--vertical", "--transpose"]).is_err());
+    }
+
+    #[test]
+    fn test_flat_arrays() {
+        let cli = Cli::parse_from(["jlcat", "--flat", "--flat-arrays", "index"]);
+        assert_eq!(cli.flat_arrays, Some(FlatArraysMode::Index));
+        assert!(Cli::try_parse_from(["jlcat", "--flat-arrays", "index"]).is_err());
+    }
+
+    #[test]
+    fn test_explode_repeatable() {
+        let cli = Cli::parse_from(["jlcat", "--explode", "orders", "--explode", "orders.items"]);
+        assert_eq!(cli.explode, vec!["orders", "orders.items"]);
+    }
+
     #[test]
     fn test_array_limit() {
         let cli = Cli::parse_from(["jlcat", "--flat", "--array-limit=5"]);
@@ -126,7 +927,7 @@ mod tests {
     fn test_skip_limit_parse() {
         let cli = Cli::parse_from(["jlcat", "--skip", "5", "--limit", "10"]);
         assert_eq!(cli.skip, Some(5));
-        assert_eq!(cli.limit, Some(10));
+        assert_eq!(cli.limit, Some(Limit::Rows(10)));
         assert_eq!(cli.tail, None);
     }
 
@@ -136,6 +937,218 @@ mod tests {
         assert_eq!(cli.tail, Some(25));
         assert_eq!(cli.skip, None);
         assert_eq!(cli.limit, None);
+        assert!(Cli::try_parse_from(["jlcat", "--tail", "5", "--limit-bytes", "1M"]).is_err());
+    }
+
+    #[test]
+    fn test_descending_sort_key_parse() {
+        let cli = Cli::parse_from(["jlcat", "-s", "-age,name", "-c", "name"]);
+        assert_eq!(cli.sort, Some(vec!["-age".to_string(), "name".to_string()]));
+        assert_eq!(cli.columns, Some(vec!["name".to_string()]));
+    }
+
+    #[test]
+    fn test_shuffle_parse() {
+        let cli = Cli::parse_from(["jlcat", "--shuffle", "--seed", "42"]);
+        assert!(cli.shuffle);
+        assert_eq!(cli.seed, Some(42));
+        assert!(Cli::try_parse_from(["jlcat", "--seed", "42"]).is_err());
+        assert!(Cli::try_parse_from(["jlcat", "--shuffle", "-s", "id"]).is_err());
+    }
+
+    #[test]
+    fn test_limit_by_size_parse() {
+        let cli = Cli::parse_from(["jlcat", "--limit", "12.5%", "--limit-bytes", "50M"]);
+        assert_eq!(cli.limit, Some(Limit::Percent(12.5)));
+        assert_eq!(cli.limit_bytes, Some(50 << 20));
+        for bad in ["0%", "150%", "x%", "-1"] {
+            assert!(
+                Cli::try_parse_from(["jlcat", "--limit", bad]).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_transform_cmd_parse() {
+        let cli = Cli::parse_from(["jlcat", "--transform-cmd", "python enrich.py"]);
+        assert_eq!(cli.transform_cmd.as_deref(), Some("python enrich.py"));
+        assert_eq!(cli.transform_batch, 1000);
+    }
+
+    #[test]
+    fn test_children_summary_requires_recursive() {
+        assert!(Cli::try_parse_from(["jlcat", "--children-summary"]).is_err());
+        let cli = Cli::parse_from(["jlcat", "-r", "--children-summary"]);
+        assert!(cli.children_summary);
+    }
+
+    #[test]
+    fn test_parent_key_parse() {
+        let cli = Cli::parse_from(["jlcat", "-r", "--parent-key", "id,region"]);
+        assert_eq!(cli.parent_key, vec!["id", "region"]);
+        assert!(Cli::try_parse_from(["jlcat", "--parent-key", "id"]).is_err());
+    }
+
+    #[test]
+    fn test_children_parse() {
+        let cli = Cli::parse_from(["jlcat", "-r", "--children", "orders,address.*"]);
+        assert_eq!(
+            cli.children,
+            Some(vec!["orders".to_string(), "address.*".to_string()])
+        );
+        assert!(Cli::try_parse_from(["jlcat", "--children", "orders"]).is_err());
+    }
+
+    #[test]
+    fn test_out_dir_parse() {
+        let cli = Cli::parse_from(["jlcat", "-r", "--out-dir", "out", "--output", "jsonl"]);
+        assert_eq!(cli.out_dir, Some(PathBuf::from("out")));
+        assert_eq!(cli.output, Some(OutputFormat::Jsonl));
+        assert_eq!(Cli::parse_from(["jlcat", "--out-dir", "out"]).output, None);
+        assert!(Cli::try_parse_from(["jlcat", "-i", "--out-dir", "out"]).is_err());
+    }
+
+    #[test]
+    fn test_output_parse() {
+        let cli = Cli::parse_from(["jlcat", "-r", "--output", "markdown"]);
+        assert_eq!(cli.output, Some(OutputFormat::Markdown));
+        assert!(Cli::try_parse_from(["jlcat", "-i", "--output", "csv"]).is_err());
+        assert!(Cli::try_parse_from(["jlcat", "--vertical", "--output", "csv"]).is_err());
+    }
+
+    #[test]
+    fn test_child_limit_parse() {
+        let cli = Cli::parse_from(["jlcat", "-r", "--child-limit", "20"]);
+        assert_eq!(
+            cli.child_limit,
+            Some(ChildLimit {
+                rows: Some(20),
+                tables: vec![],
+            })
+        );
+        let cli = Cli::parse_from(["jlcat", "-r", "--child-limit", "orders=5, 20"]);
+        assert_eq!(
+            cli.child_limit,
+            Some(ChildLimit {
+                rows: Some(20),
+                tables: vec![("orders".to_string(), 5)],
+            })
+        );
+        assert!(Cli::try_parse_from(["jlcat", "-r", "--child-limit", "=5"]).is_err());
+        assert!(Cli::try_parse_from(["jlcat", "-r", "--child-limit", "orders=x"]).is_err());
+        assert!(Cli::try_parse_from(["jlcat", "--child-limit", "20"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("64k"), Ok(64 << 10));
+        assert_eq!(parse_size("512M"), Ok(512 << 20));
+        assert_eq!(parse_size("2GB"), Ok(2 << 30));
+        assert_eq!(parse_size("1GiB"), Ok(1 << 30));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("1.5G").is_err());
+        assert!(parse_size("12X").is_err());
+    }
+
+    #[test]
+    fn test_nested_parse() {
+        let nested = |arg: &str| Cli::try_parse_from(["jlcat", "--nested", arg]).map(|c| c.nested);
+        assert_eq!(
+            nested("inline").unwrap(),
+            Some(NestedMode::Inline(DEFAULT_INLINE_WIDTH))
+        );
+        assert_eq!(nested("inline:12").unwrap(), Some(NestedMode::Inline(12)));
+        assert_eq!(
+            nested("placeholder").unwrap(),
+            Some(NestedMode::Placeholder)
+        );
+        assert!(nested("inline:0").is_err());
+        assert!(nested("inline:x").is_err());
+        assert!(nested("json").is_err());
+    }
+
+    #[test]
+    fn test_labels_parse() {
+        let cli = Cli::parse_from(["jlcat", "--null-label", "-", "--bool-labels", "✓,✗"]);
+        assert_eq!(cli.null_label.as_deref(), Some("-"));
+        assert_eq!(
+            cli.bool_labels,
+            Some(BoolLabels {
+                true_label: "✓".to_string(),
+                false_label: "✗".to_string(),
+            })
+        );
+        assert!(Cli::try_parse_from(["jlcat", "--bool-labels", "yes"]).is_err());
+        assert!(Cli::try_parse_from(["jlcat", "--bool-labels", "a,b,c"]).is_err());
+    }
+
+    #[test]
+    fn test_max_col_width_parse() {
+        let cli = Cli::parse_from(["jlcat", "--max-col-width", "40"]);
+        assert_eq!(cli.max_col_width, Some(40));
+    }
+
+    #[test]
+    fn test_color_parse() {
+        let cli = Cli::parse_from(["jlcat"]);
+        assert_eq!(cli.color, ColorMode::Auto);
+        let cli = Cli::parse_from(["jlcat", "--color=never"]);
+        assert_eq!(cli.color, ColorMode::Never);
+    }
+
+    #[test]
+    fn test_elide_parse() {
+        let cli = Cli::parse_from(["jlcat", "--max-col-width", "12", "--elide", "middle"]);
+        assert_eq!(cli.elide, Some(ElideMode::Middle));
+        assert!(Cli::try_parse_from(["jlcat", "--elide", "middle"]).is_err());
+    }
+
+    #[test]
+    fn test_wrap_parse() {
+        let cli = Cli::parse_from(["jlcat", "--wrap", "--max-col-width", "30"]);
+        assert!(cli.wrap);
+        assert_eq!(cli.max_col_width, Some(30));
+    }
+
+    #[test]
+    fn test_date_format_parse() {
+        let cli = Cli::parse_from([
+            "jlcat",
+            "--date-format",
+            "%Y-%m-%d",
+            "--date-format",
+            "ts=%H:%M",
+        ]);
+        assert_eq!(cli.date_format, vec!["%Y-%m-%d", "ts=%H:%M"]);
+    }
+
+    #[test]
+    fn test_epoch_unit_parse() {
+        let cli = Cli::parse_from(["jlcat", "--epoch-unit", "ms", "--epoch-unit", "ts=ns"]);
+        assert_eq!(cli.epoch_unit, vec!["ms", "ts=ns"]);
+    }
+
+    #[test]
+    fn test_relative_time_parse() {
+        let cli = Cli::parse_from(["jlcat", "--relative-time", "ts,seen_at"]);
+        assert_eq!(cli.relative_time, vec!["ts", "seen_at"]);
+    }
+
+    #[test]
+    fn test_tz_parse() {
+        let cli = Cli::parse_from(["jlcat", "--tz", "Asia/Tokyo"]);
+        assert_eq!(cli.tz.as_deref(), Some("Asia/Tokyo"));
+    }
+
+    #[test]
+    fn test_level_parse() {
+        let cli = Cli::parse_from(["jlcat", "--level", "warn+", "--level-field", "sev"]);
+        assert_eq!(cli.level.as_deref(), Some("warn+"));
+        assert_eq!(cli.level_field.as_deref(), Some("sev"));
     }
 
     #[test]
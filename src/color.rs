@@ -0,0 +1,86 @@
+//! Central color policy for all output: tables, warnings, and highlights.
+//!
+//! `--color` sets the mode once at startup; `auto` colors only terminals and
+//! honors the `NO_COLOR` convention (<https://no-color.org>).
+
+use crate::cli::ColorMode;
+use std::ffi::OsString;
+use std::sync::OnceLock;
+
+static MODE: OnceLock<ColorMode> = OnceLock::new();
+
+/// Set the color mode for the process. Later calls are ignored.
+pub fn init(mode: ColorMode) {
+    let _ = MODE.set(mode);
+}
+
+/// Whether standard output should be colored
+pub fn stdout_enabled() -> bool {
+    enabled(atty::Stream::Stdout)
+}
+
+/// Whether standard error should be colored
+pub fn stderr_enabled() -> bool {
+    enabled(atty::Stream::Stderr)
+}
+
+fn enabled(stream: atty::Stream) -> bool {
+    let mode = MODE.get().copied().unwrap_or_default();
+    decide(mode, std::env::var_os("NO_COLOR"), atty::is(stream))
+}
+
+fn decide(mode: ColorMode, no_color: Option<OsString>, is_tty: bool) -> bool {
+    match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        // Any non-empty NO_COLOR disables color
+        ColorMode::Auto => is_tty && no_color.is_none_or(|v| v.is_empty()),
+    }
+}
+
+/// Print a warning to stderr, highlighting the label when color is enabled
+pub fn warn(message: &str) {
+    if stderr_enabled() {
+        eprintln!("jlcat: \x1b[33mwarning\x1b[0m: {}", message);
+    } else {
+        eprintln!("jlcat: warning: {}", message);
+    }
+}
+
+/// Print an error message to stderr, in red when colored
+pub fn error(message: &str) {
+    if stderr_enabled() {
+        eprintln!("jlcat: \x1b[31merror\x1b[0m: {}", message);
+    } else {
+        eprintln!("jlcat: error: {}", message);
+    }
+}
+
+/// Print an informational message to stderr
+pub fn note(message: &str) {
+    eprintln!("jlcat: {}", message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide_auto() {
+        assert!(decide(ColorMode::Auto, None, true));
+        assert!(!decide(ColorMode::Auto, None, false));
+    }
+
+    #[test]
+    fn test_decide_no_color() {
+        assert!(!decide(ColorMode::Auto, Some("1".into()), true));
+        // An empty NO_COLOR is treated as unset
+        assert!(decide(ColorMode::Auto, Some("".into()), true));
+    }
+
+    #[test]
+    fn test_decide_explicit_modes_win() {
+        assert!(decide(ColorMode::Always, Some("1".into()), false));
+        assert!(!decide(ColorMode::Never, None, true));
+    }
+}
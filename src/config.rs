@@ -0,0 +1,379 @@
+use crate::cli::{ColorMode, TableStyle};
+use crate::core::{FormatRule, Formatter};
+use crate::error::{JlcatError, Result};
+use crate::render::tui::Keymap;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the config file, used when `--config` is not given
+pub const CONFIG_ENV: &str = "JLCAT_CONFIG";
+
+/// User configuration loaded from `config.toml`
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    /// Conditional formatting rules, applied in order
+    #[serde(default)]
+    pub format: Vec<FormatRuleConfig>,
+    /// Option defaults, overridden by command-line flags
+    #[serde(default)]
+    pub defaults: Defaults,
+    /// Extra TUI key bindings, from action name to key
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
+    /// Default columns for input files matching a pattern, first match wins
+    #[serde(default)]
+    pub columns: Vec<ColumnsConfig>,
+    /// Named option sets selected with `--profile`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// The `[defaults]` table in the config file
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Defaults {
+    pub style: Option<TableStyle>,
+    pub array_limit: Option<usize>,
+    pub color: Option<ColorMode>,
+}
+
+/// A `[profiles.<name>]` table: options applied together by `--profile <name>`
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Profile {
+    pub columns: Option<Vec<String>>,
+    /// Filter expression (e.g., "level=error status>=500")
+    pub filter: Option<String>,
+    pub sort: Option<Vec<String>>,
+    pub style: Option<TableStyle>,
+    /// `--date-format` values, each `PATTERN` or `COLUMN=PATTERN`
+    pub date_format: Option<Vec<String>>,
+}
+
+/// A `[[columns]]` entry in the config file
+#[derive(Debug, Deserialize)]
+pub struct ColumnsConfig {
+    /// Glob with `*` and `?` (e.g., "access-*.jsonl"), matched against the file
+    /// name, or against the whole path when it contains `/`
+    pub pattern: String,
+    pub columns: Vec<String>,
+}
+
+/// A `[[format]]` entry in the config file
+#[derive(Debug, Deserialize)]
+pub struct FormatRuleConfig {
+    /// Filter expression that selects the rows to style (e.g., "latency>1000")
+    pub when: String,
+    /// Style spec (e.g., "bold red", "white on blue")
+    pub style: String,
+    /// Columns to style; the whole row is styled when omitted
+    #[serde(default)]
+    pub columns: Vec<String>,
+}
+
+impl Config {
+    /// Load the config from an explicit path, then `$JLCAT_CONFIG`, or from the
+    /// default location if present
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = path.map(Path::to_path_buf).or_else(|| {
+            std::env::var_os(CONFIG_ENV)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        });
+        match path {
+            Some(path) => Self::from_file(&path),
+            None => match Self::default_path() {
+                Some(path) if path.is_file() => Self::from_file(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    /// `$XDG_CONFIG_HOME/jlcat/config.toml`, falling back to `~/.config/jlcat/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("jlcat").join("config.toml"))
+    }
+
+    fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content).map_err(|e| match e {
+            JlcatError::Config(msg) => JlcatError::Config(format!("{}: {}", path.display(), msg)),
+            other => other,
+        })
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| JlcatError::Config(e.to_string()))
+    }
+
+    /// Columns of the first `[[columns]]` entry whose pattern matches `file`
+    pub fn columns_for(&self, file: &Path) -> Option<&[String]> {
+        let path = file.to_string_lossy();
+        let name = file
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        self.columns
+            .iter()
+            .find(|entry| {
+                let text = if entry.pattern.contains('/') {
+                    &path
+                } else {
+                    &name
+                };
+                glob_match(&entry.pattern, text)
+            })
+            .map(|entry| entry.columns.as_slice())
+    }
+
+    /// The profile called `name`
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| {
+            let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            JlcatError::Config(if names.is_empty() {
+                format!("unknown profile '{}': no profiles in the config file", name)
+            } else {
+                format!(
+                    "unknown profile '{}' (available: {})",
+                    name,
+                    names.join(", ")
+                )
+            })
+        })
+    }
+
+    /// Build the TUI key bindings from the `[keys]` table
+    pub fn keymap(&self) -> Result<Keymap> {
+        Keymap::parse(&self.keys)
+    }
+
+    /// Build the conditional formatter from `[[format]]` rules
+    pub fn formatter(&self) -> Result<Formatter> {
+        let rules: Result<Vec<_>> = self
+            .format
+            .iter()
+            .map(|r| FormatRule::parse(&r.when, &r.style, r.columns.clone()))
+            .collect();
+        Ok(Formatter::new(rules?))
+    }
+}
+
+/// Load a `--header-map` file: TOML mapping column names to the labels shown
+/// for them. Tables nest as dot paths, so `[user]` with `name = "Name"` maps
+/// the `user.name` column.
+pub fn load_header_map(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path)?;
+    parse_header_map(&content)
+        .map_err(|msg| JlcatError::Config(format!("{}: {}", path.display(), msg)))
+}
+
+fn parse_header_map(content: &str) -> std::result::Result<HashMap<String, String>, String> {
+    fn collect(
+        prefix: &str,
+        table: toml::Table,
+        map: &mut HashMap<String, String>,
+    ) -> std::result::Result<(), String> {
+        for (key, value) in table {
+            let column = if prefix.is_empty() {
+                key
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            match value {
+                toml::Value::String(label) => {
+                    map.insert(column, label);
+                }
+                toml::Value::Table(table) => collect(&column, table, map)?,
+                _ => return Err(format!("label of '{}' must be a string", column)),
+            }
+        }
+        Ok(())
+    }
+
+    let table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    let mut map = HashMap::new();
+    collect("", table, &mut map)?;
+    Ok(map)
+}
+
+/// Match `text` against a glob where `*` is any run of characters and `?` one character
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Last `*` seen and the text position it currently stands for
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((sp, st)) => {
+                    p = sp + 1;
+                    t = st + 1;
+                    star = Some((sp, st + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty_config() {
+        let config = Config::parse("").unwrap();
+        assert!(config.format.is_empty());
+        assert!(config.formatter().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_format_rules() {
+        let config = Config::parse(
+            r#"
+[[format]]
+when = "latency>1000"
+style = "bold red"
+columns = ["latency"]
+
+[[format]]
+when = "level=error"
+style = "red"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.format.len(), 2);
+        assert_eq!(config.format[0].columns, vec!["latency"]);
+        assert!(config.format[1].columns.is_empty());
+        assert!(!config.formatter().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_rule_rejected() {
+        let config = Config::parse(
+            r#"
+[[format]]
+when = "latency>1000"
+style = "sparkly"
+"#,
+        )
+        .unwrap();
+
+        assert!(config.formatter().is_err());
+    }
+
+    #[test]
+    fn test_parse_defaults_keys_and_columns() {
+        let config = Config::parse(
+            r#"
+[defaults]
+style = "psql"
+array-limit = 5
+color = "never"
+
+[keys]
+search = "s"
+
+[[columns]]
+pattern = "access-*.jsonl"
+columns = ["ts", "path", "status"]
+
+[[columns]]
+pattern = "logs/*.jsonl"
+columns = ["level", "msg"]
+"#,
+        )
+        .unwrap();
+
+        assert!(matches!(config.defaults.style, Some(TableStyle::Psql)));
+        assert_eq!(config.defaults.array_limit, Some(5));
+        assert_eq!(config.defaults.color, Some(ColorMode::Never));
+        assert!(config.keymap().is_ok());
+
+        let columns = config.columns_for(Path::new("/var/log/access-2024.jsonl"));
+        assert_eq!(columns.unwrap(), ["ts", "path", "status"]);
+        let columns = config.columns_for(Path::new("logs/app.jsonl"));
+        assert_eq!(columns.unwrap(), ["level", "msg"]);
+        assert!(config.columns_for(Path::new("other.jsonl")).is_none());
+    }
+
+    #[test]
+    fn test_unknown_default_rejected() {
+        assert!(Config::parse("[defaults]\nstlye = \"psql\"\n").is_err());
+        assert!(Config::parse("[defaults]\nstyle = \"fancy\"\n").is_err());
+
+        let config = Config::parse("[keys]\nfly = \"x\"\n").unwrap();
+        assert!(config.keymap().is_err());
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let config = Config::parse(
+            r#"
+[profiles.k8s-logs]
+columns = ["ts", "pod", "msg"]
+filter = "level=error"
+sort = ["-ts"]
+style = "plain"
+date-format = ["%H:%M:%S"]
+"#,
+        )
+        .unwrap();
+
+        let profile = config.profile("k8s-logs").unwrap();
+        assert_eq!(profile.filter.as_deref(), Some("level=error"));
+        assert_eq!(profile.sort, Some(vec!["-ts".to_string()]));
+        assert!(matches!(profile.style, Some(TableStyle::Plain)));
+
+        let err = config.profile("nginx").unwrap_err();
+        assert!(err.to_string().contains("available: k8s-logs"));
+        assert!(Config::parse("[profiles.x]\ncolumn = [\"a\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_header_map() {
+        let map = parse_header_map(
+            r#"
+t_lat_ms = "Latency (ms)"
+"req.id" = "Request"
+[user]
+nm = "User name"
+"#,
+        )
+        .unwrap();
+        assert_eq!(map["t_lat_ms"], "Latency (ms)");
+        assert_eq!(map["req.id"], "Request");
+        assert_eq!(map["user.nm"], "User name");
+        assert!(parse_header_map("id = 1").is_err());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.jsonl", "a.jsonl"));
+        assert!(glob_match("a?c*", "abcdef"));
+        assert!(glob_match("*-*.log", "app-1.log"));
+        assert!(!glob_match("*.jsonl", "a.json"));
+        assert!(!glob_match("a?c", "ac"));
+    }
+
+    #[test]
+    fn test_invalid_toml_rejected() {
+        assert!(Config::parse("[[format]\n").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use super::path::CompiledPath;
+use crate::error::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Which row of a group of rows with equal keys survives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Keep {
+    #[default]
+    First,
+    Last,
+}
+
+/// Keeps one row per distinct combination of key values ("latest record per
+/// entity" after sorting by time with `Keep::Last`)
+#[derive(Debug, Clone)]
+pub struct DistinctOn {
+    keys: Vec<CompiledPath>,
+    keep: Keep,
+}
+
+impl DistinctOn {
+    pub fn new(columns: &[String], keep: Keep) -> Result<Self> {
+        let keys = columns
+            .iter()
+            .map(|column| CompiledPath::compile(column))
+            .collect::<Result<_>>()?;
+        Ok(Self { keys, keep })
+    }
+
+    /// Drop rows whose keys repeat an earlier (or, keeping the last, a later) row.
+    /// Surviving rows stay in their original order; missing keys count as null.
+    pub fn apply(&self, rows: Vec<Value>) -> Vec<Value> {
+        let keys: Vec<String> = rows.iter().map(|row| self.key(row)).collect();
+        let mut chosen: HashMap<&str, usize> = HashMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            match self.keep {
+                Keep::First => {
+                    chosen.entry(key).or_insert(i);
+                }
+                Keep::Last => {
+                    chosen.insert(key, i);
+                }
+            }
+        }
+
+        rows.into_iter()
+            .enumerate()
+            .filter(|(i, _)| chosen[keys[*i].as_str()] == *i)
+            .map(|(_, row)| row)
+            .collect()
+    }
+
+    fn key(&self, row: &Value) -> String {
+        let values: Vec<&Value> = self
+            .keys
+            .iter()
+            .map(|path| path.get(row).unwrap_or(&Value::Null))
+            .collect();
+        serde_json::to_string(&values).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ids(rows: &[Value]) -> Vec<i64> {
+        rows.iter().map(|r| r["id"].as_i64().unwrap()).collect()
+    }
+
+    fn rows() -> Vec<Value> {
+        vec![
+            json!({"id": 1, "user": "a", "region": "eu"}),
+            json!({"id": 2, "user": "b", "region": "eu"}),
+            json!({"id": 3, "user": "a", "region": "us"}),
+            json!({"id": 4, "user": "a", "region": "eu"}),
+            json!({"id": 5}),
+            json!({"id": 6, "user": null}),
+        ]
+    }
+
+    #[test]
+    fn test_keep_first() {
+        let distinct = DistinctOn::new(&["user".into()], Keep::First).unwrap();
+        assert_eq!(ids(&distinct.apply(rows())), vec![1, 2, 5]);
+    }
+
+    #[test]
+    fn test_keep_last() {
+        let distinct = DistinctOn::new(&["user".into()], Keep::Last).unwrap();
+        assert_eq!(ids(&distinct.apply(rows())), vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn test_multiple_keys() {
+        let distinct = DistinctOn::new(&["user".into(), "region".into()], Keep::Last).unwrap();
+        assert_eq!(ids(&distinct.apply(rows())), vec![2, 3, 4, 6]);
+    }
+}
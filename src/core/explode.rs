@@ -0,0 +1,115 @@
+use crate::error::{JlcatError, Result};
+use serde_json::Value;
+
+/// Duplicates each row once per element of an array field ("unnest"),
+/// replacing the array with the element
+#[derive(Debug, Clone)]
+pub struct Explode {
+    path: Vec<String>,
+}
+
+impl Explode {
+    /// Parse a dot-separated field path such as `orders` or `user.orders`
+    pub fn parse(path: &str) -> Result<Self> {
+        let keys: Vec<String> = path.split('.').map(str::to_string).collect();
+        if keys.iter().any(|k| k.is_empty() || k.contains(['[', ']'])) {
+            return Err(JlcatError::InvalidColumnPath(format!(
+                "cannot explode '{}': expected a dot-separated field path",
+                path
+            )));
+        }
+        Ok(Self { path: keys })
+    }
+
+    /// Explode every row. Rows whose field is an empty array keep a single row
+    /// with the field set to null; rows without an array are passed through.
+    pub fn apply(&self, rows: Vec<Value>) -> Vec<Value> {
+        let mut exploded = Vec::with_capacity(rows.len());
+        for mut row in rows {
+            let elements = match self.field_mut(&mut row) {
+                Some(Value::Array(arr)) => std::mem::take(arr),
+                _ => {
+                    exploded.push(row);
+                    continue;
+                }
+            };
+
+            if elements.is_empty() {
+                exploded.push(self.with_field(row, Value::Null));
+                continue;
+            }
+            for element in elements {
+                exploded.push(self.with_field(row.clone(), element));
+            }
+        }
+        exploded
+    }
+
+    fn field_mut<'a>(&self, row: &'a mut Value) -> Option<&'a mut Value> {
+        self.path
+            .iter()
+            .try_fold(row, |value, key| value.as_object_mut()?.get_mut(key))
+    }
+
+    fn with_field(&self, mut row: Value, value: Value) -> Value {
+        if let Some(field) = self.field_mut(&mut row) {
+            *field = value;
+        }
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_explode_array_of_objects() {
+        let rows = vec![json!({"id": 1, "orders": [{"sku": "a"}, {"sku": "b"}]})];
+        let exploded = Explode::parse("orders").unwrap().apply(rows);
+
+        assert_eq!(
+            exploded,
+            vec![
+                json!({"id": 1, "orders": {"sku": "a"}}),
+                json!({"id": 1, "orders": {"sku": "b"}}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_explode_nested_path() {
+        let rows = vec![json!({"user": {"name": "Alice", "tags": ["x", "y"]}})];
+        let exploded = Explode::parse("user.tags").unwrap().apply(rows);
+
+        assert_eq!(exploded.len(), 2);
+        assert_eq!(exploded[1], json!({"user": {"name": "Alice", "tags": "y"}}));
+    }
+
+    #[test]
+    fn test_explode_keeps_rows_without_elements() {
+        let rows = vec![
+            json!({"id": 1, "orders": []}),
+            json!({"id": 2}),
+            json!({"id": 3, "orders": "n/a"}),
+        ];
+        let exploded = Explode::parse("orders").unwrap().apply(rows);
+
+        assert_eq!(
+            exploded,
+            vec![
+                json!({"id": 1, "orders": null}),
+                json!({"id": 2}),
+                json!({"id": 3, "orders": "n/a"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_explode_rejects_invalid_path() {
+        assert!(Explode::parse("").is_err());
+        assert!(Explode::parse("orders[0]").is_err());
+        assert!(Explode::parse("user..orders").is_err());
+    }
+}
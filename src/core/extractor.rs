@@ -1,5 +1,30 @@
+use super::value::{compare_numbers, placeholder};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// Which rows of each child table are shown (`--child-skip`, `--child-limit`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChildRows {
+    /// Rows left out at the start of every child table
+    pub skip: usize,
+    /// Rows shown per child table
+    pub limit: Option<usize>,
+    /// Limits for particular child tables by path, overriding `limit`
+    pub table_limits: HashMap<String, usize>,
+}
+
+impl ChildRows {
+    /// The rows of `child` to show, and how many rows follow them
+    pub fn apply(&self, name: &str, child: &ChildTable) -> (ChildTable, usize) {
+        let limit = self.table_limits.get(name).copied().or(self.limit);
+        let shown = child.window(self.skip, limit);
+        let rest = child
+            .rows
+            .len()
+            .saturating_sub(self.skip + shown.rows.len());
+        (shown, rest)
+    }
+}
 
 /// Represents an extracted child table from nested data
 #[derive(Debug, Clone)]
@@ -11,6 +36,10 @@ pub struct ChildTable {
     pub columns: Vec<String>,
     /// Rows with (parent_row_index, values)
     pub rows: Vec<(usize, Vec<Value>)>,
+    /// Fields identifying the parent row (`--parent-key`); empty to use the row index
+    pub parent_key: Vec<String>,
+    /// Parent key values by parent row index
+    pub parent_refs: HashMap<usize, Vec<Value>>,
 }
 
 impl ChildTable {
@@ -19,6 +48,8 @@ impl ChildTable {
             name,
             columns: Vec::new(),
             rows: Vec::new(),
+            parent_key: Vec::new(),
+            parent_refs: HashMap::new(),
         }
     }
 
@@ -26,24 +57,101 @@ impl ChildTable {
         self.rows.is_empty()
     }
 
-    /// Get columns with _parent_row prepended
+    /// Copy of this table keeping `limit` rows (or all) after the first `skip`
+    pub fn window(&self, skip: usize, limit: Option<usize>) -> ChildTable {
+        ChildTable {
+            name: self.name.clone(),
+            columns: self.columns.clone(),
+            rows: self
+                .rows
+                .iter()
+                .skip(skip)
+                .take(limit.unwrap_or(usize::MAX))
+                .cloned()
+                .collect(),
+            parent_key: self.parent_key.clone(),
+            parent_refs: self.parent_refs.clone(),
+        }
+    }
+
+    /// Summarize as one row per parent: the number of child rows,
+    /// plus min/max of every column holding numbers
+    pub fn summarize(&self) -> ChildTable {
+        let numeric: Vec<usize> = (0..self.columns.len())
+            .filter(|&i| {
+                self.rows
+                    .iter()
+                    .any(|(_, values)| matches!(values.get(i), Some(Value::Number(_))))
+            })
+            .collect();
+
+        let mut summary = ChildTable::new(self.name.clone());
+        summary.parent_key = self.parent_key.clone();
+        summary.parent_refs = self.parent_refs.clone();
+        summary.columns.push("count".to_string());
+        for &i in &numeric {
+            summary.columns.push(format!("min({})", self.columns[i]));
+            summary.columns.push(format!("max({})", self.columns[i]));
+        }
+
+        let mut groups: BTreeMap<usize, Vec<&[Value]>> = BTreeMap::new();
+        for (parent_idx, values) in &self.rows {
+            groups.entry(*parent_idx).or_default().push(values);
+        }
+
+        for (parent_idx, rows) in groups {
+            let mut values = vec![Value::Number(rows.len().into())];
+            for &i in &numeric {
+                let numbers = rows.iter().filter_map(|row| match row.get(i) {
+                    Some(Value::Number(n)) => Some(n),
+                    _ => None,
+                });
+                let min = numbers.clone().min_by(|a, b| compare_numbers(a, b));
+                let max = numbers.max_by(|a, b| compare_numbers(a, b));
+                values.push(min.map_or(Value::Null, |n| Value::Number(n.clone())));
+                values.push(max.map_or(Value::Null, |n| Value::Number(n.clone())));
+            }
+            summary.rows.push((parent_idx, values));
+        }
+
+        summary
+    }
+
+    /// Get columns with the parent reference prepended: `_parent_row`,
+    /// or `_parent_<field>` for each `--parent-key` field
     pub fn columns_with_parent(&self) -> Vec<String> {
-        let mut cols = vec!["_parent_row".to_string()];
+        let mut cols = if self.parent_key.is_empty() {
+            vec!["_parent_row".to_string()]
+        } else {
+            self.parent_key
+                .iter()
+                .map(|field| format!("_parent_{}", field))
+                .collect()
+        };
         cols.extend(self.columns.clone());
         cols
     }
 
-    /// Get rows as Vec<Vec<Value>> with parent row index as first column
+    /// Get rows as Vec<Vec<Value>> with the parent reference as first column(s)
     /// Pads short rows with nulls to match column count (for heterogeneous arrays)
     pub fn rows_with_parent(&self) -> Vec<Vec<Value>> {
-        let col_count = self.columns.len();
+        let width = self.parent_key.len().max(1) + self.columns.len();
         self.rows
             .iter()
             .map(|(parent_idx, values)| {
-                let mut row = vec![Value::Number((*parent_idx as i64).into())];
+                let mut row = if self.parent_key.is_empty() {
+                    vec![Value::Number((*parent_idx as i64).into())]
+                } else {
+                    self.parent_refs
+                        .get(parent_idx)
+                        .cloned()
+                        .unwrap_or_default()
+                };
+                // Parents without the key fields get nulls
+                row.resize(self.parent_key.len().max(1), Value::Null);
                 row.extend(values.clone());
                 // Pad with nulls if row is shorter than expected
-                while row.len() < col_count + 1 {
+                while row.len() < width {
                     row.push(Value::Null);
                 }
                 row
@@ -53,24 +161,60 @@ impl ChildTable {
 }
 
 /// Extracts nested objects and arrays from JSON rows into child tables
-pub struct NestedExtractor;
+#[derive(Debug, Clone, Default)]
+pub struct NestedExtractor {
+    /// Fields whose values identify a parent row in child tables
+    parent_key: Vec<String>,
+    /// Child table paths to keep (`orders`, or `orders.*` with descendants); all if `None`
+    children: Option<Vec<String>>,
+}
 
 impl NestedExtractor {
-    /// Extract all nested structures from rows (recursively)
-    /// Returns a map of field_path -> ChildTable
-    /// Nested structures use dotted paths (e.g., "user.address" for address inside user)
-    pub fn extract(rows: &[Value]) -> HashMap<String, ChildTable> {
+    /// Reference parent rows by the values of these fields instead of their
+    /// row index. Nested parents lacking the fields inherit their own parent's key.
+    pub fn with_parent_key(mut self, parent_key: Vec<String>) -> Self {
+        self.parent_key = parent_key;
+        self
+    }
+
+    /// Only produce child tables for these paths. `orders.*` also keeps
+    /// the tables nested under `orders`.
+    pub fn with_children(mut self, children: Option<Vec<String>>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// Extract all nested structures from rows (recursively), referencing
+    /// parents by `parent_key`. Returns a map of field path -> ChildTable;
+    /// nested structures use dotted paths (e.g., "user.address" for address
+    /// inside user)
+    pub fn extract_children(&self, rows: &[Value]) -> HashMap<String, ChildTable> {
         let mut children: HashMap<String, ChildTable> = HashMap::new();
+        let no_key = vec![Value::Null; self.parent_key.len()];
 
         for (row_idx, row) in rows.iter().enumerate() {
             if let Value::Object(obj) = row {
-                for (key, value) in obj {
+                let key = self.key_of(obj, &no_key);
+                for (field, value) in obj {
+                    if !self.walks(field) {
+                        continue;
+                    }
                     match value {
                         Value::Object(nested_obj) => {
-                            Self::extract_object_recursive(&mut children, key, row_idx, nested_obj);
+                            self.extract_object_recursive(
+                                &mut children,
+                                field,
+                                (row_idx, &key),
+                                nested_obj,
+                            );
                         }
                         Value::Array(arr) => {
-                            Self::extract_array_recursive(&mut children, key, row_idx, arr);
+                            self.extract_array_recursive(
+                                &mut children,
+                                field,
+                                (row_idx, &key),
+                                arr,
+                            );
                         }
                         _ => {}
                     }
@@ -78,25 +222,83 @@ impl NestedExtractor {
             }
         }
 
+        if self.children.is_some() {
+            children.retain(|path, _| self.includes(path));
+        }
         children
     }
 
+    /// Whether the child table at `path` was selected with `with_children`
+    fn includes(&self, path: &str) -> bool {
+        let Some(selected) = &self.children else {
+            return true;
+        };
+        selected.iter().any(|name| match name.strip_suffix(".*") {
+            Some(prefix) => path
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.')),
+            None => path == name,
+        })
+    }
+
+    /// Whether the walk must enter `path`: its table or one nested under it
+    /// was selected. Unselected ancestors are still walked, since their row
+    /// numbers are what selected descendants refer to.
+    fn walks(&self, path: &str) -> bool {
+        let Some(selected) = &self.children else {
+            return true;
+        };
+        self.includes(path)
+            || selected.iter().any(|name| {
+                name.strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('.'))
+            })
+    }
+
+    /// Key values of a row, or `inherited` when it lacks any key field
+    fn key_of(&self, obj: &serde_json::Map<String, Value>, inherited: &[Value]) -> Vec<Value> {
+        let own: Option<Vec<Value>> = self
+            .parent_key
+            .iter()
+            .map(|field| obj.get(field).filter(|v| !v.is_null()).cloned())
+            .collect();
+        own.unwrap_or_else(|| inherited.to_vec())
+    }
+
+    /// Create the child table for `path` on first use
+    fn child<'a>(
+        &self,
+        children: &'a mut HashMap<String, ChildTable>,
+        path: &str,
+    ) -> &'a mut ChildTable {
+        children.entry(path.to_string()).or_insert_with(|| {
+            let mut child = ChildTable::new(path.to_string());
+            child.parent_key = self.parent_key.clone();
+            child
+        })
+    }
+
     /// Extract a nested object into a child table row (recursively)
-    /// parent_row_idx is the row index in the immediate parent table
+    /// parent is the row index in the immediate parent table and that row's key
     fn extract_object_recursive(
+        &self,
         children: &mut HashMap<String, ChildTable>,
         path: &str,
-        parent_row_idx: usize,
+        parent: (usize, &[Value]),
         obj: &serde_json::Map<String, Value>,
     ) {
+        let (parent_row_idx, parent_ref) = parent;
         // Collect nested structures to process after releasing borrow
-        // (nested_path, value, this_row_idx in current child table)
-        let mut nested_to_process: Vec<(String, Value, usize)> = Vec::new();
+        // (nested_path, value, this_row_idx in current child table, this row's key)
+        let mut nested_to_process: Vec<(String, Value, usize, Vec<Value>)> = Vec::new();
 
         {
-            let child = children
-                .entry(path.to_string())
-                .or_insert_with(|| ChildTable::new(path.to_string()));
+            let child = self.child(children, path);
+            if !self.parent_key.is_empty() {
+                child
+                    .parent_refs
+                    .insert(parent_row_idx, parent_ref.to_vec());
+            }
 
             // Collect all keys from this object and add any new columns
             for obj_key in obj.keys() {
@@ -115,13 +317,22 @@ impl NestedExtractor {
             // Track this row's index in the child table for nested extractions
             let this_row_idx = child.rows.len();
             child.rows.push((parent_row_idx, values));
+            let this_ref = self.key_of(obj, parent_ref);
 
             // Collect nested structures for later processing
             for (key, value) in obj {
                 match value {
                     Value::Object(_) | Value::Array(_) => {
                         let nested_path = format!("{}.{}", path, key);
-                        nested_to_process.push((nested_path, value.clone(), this_row_idx));
+                        if !self.walks(&nested_path) {
+                            continue;
+                        }
+                        nested_to_process.push((
+                            nested_path,
+                            value.clone(),
+                            this_row_idx,
+                            this_ref.clone(),
+                        ));
                     }
                     _ => {}
                 }
@@ -130,40 +341,30 @@ impl NestedExtractor {
 
         // Now process nested structures (borrow released)
         // Pass this row's index as the parent row for nested children
-        for (nested_path, value, this_row_idx) in nested_to_process {
-            match &value {
-                Value::Object(nested_obj) => {
-                    Self::extract_object_recursive(
-                        children,
-                        &nested_path,
-                        this_row_idx,
-                        nested_obj,
-                    );
-                }
-                Value::Array(arr) => {
-                    Self::extract_array_recursive(children, &nested_path, this_row_idx, arr);
-                }
-                _ => {}
-            }
-        }
+        self.process_nested(children, nested_to_process);
     }
 
     /// Extract array elements into child table rows (recursively)
-    /// parent_row_idx is the row index in the immediate parent table
+    /// parent is the row index in the immediate parent table and that row's key
     fn extract_array_recursive(
+        &self,
         children: &mut HashMap<String, ChildTable>,
         path: &str,
-        parent_row_idx: usize,
+        parent: (usize, &[Value]),
         arr: &[Value],
     ) {
+        let (parent_row_idx, parent_ref) = parent;
         // Collect nested structures to process after releasing borrow
-        // (nested_path, value, this_row_idx in current child table)
-        let mut nested_to_process: Vec<(String, Value, usize)> = Vec::new();
+        // (nested_path, value, this_row_idx in current child table, this row's key)
+        let mut nested_to_process: Vec<(String, Value, usize, Vec<Value>)> = Vec::new();
 
         {
-            let child = children
-                .entry(path.to_string())
-                .or_insert_with(|| ChildTable::new(path.to_string()));
+            let child = self.child(children, path);
+            if !self.parent_key.is_empty() {
+                child
+                    .parent_refs
+                    .insert(parent_row_idx, parent_ref.to_vec());
+            }
 
             for element in arr {
                 match element {
@@ -185,16 +386,21 @@ impl NestedExtractor {
                         // Track this row's index for nested extractions
                         let this_row_idx = child.rows.len();
                         child.rows.push((parent_row_idx, values));
+                        let this_ref = self.key_of(obj, parent_ref);
 
                         // Collect nested structures for later processing
                         for (key, value) in obj {
                             match value {
                                 Value::Object(_) | Value::Array(_) => {
                                     let nested_path = format!("{}.{}", path, key);
+                                    if !self.walks(&nested_path) {
+                                        continue;
+                                    }
                                     nested_to_process.push((
                                         nested_path,
                                         value.clone(),
                                         this_row_idx,
+                                        this_ref.clone(),
                                     ));
                                 }
                                 _ => {}
@@ -216,7 +422,7 @@ impl NestedExtractor {
                             .iter()
                             .map(|col| {
                                 if col == "value" {
-                                    Value::String("[...]".to_string())
+                                    Value::String(placeholder(element))
                                 } else {
                                     Value::Null
                                 }
@@ -227,10 +433,14 @@ impl NestedExtractor {
 
                         // Queue the nested array for recursive processing
                         let nested_path = format!("{}.value", path);
+                        if !self.walks(&nested_path) {
+                            continue;
+                        }
                         nested_to_process.push((
                             nested_path,
                             Value::Array(inner_arr.clone()),
                             this_row_idx,
+                            parent_ref.to_vec(),
                         ));
                     }
                     _ => {
@@ -268,13 +478,32 @@ impl NestedExtractor {
 
         // Now process nested structures (borrow released)
         // Pass each row's index as the parent row for its nested children
-        for (nested_path, value, this_row_idx) in nested_to_process {
+        self.process_nested(children, nested_to_process);
+    }
+
+    /// Extract queued nested structures, each under the child row that holds it
+    fn process_nested(
+        &self,
+        children: &mut HashMap<String, ChildTable>,
+        nested: Vec<(String, Value, usize, Vec<Value>)>,
+    ) {
+        for (nested_path, value, this_row_idx, this_ref) in nested {
             match &value {
                 Value::Object(obj) => {
-                    Self::extract_object_recursive(children, &nested_path, this_row_idx, obj);
+                    self.extract_object_recursive(
+                        children,
+                        &nested_path,
+                        (this_row_idx, &this_ref),
+                        obj,
+                    );
                 }
                 Value::Array(arr) => {
-                    Self::extract_array_recursive(children, &nested_path, this_row_idx, arr);
+                    self.extract_array_recursive(
+                        children,
+                        &nested_path,
+                        (this_row_idx, &this_ref),
+                        arr,
+                    );
                 }
                 _ => {}
             }
@@ -284,8 +513,7 @@ impl NestedExtractor {
     /// Flatten a value for display in parent table (replace nested with placeholder)
     fn flatten_value(value: &Value) -> Value {
         match value {
-            Value::Object(_) => Value::String("{...}".to_string()),
-            Value::Array(_) => Value::String("[...]".to_string()),
+            Value::Object(_) | Value::Array(_) => Value::String(placeholder(value)),
             _ => value.clone(),
         }
     }
@@ -295,17 +523,7 @@ impl NestedExtractor {
         if let Value::Object(obj) = row {
             let mut flat = serde_json::Map::new();
             for (key, value) in obj {
-                match value {
-                    Value::Object(_) => {
-                        flat.insert(key.clone(), Value::String("{...}".to_string()));
-                    }
-                    Value::Array(_) => {
-                        flat.insert(key.clone(), Value::String("[...]".to_string()));
-                    }
-                    _ => {
-                        flat.insert(key.clone(), value.clone());
-                    }
-                }
+                flat.insert(key.clone(), Self::flatten_value(value));
             }
             Value::Object(flat)
         } else {
@@ -326,7 +544,7 @@ mod tests {
             json!({"id": 2, "address": {"city": "Osaka", "zip": "530"}}),
         ];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         assert!(children.contains_key("address"));
         let address = &children["address"];
@@ -345,7 +563,7 @@ mod tests {
             ]
         })];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         assert!(children.contains_key("orders"));
         let orders = &children["orders"];
@@ -362,7 +580,7 @@ mod tests {
     fn test_extract_primitive_array() {
         let rows = vec![json!({"id": 1, "tags": ["a", "b", "c"]})];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         assert!(children.contains_key("tags"));
         let tags = &children["tags"];
@@ -377,7 +595,7 @@ mod tests {
             json!({"id": 2, "items": [{"name": "B"}, {"name": "C"}]}),
         ];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
         let items = &children["items"];
 
         assert_eq!(items.rows.len(), 3); // 1 from row 0, 2 from row 1
@@ -399,8 +617,8 @@ mod tests {
 
         assert_eq!(flat["id"], json!(1));
         assert_eq!(flat["name"], json!("Alice"));
-        assert_eq!(flat["address"], json!("{...}"));
-        assert_eq!(flat["orders"], json!("[...]"));
+        assert_eq!(flat["address"], json!("{1 key}"));
+        assert_eq!(flat["orders"], json!("[3 items]"));
     }
 
     #[test]
@@ -410,7 +628,7 @@ mod tests {
             json!({"id": 2, "name": "Bob"}),
         ];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         assert!(children.is_empty());
     }
@@ -423,7 +641,7 @@ mod tests {
             json!({"id": 3, "meta": {"type": "B", "extra": true}}),
         ];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
         let meta = &children["meta"];
 
         assert_eq!(meta.rows.len(), 2); // Only rows with meta
@@ -434,7 +652,7 @@ mod tests {
     #[test]
     fn test_columns_with_parent() {
         let rows = vec![json!({"id": 1, "address": {"city": "Tokyo"}})];
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
         let address = &children["address"];
 
         let cols = address.columns_with_parent();
@@ -448,7 +666,7 @@ mod tests {
             json!({"id": 1, "items": [{"name": "A"}]}),
             json!({"id": 2, "items": [{"name": "B"}]}),
         ];
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
         let items = &children["items"];
 
         let rows_with_parent = items.rows_with_parent();
@@ -464,7 +682,7 @@ mod tests {
             "items": [{"name": "A"}, "B", {"name": "C"}]
         })];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
         let items = &children["items"];
 
         // Should have both "name" and "value" columns
@@ -516,7 +734,7 @@ mod tests {
             }
         })];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         // Should have child tables for all levels
         assert!(children.contains_key("user"), "Should have 'user' table");
@@ -558,7 +776,7 @@ mod tests {
             ]
         })];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         // Should have child tables for orders and orders.shipping
         assert!(
@@ -593,7 +811,7 @@ mod tests {
             }
         })];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         assert!(children.contains_key("data"), "Should have 'data' table");
         assert!(
@@ -624,7 +842,7 @@ mod tests {
             ]
         })];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         let orders = &children["orders"];
         let shipping = &children["orders.shipping"];
@@ -652,6 +870,114 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parent_key_columns() {
+        let rows = vec![
+            json!({"id": "a1", "items": [{"n": 1}, {"n": 2}]}),
+            json!({"id": "b2", "items": [{"n": 3}]}),
+        ];
+
+        let children = NestedExtractor::default()
+            .with_parent_key(vec!["id".into()])
+            .extract_children(&rows);
+
+        let items = &children["items"];
+        assert_eq!(items.columns_with_parent(), vec!["_parent_id", "n"]);
+        let rows = items.rows_with_parent();
+        assert_eq!(rows[0], vec![json!("a1"), json!(1)]);
+        assert_eq!(rows[2], vec![json!("b2"), json!(3)]);
+    }
+
+    #[test]
+    fn test_parent_key_composite_and_inherited() {
+        let rows = vec![json!({
+            "id": 7,
+            "region": "eu",
+            "orders": [
+                {"id": 100, "region": "us", "shipping": {"cost": 10}},
+                {"item": "Banana", "shipping": {"cost": 5}}
+            ]
+        })];
+
+        let extractor =
+            NestedExtractor::default().with_parent_key(vec!["id".into(), "region".into()]);
+        let children = extractor.extract_children(&rows);
+
+        let orders = children["orders"].rows_with_parent();
+        assert_eq!(orders[0][..2], [json!(7), json!("eu")]);
+
+        // Orders with their own key are referenced by it; others inherit the top-level key
+        let shipping = &children["orders.shipping"];
+        assert_eq!(
+            shipping.columns_with_parent(),
+            vec!["_parent_id", "_parent_region", "cost"]
+        );
+        let shipping = shipping.rows_with_parent();
+        assert_eq!(shipping[0], vec![json!(100), json!("us"), json!(10)]);
+        assert_eq!(shipping[1], vec![json!(7), json!("eu"), json!(5)]);
+    }
+
+    #[test]
+    fn test_parent_key_missing_is_null() {
+        let rows = vec![json!({"name": "x", "items": [1]})];
+        let children = NestedExtractor::default()
+            .with_parent_key(vec!["id".into()])
+            .extract_children(&rows);
+        let summary = children["items"].summarize();
+        assert_eq!(
+            summary.rows_with_parent()[0],
+            vec![Value::Null, json!(1), json!(1), json!(1)]
+        );
+    }
+
+    #[test]
+    fn test_with_children_filters_tables() {
+        let rows = vec![json!({
+            "id": 1,
+            "address": {"city": "Tokyo"},
+            "orders": [{"sku": "a", "shipping": {"cost": 10}}],
+            "meta": {"blob": [1, 2]}
+        })];
+
+        let only_orders = NestedExtractor::default()
+            .with_children(Some(vec!["orders".into()]))
+            .extract_children(&rows);
+        assert_eq!(only_orders.keys().collect::<Vec<_>>(), vec!["orders"]);
+
+        let mut with_descendants: Vec<_> = NestedExtractor::default()
+            .with_children(Some(vec!["orders.*".into(), "address".into()]))
+            .extract_children(&rows)
+            .into_keys()
+            .collect();
+        with_descendants.sort();
+        assert_eq!(
+            with_descendants,
+            vec!["address", "orders", "orders.shipping"]
+        );
+    }
+
+    #[test]
+    fn test_with_children_keeps_row_numbers_of_skipped_parents() {
+        let rows = vec![json!({
+            "orders": [
+                {"sku": "a", "lines": [{"n": 1}]},
+                {"sku": "b", "lines": [{"n": 2}, {"n": 3}]}
+            ],
+            "meta": {"deep": {"deeper": [1, 2]}}
+        })];
+        let children = NestedExtractor::default()
+            .with_children(Some(vec!["orders.lines".into()]))
+            .extract_children(&rows);
+
+        assert_eq!(children.keys().collect::<Vec<_>>(), vec!["orders.lines"]);
+        let parents: Vec<usize> = children["orders.lines"]
+            .rows
+            .iter()
+            .map(|(parent, _)| *parent)
+            .collect();
+        assert_eq!(parents, vec![0, 1, 1]);
+    }
+
     #[test]
     fn test_deep_nested_parent_mapping() {
         // Test 3-level nesting: user -> address -> coordinates
@@ -678,7 +1004,7 @@ mod tests {
             }),
         ];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         let user = &children["user"];
         let address = &children["user.address"];
@@ -708,7 +1034,7 @@ mod tests {
             "data": [[1, 2, 3], [4, 5, 6]]
         })];
 
-        let children = NestedExtractor::extract(&rows);
+        let children = NestedExtractor::default().extract_children(&rows);
 
         // Should have a 'data' table for the outer array
         assert!(children.contains_key("data"), "Should have 'data' table");
@@ -718,15 +1044,15 @@ mod tests {
         assert_eq!(data.rows.len(), 2, "data table should have 2 rows");
         assert_eq!(data.columns, vec!["value"]);
 
-        // Each row in data should have "[...]" placeholder since they are arrays
+        // Each row in data should have a placeholder since they are arrays
         assert_eq!(
             data.rows[0].1[0],
-            Value::String("[...]".to_string()),
+            Value::String("[3 items]".to_string()),
             "First inner array should be placeholder"
         );
         assert_eq!(
             data.rows[1].1[0],
-            Value::String("[...]".to_string()),
+            Value::String("[3 items]".to_string()),
             "Second inner array should be placeholder"
         );
 
@@ -767,4 +1093,67 @@ mod tests {
         assert_eq!(data_value.rows[5].0, 1);
         assert_eq!(data_value.rows[5].1[0], json!(6));
     }
+
+    #[test]
+    fn test_summarize_child_table() {
+        let rows = vec![
+            json!({"id": 1, "orders": [{"sku": "a", "price": 10}, {"sku": "b", "price": 2.5}]}),
+            json!({"id": 2, "orders": [{"sku": "c", "price": 7}]}),
+            json!({"id": 3, "orders": []}),
+        ];
+
+        let children = NestedExtractor::default().extract_children(&rows);
+        let summary = children["orders"].summarize();
+
+        assert_eq!(summary.columns, vec!["count", "min(price)", "max(price)"]);
+        assert_eq!(summary.rows.len(), 2);
+        assert_eq!(summary.rows[0], (0, vec![json!(2), json!(2.5), json!(10)]));
+        assert_eq!(summary.rows[1], (1, vec![json!(1), json!(7), json!(7)]));
+    }
+
+    #[test]
+    fn test_child_table_window() {
+        let rows = vec![json!({"items": [{"n": 1}, {"n": 2}, {"n": 3}]})];
+        let children = NestedExtractor::default().extract_children(&rows);
+
+        let head = children["items"].window(0, Some(2));
+        assert_eq!(head.rows.len(), 2);
+        assert_eq!(head.columns, vec!["n"]);
+        assert_eq!(children["items"].window(0, Some(10)).rows.len(), 3);
+        assert_eq!(
+            children["items"].window(2, None).rows,
+            vec![(0, vec![json!(3)])]
+        );
+    }
+
+    #[test]
+    fn test_child_rows_per_table_limits() {
+        let rows = vec![
+            json!({"items": [{"n": 1}, {"n": 2}, {"n": 3}, {"n": 4}], "tags": ["a", "b", "c"]}),
+        ];
+        let children = NestedExtractor::default().extract_children(&rows);
+        let child_rows = ChildRows {
+            skip: 1,
+            limit: Some(1),
+            table_limits: HashMap::from([("items".to_string(), 2)]),
+        };
+
+        let (items, rest) = child_rows.apply("items", &children["items"]);
+        assert_eq!(
+            items
+                .rows
+                .iter()
+                .map(|r| r.1[0].clone())
+                .collect::<Vec<_>>(),
+            vec![json!(2), json!(3)]
+        );
+        assert_eq!(rest, 1);
+
+        let (tags, rest) = child_rows.apply("tags", &children["tags"]);
+        assert_eq!(tags.rows.len(), 1);
+        assert_eq!(rest, 1);
+
+        let (all, rest) = ChildRows::default().apply("tags", &children["tags"]);
+        assert_eq!((all.rows.len(), rest), (3, 0));
+    }
 }
@@ -0,0 +1,264 @@
+//! External merge sort for row sets larger than memory: rows are sorted in
+//! runs that fit, each run is written to a temp file as JSONL, and the runs
+//! are merged k ways into the output. With more runs than can be open at
+//! once, groups of them are first merged into longer runs.
+
+use super::sorter::Sorter;
+use crate::error::{JlcatError, Result};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use tempfile::{NamedTempFile, TempPath};
+
+/// Most runs merged at once, which bounds the files open during a merge
+const MERGE_FAN_IN: usize = 64;
+
+/// Sorts rows pushed one at a time, holding at most `run_rows` in memory
+pub struct ExternalSort {
+    sorter: Sorter,
+    run_rows: usize,
+    /// Rows of the run being filled
+    buffer: Vec<Value>,
+    /// Sorted runs written so far, in input order; closed until merged
+    runs: Vec<TempPath>,
+    fan_in: usize,
+}
+
+impl ExternalSort {
+    pub fn new(sorter: Sorter, run_rows: usize) -> Self {
+        Self {
+            sorter,
+            run_rows: run_rows.max(1),
+            buffer: Vec::new(),
+            runs: Vec::new(),
+            fan_in: MERGE_FAN_IN,
+        }
+    }
+
+    /// Add a row, writing a sorted run once `run_rows` are buffered
+    pub fn push(&mut self, row: Value) -> Result<()> {
+        self.buffer.push(row);
+        if self.buffer.len() >= self.run_rows {
+            self.write_run()?;
+        }
+        Ok(())
+    }
+
+    /// Number of runs written to disk so far
+    #[allow(dead_code)]
+    pub fn run_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Write every row to `out` as JSONL in sorted order; returns the row count.
+    /// Rows that compare equal keep their input order.
+    pub fn finish<W: Write>(mut self, out: W) -> Result<usize> {
+        // Everything fit in one run: no need to go through disk
+        if self.runs.is_empty() {
+            let mut out = BufWriter::new(out);
+            self.sorter.sort(&mut self.buffer);
+            for row in &self.buffer {
+                write_row(&mut out, row)?;
+            }
+            out.flush()?;
+            return Ok(self.buffer.len());
+        }
+        if !self.buffer.is_empty() {
+            self.write_run()?;
+        }
+
+        // Consecutive runs are merged together, so equal rows stay in input
+        // order from one pass to the next
+        while self.runs.len() > self.fan_in {
+            let mut merged = Vec::with_capacity(self.runs.len().div_ceil(self.fan_in));
+            for group in self.runs.chunks(self.fan_in) {
+                let mut file = NamedTempFile::new()?;
+                self.merge(group, file.as_file_mut())?;
+                merged.push(file.into_temp_path());
+            }
+            self.runs = merged;
+        }
+        self.merge(&self.runs, out)
+    }
+
+    /// Merge sorted `runs` into `out` as JSONL; returns the row count
+    fn merge<W: Write>(&self, runs: &[TempPath], out: W) -> Result<usize> {
+        let mut out = BufWriter::new(out);
+        let mut written = 0;
+
+        let mut readers = Vec::with_capacity(runs.len());
+        for run in runs {
+            readers.push(BufReader::new(File::open(run)?).lines());
+        }
+
+        let mut heap = BinaryHeap::with_capacity(readers.len());
+        for (run, reader) in readers.iter_mut().enumerate() {
+            if let Some(row) = next_row(reader)? {
+                heap.push(Head {
+                    row,
+                    run,
+                    sorter: &self.sorter,
+                });
+            }
+        }
+        while let Some(Head { row, run, sorter }) = heap.pop() {
+            write_row(&mut out, &row)?;
+            written += 1;
+            if let Some(row) = next_row(&mut readers[run])? {
+                heap.push(Head { row, run, sorter });
+            }
+        }
+        out.flush()?;
+        Ok(written)
+    }
+
+    /// Sort the buffered rows and write them to a new temp file
+    fn write_run(&mut self) -> Result<()> {
+        self.sorter.sort(&mut self.buffer);
+        let mut file = NamedTempFile::new()?;
+        {
+            let mut out = BufWriter::new(file.as_file_mut());
+            for row in self.buffer.drain(..) {
+                write_row(&mut out, &row)?;
+            }
+            out.flush()?;
+        }
+        self.runs.push(file.into_temp_path());
+        Ok(())
+    }
+}
+
+/// The next row of a run, in the order it was written
+struct Head<'a> {
+    row: Value,
+    /// Index of the run, breaking ties so equal rows keep input order
+    run: usize,
+    sorter: &'a Sorter,
+}
+
+impl Ord for Head<'_> {
+    // BinaryHeap pops the greatest entry, so the order is reversed
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorter
+            .compare(&self.row, &other.row)
+            .then(self.run.cmp(&other.run))
+            .reverse()
+    }
+}
+
+impl PartialOrd for Head<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head<'_> {}
+
+fn write_row<W: Write>(out: &mut W, row: &Value) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, row)?;
+    out.write_all(b"\n")
+}
+
+fn next_row(lines: &mut Lines<BufReader<File>>) -> Result<Option<Value>> {
+    match lines.next() {
+        Some(line) => serde_json::from_str(&line?)
+            .map(Some)
+            .map_err(|e| JlcatError::Io(e.into())),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sorted(rows: Vec<Value>, keys: &[&str], run_rows: usize) -> (Vec<Value>, usize) {
+        let keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+        let mut sort = ExternalSort::new(Sorter::parse(&keys).unwrap(), run_rows);
+        for row in rows {
+            sort.push(row).unwrap();
+        }
+        let runs = sort.run_count();
+        let mut out = Vec::new();
+        sort.finish(&mut out).unwrap();
+        let rows = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        (rows, runs)
+    }
+
+    #[test]
+    fn test_merges_runs_in_order() {
+        let rows: Vec<Value> = [5, 3, 9, 1, 7, 2, 8]
+            .iter()
+            .map(|n| json!({"n": n}))
+            .collect();
+        let (rows, runs) = sorted(rows, &["n"], 2);
+        assert_eq!(runs, 3);
+        let ns: Vec<i64> = rows.iter().map(|r| r["n"].as_i64().unwrap()).collect();
+        assert_eq!(ns, vec![1, 2, 3, 5, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_equal_rows_keep_input_order() {
+        let rows = vec![
+            json!({"k": 1, "id": "a"}),
+            json!({"k": 0, "id": "b"}),
+            json!({"k": 1, "id": "c"}),
+            json!({"k": 0, "id": "d"}),
+            json!({"k": 1, "id": "e"}),
+        ];
+        let (rows, _) = sorted(rows, &["-k"], 2);
+        let ids: Vec<&str> = rows.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["a", "c", "e", "b", "d"]);
+    }
+
+    #[test]
+    fn test_merges_in_passes_beyond_fan_in() {
+        // Ten runs of two rows, merged three at a time: 10 -> 4 -> 2 -> 1
+        let mut sort = ExternalSort::new(Sorter::parse(&["k".to_string()]).unwrap(), 2);
+        sort.fan_in = 3;
+        for i in 0..20 {
+            sort.push(json!({"k": (i * 7) % 4, "id": i})).unwrap();
+        }
+        assert_eq!(sort.run_count(), 10);
+
+        let mut out = Vec::new();
+        assert_eq!(sort.finish(&mut out).unwrap(), 20);
+        let rows: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let keys: Vec<i64> = rows.iter().map(|r| r["k"].as_i64().unwrap()).collect();
+        assert!(keys.windows(2).all(|w| w[0] <= w[1]), "{:?}", keys);
+        // Equal keys keep input order across passes
+        for k in 0..4 {
+            let ids: Vec<i64> = rows
+                .iter()
+                .filter(|r| r["k"] == k)
+                .map(|r| r["id"].as_i64().unwrap())
+                .collect();
+            assert!(ids.windows(2).all(|w| w[0] < w[1]), "{:?}", ids);
+        }
+    }
+
+    #[test]
+    fn test_single_run_stays_in_memory() {
+        let rows = vec![json!({"n": 2}), json!({"n": 1})];
+        let (rows, runs) = sorted(rows, &["n"], 10);
+        assert_eq!(runs, 0);
+        assert_eq!(rows, vec![json!({"n": 1}), json!({"n": 2})]);
+    }
+}
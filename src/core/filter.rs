@@ -1,4 +1,5 @@
-use super::path::CompiledPath;
+use super::path::{path_len, CompiledPath, PathFn};
+use super::value::compare_decimal_str;
 use crate::error::{JlcatError, Result};
 use serde_json::Value;
 
@@ -34,14 +35,37 @@ impl FilterOp {
 pub struct FilterCondition {
     pub column: String,
     pub path: CompiledPath,
+    /// Compare a function of the value at `path`, such as `len(path)`
+    pub function: Option<PathFn>,
     pub op: FilterOp,
     pub value: String,
+    /// `~` and `!~` only match whole words
+    pub whole_word: bool,
 }
 
 impl FilterCondition {
+    /// Whether the row matches; with a path matching several values, whether any does
     fn matches(&self, row: &Value) -> bool {
-        let row_value = self.path.get(row);
+        let computed: Vec<Value>;
+        let mut values = self.path.get_all(row);
+        if let Some(function) = self.function {
+            computed = values
+                .into_iter()
+                .filter_map(|v| function.apply(v))
+                .collect();
+            values = computed.iter().collect();
+        }
+
+        if self.path.is_multi_valued() {
+            values
+                .into_iter()
+                .any(|value| self.matches_value(Some(value)))
+        } else {
+            self.matches_value(values.first().copied())
+        }
+    }
 
+    fn matches_value(&self, row_value: Option<&Value>) -> bool {
         match &self.op {
             FilterOp::Eq => self.matches_eq(row_value),
             FilterOp::Ne => !self.matches_eq(row_value),
@@ -58,6 +82,10 @@ impl FilterCondition {
         match row_value {
             Some(Value::String(s)) => s == &self.value,
             Some(Value::Number(n)) => {
+                // Exact decimal comparison handles large integers and high precision
+                if let Some(ord) = compare_decimal_str(&n.to_string(), &self.value) {
+                    return ord == std::cmp::Ordering::Equal;
+                }
                 // Try numeric comparison first for consistency with >/< operators
                 if let Ok(filter_num) = self.value.parse::<f64>() {
                     if let Some(row_num) = n.as_f64() {
@@ -85,6 +113,9 @@ impl FilterCondition {
 
         match row_value {
             Some(Value::Number(n)) => {
+                if let Some(ord) = compare_decimal_str(&n.to_string(), &self.value) {
+                    return predicate(ord);
+                }
                 if let Some(row_num) = n.as_f64() {
                     predicate(
                         row_num
@@ -95,6 +126,8 @@ impl FilterCondition {
                     false
                 }
             }
+            // Numbers logged as strings ("42") compare as numbers
+            Some(Value::String(s)) => compare_decimal_str(s, &self.value).is_some_and(predicate),
             _ => false,
         }
     }
@@ -102,8 +135,14 @@ impl FilterCondition {
     fn matches_contains(&self, row_value: Option<&Value>) -> bool {
         let search_lower = self.value.to_lowercase();
         match row_value {
-            Some(Value::String(s)) => s.to_lowercase().contains(&search_lower),
-            Some(v) => v.to_string().to_lowercase().contains(&search_lower),
+            Some(Value::String(s)) => {
+                contains_text(&s.to_lowercase(), &search_lower, self.whole_word)
+            }
+            Some(v) => contains_text(
+                &v.to_string().to_lowercase(),
+                &search_lower,
+                self.whole_word,
+            ),
             None => false,
         }
     }
@@ -129,16 +168,12 @@ impl FilterExpr {
                 break;
             }
 
-            // Parse column name
-            let mut column = String::new();
-            while let Some(&c) = chars.peek() {
-                if c == '=' || c == '!' || c == '>' || c == '<' || c == '~' {
-                    break;
-                }
-                if c == ' ' {
-                    break;
-                }
-                column.push(chars.next().unwrap());
+            // Parse column name, which may quote or escape operator characters
+            let rest: String = chars.clone().collect();
+            let len = path_len(&rest, |c| matches!(c, '=' | '!' | '>' | '<' | '~' | ' '));
+            let column = rest[..len].to_string();
+            for _ in column.chars() {
+                chars.next();
             }
 
             if column.is_empty() {
@@ -226,18 +261,39 @@ impl FilterExpr {
                 val
             };
 
-            let path = CompiledPath::compile(&column)?;
+            let (function, path) = PathFn::parse(&column);
+            if function == Some(PathFn::Type)
+                && matches!(op, FilterOp::Eq | FilterOp::Ne)
+                && !PathFn::TYPE_NAMES.contains(&value.as_str())
+            {
+                return Err(JlcatError::InvalidFilter(format!(
+                    "unknown type '{}' (expected one of: {})",
+                    value,
+                    PathFn::TYPE_NAMES.join(", ")
+                )));
+            }
+            let path = CompiledPath::compile(path)?;
             conditions.push(FilterCondition {
+                function,
                 column,
                 path,
                 op,
                 value,
+                whole_word: false,
             });
         }
 
         Ok(Self { conditions })
     }
 
+    /// Make `~` and `!~` match whole words only, so `id` doesn't match `valid`
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        for condition in &mut self.conditions {
+            condition.whole_word = whole_word;
+        }
+        self
+    }
+
     pub fn matches(&self, row: &Value) -> bool {
         self.conditions.iter().all(|c| c.matches(row))
     }
@@ -246,31 +302,79 @@ impl FilterExpr {
 #[derive(Debug, Clone)]
 pub struct FullTextSearch {
     query: String,
+    /// A column and the text to search in it only (`status:error`)
+    column: Option<(CompiledPath, String)>,
+    whole_word: bool,
 }
 
 impl FullTextSearch {
+    /// A case-insensitive search of every value in a row, or of one column when the
+    /// query starts with a column path and `:`.
+    ///
+    /// Rows without that column are searched whole for the entire query, so text
+    /// that merely contains a colon (`12:30`) still finds matches.
     pub fn new(query: &str) -> Self {
+        let split = path_len(query, |c| c == ':');
+        let column = query[split..].strip_prefix(':').and_then(|text| {
+            let column = &query[..split];
+            if column.contains(char::is_whitespace) {
+                return None;
+            }
+            let path = CompiledPath::compile(column).ok()?;
+            Some((path, text.to_lowercase()))
+        });
+
         Self {
             query: query.to_lowercase(),
+            column,
+            whole_word: false,
         }
     }
 
+    /// Only match whole words, so `id` doesn't match `valid`
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
     pub fn matches(&self, row: &Value) -> bool {
-        self.search_value(row)
+        if let Some((path, text)) = &self.column {
+            let values = path.get_all(row);
+            if !values.is_empty() {
+                return values
+                    .into_iter()
+                    .any(|value| self.search_value(value, text));
+            }
+        }
+        self.search_value(row, &self.query)
     }
 
-    fn search_value(&self, value: &Value) -> bool {
+    fn search_value(&self, value: &Value, query: &str) -> bool {
         match value {
-            Value::String(s) => s.to_lowercase().contains(&self.query),
-            Value::Number(n) => n.to_string().contains(&self.query),
-            Value::Bool(b) => b.to_string().contains(&self.query),
-            Value::Array(arr) => arr.iter().any(|v| self.search_value(v)),
-            Value::Object(obj) => obj.values().any(|v| self.search_value(v)),
+            Value::String(s) => contains_text(&s.to_lowercase(), query, self.whole_word),
+            Value::Number(n) => contains_text(&n.to_string(), query, self.whole_word),
+            Value::Bool(b) => contains_text(&b.to_string(), query, self.whole_word),
+            Value::Array(arr) => arr.iter().any(|v| self.search_value(v, query)),
+            Value::Object(obj) => obj.values().any(|v| self.search_value(v, query)),
             Value::Null => false,
         }
     }
 }
 
+/// Whether `haystack` contains `needle`, optionally only where it isn't part of a
+/// longer word (letters, digits and `_`)
+fn contains_text(haystack: &str, needle: &str, whole_word: bool) -> bool {
+    if !whole_word || needle.is_empty() {
+        return haystack.contains(needle);
+    }
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -389,6 +493,58 @@ mod tests {
         assert!(!search.matches(&json!({"city": "Osaka"})));
     }
 
+    #[test]
+    fn test_fulltext_search_column_scoped() {
+        let search = FullTextSearch::new("status:error");
+        assert!(search.matches(&json!({"status": "Error", "msg": "ok"})));
+        assert!(!search.matches(&json!({"status": "ok", "msg": "error"})));
+        // Rows without the column are searched whole for the entire query
+        assert!(!search.matches(&json!({"msg": "error"})));
+        assert!(search.matches(&json!({"msg": "status:error"})));
+
+        let nested = FullTextSearch::new("user.name:ali");
+        assert!(nested.matches(&json!({"user": {"name": "Alice"}})));
+        // Flattened TUI rows key nested columns by their full name
+        assert!(nested.matches(&json!({"user.name": "Alice"})));
+
+        // No usable column prefix searches the whole row
+        assert!(FullTextSearch::new("12:30").matches(&json!({"at": "12:30"})));
+        assert!(FullTextSearch::new("error").matches(&json!({"msg": "Error"})));
+        assert!(FullTextSearch::new("a b:c").matches(&json!({"msg": "a b:c"})));
+        assert!(FullTextSearch::new(":x").matches(&json!({"msg": ":x"})));
+    }
+
+    #[test]
+    fn test_whole_word() {
+        assert!(contains_text("valid id", "id", true));
+        assert!(!contains_text("valid", "id", true));
+        assert!(contains_text("valid", "id", false));
+        assert!(contains_text("user_id=7, id=8", "id", true));
+        assert!(!contains_text("user_id", "id", true));
+        assert!(contains_text("(id)", "id", true));
+
+        let search = FullTextSearch::new("id").whole_word(true);
+        assert!(!search.matches(&json!({"status": "valid"})));
+        assert!(search.matches(&json!({"tags": ["an id"]})));
+
+        let expr = FilterExpr::parse("msg~id").unwrap().whole_word(true);
+        assert!(!expr.matches(&json!({"msg": "invalid"})));
+        assert!(expr.matches(&json!({"msg": "missing id"})));
+        let expr = FilterExpr::parse("msg!~id").unwrap().whole_word(true);
+        assert!(expr.matches(&json!({"msg": "invalid"})));
+    }
+
+    #[test]
+    fn test_compare_numeric_strings() {
+        let expr = FilterExpr::parse("age>30").unwrap();
+        assert!(expr.matches(&json!({"age": "42"})));
+        assert!(!expr.matches(&json!({"age": "7"})));
+        assert!(!expr.matches(&json!({"age": "unknown"})));
+        assert!(FilterExpr::parse("price<=1e2")
+            .unwrap()
+            .matches(&json!({"price": " 99.5 "})));
+    }
+
     #[test]
     fn test_numeric_equality() {
         // Integer filter should match float representation
@@ -418,6 +574,21 @@ mod tests {
         assert!(expr.matches(&json!({"age": 31})));
     }
 
+    #[test]
+    fn test_large_integer_comparison() {
+        let row: Value = serde_json::from_str(r#"{"id": 9007199254740993}"#).unwrap();
+
+        assert!(FilterExpr::parse("id=9007199254740993")
+            .unwrap()
+            .matches(&row));
+        assert!(!FilterExpr::parse("id=9007199254740992")
+            .unwrap()
+            .matches(&row));
+        assert!(FilterExpr::parse("id>9007199254740992")
+            .unwrap()
+            .matches(&row));
+    }
+
     #[test]
     fn test_escaped_quotes_in_filter() {
         // Escaped double quotes within double-quoted value
@@ -439,6 +610,84 @@ mod tests {
         assert!(expr.matches(&json!({"path": r"C:\Users\Alice"})));
     }
 
+    #[test]
+    fn test_quoted_and_escaped_keys_in_filter() {
+        let row = json!({"meta": {"a.b": 3, "x=y": "on"}});
+        let expr = FilterExpr::parse(r#"meta["a.b"]>2 meta['x=y']=on"#).unwrap();
+        assert_eq!(expr.conditions.len(), 2);
+        assert_eq!(expr.conditions[1].column, "meta['x=y']");
+        assert!(expr.matches(&row));
+
+        assert!(FilterExpr::parse(r"meta.x\=y=on").unwrap().matches(&row));
+    }
+
+    #[test]
+    fn test_wildcard_filter_matches_any_element() {
+        let row = json!({"orders": [{"total": 10}, {"total": 250}]});
+        assert!(FilterExpr::parse("orders[*].total>100")
+            .unwrap()
+            .matches(&row));
+        assert!(FilterExpr::parse("orders[*].total=10")
+            .unwrap()
+            .matches(&row));
+        assert!(!FilterExpr::parse("orders[*].total>500")
+            .unwrap()
+            .matches(&row));
+        assert!(!FilterExpr::parse("orders[*].total>0")
+            .unwrap()
+            .matches(&json!({"orders": []})));
+    }
+
+    #[test]
+    fn test_recursive_descent_filter() {
+        let row = json!({"a": {"b": [{"status": "ok"}, {"status": "failed"}]}});
+        assert!(FilterExpr::parse("..status=failed").unwrap().matches(&row));
+        assert!(!FilterExpr::parse("..status=pending").unwrap().matches(&row));
+    }
+
+    #[test]
+    fn test_len_filter() {
+        let row = json!({"items": [1, 2, 3, 4, 5, 6], "tags": {"a": 1}, "name": "x"});
+        assert!(FilterExpr::parse("len(items)>5").unwrap().matches(&row));
+        assert!(!FilterExpr::parse("len(items)<5").unwrap().matches(&row));
+        assert!(FilterExpr::parse("len(tags)=1").unwrap().matches(&row));
+        // Values that aren't collections have no length
+        assert!(!FilterExpr::parse("len(name)>0").unwrap().matches(&row));
+        assert!(!FilterExpr::parse("len(missing)>=0").unwrap().matches(&row));
+
+        let orders = json!({"orders": [{"items": [1]}, {"items": [1, 2, 3]}]});
+        assert!(FilterExpr::parse("len(orders[*].items)=3")
+            .unwrap()
+            .matches(&orders));
+    }
+
+    #[test]
+    fn test_type_filter() {
+        let rows = [
+            json!({"v": "a"}),
+            json!({"v": 1}),
+            json!({"v": {"x": 1}}),
+            json!({"v": [1]}),
+            json!({"v": null}),
+            json!({"v": false}),
+            json!({}),
+        ];
+        let matching = |expr: &str| {
+            let expr = FilterExpr::parse(expr).unwrap();
+            rows.iter().filter(|row| expr.matches(row)).count()
+        };
+        assert_eq!(matching("type(v)=string"), 1);
+        assert_eq!(matching("type(v)=number"), 1);
+        assert_eq!(matching("type(v)=object"), 1);
+        assert_eq!(matching("type(v)=array"), 1);
+        assert_eq!(matching("type(v)=null"), 1);
+        assert_eq!(matching("type(v)=boolean"), 1);
+        assert_eq!(matching("type(v)!=string"), 6);
+
+        let err = FilterExpr::parse("type(v)=str").unwrap_err();
+        assert!(err.to_string().contains("unknown type 'str'"));
+    }
+
     #[test]
     fn test_unrecognized_escape_preserved() {
         // Unrecognized escape sequences preserve the backslash
@@ -1,6 +1,19 @@
+use super::selector::ColumnSelector;
+use super::value::placeholder;
 use serde_json::Value;
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 
+/// How arrays are shown in flat mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayExpansion {
+    /// One column with elements joined by commas
+    #[default]
+    Join,
+    /// One column per element: `tags[0]`, `tags[1]`, ...
+    Index,
+}
+
 /// Configuration for flat mode
 #[derive(Debug, Clone)]
 pub struct FlatConfig {
@@ -8,20 +21,29 @@ pub struct FlatConfig {
     pub depth: Option<usize>,
     /// Maximum array elements to display
     pub array_limit: usize,
+    /// How arrays are expanded
+    pub arrays: ArrayExpansion,
 }
 
 impl FlatConfig {
     pub fn new(depth: Option<usize>, array_limit: usize) -> Self {
-        Self { depth, array_limit }
+        Self {
+            depth,
+            array_limit,
+            arrays: ArrayExpansion::default(),
+        }
+    }
+
+    /// Choose how arrays are expanded
+    pub fn with_arrays(mut self, arrays: ArrayExpansion) -> Self {
+        self.arrays = arrays;
+        self
     }
 }
 
 impl Default for FlatConfig {
     fn default() -> Self {
-        Self {
-            depth: None,
-            array_limit: 3,
-        }
+        Self::new(None, 3)
     }
 }
 
@@ -69,8 +91,8 @@ impl FlatSchema {
         }
 
         if is_child {
-            // Extract parent from path (e.g., "user.name" -> "user")
-            if let Some(dot_pos) = path.find('.') {
+            // Extract parent from path (e.g., "user.name" -> "user", "tags[0]" -> "tags")
+            if let Some(dot_pos) = path.find(['.', '[']) {
                 let parent = &path[..dot_pos];
 
                 // Add parent to first-level order if not present
@@ -82,7 +104,7 @@ impl FlatSchema {
                 let children = self.children.entry(parent.to_string()).or_default();
                 if !children.contains(&path) {
                     children.push(path);
-                    children.sort();
+                    children.sort_by(|a, b| compare_paths(a, b));
                 }
             }
         } else {
@@ -105,16 +127,6 @@ impl FlatSchema {
         self.dynamic_columns.contains(path)
     }
 
-    /// Check if a key has children (was expanded as an object)
-    pub fn has_children(&self, key: &str) -> bool {
-        self.children.contains_key(key)
-    }
-
-    /// Check if a column path exists in the schema
-    pub fn contains_column(&self, path: &str) -> bool {
-        self.all_columns.contains(path)
-    }
-
     /// Get columns in proper order:
     /// - First-level keys in appearance order
     /// - Children sorted alphabetically under their parent's position
@@ -147,6 +159,34 @@ impl Default for FlatSchema {
     }
 }
 
+/// Order column paths alphabetically, except that array indices compare
+/// numerically (`tags[2]` before `tags[10]`)
+fn compare_paths(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split(['[', ']']);
+    let mut b_parts = b.split(['[', ']']);
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (Some(x), Some(y)) => {
+                let ord = match (x.parse::<usize>(), y.parse::<usize>()) {
+                    (Ok(i), Ok(j)) => i.cmp(&j),
+                    _ => x.cmp(y),
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            (x, y) => return x.is_some().cmp(&y.is_some()),
+        }
+    }
+}
+
+/// Whether a flattened column sits under `parent` (`user.name` or `tags[0]` under `user`/`tags`)
+fn is_child_of(column: &str, parent: &str) -> bool {
+    column
+        .strip_prefix(parent)
+        .is_some_and(|rest| rest.starts_with(['.', '[']))
+}
+
 /// Table data with flattened structure
 #[derive(Debug, Clone)]
 pub struct FlatTableData {
@@ -154,157 +194,105 @@ pub struct FlatTableData {
     rows: Vec<Vec<Value>>,
     #[allow(dead_code)]
     config: FlatConfig,
+    /// Columns chosen with `-c`, replacing the schema's columns
+    selected: Option<Vec<String>>,
 }
 
 impl FlatTableData {
-    /// Build flat table data from JSON rows
+    /// Build flat table data from JSON rows in a single pass.
+    ///
+    /// Each row is flattened straight into cells indexed by column id, with ids
+    /// assigned as columns are discovered. Display order is applied once at the
+    /// end by moving cells into place.
     pub fn from_rows(rows: &[Value], config: FlatConfig) -> Self {
-        let mut schema = FlatSchema::new();
-        let mut flat_rows: Vec<HashMap<String, Value>> = Vec::new();
-
-        // First pass: build schema from all rows in first chunk
-        // We process original JSON to preserve key order
-        for row in rows {
-            let flattened = flatten_object(row, &config);
-
-            // Add columns by traversing original JSON structure (preserves order)
-            Self::add_columns_from_json(&mut schema, row, "", 0, &config);
-
-            flat_rows.push(flattened);
-        }
-
+        let mut builder = FlatBuilder::new(&config);
+        let cells: Vec<Vec<Option<Value>>> =
+            rows.iter().map(|row| builder.flatten_row(row)).collect();
+        let FlatBuilder {
+            mut schema, index, ..
+        } = builder;
         schema.finalize_initial_schema();
 
-        // Second pass: handle conflicts and build final rows
         let columns = schema.columns();
-        let mut result_rows: Vec<Vec<Value>> = Vec::new();
-
-        for (idx, row) in rows.iter().enumerate() {
-            let flattened = &flat_rows[idx];
-            let mut result_row: Vec<Value> = Vec::new();
-
-            for col in &columns {
-                if let Some(value) = flattened.get(col) {
-                    result_row.push(value.clone());
-                } else {
-                    // Check for structure conflict
-                    let original_value = Self::get_original_value(row, col);
-                    match original_value {
-                        Some(Value::Object(_)) => {
-                            // Object where we expected scalar - show {...}
-                            result_row.push(Value::String("{...}".to_string()));
-                        }
-                        Some(v) if !col.contains('.') => {
-                            // Scalar value for parent column
-                            result_row.push(v.clone());
-                        }
-                        _ => {
-                            result_row.push(Value::Null);
-                        }
-                    }
-                }
-            }
-
-            result_rows.push(result_row);
-        }
-
-        // Handle dynamic column additions (object->scalar conflicts)
-        let mut final_schema = schema.clone();
-        for row in rows.iter() {
-            if let Value::Object(obj) = row {
-                for (key, value) in obj {
-                    // If this key was expanded but current row has scalar
-                    if final_schema.has_children(key) && !matches!(value, Value::Object(_)) {
-                        // Need to add parent column dynamically
-                        if !final_schema.contains_column(key) {
-                            final_schema.add_column(key.clone(), false);
-                        }
-                    }
-                }
-            }
-        }
-
-        // Rebuild rows if schema changed
-        if final_schema.columns().len() != columns.len() {
-            let new_columns = final_schema.columns();
-            let mut new_rows: Vec<Vec<Value>> = Vec::new();
-
-            for (idx, row) in rows.iter().enumerate() {
-                let flattened = &flat_rows[idx];
-                let mut result_row: Vec<Value> = Vec::new();
-
-                for col in &new_columns {
-                    if let Some(value) = flattened.get(col) {
-                        result_row.push(value.clone());
-                    } else {
-                        let original_value = Self::get_original_value(row, col);
-                        match original_value {
-                            Some(Value::Object(_)) => {
-                                result_row.push(Value::String("{...}".to_string()));
-                            }
-                            Some(v) if !col.contains('.') => {
-                                result_row.push(v.clone());
-                            }
-                            _ => {
-                                result_row.push(Value::Null);
-                            }
-                        }
-                    }
-                }
-
-                new_rows.push(result_row);
-            }
-
-            return Self {
-                schema: final_schema,
-                rows: new_rows,
-                config,
-            };
-        }
+        let order: Vec<usize> = columns.iter().map(|c| index[c.as_str()]).collect();
+
+        let result_rows = cells
+            .into_iter()
+            .zip(rows)
+            .map(|(mut cells, row)| {
+                order
+                    .iter()
+                    .zip(&columns)
+                    .map(
+                        |(&id, col)| match cells.get_mut(id).and_then(Option::take) {
+                            Some(value) => value,
+                            None => Self::missing_value(row, col),
+                        },
+                    )
+                    .collect()
+            })
+            .collect();
 
         Self {
             schema,
             rows: result_rows,
             config,
+            selected: None,
         }
     }
 
-    /// Recursively add columns from JSON structure while preserving key order
-    fn add_columns_from_json(
-        schema: &mut FlatSchema,
-        value: &Value,
-        prefix: &str,
-        depth: usize,
-        config: &FlatConfig,
-    ) {
-        if let Value::Object(obj) = value {
-            for (key, val) in obj {
-                let full_key = if prefix.is_empty() {
-                    key.clone()
-                } else {
-                    format!("{}.{}", prefix, key)
-                };
+    /// Cell for a column the row has no flattened value for, handling
+    /// structure conflicts (a key that is scalar in some rows, nested in others)
+    fn missing_value(row: &Value, col: &str) -> Value {
+        match Self::get_original_value(row, col) {
+            // Object where we expected scalar - show a placeholder
+            Some(obj @ Value::Object(_)) => Value::String(placeholder(obj)),
+            // Scalar value for parent column
+            Some(v) if !col.contains('.') => v.clone(),
+            _ => Value::Null,
+        }
+    }
 
-                match val {
-                    Value::Object(_) => {
-                        // Check depth limit
-                        if config.depth.is_none_or(|max| depth < max) {
-                            // Expand the object - recurse but don't add parent as column
-                            Self::add_columns_from_json(schema, val, &full_key, depth + 1, config);
-                        } else {
-                            // Depth limit reached - add as leaf column
-                            let is_child = full_key.contains('.');
-                            schema.add_column(full_key, is_child);
-                        }
-                    }
-                    _ => {
-                        // Scalar or array - add as column
-                        let is_child = full_key.contains('.');
-                        schema.add_column(full_key, is_child);
-                    }
-                }
+    /// Restrict and order the flattened columns by a column selector.
+    /// `user.*` or `user` selects every column under `user`; other names must
+    /// match a flattened column and are empty when absent.
+    pub fn select(mut self, selector: &ColumnSelector) -> Self {
+        let columns = self.columns();
+        let mut selected: Vec<(String, Option<usize>)> = Vec::new();
+
+        for name in selector.columns() {
+            let prefix = name.strip_suffix(".*").unwrap_or(name);
+            let exact = columns.iter().position(|c| c == name);
+            if exact.is_some() && prefix == name {
+                selected.push((name.to_string(), exact));
+                continue;
+            }
+
+            let children: Vec<usize> = columns
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| is_child_of(c, prefix))
+                .map(|(i, _)| i)
+                .collect();
+            if children.is_empty() {
+                selected.push((name.to_string(), exact));
+            } else {
+                selected.extend(children.into_iter().map(|i| (columns[i].clone(), Some(i))));
             }
         }
+
+        self.rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                selected
+                    .iter()
+                    .map(|(_, idx)| idx.map_or(Value::Null, |i| row[i].clone()))
+                    .collect()
+            })
+            .collect();
+        self.selected = Some(selected.into_iter().map(|(name, _)| name).collect());
+        self
     }
 
     fn get_original_value<'a>(row: &'a Value, path: &str) -> Option<&'a Value> {
@@ -324,7 +312,10 @@ impl FlatTableData {
     }
 
     pub fn columns(&self) -> Vec<String> {
-        self.schema.columns()
+        match self.selected {
+            Some(ref columns) => columns.clone(),
+            None => self.schema.columns(),
+        }
     }
 
     pub fn rows(&self) -> &[Vec<Value>] {
@@ -341,6 +332,96 @@ impl FlatTableData {
     }
 }
 
+/// Streams rows into per-row cells while growing the schema
+struct FlatBuilder<'a> {
+    config: &'a FlatConfig,
+    schema: FlatSchema,
+    /// Column path to column id, in discovery order
+    index: HashMap<String, usize>,
+    /// Path of the value being visited, reused across rows
+    key: String,
+}
+
+impl<'a> FlatBuilder<'a> {
+    fn new(config: &'a FlatConfig) -> Self {
+        Self {
+            config,
+            schema: FlatSchema::new(),
+            index: HashMap::new(),
+            key: String::new(),
+        }
+    }
+
+    /// Flatten a row into cells indexed by column id (`None` = no value)
+    fn flatten_row(&mut self, row: &Value) -> Vec<Option<Value>> {
+        let mut cells = Vec::with_capacity(self.index.len());
+        if let Value::Object(obj) = row {
+            self.add_object(obj, 0, &mut cells);
+        }
+        cells
+    }
+
+    fn add_object(
+        &mut self,
+        obj: &serde_json::Map<String, Value>,
+        depth: usize,
+        cells: &mut Vec<Option<Value>>,
+    ) {
+        for (key, value) in obj {
+            let len = self.key.len();
+            if len > 0 {
+                self.key.push('.');
+            }
+            self.key.push_str(key);
+            self.add_value(value, depth, cells);
+            self.key.truncate(len);
+        }
+    }
+
+    fn add_value(&mut self, value: &Value, depth: usize, cells: &mut Vec<Option<Value>>) {
+        match value {
+            Value::Object(obj) if self.config.depth.is_none_or(|max| depth < max) => {
+                self.add_object(obj, depth + 1, cells);
+            }
+            // Depth limit reached, use placeholder
+            Value::Object(_) => self.set(cells, Value::String(placeholder(value))),
+            Value::Array(arr) => match self.config.arrays {
+                ArrayExpansion::Join => {
+                    let formatted = format_array(value, self.config.array_limit);
+                    self.set(cells, Value::String(formatted));
+                }
+                ArrayExpansion::Index => {
+                    for (i, element) in arr.iter().take(self.config.array_limit).enumerate() {
+                        let len = self.key.len();
+                        self.key.push_str(&format!("[{}]", i));
+                        self.add_value(element, depth, cells);
+                        self.key.truncate(len);
+                    }
+                }
+            },
+            _ => self.set(cells, value.clone()),
+        }
+    }
+
+    /// Store a value for the current path, registering the column on first sight
+    fn set(&mut self, cells: &mut Vec<Option<Value>>, value: Value) {
+        let id = match self.index.get(self.key.as_str()) {
+            Some(&id) => id,
+            None => {
+                let id = self.index.len();
+                self.index.insert(self.key.clone(), id);
+                let is_child = self.key.contains(['.', '[']);
+                self.schema.add_column(self.key.clone(), is_child);
+                id
+            }
+        };
+        if cells.len() <= id {
+            cells.resize(id + 1, None);
+        }
+        cells[id] = Some(value);
+    }
+}
+
 /// Flatten a JSON object into dot-notation key-value pairs
 pub fn flatten_object(value: &Value, config: &FlatConfig) -> HashMap<String, Value> {
     let mut result = HashMap::new();
@@ -365,26 +446,42 @@ fn flatten_object_recursive(
         } else {
             format!("{}.{}", prefix, key)
         };
+        flatten_value(full_key, value, depth, config, result);
+    }
+}
 
-        match value {
-            Value::Object(nested_obj) => {
-                // Check depth limit
-                if config.depth.is_none_or(|max| depth < max) {
-                    // Expand the object
-                    flatten_object_recursive(nested_obj, &full_key, depth + 1, config, result);
-                } else {
-                    // Depth limit reached, use placeholder
-                    result.insert(full_key, Value::String("{...}".to_string()));
-                }
+fn flatten_value(
+    key: String,
+    value: &Value,
+    depth: usize,
+    config: &FlatConfig,
+    result: &mut HashMap<String, Value>,
+) {
+    match value {
+        Value::Object(nested_obj) => {
+            // Check depth limit
+            if config.depth.is_none_or(|max| depth < max) {
+                // Expand the object
+                flatten_object_recursive(nested_obj, &key, depth + 1, config, result);
+            } else {
+                // Depth limit reached, use placeholder
+                result.insert(key, Value::String(placeholder(value)));
             }
-            Value::Array(_) => {
+        }
+        Value::Array(arr) => match config.arrays {
+            ArrayExpansion::Join => {
                 // Format array with limit
                 let formatted = format_array(value, config.array_limit);
-                result.insert(full_key, Value::String(formatted));
+                result.insert(key, Value::String(formatted));
             }
-            _ => {
-                result.insert(full_key, value.clone());
+            ArrayExpansion::Index => {
+                for (i, element) in arr.iter().take(config.array_limit).enumerate() {
+                    flatten_value(format!("{}[{}]", key, i), element, depth, config, result);
+                }
             }
+        },
+        _ => {
+            result.insert(key, value.clone());
         }
     }
 }
@@ -415,8 +512,7 @@ fn format_array_element(value: &Value) -> String {
         Value::Bool(b) => b.to_string(),
         Value::Number(n) => n.to_string(),
         Value::String(s) => s.clone(),
-        Value::Array(_) => "[...]".to_string(),
-        Value::Object(_) => "{...}".to_string(),
+        Value::Array(_) | Value::Object(_) => placeholder(value),
     }
 }
 
@@ -452,7 +548,7 @@ mod tests {
     #[test]
     fn test_format_array_nested_objects() {
         let arr = json!([1, {"x": 2}, [3, 4]]);
-        assert_eq!(format_array(&arr, 3), "1, {...}, [...]");
+        assert_eq!(format_array(&arr, 3), "1, {1 key}, [2 items]");
     }
 
     #[test]
@@ -545,8 +641,8 @@ mod tests {
         let config = FlatConfig::new(Some(1), 3);
         let flattened = flatten_object(&obj, &config);
 
-        // Only 1 level deep, so a.b is {c: 1} displayed as {...}
-        assert_eq!(flattened.get("a.b"), Some(&json!("{...}")));
+        // Only 1 level deep, so a.b is {c: 1} displayed as a placeholder
+        assert_eq!(flattened.get("a.b"), Some(&json!("{1 key}")));
     }
 
     #[test]
@@ -605,8 +701,87 @@ mod tests {
         let table = FlatTableData::from_rows(&rows, config);
 
         // user column should exist (from first row)
-        // Second row's object should be displayed as {...}
+        // Second row's object should be displayed as a placeholder
         let cols = table.columns();
         assert!(cols.contains(&"user".to_string()));
     }
+
+    #[test]
+    fn test_flatten_object_index_arrays() {
+        let obj = json!({"tags": ["a", "b", "c", "d"], "orders": [{"sku": "x"}]});
+        let config = FlatConfig::default().with_arrays(ArrayExpansion::Index);
+        let flattened = flatten_object(&obj, &config);
+
+        assert_eq!(flattened.get("tags[0]"), Some(&json!("a")));
+        assert_eq!(flattened.get("tags[2]"), Some(&json!("c")));
+        assert!(!flattened.contains_key("tags[3]")); // past the array limit
+        assert!(!flattened.contains_key("tags"));
+        assert_eq!(flattened.get("orders[0].sku"), Some(&json!("x")));
+    }
+
+    #[test]
+    fn test_flat_table_data_index_arrays() {
+        let rows = vec![
+            json!({"id": 1, "tags": ["a"]}),
+            json!({"id": 2, "tags": ["b", "c"], "name": "Bob"}),
+        ];
+        let config = FlatConfig::default().with_arrays(ArrayExpansion::Index);
+
+        let table = FlatTableData::from_rows(&rows, config);
+
+        assert_eq!(table.columns(), &["id", "tags[0]", "tags[1]", "name"]);
+        assert_eq!(table.rows()[0][2], Value::Null);
+        assert_eq!(table.rows()[1][2], json!("c"));
+    }
+
+    #[test]
+    fn test_flat_table_data_sparse_columns() {
+        let rows = vec![
+            json!({"id": 1}),
+            json!({"id": 2, "meta": {"tag": "x"}}),
+            json!({"meta": {"note": "late"}, "id": 3}),
+        ];
+        let table = FlatTableData::from_rows(&rows, FlatConfig::default());
+
+        assert_eq!(table.columns(), &["id", "meta.note", "meta.tag"]);
+        assert_eq!(table.rows()[0], vec![json!(1), Value::Null, Value::Null]);
+        assert_eq!(table.rows()[1], vec![json!(2), Value::Null, json!("x")]);
+        assert_eq!(table.rows()[2], vec![json!(3), json!("late"), Value::Null]);
+    }
+
+    #[test]
+    fn test_compare_paths_orders_indices_numerically() {
+        let mut paths = vec!["tags[10]", "tags[2]", "tags[1].b", "tags[1].a"];
+        paths.sort_by(|a, b| compare_paths(a, b));
+        assert_eq!(paths, vec!["tags[1].a", "tags[1].b", "tags[2]", "tags[10]"]);
+    }
+
+    #[test]
+    fn test_flat_table_data_select() {
+        let rows = vec![
+            json!({"id": 1, "user": {"name": "Alice", "age": 30}, "tags": ["a"]}),
+            json!({"id": 2, "user": {"name": "Bob", "age": 25}, "tags": ["b"]}),
+        ];
+        let selector = ColumnSelector::new(vec!["user.name".into(), "id".into()]).unwrap();
+
+        let table = FlatTableData::from_rows(&rows, FlatConfig::default()).select(&selector);
+
+        assert_eq!(table.columns(), &["user.name", "id"]);
+        assert_eq!(table.rows()[1], vec![json!("Bob"), json!(2)]);
+    }
+
+    #[test]
+    fn test_flat_table_data_select_parent_and_wildcard() {
+        let rows = vec![json!({"id": 1, "user": {"name": "Alice", "age": 30}})];
+        let table = FlatTableData::from_rows(&rows, FlatConfig::default());
+
+        let selector = ColumnSelector::new(vec!["user.*".into()]).unwrap();
+        let selected = table.clone().select(&selector);
+        assert_eq!(selected.columns(), &["user.age", "user.name"]);
+
+        let selector = ColumnSelector::new(vec!["user".into(), "missing".into()]).unwrap();
+        let selected = table.select(&selector);
+        assert_eq!(selected.columns(), &["user.age", "user.name", "missing"]);
+        assert_eq!(selected.rows()[0][2], Value::Null);
+    }
 }
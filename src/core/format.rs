@@ -0,0 +1,854 @@
+use super::filter::FilterExpr;
+use super::path::PathFn;
+use super::schema::{ColumnType, Schema};
+use super::time::TimeFormat;
+use crate::error::{JlcatError, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Renderer-agnostic color names usable in formatting rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleColor {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Gray,
+}
+
+impl StyleColor {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "black" => Some(StyleColor::Black),
+            "red" => Some(StyleColor::Red),
+            "green" => Some(StyleColor::Green),
+            "yellow" => Some(StyleColor::Yellow),
+            "blue" => Some(StyleColor::Blue),
+            "magenta" => Some(StyleColor::Magenta),
+            "cyan" => Some(StyleColor::Cyan),
+            "white" => Some(StyleColor::White),
+            "gray" | "grey" => Some(StyleColor::Gray),
+            _ => None,
+        }
+    }
+}
+
+/// A cell style shared by the cat and TUI renderers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CellStyle {
+    pub fg: Option<StyleColor>,
+    pub bg: Option<StyleColor>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub dim: bool,
+}
+
+impl CellStyle {
+    /// Parse a style spec such as "bold red" or "white on blue"
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut style = CellStyle::default();
+        let mut words = spec.split_whitespace().map(|w| w.to_lowercase());
+
+        while let Some(word) = words.next() {
+            match word.as_str() {
+                "bold" => style.bold = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "dim" => style.dim = true,
+                "on" => {
+                    let color = words.next().ok_or_else(|| {
+                        JlcatError::InvalidStyle(format!("expected color after 'on' in '{}'", spec))
+                    })?;
+                    style.bg = Some(StyleColor::parse(&color).ok_or_else(|| {
+                        JlcatError::InvalidStyle(format!("unknown color '{}'", color))
+                    })?);
+                }
+                other => {
+                    style.fg = Some(StyleColor::parse(other).ok_or_else(|| {
+                        JlcatError::InvalidStyle(format!("unknown style '{}'", other))
+                    })?);
+                }
+            }
+        }
+
+        Ok(style)
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        *self == CellStyle::default()
+    }
+
+    /// Layer another style on top of this one (later rules win)
+    pub fn merge(&mut self, other: &CellStyle) {
+        if other.fg.is_some() {
+            self.fg = other.fg;
+        }
+        if other.bg.is_some() {
+            self.bg = other.bg;
+        }
+        self.bold |= other.bold;
+        self.italic |= other.italic;
+        self.underline |= other.underline;
+        self.dim |= other.dim;
+    }
+}
+
+/// Which cells a formatting rule applies to
+#[derive(Debug, Clone)]
+pub enum RuleScope {
+    Row,
+    Columns(Vec<String>),
+    /// Cells holding a value of this type, in any column
+    Type(ColumnType),
+}
+
+impl RuleScope {
+    fn applies_to(&self, column: &str, value: &Value) -> bool {
+        match self {
+            RuleScope::Row => true,
+            RuleScope::Columns(cols) => cols.iter().any(|c| c == column),
+            RuleScope::Type(col_type) => ColumnType::from_value(value) == *col_type,
+        }
+    }
+}
+
+/// A conditional formatting rule: apply `style` to `scope` when `when` matches
+#[derive(Debug, Clone)]
+pub struct FormatRule {
+    pub when: FilterExpr,
+    pub style: CellStyle,
+    pub scope: RuleScope,
+}
+
+impl FormatRule {
+    pub fn parse(when: &str, style: &str, columns: Vec<String>) -> Result<Self> {
+        let scope = if columns.is_empty() {
+            RuleScope::Row
+        } else {
+            RuleScope::Columns(columns)
+        };
+
+        Ok(Self {
+            when: FilterExpr::parse(when)?,
+            style: CellStyle::parse(style)?,
+            scope,
+        })
+    }
+
+    /// Parse a `--highlight` rule, `EXPR:STYLE`, styling the cells the
+    /// expression tests (the whole row with `row`). A JSON type name such as
+    /// `null` or `number` in place of the expression styles every cell of
+    /// that type.
+    pub fn parse_highlight(spec: &str, row: bool) -> Result<Self> {
+        let (when, style) = spec.rsplit_once(':').ok_or_else(|| {
+            JlcatError::InvalidStyle(format!("expected EXPR:STYLE in '{}'", spec))
+        })?;
+        let style = CellStyle::parse(style)?;
+        if let Some(col_type) = column_type_named(when.trim()) {
+            return Ok(Self {
+                when: FilterExpr {
+                    conditions: Vec::new(),
+                },
+                style,
+                scope: RuleScope::Type(col_type),
+            });
+        }
+
+        let when = FilterExpr::parse(when)?;
+        let scope = if row {
+            RuleScope::Row
+        } else {
+            RuleScope::Columns(
+                when.conditions
+                    .iter()
+                    .map(|c| PathFn::parse(&c.column).1.to_string())
+                    .collect(),
+            )
+        };
+        Ok(Self { when, style, scope })
+    }
+}
+
+/// The type `type(path)` reports as `name`
+fn column_type_named(name: &str) -> Option<ColumnType> {
+    [
+        ColumnType::Null,
+        ColumnType::Bool,
+        ColumnType::Number,
+        ColumnType::String,
+        ColumnType::Array,
+        ColumnType::Object,
+    ]
+    .into_iter()
+    .find(|t| t.name() == name)
+}
+
+/// Where truncated cells lose their text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Elision {
+    /// Keep the head: `abcdefg…`
+    #[default]
+    End,
+    /// Keep the head and tail: `abcd…wxyz`, better for tokens, URLs, and hashes
+    Middle,
+}
+
+/// Column widths pinned by `--widths`, such as `id=6,message=60,*=20`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnWidths {
+    columns: HashMap<String, usize>,
+    /// Width of every column not named (`*=N`)
+    default: Option<usize>,
+}
+
+impl ColumnWidths {
+    pub fn parse(spec: &str) -> std::result::Result<Self, String> {
+        let mut widths = ColumnWidths::default();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (column, n) = part
+                .rsplit_once('=')
+                .ok_or_else(|| format!("expected COLUMN=WIDTH in '{}'", part))?;
+            let width = n
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| format!("invalid width '{}'", n))?;
+            match column.trim() {
+                "" => return Err(format!("missing column name in '{}'", part)),
+                "*" => widths.default = Some(width),
+                column => {
+                    widths.columns.insert(column.to_string(), width);
+                }
+            }
+        }
+        Ok(widths)
+    }
+
+    /// Width pinned for a column, if any
+    pub fn get(&self, column: &str) -> Option<usize> {
+        self.columns.get(column).copied().or(self.default)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty() && self.default.is_none()
+    }
+}
+
+/// Case applied to column headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderCase {
+    Upper,
+    Lower,
+    /// First letter of each word upper-cased, the rest lower-cased
+    Title,
+}
+
+/// Evaluates conditional formatting rules and display formats against rendered rows
+#[derive(Debug, Clone, Default)]
+pub struct Formatter {
+    rules: Vec<FormatRule>,
+    time_format: TimeFormat,
+    max_width: Option<usize>,
+    elision: Elision,
+    inline_nested: Option<usize>,
+    null_label: Option<String>,
+    bool_labels: Option<(String, String)>,
+    header_case: Option<HeaderCase>,
+    raw_control: bool,
+    show_types: bool,
+    widths: ColumnWidths,
+    /// Labels shown in place of column names (`--header-map`)
+    header_map: HashMap<String, String>,
+}
+
+impl Formatter {
+    pub fn new(rules: Vec<FormatRule>) -> Self {
+        Self {
+            rules,
+            time_format: TimeFormat::default(),
+            max_width: None,
+            elision: Elision::default(),
+            inline_nested: None,
+            null_label: None,
+            bool_labels: None,
+            header_case: None,
+            raw_control: false,
+            show_types: false,
+            widths: ColumnWidths::default(),
+            header_map: HashMap::new(),
+        }
+    }
+
+    /// Cap displayed cell width, truncating longer text with an ellipsis
+    pub fn with_max_width(mut self, max_width: Option<usize>) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Pin the widths of columns (`--widths`); pinned cells are cut to fit
+    /// rather than to `--max-col-width`
+    pub fn with_widths(mut self, widths: ColumnWidths) -> Self {
+        self.widths = widths;
+        self
+    }
+
+    /// Width pinned for a column by `--widths`
+    pub fn pinned_width(&self, column: &str) -> Option<usize> {
+        self.widths.get(column)
+    }
+
+    pub fn has_pinned_widths(&self) -> bool {
+        !self.widths.is_empty()
+    }
+
+    /// Choose which part of a truncated cell is elided
+    pub fn with_elision(mut self, elision: Elision) -> Self {
+        self.elision = elision;
+        self
+    }
+
+    /// Show nested objects and arrays as compact JSON of at most `width`
+    /// characters instead of `{3 keys}` and `[12 items]` placeholders
+    pub fn with_inline_nested(mut self, width: Option<usize>) -> Self {
+        self.inline_nested = width;
+        self
+    }
+
+    /// Show null cells as `label` instead of "null"
+    pub fn with_null_label(mut self, label: Option<String>) -> Self {
+        self.null_label = label;
+        self
+    }
+
+    /// Show true and false cells as the given labels
+    pub fn with_bool_labels(mut self, labels: Option<(String, String)>) -> Self {
+        self.bool_labels = labels;
+        self
+    }
+
+    /// Normalize the case of column headers
+    pub fn with_header_case(mut self, case: Option<HeaderCase>) -> Self {
+        self.header_case = case;
+        self
+    }
+
+    /// Print control characters and ANSI escapes in cells as they are,
+    /// rather than escaped
+    pub fn with_raw_control(mut self, raw: bool) -> Self {
+        self.raw_control = raw;
+        self
+    }
+
+    /// Add rules after those already set, so they win where both apply
+    pub fn with_rules(mut self, rules: Vec<FormatRule>) -> Self {
+        self.rules.extend(rules);
+        self
+    }
+
+    /// Follow each header with its column's type (`--show-types`)
+    pub fn with_show_types(mut self, show: bool) -> Self {
+        self.show_types = show;
+        self
+    }
+
+    pub fn shows_types(&self) -> bool {
+        self.show_types
+    }
+
+    /// Escape control characters in display text, so values holding ANSI
+    /// escapes or carriage returns cannot restyle the terminal or break the
+    /// table. Line breaks are kept, with `\r\n` shown as `\n`; `--raw-control`
+    /// turns this off.
+    pub fn sanitize(&self, text: String) -> String {
+        if self.raw_control || !text.chars().any(is_unsafe_control) {
+            return text;
+        }
+        let mut escaped = String::with_capacity(text.len() + 8);
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                // A raw carriage return would send the cursor back over the row
+                continue;
+            } else if is_unsafe_control(c) {
+                escaped.extend(c.escape_default());
+            } else {
+                escaped.push(c);
+            }
+        }
+        escaped
+    }
+
+    /// Show columns under other labels, as written (`--header-map`)
+    pub fn with_header_map(mut self, map: HashMap<String, String>) -> Self {
+        self.header_map = map;
+        self
+    }
+
+    /// Display text for a column header: its mapped label, or the name in
+    /// the header case
+    pub fn header_text(&self, column: &str) -> String {
+        match self.header_map.get(column) {
+            Some(label) => self.sanitize(label.clone()),
+            None => self.sanitize(self.header_case_text(column)),
+        }
+    }
+
+    /// Column names as exports write them, mapped labels in place of names
+    pub fn export_columns(&self, columns: &[String]) -> Vec<String> {
+        rename_columns(columns, &self.header_map)
+    }
+
+    /// Header text followed by the column's type from `schema`, such as
+    /// `age:number`, when types are shown
+    pub fn typed_header_text(&self, column: &str, schema: Option<&Schema>) -> String {
+        let text = self.header_text(column);
+        match schema.filter(|_| self.show_types) {
+            Some(schema) => match schema.type_label(column) {
+                Some(label) => format!("{}:{}", text, label),
+                None => text,
+            },
+            None => text,
+        }
+    }
+
+    fn header_case_text(&self, column: &str) -> String {
+        match self.header_case {
+            None => column.to_string(),
+            Some(HeaderCase::Upper) => column.to_uppercase(),
+            Some(HeaderCase::Lower) => column.to_lowercase(),
+            Some(HeaderCase::Title) => {
+                let mut text = String::with_capacity(column.len());
+                let mut word_start = true;
+                for c in column.chars() {
+                    if word_start {
+                        text.extend(c.to_uppercase());
+                    } else {
+                        text.extend(c.to_lowercase());
+                    }
+                    word_start = !c.is_alphanumeric();
+                }
+                text
+            }
+        }
+    }
+
+    pub fn max_width(&self) -> Option<usize> {
+        self.max_width
+    }
+
+    /// Reformat recognized timestamp cells for display
+    pub fn with_time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Whether no style rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Display text for a cell, if a display format applies to it.
+    /// Renderers fall back to their default formatting on `None`.
+    pub fn cell_text(&self, column: &str, value: &Value) -> Option<String> {
+        match value {
+            Value::Null => {
+                if let Some(ref label) = self.null_label {
+                    return Some(label.clone());
+                }
+            }
+            Value::Bool(b) => {
+                if let Some((ref t, ref f)) = self.bool_labels {
+                    return Some(if *b { t.clone() } else { f.clone() });
+                }
+            }
+            Value::Array(_) | Value::Object(_) => {
+                if let Some(width) = self.inline_nested {
+                    return Some(truncate_with_ellipsis(value.to_string(), width));
+                }
+            }
+            _ => {}
+        }
+        if self.time_format.is_empty() {
+            return None;
+        }
+        self.time_format.format(column, value)
+    }
+
+    /// Truncate text to the maximum cell width, if one is set
+    pub fn truncate(&self, text: String) -> String {
+        match self.max_width {
+            Some(max) => self.truncate_to(text, max),
+            None => text,
+        }
+    }
+
+    /// Truncate a cell of `column` to its pinned width, or else as
+    /// [`Formatter::truncate`] does
+    pub fn truncate_cell(&self, column: &str, text: String) -> String {
+        match self.pinned_width(column) {
+            Some(width) => self.truncate_to(text, width),
+            None => self.truncate(text),
+        }
+    }
+
+    /// Truncate text to `max` columns using the configured elision
+    pub fn truncate_to(&self, text: String, max: usize) -> String {
+        match self.elision {
+            Elision::End => truncate_with_ellipsis(text, max),
+            Elision::Middle => elide_middle(text, max),
+        }
+    }
+
+    /// Resolve the style of each cell in a row.
+    /// Rules are evaluated against the displayed columns of the row.
+    pub fn cell_styles(&self, columns: &[String], values: &[Value]) -> Vec<CellStyle> {
+        let mut styles = vec![CellStyle::default(); columns.len()];
+        if self.rules.is_empty() {
+            return styles;
+        }
+
+        let mut obj = serde_json::Map::new();
+        for (col, val) in columns.iter().zip(values) {
+            obj.insert(col.clone(), val.clone());
+        }
+        let row = Value::Object(obj);
+
+        for rule in &self.rules {
+            if !rule.when.matches(&row) {
+                continue;
+            }
+            for ((col, value), style) in columns.iter().zip(values).zip(styles.iter_mut()) {
+                if rule.scope.applies_to(col, value) {
+                    style.merge(&rule.style);
+                }
+            }
+        }
+
+        styles
+    }
+}
+
+/// Replace the columns `map` has labels for
+pub fn rename_columns(columns: &[String], map: &HashMap<String, String>) -> Vec<String> {
+    columns
+        .iter()
+        .map(|c| map.get(c).unwrap_or(c).clone())
+        .collect()
+}
+
+/// Control characters other than a line break (`\x1b`, `\r`, `\t`, ...)
+fn is_unsafe_control(c: char) -> bool {
+    c.is_control() && c != '\n'
+}
+
+/// Terminal columns `text` takes up: wide characters such as CJK and emoji
+/// count twice, combining marks not at all
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// The longest start of `text` at most `max` columns wide
+pub fn take_width(text: &str, max: usize) -> &str {
+    let mut width = 0;
+    for (i, c) in text.char_indices() {
+        width += c.width().unwrap_or(0);
+        if width > max {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// The longest end of `text` at most `max` columns wide
+fn take_width_end(text: &str, max: usize) -> &str {
+    let mut width = 0;
+    for (i, c) in text.char_indices().rev() {
+        width += c.width().unwrap_or(0);
+        if width > max {
+            return &text[i + c.len_utf8()..];
+        }
+    }
+    text
+}
+
+/// Truncate to at most `max` columns, marking the cut with an ellipsis
+pub fn truncate_with_ellipsis(text: String, max: usize) -> String {
+    if display_width(&text) <= max {
+        return text;
+    }
+    if max == 0 {
+        return String::new();
+    }
+    format!("{}…", take_width(&text, max - 1))
+}
+
+/// Shorten to at most `max` columns by replacing the middle with an ellipsis
+pub fn elide_middle(text: String, max: usize) -> String {
+    if display_width(&text) <= max {
+        return text;
+    }
+    if max == 0 {
+        return String::new();
+    }
+    let keep = max - 1;
+    let head = keep.div_ceil(2);
+    let tail = keep / 2;
+
+    format!(
+        "{}…{}",
+        take_width(&text, head),
+        take_width_end(&text, tail)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cols(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_header_text_case() {
+        let header = |case| {
+            Formatter::default()
+                .with_header_case(case)
+                .header_text("user.first_name")
+        };
+        assert_eq!(header(None), "user.first_name");
+        assert_eq!(header(Some(HeaderCase::Upper)), "USER.FIRST_NAME");
+        assert_eq!(header(Some(HeaderCase::Title)), "User.First_Name");
+        assert_eq!(
+            Formatter::default()
+                .with_header_case(Some(HeaderCase::Lower))
+                .header_text("UserID"),
+            "userid"
+        );
+    }
+
+    #[test]
+    fn test_header_map() {
+        let map = HashMap::from([("t_ms".to_string(), "Latency (ms)".to_string())]);
+        let formatter = Formatter::default()
+            .with_header_case(Some(HeaderCase::Upper))
+            .with_header_map(map);
+        assert_eq!(formatter.header_text("t_ms"), "Latency (ms)");
+        assert_eq!(formatter.header_text("id"), "ID");
+        assert_eq!(
+            formatter.export_columns(&["id".to_string(), "t_ms".to_string()]),
+            vec!["id", "Latency (ms)"]
+        );
+    }
+
+    #[test]
+    fn test_cell_text_labels() {
+        let formatter = Formatter::default()
+            .with_null_label(Some("-".to_string()))
+            .with_bool_labels(Some(("✓".to_string(), "✗".to_string())));
+        assert_eq!(
+            formatter.cell_text("a", &Value::Null),
+            Some("-".to_string())
+        );
+        assert_eq!(
+            formatter.cell_text("a", &json!(true)),
+            Some("✓".to_string())
+        );
+        assert_eq!(
+            formatter.cell_text("a", &json!(false)),
+            Some("✗".to_string())
+        );
+        assert_eq!(formatter.cell_text("a", &json!(1)), None);
+        assert_eq!(Formatter::default().cell_text("a", &Value::Null), None);
+    }
+
+    #[test]
+    fn test_cell_text_inline_nested() {
+        let formatter = Formatter::default().with_inline_nested(Some(12));
+        assert_eq!(
+            formatter.cell_text("user", &json!({"city": "Tokyo", "zip": "100"})),
+            Some("{\"city\":\"To…".to_string())
+        );
+        assert_eq!(
+            formatter.cell_text("tags", &json!(["a", "b"])),
+            Some("[\"a\",\"b\"]".to_string())
+        );
+        assert_eq!(formatter.cell_text("name", &json!("Tokyo")), None);
+        assert_eq!(Formatter::default().cell_text("tags", &json!(["a"])), None);
+    }
+
+    #[test]
+    fn test_parse_style() {
+        let style = CellStyle::parse("bold red").unwrap();
+        assert!(style.bold);
+        assert_eq!(style.fg, Some(StyleColor::Red));
+        assert_eq!(style.bg, None);
+    }
+
+    #[test]
+    fn test_parse_style_background() {
+        let style = CellStyle::parse("white on blue").unwrap();
+        assert_eq!(style.fg, Some(StyleColor::White));
+        assert_eq!(style.bg, Some(StyleColor::Blue));
+    }
+
+    #[test]
+    fn test_parse_style_invalid() {
+        assert!(CellStyle::parse("sparkly").is_err());
+        assert!(CellStyle::parse("red on").is_err());
+    }
+
+    #[test]
+    fn test_column_widths() {
+        let widths = ColumnWidths::parse("id=6, message=60,*=20").unwrap();
+        assert_eq!(widths.get("id"), Some(6));
+        assert_eq!(widths.get("message"), Some(60));
+        assert_eq!(widths.get("other"), Some(20));
+        assert_eq!(ColumnWidths::parse("id=6").unwrap().get("other"), None);
+
+        assert!(ColumnWidths::parse("id").is_err());
+        assert!(ColumnWidths::parse("id=0").is_err());
+        assert!(ColumnWidths::parse("=5").is_err());
+
+        let formatter = Formatter::default()
+            .with_max_width(Some(4))
+            .with_widths(widths);
+        assert_eq!(formatter.truncate_cell("id", "abcdefgh".into()), "abcde…");
+        let formatter = formatter.with_widths(ColumnWidths::parse("id=6").unwrap());
+        assert_eq!(formatter.truncate_cell("name", "abcdefgh".into()), "abc…");
+    }
+
+    #[test]
+    fn test_highlight_rules() {
+        let formatter = Formatter::default().with_rules(vec![
+            FormatRule::parse_highlight("latency_ms>1000:yellow", false).unwrap(),
+            FormatRule::parse_highlight("status=error:red", true).unwrap(),
+            FormatRule::parse_highlight("null:dim", false).unwrap(),
+        ]);
+        let columns = vec![
+            "status".to_string(),
+            "latency_ms".to_string(),
+            "note".into(),
+        ];
+
+        let styles = formatter.cell_styles(&columns, &[json!("ok"), json!(1500), json!(null)]);
+        assert_eq!(styles[0], CellStyle::default());
+        assert_eq!(styles[1].fg, Some(StyleColor::Yellow));
+        assert!(styles[2].dim);
+
+        let styles = formatter.cell_styles(&columns, &[json!("error"), json!(20), json!("x")]);
+        assert!(styles.iter().all(|s| s.fg == Some(StyleColor::Red)));
+        assert!(!styles[2].dim);
+
+        assert!(FormatRule::parse_highlight("status=error", false).is_err());
+        assert!(FormatRule::parse_highlight("status=error:sparkly", false).is_err());
+    }
+
+    #[test]
+    fn test_column_scoped_rule() {
+        let rule = FormatRule::parse("latency>1000", "bold red", vec!["latency".into()]).unwrap();
+        let formatter = Formatter::new(vec![rule]);
+        let columns = cols(&["id", "latency"]);
+
+        let styles = formatter.cell_styles(&columns, &[json!(1), json!(1500)]);
+        assert!(styles[0].is_empty());
+        assert_eq!(styles[1].fg, Some(StyleColor::Red));
+
+        let styles = formatter.cell_styles(&columns, &[json!(2), json!(10)]);
+        assert!(styles.iter().all(|s| s.is_empty()));
+    }
+
+    #[test]
+    fn test_row_scoped_rule() {
+        let rule = FormatRule::parse("level=error", "red", vec![]).unwrap();
+        let formatter = Formatter::new(vec![rule]);
+        let columns = cols(&["level", "msg"]);
+
+        let styles = formatter.cell_styles(&columns, &[json!("error"), json!("boom")]);
+        assert!(styles.iter().all(|s| s.fg == Some(StyleColor::Red)));
+    }
+
+    #[test]
+    fn test_later_rules_override_color() {
+        let formatter = Formatter::new(vec![
+            FormatRule::parse("n>1", "bold red", vec![]).unwrap(),
+            FormatRule::parse("n>5", "yellow", vec![]).unwrap(),
+        ]);
+        let columns = cols(&["n"]);
+
+        let styles = formatter.cell_styles(&columns, &[json!(10)]);
+        assert_eq!(styles[0].fg, Some(StyleColor::Yellow));
+        assert!(styles[0].bold);
+    }
+
+    #[test]
+    fn test_truncate_with_ellipsis() {
+        assert_eq!(truncate_with_ellipsis("hello".into(), 10), "hello");
+        assert_eq!(truncate_with_ellipsis("hello".into(), 5), "hello");
+        assert_eq!(truncate_with_ellipsis("hello world".into(), 5), "hell…");
+        // Wide characters take two columns each
+        assert_eq!(truncate_with_ellipsis("日本語テキスト".into(), 3), "日…");
+        assert_eq!(truncate_with_ellipsis("日本語テキスト".into(), 6), "日本…");
+        assert_eq!(truncate_with_ellipsis("日本".into(), 4), "日本");
+        assert_eq!(truncate_with_ellipsis("hello".into(), 0), "");
+    }
+
+    #[test]
+    fn test_formatter_truncate() {
+        let formatter = Formatter::default().with_max_width(Some(4));
+        assert_eq!(formatter.truncate("abcdef".into()), "abc…");
+        assert_eq!(Formatter::default().truncate("abcdef".into()), "abcdef");
+    }
+
+    #[test]
+    fn test_sanitize_escapes_control_characters() {
+        let formatter = Formatter::default();
+        assert_eq!(
+            formatter.sanitize("\u{1b}[31mred\u{1b}[0m".into()),
+            "\\u{1b}[31mred\\u{1b}[0m"
+        );
+        assert_eq!(formatter.sanitize("a\tb\rc\nd".into()), "a\\tb\\rc\nd");
+        assert_eq!(formatter.sanitize("a\r\nb\t".into()), "a\nb\\t");
+        let crlf = formatter.sanitize("x\r\nyy\r\n".into());
+        assert_eq!(crlf, "x\nyy\n");
+        assert!(!crlf.contains('\r'));
+        assert_eq!(formatter.sanitize("日本 ✓".into()), "日本 ✓");
+        assert_eq!(formatter.header_text("x\u{7}"), "x\\u{7}");
+
+        let raw = Formatter::default().with_raw_control(true);
+        assert_eq!(raw.sanitize("\u{1b}[1m".into()), "\u{1b}[1m");
+    }
+
+    #[test]
+    fn test_elide_middle() {
+        assert_eq!(elide_middle("short".into(), 10), "short");
+        assert_eq!(
+            elide_middle("abcdefghijklmnopqrstuvwxyz".into(), 9),
+            "abcd…wxyz"
+        );
+        assert_eq!(elide_middle("abcdefghij".into(), 4), "ab…j");
+        assert_eq!(elide_middle("abcdefghij".into(), 1), "…");
+        assert_eq!(elide_middle("abcdefghij".into(), 0), "");
+        assert_eq!(elide_middle("日本語テキスト".into(), 9), "日本…スト");
+    }
+
+    #[test]
+    fn test_formatter_middle_elision() {
+        let formatter = Formatter::default()
+            .with_max_width(Some(9))
+            .with_elision(Elision::Middle);
+        assert_eq!(
+            formatter.truncate("https://example.com/path/to/resource".into()),
+            "http…urce"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use super::flat::{flatten_object, FlatConfig};
+use super::format::display_width;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Number of rows inspected when choosing a layout
+const SAMPLE_SIZE: usize = 100;
+
+/// Width assumed when output is not a terminal
+const DEFAULT_WIDTH: usize = 120;
+
+/// Cells wider than this are assumed to be wrapped or truncated, so they don't
+/// count fully against the terminal width
+const CELL_WIDTH_CAP: usize = 30;
+
+/// Nesting deeper than this reads poorly as dot-notation columns
+const MAX_FLAT_DEPTH: usize = 3;
+
+/// Arrays of objects longer than this on average are summarized rather than listed
+const SUMMARY_ARRAY_LEN: f64 = 5.0;
+
+/// Display layout chosen by `--auto-layout`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// One column per top-level key, nested values as placeholders
+    Normal,
+    /// Nested objects expanded into dot-notation columns
+    Flat,
+    /// Placeholders in the parent table plus per-parent summaries of child tables
+    Summary,
+}
+
+/// Pick a readable layout for the data from a sample of its rows.
+///
+/// - Flat data stays in the normal layout.
+/// - Large arrays of objects get child table summaries, since flattening would
+///   cram them into a single cell and listing them would be overwhelming.
+/// - Otherwise nested data is flattened if it is shallow enough and the flattened
+///   table fits the terminal width; if not, placeholders keep the table narrow.
+pub fn choose_layout(rows: &[Value], terminal_width: Option<usize>) -> Layout {
+    let sample = &rows[..rows.len().min(SAMPLE_SIZE)];
+    let width = terminal_width.unwrap_or(DEFAULT_WIDTH);
+
+    let depth = sample.iter().map(nesting_depth).max().unwrap_or(0);
+    if depth <= 1 {
+        return Layout::Normal;
+    }
+
+    let (object_arrays, object_array_len) = sample
+        .iter()
+        .flat_map(object_arrays)
+        .fold((0usize, 0usize), |(count, len), n| (count + 1, len + n));
+    if object_arrays > 0 && object_array_len as f64 / object_arrays as f64 > SUMMARY_ARRAY_LEN {
+        return Layout::Summary;
+    }
+
+    if depth - 1 <= MAX_FLAT_DEPTH && flat_width(sample) <= width {
+        Layout::Flat
+    } else {
+        Layout::Normal
+    }
+}
+
+/// Depth of a value: 0 for scalars, 1 for a flat object, and so on
+fn nesting_depth(value: &Value) -> usize {
+    match value {
+        Value::Object(obj) => 1 + obj.values().map(nesting_depth).max().unwrap_or(0),
+        Value::Array(arr) => 1 + arr.iter().map(nesting_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Lengths of the top-level arrays of objects in a row
+fn object_arrays(row: &Value) -> Vec<usize> {
+    match row {
+        Value::Object(obj) => obj
+            .values()
+            .filter_map(|v| match v {
+                Value::Array(arr) if arr.iter().any(Value::is_object) => Some(arr.len()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Estimated rendered width of the sample in flat mode
+fn flat_width(sample: &[Value]) -> usize {
+    let config = FlatConfig::default();
+    let mut widths: HashMap<String, usize> = HashMap::new();
+
+    for row in sample {
+        for (column, value) in flatten_object(row, &config) {
+            let cell = match &value {
+                Value::String(s) => display_width(s),
+                other => display_width(&other.to_string()),
+            };
+            let width = cell.max(display_width(&column)).min(CELL_WIDTH_CAP);
+            let entry = widths.entry(column).or_insert(0);
+            *entry = (*entry).max(width);
+        }
+    }
+
+    // Each column adds padding and a border
+    widths.values().map(|w| w + 3).sum::<usize>() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flat_data_stays_normal() {
+        let rows = vec![json!({"id": 1, "name": "Alice"})];
+        assert_eq!(choose_layout(&rows, Some(80)), Layout::Normal);
+    }
+
+    #[test]
+    fn test_shallow_nesting_is_flattened() {
+        let rows = vec![json!({"id": 1, "user": {"name": "Alice", "age": 30}})];
+        assert_eq!(choose_layout(&rows, Some(80)), Layout::Flat);
+    }
+
+    #[test]
+    fn test_wide_nesting_keeps_placeholders() {
+        let user: serde_json::Map<String, Value> = (0..30)
+            .map(|i| (format!("field_{}", i), json!("some value")))
+            .collect();
+        let rows = vec![json!({"id": 1, "user": user})];
+        assert_eq!(choose_layout(&rows, Some(80)), Layout::Normal);
+    }
+
+    #[test]
+    fn test_deep_nesting_keeps_placeholders() {
+        let rows = vec![json!({"a": {"b": {"c": {"d": {"e": 1}}}}})];
+        assert_eq!(choose_layout(&rows, Some(200)), Layout::Normal);
+    }
+
+    #[test]
+    fn test_large_object_arrays_are_summarized() {
+        let orders: Vec<Value> = (0..10).map(|i| json!({"price": i})).collect();
+        let rows = vec![json!({"id": 1, "orders": orders})];
+        assert_eq!(choose_layout(&rows, Some(80)), Layout::Summary);
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        assert_eq!(nesting_depth(&json!(1)), 0);
+        assert_eq!(nesting_depth(&json!({"a": 1})), 1);
+        assert_eq!(nesting_depth(&json!({"a": {"b": [1]}})), 3);
+    }
+}
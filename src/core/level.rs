@@ -0,0 +1,225 @@
+use super::path::CompiledPath;
+use crate::error::{JlcatError, Result};
+use serde_json::Value;
+
+/// Field names commonly used for log levels, checked case-insensitively
+const LEVEL_FIELDS: &[&str] = &[
+    "level",
+    "severity",
+    "lvl",
+    "loglevel",
+    "log_level",
+    "levelname",
+];
+
+/// Log severity, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl Severity {
+    /// Parse a level name across common naming conventions and case variants
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "trace" | "trc" | "verbose" | "finest" => Some(Severity::Trace),
+            "debug" | "dbg" | "fine" => Some(Severity::Debug),
+            "info" | "inf" | "information" | "informational" | "notice" => Some(Severity::Info),
+            "warn" | "wrn" | "warning" => Some(Severity::Warn),
+            "error" | "err" | "eror" => Some(Severity::Error),
+            "fatal" | "ftl" | "critical" | "crit" | "alert" | "emerg" | "emergency" | "panic" => {
+                Some(Severity::Fatal)
+            }
+            _ => None,
+        }
+    }
+
+    /// Interpret a level value: names, or pino/bunyan numeric levels (10-60)
+    pub fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) => Self::parse(s),
+            Value::Number(n) => match n.as_u64()? {
+                10 => Some(Severity::Trace),
+                20 => Some(Severity::Debug),
+                30 => Some(Severity::Info),
+                40 => Some(Severity::Warn),
+                50 => Some(Severity::Error),
+                60 => Some(Severity::Fatal),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Which severities a level filter accepts relative to its threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LevelRange {
+    /// `warn+`: the level and anything more severe
+    AtLeast,
+    /// `warn-`: the level and anything less severe
+    AtMost,
+    /// `warn`: exactly the level
+    Exactly,
+}
+
+/// Filters rows by ordered severity (`--level warn+`)
+#[derive(Debug, Clone)]
+pub struct LevelFilter {
+    severity: Severity,
+    range: LevelRange,
+    field: Option<CompiledPath>,
+}
+
+impl LevelFilter {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (name, range) = if let Some(name) = spec.strip_suffix('+') {
+            (name, LevelRange::AtLeast)
+        } else if let Some(name) = spec.strip_suffix('-') {
+            (name, LevelRange::AtMost)
+        } else {
+            (spec, LevelRange::Exactly)
+        };
+
+        let severity = Severity::parse(name)
+            .ok_or_else(|| JlcatError::InvalidFilter(format!("unknown log level '{}'", name)))?;
+
+        Ok(Self {
+            severity,
+            range,
+            field: None,
+        })
+    }
+
+    /// Use an explicit level field instead of auto-detection
+    pub fn with_field(mut self, field: &str) -> Result<Self> {
+        self.field = Some(CompiledPath::compile(field)?);
+        Ok(self)
+    }
+
+    /// Keep rows whose level matches; rows without a recognizable level are dropped
+    pub fn apply(&self, rows: Vec<Value>) -> Vec<Value> {
+        let field = match self.field.clone().or_else(|| detect_level_field(&rows)) {
+            Some(field) => field,
+            None => return Vec::new(),
+        };
+
+        rows.into_iter()
+            .filter(|row| {
+                field
+                    .get(row)
+                    .and_then(Severity::from_value)
+                    .is_some_and(|sev| self.accepts(sev))
+            })
+            .collect()
+    }
+
+    fn accepts(&self, severity: Severity) -> bool {
+        match self.range {
+            LevelRange::AtLeast => severity >= self.severity,
+            LevelRange::AtMost => severity <= self.severity,
+            LevelRange::Exactly => severity == self.severity,
+        }
+    }
+}
+
+/// Find the level field used by the rows (e.g., `level`, `Severity`, `log.level`)
+pub fn detect_level_field(rows: &[Value]) -> Option<CompiledPath> {
+    for row in rows {
+        if let Value::Object(obj) = row {
+            if let Some(key) = obj
+                .keys()
+                .find(|k| LEVEL_FIELDS.contains(&k.to_lowercase().as_str()))
+            {
+                return CompiledPath::compile(key).ok();
+            }
+            // Elastic Common Schema nests the level under `log`
+            if row.pointer("/log/level").is_some() {
+                return CompiledPath::compile("log.level").ok();
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_severity_parse_variants() {
+        assert_eq!(Severity::parse("WARN"), Some(Severity::Warn));
+        assert_eq!(Severity::parse("Warning"), Some(Severity::Warn));
+        assert_eq!(Severity::parse("err"), Some(Severity::Error));
+        assert_eq!(Severity::parse("CRITICAL"), Some(Severity::Fatal));
+        assert_eq!(Severity::parse("loud"), None);
+    }
+
+    #[test]
+    fn test_severity_numeric() {
+        assert_eq!(Severity::from_value(&json!(40)), Some(Severity::Warn));
+        assert_eq!(Severity::from_value(&json!(55)), None);
+    }
+
+    #[test]
+    fn test_detect_level_field() {
+        let rows = vec![json!({"msg": "a", "Severity": "INFO"})];
+        assert_eq!(detect_level_field(&rows).unwrap().original, "Severity");
+
+        let rows = vec![json!({"log": {"level": "info"}})];
+        assert_eq!(detect_level_field(&rows).unwrap().original, "log.level");
+
+        let rows = vec![json!({"msg": "a"})];
+        assert!(detect_level_field(&rows).is_none());
+    }
+
+    #[test]
+    fn test_level_filter_at_least() {
+        let rows = vec![
+            json!({"id": 1, "level": "debug"}),
+            json!({"id": 2, "level": "WARNING"}),
+            json!({"id": 3, "level": "info"}),
+            json!({"id": 4, "level": "error"}),
+            json!({"id": 5}),
+        ];
+
+        let filter = LevelFilter::parse("warn+").unwrap();
+        let ids: Vec<_> = filter.apply(rows).iter().map(|r| r["id"].clone()).collect();
+        assert_eq!(ids, vec![json!(2), json!(4)]);
+    }
+
+    #[test]
+    fn test_level_filter_exact_and_at_most() {
+        let rows = vec![json!({"lvl": 30}), json!({"lvl": 40}), json!({"lvl": 20})];
+
+        assert_eq!(
+            LevelFilter::parse("info")
+                .unwrap()
+                .apply(rows.clone())
+                .len(),
+            1
+        );
+        assert_eq!(LevelFilter::parse("info-").unwrap().apply(rows).len(), 2);
+    }
+
+    #[test]
+    fn test_level_filter_explicit_field() {
+        let rows = vec![json!({"level": "error", "sev": "debug"})];
+        let filter = LevelFilter::parse("warn+")
+            .unwrap()
+            .with_field("sev")
+            .unwrap();
+        assert!(filter.apply(rows).is_empty());
+    }
+
+    #[test]
+    fn test_level_filter_invalid() {
+        assert!(LevelFilter::parse("loud+").is_err());
+    }
+}
@@ -1,28 +1,51 @@
 #[allow(dead_code)]
 mod cache;
+mod distinct;
+mod explode;
 mod extractor;
+mod extsort;
 mod filter;
 mod flat;
+mod format;
+mod layout;
+mod level;
 #[allow(dead_code)]
 mod path;
+mod rows;
 #[allow(dead_code)]
 mod schema;
+mod select;
 mod selector;
 mod sorter;
 mod table;
+mod time;
+mod transform;
 #[allow(dead_code)]
 mod value;
 
 #[allow(dead_code)]
 pub use cache::RowCache;
-pub use extractor::{ChildTable, NestedExtractor};
+pub use distinct::{DistinctOn, Keep};
+pub use explode::Explode;
+pub use extractor::{ChildRows, ChildTable, NestedExtractor};
+pub use extsort::ExternalSort;
 pub use filter::{FilterExpr, FullTextSearch};
 #[allow(unused_imports)]
-pub use flat::{FlatConfig, FlatSchema, FlatTableData};
+pub use flat::{ArrayExpansion, FlatConfig, FlatSchema, FlatTableData};
+pub use format::{
+    display_width, rename_columns, take_width, CellStyle, ColumnWidths, Elision, FormatRule,
+    Formatter, HeaderCase, StyleColor,
+};
+pub use layout::{choose_layout, Layout};
+pub use level::LevelFilter;
+pub use rows::RowRanges;
 #[allow(unused_imports)]
-pub use schema::SchemaInferrer;
+pub use schema::{ColumnType, Schema, SchemaInferrer, ValueShape};
+pub use select::Select;
 pub use selector::ColumnSelector;
 pub use sorter::Sorter;
 pub use table::TableData;
+pub use time::{DisplayZone, TimeFormat};
+pub use transform::CommandTransform;
 #[allow(unused_imports)]
-pub use value::get_nested_value;
+pub use value::{get_nested_value, placeholder};
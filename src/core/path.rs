@@ -1,36 +1,95 @@
+use super::flat::format_array;
+use super::schema::ColumnType;
 use crate::error::{JlcatError, Result};
 use serde_json::Value;
+use std::iter::Peekable;
+use std::str::Chars;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PathSegment {
     Key(String),
     Index(usize),
+    /// `[-n]`: the n-th element from the end, `[-1]` being the last
+    IndexFromEnd(usize),
+    /// `[*]`: every element of an array
+    Wildcard,
+    /// `..key`: the key at any depth below the current value
+    Descendant(String),
+    /// `[start:end]`: the elements of an array in a range; either bound may be
+    /// omitted or negative to count from the end
+    Slice(Option<isize>, Option<isize>),
 }
 
 #[derive(Debug, Clone)]
 pub struct CompiledPath {
     pub segments: Vec<PathSegment>,
     pub original: String,
+    /// Per segment, the keys it forms joined with the key segments after it:
+    /// `a.b` gives `[["a", "a.b"], ["b"]]`
+    dotted: Vec<Vec<String>>,
 }
 
 impl CompiledPath {
+    /// Compile a path such as `user.name`, `items[0].sku` or `meta["a.b"].value`.
+    ///
+    /// Keys containing `.`, `[` or `]` can be quoted in brackets (`["a.b"]` or
+    /// `['a.b']`) or escaped with a backslash (`a\.b`). `..key` finds a key at
+    /// any depth.
     pub fn compile(path: &str) -> Result<Self> {
         let mut segments = Vec::new();
         let mut current = String::new();
         let mut chars = path.chars().peekable();
+        // Set after `..`, until the key it applies to
+        let mut descend = false;
 
         while let Some(c) = chars.next() {
             match c {
+                '\\' => match chars.next() {
+                    Some(escaped) => current.push(escaped),
+                    None => {
+                        return Err(JlcatError::InvalidColumnPath(format!(
+                            "trailing backslash in '{}'",
+                            path
+                        )))
+                    }
+                },
                 '.' => {
                     if !current.is_empty() {
-                        segments.push(PathSegment::Key(current.clone()));
-                        current.clear();
+                        segments.push(key_segment(std::mem::take(&mut current), &mut descend));
+                    }
+                    if descend {
+                        return Err(JlcatError::InvalidColumnPath(format!(
+                            "expected a key after '..' in '{}'",
+                            path
+                        )));
                     }
+                    descend = chars.next_if_eq(&'.').is_some();
                 }
                 '[' => {
                     if !current.is_empty() {
-                        segments.push(PathSegment::Key(current.clone()));
-                        current.clear();
+                        segments.push(key_segment(std::mem::take(&mut current), &mut descend));
+                    }
+                    if let Some(quote) = chars.next_if(|&c| c == '"' || c == '\'') {
+                        let key = quoted_key(&mut chars, quote).ok_or_else(|| {
+                            JlcatError::InvalidColumnPath(format!(
+                                "unterminated quoted key in '{}'",
+                                path
+                            ))
+                        })?;
+                        if chars.next() != Some(']') {
+                            return Err(JlcatError::InvalidColumnPath(format!(
+                                "expected ']' after quoted key in '{}'",
+                                path
+                            )));
+                        }
+                        segments.push(key_segment(key, &mut descend));
+                        continue;
+                    }
+                    if descend {
+                        return Err(JlcatError::InvalidColumnPath(format!(
+                            "expected a key after '..' in '{}'",
+                            path
+                        )));
                     }
                     // Parse index
                     let mut idx_str = String::new();
@@ -49,13 +108,32 @@ impl CompiledPath {
                             path
                         )));
                     }
-                    let idx: usize = idx_str.parse().map_err(|_| {
+                    let invalid = || {
                         JlcatError::InvalidColumnPath(format!(
                             "invalid index '{}' in '{}'",
                             idx_str, path
                         ))
-                    })?;
-                    segments.push(PathSegment::Index(idx));
+                    };
+                    if idx_str == "*" {
+                        segments.push(PathSegment::Wildcard);
+                        continue;
+                    }
+                    if let Some((start, end)) = idx_str.split_once(':') {
+                        let bound = |s: &str| match s.trim() {
+                            "" => Ok(None),
+                            s => s.parse().map(Some).map_err(|_| invalid()),
+                        };
+                        segments.push(PathSegment::Slice(bound(start)?, bound(end)?));
+                        continue;
+                    }
+                    let segment = match idx_str.strip_prefix('-') {
+                        Some(n) => match n.parse() {
+                            Ok(0) | Err(_) => return Err(invalid()),
+                            Ok(n) => PathSegment::IndexFromEnd(n),
+                        },
+                        None => PathSegment::Index(idx_str.parse().map_err(|_| invalid())?),
+                    };
+                    segments.push(segment);
                 }
                 ']' => {
                     return Err(JlcatError::InvalidColumnPath(format!(
@@ -70,7 +148,13 @@ impl CompiledPath {
         }
 
         if !current.is_empty() {
-            segments.push(PathSegment::Key(current));
+            segments.push(key_segment(current, &mut descend));
+        }
+        if descend {
+            return Err(JlcatError::InvalidColumnPath(format!(
+                "expected a key after '..' in '{}'",
+                path
+            )));
         }
 
         if segments.is_empty() {
@@ -80,13 +164,32 @@ impl CompiledPath {
             )));
         }
 
+        let dotted = (0..segments.len())
+            .map(|start| {
+                let keys: Vec<&str> = segments[start..]
+                    .iter()
+                    .map_while(|segment| match segment {
+                        PathSegment::Key(key) => Some(key.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                (1..=keys.len()).map(|n| keys[..n].join(".")).collect()
+            })
+            .collect();
+
         Ok(Self {
             segments,
             original: path.to_string(),
+            dotted,
         })
     }
 
+    /// The value at this path; for a path with `[*]` or `..key`, the first match
     pub fn get<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        if self.is_multi_valued() {
+            return self.get_all(value).into_iter().next();
+        }
+
         // First try literal key (for flattened column names like "address.city")
         if self.segments.len() > 1 {
             if let Some(v) = value.get(&self.original) {
@@ -94,17 +197,234 @@ impl CompiledPath {
             }
         }
 
-        // Fall back to nested path lookup
-        let mut current = value;
+        // Fall back to nested path lookup, then to nested keys containing dots
+        // (flat mode names {"user": {"profile.age": 1}} as "user.profile.age")
+        get_nested(value, &self.segments).or_else(|| self.get_dotted(value, 0))
+    }
 
+    /// Resolve the segments from `start` on, where any run of consecutive keys
+    /// may be a single key joined by dots
+    fn get_dotted<'a>(&self, value: &'a Value, start: usize) -> Option<&'a Value> {
+        match self.segments.get(start) {
+            None => Some(value),
+            Some(PathSegment::Wildcard | PathSegment::Descendant(_) | PathSegment::Slice(..)) => {
+                None
+            }
+            Some(segment @ (PathSegment::Index(_) | PathSegment::IndexFromEnd(_))) => {
+                self.get_dotted(step(value, segment)?, start + 1)
+            }
+            Some(PathSegment::Key(_)) => {
+                let obj = value.as_object()?;
+                self.dotted[start]
+                    .iter()
+                    .enumerate()
+                    .find_map(|(n, key)| self.get_dotted(obj.get(key)?, start + n + 1))
+            }
+        }
+    }
+
+    /// Whether the path can match more than one value (`[*]`, `..key` or a slice)
+    pub fn is_multi_valued(&self) -> bool {
+        self.segments.iter().any(|segment| {
+            matches!(
+                segment,
+                PathSegment::Wildcard | PathSegment::Descendant(_) | PathSegment::Slice(..)
+            )
+        })
+    }
+
+    /// Every value this path matches, expanding each `[*]` or slice over the elements
+    /// of an array and each `..key` over the occurrences of the key at any depth
+    pub fn get_all<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        if !self.is_multi_valued() {
+            return self.get(value).into_iter().collect();
+        }
+
+        let mut current = vec![value];
         for segment in &self.segments {
             current = match segment {
-                PathSegment::Key(key) => current.get(key)?,
-                PathSegment::Index(idx) => current.get(idx)?,
+                PathSegment::Wildcard => current
+                    .into_iter()
+                    .filter_map(Value::as_array)
+                    .flatten()
+                    .collect(),
+                PathSegment::Slice(start, end) => current
+                    .into_iter()
+                    .filter_map(Value::as_array)
+                    .flat_map(|arr| slice(arr, *start, *end))
+                    .collect(),
+                PathSegment::Descendant(key) => {
+                    let mut found = Vec::new();
+                    for value in current {
+                        descendants(value, key, &mut found);
+                    }
+                    found
+                }
+                _ => current
+                    .into_iter()
+                    .filter_map(|value| step(value, segment))
+                    .collect(),
             };
         }
+        current
+    }
+
+    /// The value shown for this path in a column: for a path that can match several
+    /// values, the matches joined as flat mode shows arrays, or null when nothing matches
+    pub fn display_value(&self, value: &Value) -> Value {
+        if !self.is_multi_valued() {
+            return self.get(value).cloned().unwrap_or(Value::Null);
+        }
 
-        Some(current)
+        let matches: Vec<Value> = self.get_all(value).into_iter().cloned().collect();
+        if matches.is_empty() {
+            return Value::Null;
+        }
+        Value::String(format_array(&Value::Array(matches), usize::MAX))
+    }
+}
+
+/// A function wrapping the path of a column, such as `len(items)`, that shows or
+/// compares something computed from the value instead of the value itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathFn {
+    /// `len(path)`: the number of elements of an array or entries of an object
+    Len,
+    /// `type(path)`: the JSON type name of the value
+    Type,
+}
+
+impl PathFn {
+    /// Type names `type(path)` can produce
+    pub const TYPE_NAMES: [&'static str; 6] =
+        ["string", "number", "boolean", "object", "array", "null"];
+
+    /// Split a column into its function, if any, and the path it applies to
+    pub fn parse(column: &str) -> (Option<Self>, &str) {
+        for (name, function) in [("len(", PathFn::Len), ("type(", PathFn::Type)] {
+            if let Some(path) = column.strip_prefix(name).and_then(|c| c.strip_suffix(')')) {
+                return (Some(function), path);
+            }
+        }
+        (None, column)
+    }
+
+    pub fn apply(self, value: &Value) -> Option<Value> {
+        match (self, value) {
+            (PathFn::Len, Value::Array(arr)) => Some(arr.len().into()),
+            (PathFn::Len, Value::Object(obj)) => Some(obj.len().into()),
+            (PathFn::Len, _) => None,
+            (PathFn::Type, value) => Some(ColumnType::from_value(value).name().into()),
+        }
+    }
+}
+
+/// A key segment, or a descendant one right after `..`
+fn key_segment(key: String, descend: &mut bool) -> PathSegment {
+    if std::mem::take(descend) {
+        PathSegment::Descendant(key)
+    } else {
+        PathSegment::Key(key)
+    }
+}
+
+/// The elements of `arr` from `start` up to `end`, with negative bounds counting
+/// from the end and out of range bounds clamped, as in Python
+fn slice(arr: &[Value], start: Option<isize>, end: Option<isize>) -> &[Value] {
+    let resolve = |bound: isize| {
+        if bound < 0 {
+            arr.len().saturating_sub(bound.unsigned_abs())
+        } else {
+            (bound as usize).min(arr.len())
+        }
+    };
+    let start = start.map_or(0, resolve);
+    let end = end.map_or(arr.len(), resolve);
+    arr.get(start..end).unwrap_or_default()
+}
+
+/// Collect the values of `key` anywhere below `value`, in document order
+fn descendants<'a>(value: &'a Value, key: &str, found: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(obj) => {
+            for (k, v) in obj {
+                if k == key {
+                    found.push(v);
+                }
+                descendants(v, key, found);
+            }
+        }
+        Value::Array(arr) => {
+            for v in arr {
+                descendants(v, key, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read a quoted key after its opening quote, up to the closing quote;
+/// `\\` escapes the quote or a backslash
+fn quoted_key(chars: &mut Peekable<Chars>, quote: char) -> Option<String> {
+    let mut key = String::new();
+    loop {
+        match chars.next()? {
+            '\\' => key.push(chars.next()?),
+            c if c == quote => return Some(key),
+            c => key.push(c),
+        }
+    }
+}
+
+/// Byte length of the path at the start of `s`, ending at the first character
+/// for which `stop` is true outside of brackets and backslash escapes.
+///
+/// Lets expressions that embed a path (filters, sort keys) find where it ends.
+pub fn path_len(s: &str, stop: impl Fn(char) -> bool) -> usize {
+    let mut chars = s.char_indices().peekable();
+    // What closes the current quoted key or index: a quote, or `]`
+    let mut quote: Option<char> = None;
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (_, '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '[') => {
+                quote = Some(
+                    chars
+                        .next_if(|&(_, c)| c == '"' || c == '\'')
+                        .map_or(']', |(_, c)| c),
+                );
+            }
+            (None, c) if stop(c) => return i,
+            (None, _) => {}
+        }
+    }
+    s.len()
+}
+
+fn get_nested<'a>(value: &'a Value, segments: &[PathSegment]) -> Option<&'a Value> {
+    let mut current = value;
+
+    for segment in segments {
+        current = step(current, segment)?;
+    }
+
+    Some(current)
+}
+
+/// The child of `value` that one segment addresses
+fn step<'a>(value: &'a Value, segment: &PathSegment) -> Option<&'a Value> {
+    match segment {
+        PathSegment::Key(key) => value.get(key),
+        PathSegment::Index(idx) => value.get(idx),
+        PathSegment::IndexFromEnd(n) => {
+            let arr = value.as_array()?;
+            arr.get(arr.len().checked_sub(*n)?)
+        }
+        PathSegment::Wildcard | PathSegment::Descendant(_) | PathSegment::Slice(..) => None,
     }
 }
 
@@ -158,6 +478,151 @@ mod tests {
         assert_eq!(path.get(&row), Some(&json!("B")));
     }
 
+    #[test]
+    fn test_negative_index() {
+        let path = CompiledPath::compile("history[-1].status").unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                PathSegment::Key("history".into()),
+                PathSegment::IndexFromEnd(1),
+                PathSegment::Key("status".into()),
+            ]
+        );
+
+        let row = json!({"history": [{"status": "new"}, {"status": "done"}]});
+        assert_eq!(path.get(&row), Some(&json!("done")));
+        let path = CompiledPath::compile("history[-2].status").unwrap();
+        assert_eq!(path.get(&row), Some(&json!("new")));
+        let path = CompiledPath::compile("history[-3].status").unwrap();
+        assert_eq!(path.get(&row), None);
+
+        assert!(CompiledPath::compile("history[-0]").is_err());
+        assert!(CompiledPath::compile("history[-x]").is_err());
+    }
+
+    #[test]
+    fn test_wildcard() {
+        let path = CompiledPath::compile("orders[*].total").unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                PathSegment::Key("orders".into()),
+                PathSegment::Wildcard,
+                PathSegment::Key("total".into()),
+            ]
+        );
+        assert!(path.is_multi_valued());
+
+        let row = json!({"orders": [{"total": 10}, {"id": 2}, {"total": 25}]});
+        assert_eq!(path.get_all(&row), vec![&json!(10), &json!(25)]);
+        assert_eq!(path.get(&row), Some(&json!(10)));
+        assert_eq!(path.display_value(&row), json!("10, 25"));
+
+        let names = CompiledPath::compile("tags[*]").unwrap();
+        assert_eq!(
+            names.display_value(&json!({"tags": ["a", "b"]})),
+            json!("a, b")
+        );
+        assert_eq!(names.display_value(&json!({"tags": []})), Value::Null);
+        assert_eq!(names.display_value(&json!({"tags": "a"})), Value::Null);
+
+        let nested = CompiledPath::compile("m[*][*]").unwrap();
+        assert_eq!(
+            nested.get_all(&json!({"m": [[1, 2], [3]]})),
+            vec![&json!(1), &json!(2), &json!(3)]
+        );
+    }
+
+    #[test]
+    fn test_recursive_descent() {
+        let path = CompiledPath::compile("..id").unwrap();
+        assert_eq!(path.segments, vec![PathSegment::Descendant("id".into())]);
+        let path = CompiledPath::compile("spec..image").unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                PathSegment::Key("spec".into()),
+                PathSegment::Descendant("image".into()),
+            ]
+        );
+        assert!(path.is_multi_valued());
+
+        let row = json!({
+            "spec": {
+                "containers": [{"image": "web:1"}, {"image": "sidecar:2"}],
+                "init": {"image": "init:3"}
+            },
+            "image": "top"
+        });
+        assert_eq!(
+            path.get_all(&row),
+            vec![&json!("web:1"), &json!("sidecar:2"), &json!("init:3")]
+        );
+        assert_eq!(path.get(&row), Some(&json!("web:1")));
+        assert_eq!(path.display_value(&row), json!("web:1, sidecar:2, init:3"));
+
+        let quoted = CompiledPath::compile(r#"..["a.b"]"#).unwrap();
+        assert_eq!(quoted.get(&json!({"x": [{"a.b": 1}]})), Some(&json!(1)));
+        assert_eq!(CompiledPath::compile("..missing").unwrap().get(&row), None);
+
+        for invalid in ["..", "a..", "a...b", "..[0]"] {
+            assert!(CompiledPath::compile(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_slice() {
+        let path = CompiledPath::compile("items[0:3]").unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                PathSegment::Key("items".into()),
+                PathSegment::Slice(Some(0), Some(3)),
+            ]
+        );
+
+        let row = json!({"items": [1, 2, 3, 4, 5], "objs": [{"n": "a"}, {"n": "b"}, {"n": "c"}]});
+        assert_eq!(path.display_value(&row), json!("1, 2, 3"));
+        assert_eq!(path.get(&row), Some(&json!(1)));
+
+        let display = |p: &str| CompiledPath::compile(p).unwrap().display_value(&row);
+        assert_eq!(display("items[:2]"), json!("1, 2"));
+        assert_eq!(display("items[3:]"), json!("4, 5"));
+        assert_eq!(display("items[-2:]"), json!("4, 5"));
+        assert_eq!(display("items[1:-3]"), json!("2"));
+        assert_eq!(display("items[2:10]"), json!("3, 4, 5"));
+        assert_eq!(display("items[4:2]"), Value::Null);
+        assert_eq!(display("objs[1:].n"), json!("b, c"));
+        assert_eq!(display("objs[:1]"), json!("{1 key}"));
+
+        assert!(CompiledPath::compile("items[a:2]").is_err());
+        assert!(CompiledPath::compile("items[1:2:3]").is_err());
+    }
+
+    #[test]
+    fn test_path_fn() {
+        assert_eq!(PathFn::parse("len(items)"), (Some(PathFn::Len), "items"));
+        assert_eq!(
+            PathFn::parse("len(a[\"x)\"])"),
+            (Some(PathFn::Len), "a[\"x)\"]")
+        );
+        assert_eq!(PathFn::parse("type(a.b)"), (Some(PathFn::Type), "a.b"));
+        assert_eq!(PathFn::parse("items"), (None, "items"));
+        assert_eq!(PathFn::parse("length(items)"), (None, "length(items)"));
+
+        assert_eq!(PathFn::Len.apply(&json!([1, 2, 3])), Some(json!(3)));
+        assert_eq!(PathFn::Len.apply(&json!({"a": 1})), Some(json!(1)));
+        assert_eq!(PathFn::Len.apply(&json!("abc")), None);
+
+        assert_eq!(PathFn::Type.apply(&json!("abc")), Some(json!("string")));
+        assert_eq!(PathFn::Type.apply(&json!(1.5)), Some(json!("number")));
+        assert_eq!(PathFn::Type.apply(&json!(true)), Some(json!("boolean")));
+        assert_eq!(PathFn::Type.apply(&json!(null)), Some(json!("null")));
+        assert_eq!(PathFn::Type.apply(&json!([])), Some(json!("array")));
+        assert_eq!(PathFn::Type.apply(&json!({})), Some(json!("object")));
+    }
+
     #[test]
     fn test_get_missing_value() {
         let path = CompiledPath::compile("missing.field").unwrap();
@@ -184,6 +649,89 @@ mod tests {
         assert_eq!(path.get(&row), Some(&json!("Literal")));
     }
 
+    #[test]
+    fn test_get_nested_dotted_key() {
+        // Flat mode column for {"user": {"profile.age": 30}}
+        let path = CompiledPath::compile("user.profile.age").unwrap();
+        let row = json!({"user": {"profile.age": 30}});
+        assert_eq!(path.get(&row), Some(&json!(30)));
+
+        let path = CompiledPath::compile("items[0].a.b").unwrap();
+        let row = json!({"items": [{"a.b": 1}]});
+        assert_eq!(path.get(&row), Some(&json!(1)));
+    }
+
+    #[test]
+    fn test_dotted_keys_joined_once() {
+        let path = CompiledPath::compile("a.b[0].c").unwrap();
+        assert_eq!(
+            path.dotted,
+            vec![
+                vec!["a".to_string(), "a.b".to_string()],
+                vec!["b".to_string()],
+                vec![],
+                vec!["c".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compile_quoted_keys() {
+        let path = CompiledPath::compile(r#"["a.b"].value"#).unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                PathSegment::Key("a.b".into()),
+                PathSegment::Key("value".into()),
+            ]
+        );
+
+        let path = CompiledPath::compile(r#"meta['x[0]']["say \"hi\""]"#).unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                PathSegment::Key("meta".into()),
+                PathSegment::Key("x[0]".into()),
+                PathSegment::Key("say \"hi\"".into()),
+            ]
+        );
+
+        assert!(CompiledPath::compile(r#"["a.b"#).is_err());
+        assert!(CompiledPath::compile(r#"["a.b"x]"#).is_err());
+    }
+
+    #[test]
+    fn test_compile_escaped_keys() {
+        let path = CompiledPath::compile(r"user.a\.b\[1\]").unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                PathSegment::Key("user".into()),
+                PathSegment::Key("a.b[1]".into()),
+            ]
+        );
+        assert!(CompiledPath::compile(r"a\").is_err());
+    }
+
+    #[test]
+    fn test_get_quoted_key_nested() {
+        // The dotted key sits below the top level, so the literal lookup can't find it
+        let row = json!({"meta": {"a.b": {"value": 1}, "a": {"b": {"value": 2}}}});
+        let path = CompiledPath::compile(r#"meta["a.b"].value"#).unwrap();
+        assert_eq!(path.get(&row), Some(&json!(1)));
+        let path = CompiledPath::compile("meta.a.b.value").unwrap();
+        assert_eq!(path.get(&row), Some(&json!(2)));
+    }
+
+    #[test]
+    fn test_path_len() {
+        let stop = |c: char| c == '=' || c == ' ';
+        assert_eq!(path_len("a.b=1", stop), 3);
+        assert_eq!(path_len(r#"["a=b"].c=1"#, stop), 9);
+        assert_eq!(path_len(r"a\=b=1", stop), 4);
+        assert_eq!(path_len("abc", stop), 3);
+    }
+
     #[test]
     fn test_unterminated_array_index_rejected() {
         // Missing closing bracket should be rejected
@@ -0,0 +1,104 @@
+use crate::error::{JlcatError, Result};
+use serde_json::Value;
+
+/// Rows picked by position, 1-based with inclusive ranges (`10..20,50,100..`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowRanges {
+    /// First and last position of each range; `None` runs to the end
+    ranges: Vec<(usize, Option<usize>)>,
+}
+
+impl RowRanges {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let ranges = spec
+            .split(',')
+            .map(|part| Self::parse_range(part.trim(), spec))
+            .collect::<Result<_>>()?;
+        Ok(Self { ranges })
+    }
+
+    fn parse_range(part: &str, spec: &str) -> Result<(usize, Option<usize>)> {
+        let invalid =
+            |reason: &str| JlcatError::InvalidRowRange(format!("{} in '{}'", reason, spec));
+        let position = |text: &str| match text.parse::<usize>() {
+            Ok(0) => Err(invalid("rows are numbered from 1")),
+            Ok(n) => Ok(n),
+            Err(_) => Err(invalid(&format!("expected a row number, got '{}'", text))),
+        };
+
+        match part.split_once("..") {
+            _ if part.is_empty() => Err(invalid("empty range")),
+            None => {
+                let n = position(part)?;
+                Ok((n, Some(n)))
+            }
+            Some((start, end)) => {
+                let start = if start.is_empty() {
+                    1
+                } else {
+                    position(start)?
+                };
+                let end = if end.is_empty() {
+                    None
+                } else {
+                    Some(position(end)?)
+                };
+                match end {
+                    Some(end) if end < start => {
+                        Err(invalid(&format!("range {} ends before it starts", part)))
+                    }
+                    _ => Ok((start, end)),
+                }
+            }
+        }
+    }
+
+    /// Whether the row at 1-based `position` is selected
+    pub fn contains(&self, position: usize) -> bool {
+        self.ranges
+            .iter()
+            .any(|&(start, end)| start <= position && end.is_none_or(|end| position <= end))
+    }
+
+    /// Keep the selected rows, in their original order
+    pub fn apply(&self, rows: Vec<Value>) -> Vec<Value> {
+        rows.into_iter()
+            .enumerate()
+            .filter(|(i, _)| self.contains(i + 1))
+            .map(|(_, row)| row)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_and_contains() {
+        let ranges = RowRanges::parse("2..3, 5,8..").unwrap();
+        let selected: Vec<usize> = (1..=10).filter(|&n| ranges.contains(n)).collect();
+        assert_eq!(selected, vec![2, 3, 5, 8, 9, 10]);
+
+        let head = RowRanges::parse("..2").unwrap();
+        assert!(head.contains(1) && head.contains(2) && !head.contains(3));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for spec in ["", "0", "3..1", "a..2", "1,,2", "1...3"] {
+            assert!(RowRanges::parse(spec).is_err(), "{} should fail", spec);
+        }
+    }
+
+    #[test]
+    fn test_apply_keeps_input_order() {
+        let rows: Vec<Value> = (1..=5).map(|id| json!({"id": id})).collect();
+        let ranges = RowRanges::parse("4,1..2").unwrap();
+        assert_eq!(
+            ranges.apply(rows),
+            vec![json!({"id": 1}), json!({"id": 2}), json!({"id": 4})]
+        );
+    }
+}
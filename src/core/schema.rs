@@ -1,5 +1,5 @@
-use serde_json::Value;
-use std::collections::{HashMap, HashSet};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColumnType {
@@ -13,7 +13,7 @@ pub enum ColumnType {
 }
 
 impl ColumnType {
-    fn from_value(value: &Value) -> Self {
+    pub fn from_value(value: &Value) -> Self {
         match value {
             Value::Null => ColumnType::Null,
             Value::Bool(_) => ColumnType::Bool,
@@ -24,6 +24,19 @@ impl ColumnType {
         }
     }
 
+    /// The name of the type, as jq's `type` reports it
+    pub fn name(self) -> &'static str {
+        match self {
+            ColumnType::Null => "null",
+            ColumnType::Bool => "boolean",
+            ColumnType::Number => "number",
+            ColumnType::String => "string",
+            ColumnType::Array => "array",
+            ColumnType::Object => "object",
+            ColumnType::Mixed => "mixed",
+        }
+    }
+
     fn merge(self, other: Self) -> Self {
         if self == other {
             self
@@ -42,6 +55,8 @@ pub struct Schema {
     columns: Vec<String>,
     types: HashMap<String, ColumnType>,
     nested: HashSet<String>,
+    /// Columns holding null in some row
+    nullable: HashSet<String>,
 }
 
 impl Schema {
@@ -50,6 +65,7 @@ impl Schema {
             columns: Vec::new(),
             types: HashMap::new(),
             nested: HashSet::new(),
+            nullable: HashSet::new(),
         }
     }
 
@@ -65,7 +81,26 @@ impl Schema {
         self.nested.contains(name)
     }
 
+    pub fn is_nullable(&self, name: &str) -> bool {
+        self.nullable.contains(name)
+    }
+
+    /// Type shown after a header by `--show-types`, e.g. `number` or
+    /// `string?` when some values are null
+    pub fn type_label(&self, name: &str) -> Option<String> {
+        let col_type = self.column_type(name)?;
+        let nullable = col_type != ColumnType::Null && self.is_nullable(name);
+        Some(format!(
+            "{}{}",
+            col_type.name(),
+            if nullable { "?" } else { "" }
+        ))
+    }
+
     fn add_column(&mut self, name: String, col_type: ColumnType) {
+        if col_type == ColumnType::Null {
+            self.nullable.insert(name.clone());
+        }
         if let Some(existing) = self.types.get_mut(&name) {
             *existing = existing.merge(col_type);
         } else {
@@ -103,6 +138,17 @@ impl SchemaInferrer {
         schema
     }
 
+    /// Infer the types of table cells, one row of values per `columns`
+    pub fn infer_cells(columns: &[String], rows: &[Vec<Value>]) -> Schema {
+        let mut schema = Schema::new();
+        for row in rows {
+            for (column, value) in columns.iter().zip(row) {
+                schema.add_column(column.clone(), ColumnType::from_value(value));
+            }
+        }
+        schema
+    }
+
     pub fn infer_streaming(row: &Value, schema: &mut Schema) {
         if let Value::Object(obj) = row {
             for (key, value) in obj {
@@ -113,10 +159,131 @@ impl SchemaInferrer {
     }
 }
 
+/// JSON Schema type names, in the order a schema lists them
+const SCHEMA_TYPES: [&str; 7] = [
+    "object", "array", "string", "integer", "number", "boolean", "null",
+];
+
+/// The values seen at one position of the rows, nested objects and array
+/// items included, for describing them as a JSON Schema
+#[derive(Debug, Clone, Default)]
+pub struct ValueShape {
+    /// JSON Schema type names seen here
+    types: HashSet<&'static str>,
+    /// Objects seen here, to tell which properties all of them have
+    objects: usize,
+    /// Shape of each property and how many objects have it
+    properties: BTreeMap<String, (ValueShape, usize)>,
+    /// Shape of the items of arrays seen here
+    items: Option<Box<ValueShape>>,
+}
+
+impl ValueShape {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, value: &Value) {
+        self.types.insert(schema_type(value));
+        match value {
+            Value::Object(obj) => {
+                self.objects += 1;
+                for (key, value) in obj {
+                    let (shape, count) = self.properties.entry(key.clone()).or_default();
+                    shape.add(value);
+                    *count += 1;
+                }
+            }
+            Value::Array(items) => {
+                let shape = self.items.get_or_insert_with(Default::default);
+                for item in items {
+                    shape.add(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// A draft 2020-12 JSON Schema document for the values seen
+    pub fn to_json_schema(&self) -> Value {
+        let mut schema = self.schema();
+        if let Value::Object(ref mut obj) = schema {
+            obj.insert(
+                "$schema".to_string(),
+                json!("https://json-schema.org/draft/2020-12/schema"),
+            );
+        }
+        schema
+    }
+
+    fn schema(&self) -> Value {
+        let mut schema = Map::new();
+        // An integer is a number too, so listing both would be redundant
+        let types: Vec<&str> = SCHEMA_TYPES
+            .iter()
+            .copied()
+            .filter(|t| self.types.contains(t))
+            .filter(|t| *t != "integer" || !self.types.contains("number"))
+            .collect();
+        match types.as_slice() {
+            [] => {}
+            [one] => {
+                schema.insert("type".to_string(), json!(one));
+            }
+            many => {
+                schema.insert("type".to_string(), json!(many));
+            }
+        }
+        if !self.properties.is_empty() {
+            let properties: Map<String, Value> = self
+                .properties
+                .iter()
+                .map(|(key, (shape, _))| (key.clone(), shape.schema()))
+                .collect();
+            schema.insert("properties".to_string(), Value::Object(properties));
+            let required: Vec<&String> = self
+                .properties
+                .iter()
+                .filter(|(_, (_, count))| *count == self.objects)
+                .map(|(key, _)| key)
+                .collect();
+            if !required.is_empty() {
+                schema.insert("required".to_string(), json!(required));
+            }
+        }
+        if let Some(ref items) = self.items {
+            // Only empty arrays were seen when the items have no type
+            if !items.types.is_empty() {
+                schema.insert("items".to_string(), items.schema());
+            }
+        }
+        Value::Object(schema)
+    }
+}
+
+/// The JSON Schema type of a value; numbers written without a fraction or
+/// exponent are integers
+fn schema_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) => {
+            let text = n.to_string();
+            if text.contains(['.', 'e', 'E']) {
+                "number"
+            } else {
+                "integer"
+            }
+        }
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json::json;
 
     #[test]
     fn test_infer_schema_simple() {
@@ -158,6 +325,22 @@ mod tests {
         assert_eq!(schema.column_type("active"), Some(ColumnType::Bool));
     }
 
+    #[test]
+    fn test_type_labels() {
+        let columns = vec!["age".to_string(), "name".to_string(), "x".to_string()];
+        let rows = vec![
+            vec![json!(30), json!("Alice"), json!(null)],
+            vec![json!(41), json!(null), json!(null)],
+        ];
+
+        let schema = SchemaInferrer::infer_cells(&columns, &rows);
+
+        assert_eq!(schema.type_label("age").as_deref(), Some("number"));
+        assert_eq!(schema.type_label("name").as_deref(), Some("string?"));
+        assert_eq!(schema.type_label("x").as_deref(), Some("null"));
+        assert_eq!(schema.type_label("missing"), None);
+    }
+
     #[test]
     fn test_mixed_types() {
         let rows = vec![json!({"value": 1}), json!({"value": "string"})];
@@ -168,6 +351,45 @@ mod tests {
         assert_eq!(schema.column_type("value"), Some(ColumnType::Mixed));
     }
 
+    #[test]
+    fn test_value_shape_json_schema() {
+        let mut shape = ValueShape::new();
+        shape.add(&json!({"id": 1, "tags": ["a"], "user": {"name": "Alice", "age": 30}}));
+        shape.add(&json!({"id": 2, "tags": [], "user": {"name": null, "age": 30.5}, "note": "x"}));
+
+        assert_eq!(
+            shape.to_json_schema(),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer"},
+                    "note": {"type": "string"},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "user": {
+                        "type": "object",
+                        "properties": {
+                            "age": {"type": "number"},
+                            "name": {"type": ["string", "null"]}
+                        },
+                        "required": ["age", "name"]
+                    }
+                },
+                "required": ["id", "tags", "user"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_value_shape_empty_arrays_have_no_items() {
+        let mut shape = ValueShape::new();
+        shape.add(&json!({"tags": []}));
+        assert_eq!(
+            shape.to_json_schema()["properties"]["tags"],
+            json!({"type": "array"})
+        );
+    }
+
     #[test]
     fn test_streaming_inference() {
         let mut schema = Schema::new();
@@ -0,0 +1,364 @@
+use crate::error::{JlcatError, Result};
+use serde_json::{Map, Value};
+
+/// Column holding results of `--select` that are not objects
+const VALUE_COLUMN: &str = "value";
+
+/// A small jq-like expression that reshapes each record before the table is built.
+///
+/// Supported: `.`, field paths (`.user.name`, `."odd key"`), indexes (`.items[0]`,
+/// `.items[-1]`), iteration (`.items[]`), object construction (`{id, name: .user.name}`)
+/// and pipes (`.items[] | {sku, qty}`).
+#[derive(Debug, Clone)]
+pub struct Select {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(i64),
+    Iterate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Path(Vec<Step>),
+    Object(Vec<(String, Term)>),
+}
+
+impl Select {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let mut parser = Parser {
+            chars: expr.chars().collect(),
+            pos: 0,
+        };
+        let mut terms = vec![parser.term()?];
+        loop {
+            parser.skip_whitespace();
+            match parser.peek() {
+                None => break,
+                Some('|') => {
+                    parser.pos += 1;
+                    terms.push(parser.term()?);
+                }
+                Some(c) => return Err(parser.error(&format!("unexpected '{}'", c))),
+            }
+        }
+        Ok(Self { terms })
+    }
+
+    /// Reshape every row; a row can yield any number of rows
+    pub fn apply(&self, rows: Vec<Value>) -> Vec<Value> {
+        rows.iter()
+            .flat_map(|row| self.eval(row))
+            .map(|value| match value {
+                Value::Object(_) => value,
+                other => {
+                    let mut object = Map::new();
+                    object.insert(VALUE_COLUMN.to_string(), other);
+                    Value::Object(object)
+                }
+            })
+            .collect()
+    }
+
+    fn eval(&self, row: &Value) -> Vec<Value> {
+        self.terms.iter().fold(vec![row.clone()], |values, term| {
+            values.iter().flat_map(|value| term.eval(value)).collect()
+        })
+    }
+}
+
+impl Term {
+    fn eval(&self, input: &Value) -> Vec<Value> {
+        match self {
+            Term::Path(steps) => steps.iter().fold(vec![input.clone()], |values, step| {
+                values.iter().flat_map(|value| step.eval(value)).collect()
+            }),
+            Term::Object(entries) => {
+                // One object per combination of entry values, as in jq
+                entries
+                    .iter()
+                    .fold(vec![Map::new()], |objects, (key, term)| {
+                        let values = term.eval(input);
+                        objects
+                            .iter()
+                            .flat_map(|object| {
+                                values.iter().map(move |value| {
+                                    let mut object = object.clone();
+                                    object.insert(key.clone(), value.clone());
+                                    object
+                                })
+                            })
+                            .collect()
+                    })
+                    .into_iter()
+                    .map(Value::Object)
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Step {
+    fn eval(&self, value: &Value) -> Vec<Value> {
+        match self {
+            Step::Key(key) => vec![value.get(key).cloned().unwrap_or(Value::Null)],
+            Step::Index(index) => {
+                let element = value.as_array().and_then(|arr| {
+                    let i = if *index < 0 {
+                        arr.len().checked_sub(index.unsigned_abs() as usize)?
+                    } else {
+                        *index as usize
+                    };
+                    arr.get(i)
+                });
+                vec![element.cloned().unwrap_or(Value::Null)]
+            }
+            // Anything but an array or object yields no rows
+            Step::Iterate => match value {
+                Value::Array(arr) => arr.clone(),
+                Value::Object(obj) => obj.values().cloned().collect(),
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> JlcatError {
+        JlcatError::InvalidSelect(format!("{} at position {}", message, self.pos + 1))
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected '{}'", expected)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn term(&mut self) -> Result<Term> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('.') => self.path(),
+            Some('{') => self.object(),
+            _ => Err(self.error("expected a path like '.name' or an object like '{id}'")),
+        }
+    }
+
+    /// `.`, or a series of `.key`, `."key"`, `[n]` and `[]` steps starting with `.`
+    fn path(&mut self) -> Result<Term> {
+        self.pos += 1;
+        let mut steps = Vec::new();
+        match self.peek() {
+            Some('"') => steps.push(Step::Key(self.string()?)),
+            Some(c) if is_ident_char(c) => steps.push(Step::Key(self.ident())),
+            _ => {}
+        }
+        loop {
+            match self.peek() {
+                Some('.') => {
+                    self.pos += 1;
+                    let key = match self.peek() {
+                        Some('"') => self.string()?,
+                        Some(c) if is_ident_char(c) => self.ident(),
+                        _ => return Err(self.error("expected a field name after '.'")),
+                    };
+                    steps.push(Step::Key(key));
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        steps.push(Step::Iterate);
+                        continue;
+                    }
+                    let start = self.pos;
+                    if self.peek() == Some('-') {
+                        self.pos += 1;
+                    }
+                    while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        self.pos += 1;
+                    }
+                    let digits: String = self.chars[start..self.pos].iter().collect();
+                    let index = digits
+                        .parse()
+                        .map_err(|_| self.error("expected an array index or ']'"))?;
+                    self.expect(']')?;
+                    steps.push(Step::Index(index));
+                }
+                _ => break,
+            }
+        }
+        Ok(Term::Path(steps))
+    }
+
+    /// `{key, key: term, "key": term}`
+    fn object(&mut self) -> Result<Term> {
+        self.pos += 1;
+        let mut entries = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if entries.is_empty() && self.peek() == Some('}') {
+                self.pos += 1;
+                break;
+            }
+            let key = match self.peek() {
+                Some('"') => self.string()?,
+                Some(c) if is_ident_char(c) => self.ident(),
+                _ => return Err(self.error("expected a key")),
+            };
+            self.skip_whitespace();
+            let term = if self.peek() == Some(':') {
+                self.pos += 1;
+                self.term()?
+            } else {
+                Term::Path(vec![Step::Key(key.clone())])
+            };
+            entries.push((key, term));
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+        Ok(Term::Object(entries))
+    }
+
+    fn ident(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_ident_char) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// A double-quoted string with `\"` and `\\` escapes
+    fn string(&mut self) -> Result<String> {
+        self.pos += 1;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                Some('\\') if matches!(self.chars.get(self.pos + 1), Some('"' | '\\')) => {
+                    s.push(self.chars[self.pos + 1]);
+                    self.pos += 2;
+                }
+                Some(c) => {
+                    s.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn select(expr: &str, row: Value) -> Vec<Value> {
+        Select::parse(expr).unwrap().apply(vec![row])
+    }
+
+    #[test]
+    fn test_identity_and_paths() {
+        let row = json!({"id": 1, "user": {"name": "Alice", "odd key": true}});
+        assert_eq!(select(".", row.clone()), vec![row.clone()]);
+        assert_eq!(
+            select(".user", row.clone()),
+            vec![json!({"name": "Alice", "odd key": true})]
+        );
+        assert_eq!(
+            select(".user.\"odd key\"", row.clone()),
+            vec![json!({"value": true})]
+        );
+        assert_eq!(select(".missing.deep", row), vec![json!({"value": null})]);
+    }
+
+    #[test]
+    fn test_iterate_and_construct() {
+        let row = json!({"id": 7, "items": [{"sku": "a", "qty": 1}, {"sku": "b", "qty": 2}]});
+        assert_eq!(
+            select(".items[] | {sku, count: .qty}", row.clone()),
+            vec![
+                json!({"sku": "a", "count": 1}),
+                json!({"sku": "b", "count": 2}),
+            ]
+        );
+        assert_eq!(
+            select("{id, sku: .items[].sku}", row.clone()),
+            vec![json!({"id": 7, "sku": "a"}), json!({"id": 7, "sku": "b"})]
+        );
+        assert_eq!(
+            select("{first: .items[0].sku, last: .items[-1].sku}", row),
+            vec![json!({"first": "a", "last": "b"})]
+        );
+        // Nothing to iterate yields no rows
+        assert!(select(".items[]", json!({"id": 1})).is_empty());
+    }
+
+    #[test]
+    fn test_nested_objects_and_quoted_keys() {
+        let row = json!({"user": {"name": "Bob"}});
+        assert_eq!(
+            select(r#"{"user name": .user.name, u: {name: .user.name}}"#, row),
+            vec![json!({"user name": "Bob", "u": {"name": "Bob"}})]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for expr in [
+            "",
+            "items",
+            ".items[",
+            ".items[x]",
+            "{id",
+            "{id,}",
+            ". | ",
+            ".a b",
+        ] {
+            let err = Select::parse(expr).unwrap_err();
+            assert!(
+                matches!(err, JlcatError::InvalidSelect(_)),
+                "{}: {}",
+                expr,
+                err
+            );
+        }
+    }
+}
@@ -1,10 +1,11 @@
-use super::path::CompiledPath;
+use super::flat::format_array;
+use super::path::{CompiledPath, PathFn};
 use crate::error::Result;
 use serde_json::Value;
 
 #[derive(Debug, Clone)]
 pub struct ColumnSelector {
-    columns: Vec<(String, CompiledPath)>, // (original_name, compiled_path)
+    columns: Vec<(String, CompiledPath, Option<PathFn>)>, // (original_name, compiled_path, function)
 }
 
 impl ColumnSelector {
@@ -12,8 +13,9 @@ impl ColumnSelector {
         let compiled: Result<Vec<_>> = columns
             .into_iter()
             .map(|col| {
-                let path = CompiledPath::compile(&col)?;
-                Ok((col, path))
+                let (function, path) = PathFn::parse(&col);
+                let path = CompiledPath::compile(path)?;
+                Ok((col, path, function))
             })
             .collect();
 
@@ -21,27 +23,42 @@ impl ColumnSelector {
     }
 
     pub fn columns(&self) -> Vec<&str> {
-        self.columns.iter().map(|(name, _)| name.as_str()).collect()
+        self.columns
+            .iter()
+            .map(|(name, _, _)| name.as_str())
+            .collect()
     }
 
     #[allow(dead_code)]
     pub fn select(&self, row: &Value) -> Vec<(String, Value)> {
         self.columns
             .iter()
-            .map(|(name, path)| {
-                let value = path.get(row).cloned().unwrap_or(Value::Null);
-                (name.clone(), value)
-            })
+            .map(|(name, path, function)| (name.clone(), Self::value(path, *function, row)))
             .collect()
     }
 
-    #[allow(dead_code)]
     pub fn select_values(&self, row: &Value) -> Vec<Value> {
         self.columns
             .iter()
-            .map(|(_, path)| path.get(row).cloned().unwrap_or(Value::Null))
+            .map(|(_, path, function)| Self::value(path, *function, row))
             .collect()
     }
+
+    fn value(path: &CompiledPath, function: Option<PathFn>, row: &Value) -> Value {
+        let Some(function) = function else {
+            return path.display_value(row);
+        };
+        let mut computed: Vec<Value> = path
+            .get_all(row)
+            .into_iter()
+            .filter_map(|value| function.apply(value))
+            .collect();
+        match computed.len() {
+            0 => Value::Null,
+            1 if !path.is_multi_valued() => computed.remove(0),
+            _ => Value::String(format_array(&Value::Array(computed), usize::MAX)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -84,6 +101,53 @@ mod tests {
         assert_eq!(selected[1], ("missing".to_string(), Value::Null));
     }
 
+    #[test]
+    fn test_select_quoted_key() {
+        let selector = ColumnSelector::new(vec![r#"meta["a.b"].value"#.into()]).unwrap();
+        let row = json!({"meta": {"a.b": {"value": 1}}});
+
+        assert_eq!(selector.select_values(&row), vec![json!(1)]);
+    }
+
+    #[test]
+    fn test_select_wildcard_joins_matches() {
+        let selector = ColumnSelector::new(vec!["orders[*].total".into()]).unwrap();
+        let row = json!({"orders": [{"total": 10}, {"total": 25.5}]});
+        assert_eq!(selector.select_values(&row), vec![json!("10, 25.5")]);
+    }
+
+    #[test]
+    fn test_select_len() {
+        let selector = ColumnSelector::new(vec![
+            "len(items)".into(),
+            "len(meta)".into(),
+            "len(name)".into(),
+            "len(orders[*].items)".into(),
+        ])
+        .unwrap();
+        let row = json!({
+            "items": [1, 2, 3],
+            "meta": {"a": 1, "b": 2},
+            "name": "x",
+            "orders": [{"items": [1]}, {"items": [1, 2]}]
+        });
+        assert_eq!(selector.columns()[0], "len(items)");
+        assert_eq!(
+            selector.select_values(&row),
+            vec![json!(3), json!(2), Value::Null, json!("1, 2")]
+        );
+    }
+
+    #[test]
+    fn test_select_type() {
+        let selector = ColumnSelector::new(vec!["type(a)".into(), "type(b)".into()]).unwrap();
+        let row = json!({"a": "x", "b": [1]});
+        assert_eq!(
+            selector.select_values(&row),
+            vec![json!("string"), json!("array")]
+        );
+    }
+
     #[test]
     fn test_columns_list() {
         let selector = ColumnSelector::new(vec!["id".into(), "name".into()]).unwrap();
@@ -1,13 +1,66 @@
 use super::path::CompiledPath;
+use super::time::timestamp_seconds;
 use super::value::SortableValue;
 use crate::error::{JlcatError, Result};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::cmp::Ordering;
 
+/// How values of a sort key are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKind {
+    /// Natural JSON ordering (see `SortableValue`)
+    #[default]
+    Value,
+    /// Chronological ordering of ISO-8601 strings and epoch numbers
+    Date,
+    /// Numeric ordering that also parses numeric-looking strings ("42")
+    Number,
+}
+
+/// Where null and missing values are placed, independent of sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullOrder {
+    First,
+    #[default]
+    Last,
+}
+
+/// A value derived from the path rather than the path's value itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputedKey {
+    /// Number of array elements, object keys, or string characters
+    /// (`len(items)` or `items.#`)
+    Length,
+}
+
+impl ComputedKey {
+    fn compute(&self, value: &Value) -> Value {
+        match self {
+            ComputedKey::Length => match value {
+                Value::Array(arr) => Value::from(arr.len()),
+                Value::Object(obj) => Value::from(obj.len()),
+                Value::String(s) => Value::from(s.chars().count()),
+                _ => Value::Null,
+            },
+        }
+    }
+}
+
+/// Timestamps of a row's sort keys, one per key; `None` for keys that are
+/// not sorted by date
+type KeyTimes = [Option<f64>];
+
+/// Modifiers a sort key may end with, each after a `:`
+const MODIFIERS: [&str; 4] = ["date", "num", "nulls-first", "nulls-last"];
+
 #[derive(Debug, Clone)]
 pub struct SortKey {
     pub path: CompiledPath,
     pub descending: bool,
+    pub kind: SortKind,
+    pub nulls: NullOrder,
+    pub computed: Option<ComputedKey>,
 }
 
 impl SortKey {
@@ -22,12 +75,94 @@ impl SortKey {
             (false, s)
         };
 
+        // Modifiers follow the column name (e.g., "created_at:date"). Only
+        // known ones are split off, so names such as "k8s:pod" stay whole.
+        let mut column = column;
+        let mut modifiers = Vec::new();
+        while let Some((rest, modifier)) = column.rsplit_once(':') {
+            if !MODIFIERS.contains(&modifier) {
+                break;
+            }
+            modifiers.push(modifier);
+            column = rest;
+        }
+
+        let (column, computed) = if let Some(inner) = column
+            .strip_prefix("len(")
+            .and_then(|c| c.strip_suffix(')'))
+        {
+            (inner, Some(ComputedKey::Length))
+        } else if let Some(inner) = column.strip_suffix(".#") {
+            (inner, Some(ComputedKey::Length))
+        } else {
+            (column, None)
+        };
+
         if column.is_empty() {
             return Err(JlcatError::InvalidSortKey("empty column name".into()));
         }
 
+        let mut kind = SortKind::Value;
+        let mut nulls = NullOrder::Last;
+        // Collected right to left; the last one written wins
+        for modifier in modifiers.into_iter().rev() {
+            match modifier {
+                "date" => kind = SortKind::Date,
+                "num" => kind = SortKind::Number,
+                "nulls-first" => nulls = NullOrder::First,
+                _ => nulls = NullOrder::Last,
+            }
+        }
+
         let path = CompiledPath::compile(column)?;
-        Ok(Self { path, descending })
+        Ok(Self {
+            path,
+            descending,
+            kind,
+            nulls,
+            computed,
+        })
+    }
+
+    /// Resolve the value this key sorts on for a row
+    fn resolve<'a>(&self, row: &'a Value) -> Option<Cow<'a, Value>> {
+        let value = self.path.get(row)?;
+        match self.computed {
+            Some(computed) => Some(Cow::Owned(computed.compute(value))),
+            None => Some(Cow::Borrowed(value)),
+        }
+    }
+
+    /// Timestamp of a `:date` key's value in a row, parsed once per row
+    /// before sorting
+    fn timestamp(&self, row: &Value) -> Option<f64> {
+        match self.kind {
+            SortKind::Date => self.resolve(row).and_then(|v| timestamp_seconds(&v)),
+            _ => None,
+        }
+    }
+
+    /// Compare two values; `times` are their timestamps when already parsed
+    fn compare_values(
+        &self,
+        a: &Value,
+        b: &Value,
+        times: Option<(Option<f64>, Option<f64>)>,
+    ) -> Ordering {
+        match self.kind {
+            SortKind::Value => SortableValue::new(a).cmp(&SortableValue::new(b)),
+            SortKind::Number => SortableValue::coercing(a).cmp(&SortableValue::coercing(b)),
+            SortKind::Date => {
+                let times = times.unwrap_or_else(|| (timestamp_seconds(a), timestamp_seconds(b)));
+                match times {
+                    (Some(ta), Some(tb)) => ta.partial_cmp(&tb).unwrap_or(Ordering::Equal),
+                    // Unparseable values sort after timestamps
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => SortableValue::new(a).cmp(&SortableValue::new(b)),
+                }
+            }
+        }
     }
 }
 
@@ -47,7 +182,23 @@ impl Sorter {
     }
 
     pub fn sort(&self, rows: &mut [Value]) {
-        rows.sort_by(|a, b| self.compare(a, b));
+        if !self.keys.iter().any(|key| key.kind == SortKind::Date) {
+            rows.sort_by(|a, b| self.compare(a, b));
+            return;
+        }
+
+        // Parse each row's timestamps once instead of on every comparison
+        let times: Vec<Vec<Option<f64>>> = rows
+            .iter()
+            .map(|row| self.keys.iter().map(|key| key.timestamp(row)).collect())
+            .collect();
+        let mut order: Vec<usize> = (0..rows.len()).collect();
+        order.sort_by(|&i, &j| self.compare_with(&rows[i], &rows[j], Some((&times[i], &times[j]))));
+
+        let mut taken: Vec<Value> = rows.iter_mut().map(std::mem::take).collect();
+        for (slot, &i) in rows.iter_mut().zip(&order) {
+            *slot = std::mem::take(&mut taken[i]);
+        }
     }
 
     #[allow(dead_code)]
@@ -57,27 +208,46 @@ impl Sorter {
         indices
     }
 
-    fn compare(&self, a: &Value, b: &Value) -> Ordering {
-        for key in &self.keys {
-            let val_a = key.path.get(a);
-            let val_b = key.path.get(b);
+    /// Order of two rows under the sort keys
+    pub fn compare(&self, a: &Value, b: &Value) -> Ordering {
+        self.compare_with(a, b, None)
+    }
 
-            // Handle nulls-last for both ascending and descending
-            let a_is_null = val_a.is_none_or(|v| v.is_null());
-            let b_is_null = val_b.is_none_or(|v| v.is_null());
+    /// Order of two rows, with the timestamps of their `:date` keys when
+    /// parsed in advance (one per key)
+    fn compare_with(
+        &self,
+        a: &Value,
+        b: &Value,
+        times: Option<(&KeyTimes, &KeyTimes)>,
+    ) -> Ordering {
+        for (k, key) in self.keys.iter().enumerate() {
+            let val_a = key.resolve(a);
+            let val_b = key.resolve(b);
+
+            // Null placement is independent of ascending/descending
+            let a_is_null = val_a.as_deref().is_none_or(Value::is_null);
+            let b_is_null = val_b.as_deref().is_none_or(Value::is_null);
 
-            if a_is_null && !b_is_null {
-                return Ordering::Greater; // null goes last
-            }
-            if !a_is_null && b_is_null {
-                return Ordering::Less; // non-null goes first
-            }
             if a_is_null && b_is_null {
                 continue; // both null, check next key
             }
+            if a_is_null != b_is_null {
+                let null_ord = if a_is_null {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                };
+                return match key.nulls {
+                    NullOrder::Last => null_ord,
+                    NullOrder::First => null_ord.reverse(),
+                };
+            }
 
             let ord = match (val_a, val_b) {
-                (Some(va), Some(vb)) => SortableValue::new(va).cmp(&SortableValue::new(vb)),
+                (Some(va), Some(vb)) => {
+                    key.compare_values(&va, &vb, times.map(|(ta, tb)| (ta[k], tb[k])))
+                }
                 _ => Ordering::Equal,
             };
 
@@ -203,6 +373,53 @@ mod tests {
         assert_eq!(rows[2]["name"], Value::Null);
     }
 
+    #[test]
+    fn test_sort_nulls_first() {
+        let mut rows = vec![
+            json!({"id": 1, "score": 10}),
+            json!({"id": 2}),
+            json!({"id": 3, "score": 5}),
+            json!({"id": 4, "score": null}),
+        ];
+
+        let sorter = Sorter::new(vec![SortKey::parse("-score:nulls-first").unwrap()]);
+        sorter.sort(&mut rows);
+
+        // Nulls and missing values first (stable), then descending scores
+        let ids: Vec<_> = rows.iter().map(|r| r["id"].as_i64().unwrap()).collect();
+        assert_eq!(ids, vec![2, 4, 1, 3]);
+    }
+
+    #[test]
+    fn test_parse_computed_length() {
+        let key = SortKey::parse("len(items)").unwrap();
+        assert_eq!(key.path.original, "items");
+        assert_eq!(key.computed, Some(ComputedKey::Length));
+
+        let key = SortKey::parse("-user.tags.#").unwrap();
+        assert_eq!(key.path.original, "user.tags");
+        assert!(key.descending);
+        assert_eq!(key.computed, Some(ComputedKey::Length));
+
+        assert!(SortKey::parse("len()").is_err());
+    }
+
+    #[test]
+    fn test_sort_by_array_length() {
+        let mut rows = vec![
+            json!({"id": 1, "items": [1, 2, 3]}),
+            json!({"id": 2, "items": []}),
+            json!({"id": 3}),
+            json!({"id": 4, "items": [1]}),
+        ];
+
+        let sorter = Sorter::new(vec![SortKey::parse("-items.#").unwrap()]);
+        sorter.sort(&mut rows);
+
+        let ids: Vec<_> = rows.iter().map(|r| r["id"].as_i64().unwrap()).collect();
+        assert_eq!(ids, vec![1, 4, 2, 3]);
+    }
+
     #[test]
     fn test_sort_indices() {
         let rows = vec![json!({"id": 3}), json!({"id": 1}), json!({"id": 2})];
@@ -213,6 +430,91 @@ mod tests {
         assert_eq!(indices, vec![1, 2, 0]); // id=1 at index 1, id=2 at index 2, id=3 at index 0
     }
 
+    #[test]
+    fn test_parse_date_modifier() {
+        let key = SortKey::parse("-created_at:date").unwrap();
+        assert_eq!(key.path.original, "created_at");
+        assert!(key.descending);
+        assert_eq!(key.kind, SortKind::Date);
+
+        // Unknown suffixes are part of the column name
+        let key = SortKey::parse("k8s:pod").unwrap();
+        assert_eq!(key.path.original, "k8s:pod");
+        assert_eq!(key.kind, SortKind::Value);
+
+        let key = SortKey::parse("k8s:pod:num:nulls-first").unwrap();
+        assert_eq!(key.path.original, "k8s:pod");
+        assert_eq!(key.kind, SortKind::Number);
+        assert_eq!(key.nulls, NullOrder::First);
+    }
+
+    #[test]
+    fn test_sort_dates_across_offsets() {
+        // Lexicographically "12:00+09:00" sorts after "05:00Z", but it is 03:00Z
+        let mut rows = vec![
+            json!({"id": 1, "at": "2024-01-01T05:00:00Z"}),
+            json!({"id": 2, "at": "not a date"}),
+            json!({"id": 3, "at": "2024-01-01T12:00:00+09:00"}),
+            json!({"id": 4, "at": 1704070800}), // 01:00Z
+        ];
+        let sorter = Sorter::new(vec![SortKey::parse("at:date").unwrap()]);
+        sorter.sort(&mut rows);
+
+        let ids: Vec<_> = rows.iter().map(|r| r["id"].as_i64().unwrap()).collect();
+        assert_eq!(ids, vec![4, 3, 1, 2]);
+    }
+
+    #[test]
+    fn test_parse_quoted_key_with_colon() {
+        let key = SortKey::parse(r#"-["a:b"].at:date"#).unwrap();
+        assert_eq!(key.path.original, r#"["a:b"].at"#);
+        assert!(key.descending);
+        assert_eq!(key.kind, SortKind::Date);
+
+        let row = json!({"a:b": {"at": 1}});
+        assert_eq!(key.path.get(&row), Some(&json!(1)));
+    }
+
+    #[test]
+    fn test_parse_slice_with_modifier() {
+        let key = SortKey::parse("-scores[1:3]:date").unwrap();
+        assert_eq!(key.path.original, "scores[1:3]");
+        assert_eq!(key.kind, SortKind::Date);
+    }
+
+    #[test]
+    fn test_sort_numeric_strings() {
+        let mut rows = vec![
+            json!({"id": 1, "age": "42"}),
+            json!({"id": 2, "age": 7}),
+            json!({"id": 3, "age": "n/a"}),
+            json!({"id": 4, "age": "100"}),
+            json!({"id": 5, "age": 30.5}),
+        ];
+
+        let sorter = Sorter::new(vec![SortKey::parse("age:num").unwrap()]);
+        sorter.sort(&mut rows);
+
+        let ids: Vec<_> = rows.iter().map(|r| r["id"].as_i64().unwrap()).collect();
+        assert_eq!(ids, vec![2, 5, 1, 4, 3]);
+    }
+
+    #[test]
+    fn test_sort_date_mixed_formats() {
+        let mut rows = vec![
+            json!({"id": 1, "at": "2024-01-02"}),
+            json!({"id": 2, "at": 1704067200}), // 2024-01-01T00:00:00Z
+            json!({"id": 3, "at": "2024-01-01T12:00:00+09:00"}), // 03:00Z
+            json!({"id": 4, "at": "not a date"}),
+        ];
+
+        let sorter = Sorter::new(vec![SortKey::parse("at:date").unwrap()]);
+        sorter.sort(&mut rows);
+
+        let ids: Vec<_> = rows.iter().map(|r| r["id"].as_i64().unwrap()).collect();
+        assert_eq!(ids, vec![2, 3, 1, 4]);
+    }
+
     #[test]
     fn test_sorter_parse() {
         let sorter = Sorter::parse(&["name".to_string(), "-age".to_string()]).unwrap();
@@ -32,12 +32,7 @@ impl TableData {
 
         let table_rows: Vec<Vec<Value>> = rows
             .iter()
-            .map(|row| {
-                columns
-                    .iter()
-                    .map(|col| get_nested_value(row, col).cloned().unwrap_or(Value::Null))
-                    .collect()
-            })
+            .map(|row| Self::row_values(row, &columns, selector.as_ref()))
             .collect();
 
         Self {
@@ -47,6 +42,21 @@ impl TableData {
         }
     }
 
+    /// Cells of one row under `columns`, picked by `selector` when there is one
+    pub fn row_values(
+        row: &Value,
+        columns: &[String],
+        selector: Option<&ColumnSelector>,
+    ) -> Vec<Value> {
+        match selector {
+            Some(sel) => sel.select_values(row),
+            None => columns
+                .iter()
+                .map(|col| get_nested_value(row, col).cloned().unwrap_or(Value::Null))
+                .collect(),
+        }
+    }
+
     pub fn columns(&self) -> &[String] {
         &self.columns
     }
@@ -70,6 +80,13 @@ impl TableData {
         self.rows.get(row).and_then(|r| r.get(col))
     }
 
+    /// Replace one cell; does nothing past the end of the table
+    pub fn set_cell(&mut self, row: usize, col: usize, value: Value) {
+        if let Some(cell) = self.rows.get_mut(row).and_then(|r| r.get_mut(col)) {
+            *cell = value;
+        }
+    }
+
     #[allow(dead_code)]
     pub fn get_row(&self, index: usize) -> Option<&[Value]> {
         self.rows.get(index).map(|r| r.as_slice())
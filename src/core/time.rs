@@ -0,0 +1,530 @@
+use crate::error::{JlcatError, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Parse an ISO-8601 / RFC 3339 timestamp string.
+/// Timestamps without an offset are treated as UTC.
+pub fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+
+    // Cheap pre-check so ordinary strings don't go through every format
+    let bytes = s.as_bytes();
+    if bytes.len() < 10 || !bytes[0].is_ascii_digit() || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    for fmt in ["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f%z"] {
+        if let Ok(dt) = DateTime::parse_from_str(s, fmt) {
+            return Some(dt.with_timezone(&Utc));
+        }
+    }
+
+    let naive = s.strip_suffix(['Z', 'z']).unwrap_or(s);
+    for fmt in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(ndt) = NaiveDateTime::parse_from_str(naive, fmt) {
+            return Some(ndt.and_utc());
+        }
+    }
+
+    NaiveDate::parse_from_str(naive, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|ndt| ndt.and_utc())
+}
+
+/// Interpret a JSON value as a point in time, in seconds since the Unix epoch.
+/// Numbers are epochs with their unit detected by magnitude; strings are parsed
+/// as ISO-8601 timestamps.
+pub fn timestamp_seconds(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64().map(|n| n / EpochUnit::detect(n).per_second()),
+        Value::String(s) => parse_datetime(s).map(|dt| dt.timestamp_millis() as f64 / 1000.0),
+        _ => None,
+    }
+}
+
+/// Unit of an epoch timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EpochUnit {
+    /// Detect the unit from the magnitude of the value
+    #[default]
+    Auto,
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl EpochUnit {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(EpochUnit::Auto),
+            "s" | "sec" | "secs" | "seconds" => Ok(EpochUnit::Seconds),
+            "ms" | "millis" | "milliseconds" => Ok(EpochUnit::Millis),
+            "us" | "micros" | "microseconds" => Ok(EpochUnit::Micros),
+            "ns" | "nanos" | "nanoseconds" => Ok(EpochUnit::Nanos),
+            other => Err(JlcatError::InvalidDateFormat(format!(
+                "unknown epoch unit '{}' (expected auto, s, ms, us, or ns)",
+                other
+            ))),
+        }
+    }
+
+    /// Pick a unit by magnitude. Epochs in each unit are about 1000x apart, so
+    /// present-day seconds (~1.7e9), millis (~1.7e12), micros (~1.7e15), and
+    /// nanos (~1.7e18) fall cleanly into separate ranges.
+    pub fn detect(n: f64) -> Self {
+        let n = n.abs();
+        if n < 1e11 {
+            EpochUnit::Seconds
+        } else if n < 1e14 {
+            EpochUnit::Millis
+        } else if n < 1e17 {
+            EpochUnit::Micros
+        } else {
+            EpochUnit::Nanos
+        }
+    }
+
+    fn per_second(self) -> f64 {
+        match self {
+            EpochUnit::Auto | EpochUnit::Seconds => 1.0,
+            EpochUnit::Millis => 1e3,
+            EpochUnit::Micros => 1e6,
+            EpochUnit::Nanos => 1e9,
+        }
+    }
+
+    /// Interpret an epoch number in this unit as a point in time
+    pub fn to_datetime(self, n: f64) -> Option<DateTime<Utc>> {
+        if !n.is_finite() {
+            return None;
+        }
+        let unit = match self {
+            EpochUnit::Auto => Self::detect(n),
+            unit => unit,
+        };
+        let micros = n * 1e6 / unit.per_second();
+        if micros.abs() >= i64::MAX as f64 {
+            return None;
+        }
+        DateTime::from_timestamp_micros(micros.round() as i64)
+    }
+}
+
+/// Whether a column name suggests it holds timestamps (e.g., `ts`, `created_at`)
+fn is_time_column(column: &str) -> bool {
+    let name = column.rsplit('.').next().unwrap_or(column).to_lowercase();
+    matches!(
+        name.as_str(),
+        "ts" | "time" | "timestamp" | "date" | "datetime" | "@timestamp"
+    ) || ["_at", "_time", "_ts", "_date"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+/// Pattern used when a timezone is set without an explicit `--date-format`
+const ZONED_PATTERN: &str = "%Y-%m-%d %H:%M:%S%:z";
+
+/// Timezone used to display timestamps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayZone {
+    Utc,
+    Local,
+    Named(Tz),
+}
+
+impl DisplayZone {
+    /// Parse `UTC`, `local`, or an IANA zone name such as `Asia/Tokyo`
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "utc" | "z" => Ok(DisplayZone::Utc),
+            "local" => Ok(DisplayZone::Local),
+            _ => s
+                .parse::<Tz>()
+                .map(DisplayZone::Named)
+                .map_err(|_| JlcatError::InvalidDateFormat(format!("unknown timezone '{}'", s))),
+        }
+    }
+
+    fn format(&self, dt: DateTime<Utc>, pattern: &str) -> String {
+        match self {
+            DisplayZone::Utc => dt.format(pattern).to_string(),
+            DisplayZone::Local => dt.with_timezone(&Local).format(pattern).to_string(),
+            DisplayZone::Named(tz) => dt.with_timezone(tz).format(pattern).to_string(),
+        }
+    }
+}
+
+/// Reformats recognized timestamp cells with strftime patterns at display time
+#[derive(Debug, Clone, Default)]
+pub struct TimeFormat {
+    default: Option<String>,
+    columns: HashMap<String, String>,
+    zone: Option<DisplayZone>,
+    unit: EpochUnit,
+    column_units: HashMap<String, EpochUnit>,
+    relative: HashSet<String>,
+}
+
+impl TimeFormat {
+    /// Parse `--date-format` specs.
+    /// `FORMAT` applies to every timestamp column; `COLUMN=FORMAT` to a single column.
+    pub fn parse(specs: &[String]) -> Result<Self> {
+        let mut time_format = TimeFormat::default();
+
+        for spec in specs {
+            match spec.split_once('=') {
+                Some((column, pattern)) if !column.contains('%') => {
+                    let column = column.trim();
+                    if column.is_empty() {
+                        return Err(JlcatError::InvalidDateFormat(format!(
+                            "missing column name in '{}'",
+                            spec
+                        )));
+                    }
+                    validate_pattern(pattern)?;
+                    time_format
+                        .columns
+                        .insert(column.to_string(), pattern.to_string());
+                }
+                _ => {
+                    validate_pattern(spec)?;
+                    time_format.default = Some(spec.clone());
+                }
+            }
+        }
+
+        Ok(time_format)
+    }
+
+    /// Convert timestamps to `zone` before formatting (UTC otherwise)
+    pub fn with_zone(mut self, zone: DisplayZone) -> Self {
+        self.zone = Some(zone);
+        self
+    }
+
+    /// Parse `--epoch-unit` specs overriding unit detection.
+    /// `UNIT` applies to every epoch column; `COLUMN=UNIT` to a single column.
+    pub fn with_epoch_units(mut self, specs: &[String]) -> Result<Self> {
+        for spec in specs {
+            match spec.split_once('=') {
+                Some((column, unit)) if column.trim().is_empty() || unit.trim().is_empty() => {
+                    return Err(JlcatError::InvalidDateFormat(format!(
+                        "expected COLUMN=UNIT, got '{}'",
+                        spec
+                    )));
+                }
+                Some((column, unit)) => {
+                    self.column_units
+                        .insert(column.trim().to_string(), EpochUnit::parse(unit)?);
+                }
+                None => self.unit = EpochUnit::parse(spec)?,
+            }
+        }
+        Ok(self)
+    }
+
+    /// Render these columns relative to now (e.g., "3m ago") instead of a pattern
+    pub fn with_relative_columns(mut self, columns: &[String]) -> Self {
+        self.relative.extend(columns.iter().cloned());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none()
+            && self.columns.is_empty()
+            && self.zone.is_none()
+            && self.relative.is_empty()
+    }
+
+    /// Format a cell if it holds a recognized timestamp.
+    /// ISO-8601 strings are recognized in any column; epoch numbers only in columns
+    /// mapped explicitly or named like timestamps, so ids and counts are left alone.
+    pub fn format(&self, column: &str, value: &Value) -> Option<String> {
+        if self.relative.contains(column) {
+            let dt = self.to_datetime(column, value, true)?;
+            return Some(format_relative(dt, Utc::now()));
+        }
+
+        let (pattern, explicit) = match (self.columns.get(column), &self.default, self.zone) {
+            (Some(pattern), _, _) => (pattern.as_str(), true),
+            (None, Some(pattern), _) => (pattern.as_str(), false),
+            // A timezone alone still converts timestamps, shown with their offset
+            (None, None, Some(_)) => (ZONED_PATTERN, false),
+            (None, None, None) => return None,
+        };
+
+        let dt = self.to_datetime(column, value, explicit)?;
+        Some(self.zone.unwrap_or(DisplayZone::Utc).format(dt, pattern))
+    }
+
+    fn to_datetime(&self, column: &str, value: &Value, explicit: bool) -> Option<DateTime<Utc>> {
+        let column_unit = self.column_units.get(column).copied();
+        match value {
+            Value::String(s) => parse_datetime(s),
+            Value::Number(n) if explicit || column_unit.is_some() || is_time_column(column) => {
+                column_unit.unwrap_or(self.unit).to_datetime(n.as_f64()?)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Render the distance from `now` compactly, e.g. "3m ago" or "in 2d"
+pub fn format_relative(dt: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let delta = now.signed_duration_since(dt).num_seconds();
+    let secs = delta.unsigned_abs();
+
+    let amount = match secs {
+        0 => return "just now".to_string(),
+        1..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86_399 => format!("{}h", secs / 3600),
+        86_400..=2_591_999 => format!("{}d", secs / 86_400),
+        2_592_000..=31_535_999 => format!("{}mo", secs / 2_592_000),
+        _ => format!("{}y", secs / 31_536_000),
+    };
+
+    if delta < 0 {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+/// Reject patterns chrono cannot render, since formatting them would panic later
+fn validate_pattern(pattern: &str) -> Result<()> {
+    if pattern.is_empty() {
+        return Err(JlcatError::InvalidDateFormat("empty pattern".into()));
+    }
+    if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+        return Err(JlcatError::InvalidDateFormat(format!(
+            "unsupported specifier in '{}'",
+            pattern
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_rfc3339() {
+        let dt = parse_datetime("2024-01-15T10:30:00Z").unwrap();
+        assert_eq!(dt.timestamp(), 1705314600);
+
+        let dt = parse_datetime("2024-01-15T19:30:00+09:00").unwrap();
+        assert_eq!(dt.timestamp(), 1705314600);
+    }
+
+    #[test]
+    fn test_parse_naive_formats() {
+        assert_eq!(
+            parse_datetime("2024-01-15 10:30:00").unwrap().timestamp(),
+            1705314600
+        );
+        assert_eq!(
+            parse_datetime("2024-01-15T10:30:00.250")
+                .unwrap()
+                .timestamp_millis(),
+            1705314600250
+        );
+        assert_eq!(
+            parse_datetime("2024-01-15").unwrap().timestamp(),
+            1705276800
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_non_dates() {
+        assert!(parse_datetime("alice").is_none());
+        assert!(parse_datetime("2024").is_none());
+        assert!(parse_datetime("2024-13-45").is_none());
+    }
+
+    #[test]
+    fn test_timestamp_seconds() {
+        assert_eq!(timestamp_seconds(&json!(1705314600)), Some(1705314600.0));
+        assert_eq!(
+            timestamp_seconds(&json!("2024-01-15T10:30:00Z")),
+            Some(1705314600.0)
+        );
+        assert_eq!(timestamp_seconds(&json!(true)), None);
+    }
+
+    #[test]
+    fn test_epoch_unit_detection() {
+        assert_eq!(EpochUnit::detect(1705314600.0), EpochUnit::Seconds);
+        assert_eq!(EpochUnit::detect(1705314600250.0), EpochUnit::Millis);
+        assert_eq!(EpochUnit::detect(1705314600250000.0), EpochUnit::Micros);
+        assert_eq!(EpochUnit::detect(1.7053146e18), EpochUnit::Nanos);
+    }
+
+    #[test]
+    fn test_epoch_to_datetime_units() {
+        let auto = EpochUnit::Auto;
+        assert_eq!(
+            auto.to_datetime(1705314600.0).unwrap().timestamp(),
+            1705314600
+        );
+        assert_eq!(
+            auto.to_datetime(1705314600250.0)
+                .unwrap()
+                .timestamp_millis(),
+            1705314600250
+        );
+        assert_eq!(
+            auto.to_datetime(1705314600250000.0)
+                .unwrap()
+                .timestamp_millis(),
+            1705314600250
+        );
+        assert_eq!(
+            auto.to_datetime(1.7053146e18).unwrap().timestamp(),
+            1705314600
+        );
+        assert!(auto.to_datetime(f64::NAN).is_none());
+
+        // Overrides skip detection
+        assert_eq!(
+            EpochUnit::Millis.to_datetime(1000.0).unwrap().timestamp(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_timestamp_seconds_mixed_units() {
+        assert_eq!(
+            timestamp_seconds(&json!(1705314600000u64)),
+            Some(1705314600.0)
+        );
+    }
+
+    #[test]
+    fn test_epoch_unit_override() {
+        let tf = TimeFormat::parse(&["%Y".to_string()])
+            .unwrap()
+            .with_epoch_units(&["n=ms".to_string()])
+            .unwrap();
+        // Explicit unit marks the column as an epoch column
+        assert_eq!(tf.format("n", &json!(86_400_000)), Some("1970".to_string()));
+        for bad in ["fortnights", "n=", "=ms", " = "] {
+            assert!(
+                TimeFormat::default()
+                    .with_epoch_units(&[bad.to_string()])
+                    .is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_format_default() {
+        let tf = TimeFormat::parse(&["%Y-%m-%d".to_string()]).unwrap();
+        assert_eq!(
+            tf.format("when", &json!("2024-01-15T10:30:00Z")),
+            Some("2024-01-15".to_string())
+        );
+        assert_eq!(
+            tf.format("created_at", &json!(1705314600)),
+            Some("2024-01-15".to_string())
+        );
+        // Plain numbers in non-time columns are not epochs
+        assert_eq!(tf.format("id", &json!(1705314600)), None);
+        assert_eq!(tf.format("name", &json!("alice")), None);
+    }
+
+    #[test]
+    fn test_time_format_per_column() {
+        let tf = TimeFormat::parse(&["started=%H:%M".to_string()]).unwrap();
+        assert_eq!(
+            tf.format("started", &json!(1705314600000u64)),
+            Some("10:30".to_string())
+        );
+        assert_eq!(tf.format("other", &json!("2024-01-15T10:30:00Z")), None);
+    }
+
+    #[test]
+    fn test_time_format_invalid_pattern() {
+        assert!(TimeFormat::parse(&["%Y-%Q".to_string()]).is_err());
+        assert!(TimeFormat::parse(&["=%Y".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_display_zone_parse() {
+        assert_eq!(DisplayZone::parse("UTC").unwrap(), DisplayZone::Utc);
+        assert_eq!(DisplayZone::parse("local").unwrap(), DisplayZone::Local);
+        assert_eq!(
+            DisplayZone::parse("Asia/Tokyo").unwrap(),
+            DisplayZone::Named(chrono_tz::Asia::Tokyo)
+        );
+        assert!(DisplayZone::parse("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn test_time_format_with_zone() {
+        let tf = TimeFormat::parse(&["%H:%M".to_string()])
+            .unwrap()
+            .with_zone(DisplayZone::parse("Asia/Tokyo").unwrap());
+        assert_eq!(
+            tf.format("ts", &json!("2024-01-15T10:30:00Z")),
+            Some("19:30".to_string())
+        );
+    }
+
+    #[test]
+    fn test_zone_without_pattern() {
+        let tf = TimeFormat::default().with_zone(DisplayZone::parse("Asia/Tokyo").unwrap());
+        assert_eq!(
+            tf.format("ts", &json!(1705314600)),
+            Some("2024-01-15 19:30:00+09:00".to_string())
+        );
+        assert_eq!(tf.format("id", &json!(1705314600)), None);
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let now = parse_datetime("2024-01-15T10:30:00Z").unwrap();
+        let at = |s: &str| parse_datetime(s).unwrap();
+
+        assert_eq!(format_relative(now, now), "just now");
+        assert_eq!(format_relative(at("2024-01-15T10:29:15Z"), now), "45s ago");
+        assert_eq!(format_relative(at("2024-01-15T10:27:00Z"), now), "3m ago");
+        assert_eq!(format_relative(at("2024-01-15T05:30:00Z"), now), "5h ago");
+        assert_eq!(format_relative(at("2024-01-13T10:30:00Z"), now), "2d ago");
+        assert_eq!(format_relative(at("2023-10-15T10:30:00Z"), now), "3mo ago");
+        assert_eq!(format_relative(at("2022-01-01T00:00:00Z"), now), "2y ago");
+        assert_eq!(format_relative(at("2024-01-15T11:30:00Z"), now), "in 1h");
+    }
+
+    #[test]
+    fn test_relative_columns() {
+        let tf = TimeFormat::default().with_relative_columns(&["seen".to_string()]);
+        let now = Utc::now().timestamp();
+
+        // Explicit relative columns accept epochs regardless of name
+        assert_eq!(
+            tf.format("seen", &json!(now - 180)),
+            Some("3m ago".to_string())
+        );
+        assert_eq!(tf.format("seen", &json!("nope")), None);
+        assert_eq!(tf.format("other", &json!(now)), None);
+    }
+}
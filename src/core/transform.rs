@@ -0,0 +1,170 @@
+use crate::error::{JlcatError, Result};
+use serde_json::Value;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+
+/// Pipes rows through an external command as JSONL and reads the transformed rows back
+#[derive(Debug, Clone)]
+pub struct CommandTransform {
+    command: String,
+    batch_size: usize,
+}
+
+impl CommandTransform {
+    pub fn new(command: impl Into<String>, batch_size: usize) -> Self {
+        Self {
+            command: command.into(),
+            batch_size: batch_size.max(1),
+        }
+    }
+
+    /// Run the command over all rows.
+    /// Rows are written from a separate thread and flushed every `batch_size` rows,
+    /// so the pipe buffer provides backpressure while output is read concurrently.
+    pub fn apply(&self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        let mut child = shell_command(&self.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| {
+                JlcatError::Transform(format!("failed to start '{}': {}", self.command, e))
+            })?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let batch_size = self.batch_size;
+
+        let writer = thread::spawn(move || -> io::Result<()> {
+            let mut out = BufWriter::new(stdin);
+            for (idx, row) in rows.iter().enumerate() {
+                serde_json::to_writer(&mut out, row)?;
+                out.write_all(b"\n")?;
+                if (idx + 1) % batch_size == 0 {
+                    out.flush()?;
+                }
+            }
+            out.flush()
+            // Dropping the writer closes stdin so the command sees EOF
+        });
+
+        let result = Self::read_rows(BufReader::new(stdout));
+        let result = match result {
+            Ok(rows) => rows,
+            Err(e) => {
+                kill(&mut child);
+                return Err(e);
+            }
+        };
+
+        match writer.join() {
+            Ok(Ok(())) => {}
+            // The command may legitimately stop reading early (e.g., `head`)
+            Ok(Err(e)) if e.kind() == io::ErrorKind::BrokenPipe => {}
+            Ok(Err(e)) => {
+                kill(&mut child);
+                return Err(JlcatError::Io(e));
+            }
+            Err(_) => {
+                kill(&mut child);
+                return Err(JlcatError::Transform("writer thread panicked".into()));
+            }
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(JlcatError::Transform(format!(
+                "'{}' exited with {}",
+                self.command, status
+            )));
+        }
+
+        Ok(result)
+    }
+
+    fn read_rows<R: BufRead>(reader: R) -> Result<Vec<Value>> {
+        let mut rows = Vec::new();
+
+        for (line_num, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Value>(&line) {
+                Ok(value) if value.is_object() => rows.push(value),
+                Ok(_) => {
+                    return Err(JlcatError::Transform(format!(
+                        "output line {}: expected JSON object",
+                        line_num + 1
+                    )))
+                }
+                Err(e) => {
+                    return Err(JlcatError::Transform(format!(
+                        "output line {}: {}",
+                        line_num + 1,
+                        e
+                    )))
+                }
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+fn kill(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_identity_transform() {
+        let rows = vec![json!({"id": 1}), json!({"id": 2}), json!({"id": 3})];
+        let transform = CommandTransform::new("cat", 2);
+
+        let result = transform.apply(rows.clone()).unwrap();
+        assert_eq!(result, rows);
+    }
+
+    #[test]
+    fn test_transform_early_exit() {
+        let rows: Vec<Value> = (0..10_000).map(|i| json!({"id": i})).collect();
+        let transform = CommandTransform::new("head -n 2", 100);
+
+        let result = transform.apply(rows).unwrap();
+        assert_eq!(result, vec![json!({"id": 0}), json!({"id": 1})]);
+    }
+
+    #[test]
+    fn test_transform_non_object_output() {
+        let transform = CommandTransform::new("echo 42", 10);
+        assert!(transform.apply(vec![json!({"id": 1})]).is_err());
+    }
+
+    #[test]
+    fn test_transform_failing_command() {
+        let transform = CommandTransform::new("cat >/dev/null; exit 3", 10);
+        let err = transform.apply(vec![json!({"id": 1})]).unwrap_err();
+        assert!(err.to_string().contains("exited"));
+    }
+}
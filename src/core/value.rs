@@ -1,20 +1,47 @@
-use serde_json::Value;
+use serde_json::{Number, Value};
+use std::borrow::Cow;
 use std::cmp::Ordering;
 
 /// Wrapper for JSON values that implements Ord for sorting.
 /// Ordering: numbers < strings < bools < null
 /// Nulls are always last (both ascending and descending).
+/// Strings compare by text; the `:date` sort modifier compares timestamps.
 #[derive(Debug, Clone)]
 pub struct SortableValue<'a> {
     value: &'a Value,
+    /// Treat numeric-looking strings ("42") as numbers
+    coerce: bool,
 }
 
 impl<'a> SortableValue<'a> {
     pub fn new(value: &'a Value) -> Self {
-        Self { value }
+        Self {
+            value,
+            coerce: false,
+        }
+    }
+
+    /// Like `new`, but strings that parse as numbers sort as numbers
+    pub fn coercing(value: &'a Value) -> Self {
+        Self {
+            value,
+            coerce: true,
+        }
+    }
+
+    /// The decimal text of a number, or of a numeric string when coercing
+    fn numeric_text(&self) -> Option<Cow<'a, str>> {
+        match self.value {
+            Value::Number(n) => Some(Cow::Owned(n.to_string())),
+            Value::String(s) if self.coerce && is_numeric_str(s) => Some(Cow::Borrowed(s)),
+            _ => None,
+        }
     }
 
     fn type_order(&self) -> u8 {
+        if self.numeric_text().is_some() {
+            return 0;
+        }
         match self.value {
             Value::Number(_) => 0,
             Value::String(_) => 1,
@@ -48,11 +75,12 @@ impl<'a> Ord for SortableValue<'a> {
         }
 
         match (self.value, other.value) {
-            (Value::Number(a), Value::Number(b)) => {
-                let a_f = a.as_f64().unwrap_or(f64::NAN);
-                let b_f = b.as_f64().unwrap_or(f64::NAN);
-                a_f.partial_cmp(&b_f).unwrap_or(Ordering::Equal)
-            }
+            (Value::Number(a), Value::Number(b)) => compare_numbers(a, b),
+            // A number and a numeric string, or two numeric strings, when coercing
+            _ if self.type_order() == 0 => match (self.numeric_text(), other.numeric_text()) {
+                (Some(a), Some(b)) => compare_decimal_str(&a, &b).unwrap_or(Ordering::Equal),
+                _ => Ordering::Equal,
+            },
             (Value::String(a), Value::String(b)) => a.cmp(b),
             (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
             _ => Ordering::Equal,
@@ -60,6 +88,125 @@ impl<'a> Ord for SortableValue<'a> {
     }
 }
 
+/// Compare two JSON numbers exactly, without rounding through f64.
+/// Numbers keep their original text (serde_json `arbitrary_precision`),
+/// so 64-bit IDs and high-precision decimals order correctly.
+pub fn compare_numbers(a: &Number, b: &Number) -> Ordering {
+    if let (Some(x), Some(y)) = (a.as_i64(), b.as_i64()) {
+        return x.cmp(&y);
+    }
+    if let (Some(x), Some(y)) = (a.as_u64(), b.as_u64()) {
+        return x.cmp(&y);
+    }
+
+    compare_decimal_str(&a.to_string(), &b.to_string()).unwrap_or_else(|| {
+        let a_f = a.as_f64().unwrap_or(f64::NAN);
+        let b_f = b.as_f64().unwrap_or(f64::NAN);
+        a_f.partial_cmp(&b_f).unwrap_or(Ordering::Equal)
+    })
+}
+
+/// Whether a string is a decimal number such as "42", "-1.5" or "2e3"
+pub fn is_numeric_str(s: &str) -> bool {
+    Decimal::parse(s).is_some()
+}
+
+/// Compare two decimal number strings (e.g., "-12.5e3") exactly.
+/// Returns None if either string is not a decimal number.
+pub fn compare_decimal_str(a: &str, b: &str) -> Option<Ordering> {
+    let a = Decimal::parse(a)?;
+    let b = Decimal::parse(b)?;
+
+    Some(match (a.negative, b.negative) {
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.cmp_magnitude(&b),
+        (true, true) => b.cmp_magnitude(&a),
+    })
+}
+
+/// A decimal number as `0.d1d2d3... × 10^exp` with no leading or trailing zero digits
+struct Decimal {
+    negative: bool,
+    digits: Vec<u8>,
+    exp: i64,
+}
+
+impl Decimal {
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (negative, rest) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+
+        let (mantissa, exp) = match rest.find(['e', 'E']) {
+            Some(i) => (&rest[..i], rest[i + 1..].parse::<i64>().ok()?),
+            None => (rest, 0),
+        };
+        let (int_part, frac_part) = match mantissa.find('.') {
+            Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+            None => (mantissa, ""),
+        };
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+
+        let mut digits: Vec<u8> = int_part
+            .bytes()
+            .chain(frac_part.bytes())
+            .map(|b| {
+                if b.is_ascii_digit() {
+                    Some(b - b'0')
+                } else {
+                    None
+                }
+            })
+            .collect::<Option<_>>()?;
+        let mut exp = int_part.len() as i64 + exp;
+
+        let leading = digits.iter().take_while(|&&d| d == 0).count();
+        digits.drain(..leading);
+        exp -= leading as i64;
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        Some(Self {
+            negative: negative && !digits.is_empty(),
+            digits,
+            exp,
+        })
+    }
+
+    fn cmp_magnitude(&self, other: &Self) -> Ordering {
+        match (self.digits.is_empty(), other.digits.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => self
+                .exp
+                .cmp(&other.exp)
+                .then_with(|| self.digits.cmp(&other.digits)),
+        }
+    }
+}
+
+/// Placeholder shown for a nested value: `{3 keys}` or `[12 items]`, and
+/// `{}` or `[]` when empty. Other values are shown as JSON.
+pub fn placeholder(value: &Value) -> String {
+    fn count(n: usize, noun: &str) -> String {
+        format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" })
+    }
+    match value {
+        Value::Object(obj) if obj.is_empty() => "{}".to_string(),
+        Value::Object(obj) => format!("{{{}}}", count(obj.len(), "key")),
+        Value::Array(arr) if arr.is_empty() => "[]".to_string(),
+        Value::Array(arr) => format!("[{}]", count(arr.len(), "item")),
+        other => other.to_string(),
+    }
+}
+
 /// Helper function to get a nested value using dot notation.
 /// First tries literal key lookup (for flattened column names like "address.city"),
 /// then falls back to nested path traversal.
@@ -90,8 +237,16 @@ pub fn get_nested_value<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
             let mut remaining = &part[first_bracket..];
             while remaining.starts_with('[') {
                 let idx_end = remaining.find(']')?;
-                let idx: usize = remaining[1..idx_end].parse().ok()?;
-                current = current.get(idx)?;
+                let idx = &remaining[1..idx_end];
+                current = match idx.strip_prefix('-') {
+                    // Negative indexes count from the end, [-1] being the last
+                    Some(n) => {
+                        let arr = current.as_array()?;
+                        let n: usize = n.parse().ok().filter(|&n| n > 0)?;
+                        arr.get(arr.len().checked_sub(n)?)?
+                    }
+                    None => current.get(idx.parse::<usize>().ok()?)?,
+                };
                 remaining = &remaining[idx_end + 1..];
             }
         } else {
@@ -107,6 +262,16 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_placeholder() {
+        assert_eq!(placeholder(&json!({"a": 1, "b": 2, "c": 3})), "{3 keys}");
+        assert_eq!(placeholder(&json!({"a": 1})), "{1 key}");
+        assert_eq!(placeholder(&json!({})), "{}");
+        assert_eq!(placeholder(&json!([1, 2])), "[2 items]");
+        assert_eq!(placeholder(&json!([[]])), "[1 item]");
+        assert_eq!(placeholder(&json!([])), "[]");
+    }
+
     #[test]
     fn test_ordering_numbers() {
         let j1 = json!(1);
@@ -160,6 +325,64 @@ mod tests {
         assert!(bool_val < null_val);
     }
 
+    #[test]
+    fn test_ordering_large_integers() {
+        // These collapse to the same f64
+        let j1: Value = serde_json::from_str("9007199254740993").unwrap();
+        let j2: Value = serde_json::from_str("9007199254740992").unwrap();
+        assert!(SortableValue::new(&j2) < SortableValue::new(&j1));
+
+        // Beyond u64
+        let j3: Value = serde_json::from_str("123456789012345678901234567890").unwrap();
+        let j4: Value = serde_json::from_str("123456789012345678901234567891").unwrap();
+        assert!(SortableValue::new(&j3) < SortableValue::new(&j4));
+    }
+
+    #[test]
+    fn test_ordering_high_precision_decimals() {
+        let j1: Value = serde_json::from_str("0.10000000000000000001").unwrap();
+        let j2: Value = serde_json::from_str("0.1").unwrap();
+        assert!(SortableValue::new(&j2) < SortableValue::new(&j1));
+    }
+
+    #[test]
+    fn test_ordering_coerced_numeric_strings() {
+        let (j9, j10, j42, jstr) = (json!("9"), json!("10"), json!(42), json!("abc"));
+        // Without coercion strings compare as text and after numbers
+        assert!(SortableValue::new(&j10) < SortableValue::new(&j9));
+        assert!(SortableValue::new(&j42) < SortableValue::new(&j9));
+
+        assert!(SortableValue::coercing(&j9) < SortableValue::coercing(&j10));
+        assert!(SortableValue::coercing(&j10) < SortableValue::coercing(&j42));
+        assert!(SortableValue::coercing(&j42) < SortableValue::coercing(&jstr));
+        assert_eq!(
+            SortableValue::coercing(&json!("42.0")),
+            SortableValue::coercing(&j42)
+        );
+    }
+
+    #[test]
+    fn test_compare_decimal_str() {
+        assert_eq!(compare_decimal_str("1e3", "1000"), Some(Ordering::Equal));
+        assert_eq!(compare_decimal_str("30", "30.0"), Some(Ordering::Equal));
+        assert_eq!(compare_decimal_str("-0", "0"), Some(Ordering::Equal));
+        assert_eq!(compare_decimal_str("-2", "-10"), Some(Ordering::Greater));
+        assert_eq!(compare_decimal_str("0.05", "0.5"), Some(Ordering::Less));
+        assert_eq!(
+            compare_decimal_str("1.5E-3", "0.0015"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(compare_decimal_str("abc", "1"), None);
+    }
+
+    #[test]
+    fn test_number_display_preserved() {
+        let row: Value =
+            serde_json::from_str(r#"{"id": 12345678901234567890123, "price": 1.10}"#).unwrap();
+        assert_eq!(row["id"].to_string(), "12345678901234567890123");
+        assert_eq!(row["price"].to_string(), "1.10");
+    }
+
     #[test]
     fn test_get_nested_simple() {
         let row = json!({"name": "Alice"});
@@ -181,6 +404,15 @@ mod tests {
         assert_eq!(get_nested_value(&row, "items[1]"), Some(&json!(2)));
     }
 
+    #[test]
+    fn test_get_nested_negative_index() {
+        let row = json!({"items": [1, 2, 3], "matrix": [[1, 2], [3, 4]]});
+        assert_eq!(get_nested_value(&row, "items[-1]"), Some(&json!(3)));
+        assert_eq!(get_nested_value(&row, "items[-3]"), Some(&json!(1)));
+        assert_eq!(get_nested_value(&row, "items[-4]"), None);
+        assert_eq!(get_nested_value(&row, "matrix[-1][-2]"), Some(&json!(3)));
+    }
+
     #[test]
     fn test_get_literal_dotted_key() {
         // When column selection flattens "address.city" into a literal key
@@ -8,6 +8,18 @@ pub enum JlcatError {
     #[error("JSON parse error at line {line}: {message}")]
     JsonParse { line: usize, message: String },
 
+    #[error("Input appears to be binary, not JSON ({0})")]
+    BinaryInput(String),
+
+    #[error(
+        "Too many invalid lines: more than --max-errors {max}, stopped at line {line} ({summary})"
+    )]
+    TooManyErrors {
+        max: usize,
+        line: usize,
+        summary: String,
+    },
+
     #[error("Invalid column path: {0}")]
     InvalidColumnPath(String),
 
@@ -16,6 +28,129 @@ pub enum JlcatError {
 
     #[error("Invalid sort key: {0}")]
     InvalidSortKey(String),
+
+    #[error("Invalid select expression: {0}")]
+    InvalidSelect(String),
+
+    #[error("Invalid row range: {0}")]
+    InvalidRowRange(String),
+
+    #[error("Invalid style: {0}")]
+    InvalidStyle(String),
+
+    #[error("Invalid date format: {0}")]
+    InvalidDateFormat(String),
+
+    #[error("Config error: {0}")]
+    Config(String),
+
+    #[error("Transform command error: {0}")]
+    Transform(String),
+
+    #[cfg(feature = "wasm")]
+    #[error("Plugin error: {0}")]
+    Plugin(String),
+
+    #[cfg(feature = "script")]
+    #[error("Script error: {0}")]
+    Script(String),
+
+    #[cfg(feature = "sql")]
+    #[error("SQL error: {0}")]
+    Sql(String),
+
+    #[error("Memory limit: {0}")]
+    MemoryLimit(String),
+
+    #[error("Check failed: {0}")]
+    CheckFailed(String),
 }
 
 pub type Result<T> = std::result::Result<T, JlcatError>;
+
+/// Process exit codes, one per failure class
+pub mod exit_code {
+    /// Transform, plugin or script failures, or an input over `--max-memory`
+    /// that cannot be read from disk
+    pub const FAILURE: i32 = 1;
+    /// Bad command line, option value or config file (also used by clap)
+    pub const USAGE: i32 = 2;
+    /// Input that is not valid JSON, or too many invalid lines in lenient mode
+    pub const PARSE: i32 = 3;
+    /// Reading input or writing output failed
+    pub const IO: i32 = 4;
+    /// Invalid filter, sort key, select expression, row range, SQL query or column path
+    pub const INVALID_EXPR: i32 = 5;
+}
+
+impl JlcatError {
+    /// Whether stdout was closed by the reader, as in `jlcat big.jsonl | head`;
+    /// not a failure, so it ends the run quietly
+    pub fn is_broken_pipe(&self) -> bool {
+        matches!(self, JlcatError::Io(e) if e.kind() == std::io::ErrorKind::BrokenPipe)
+    }
+
+    /// Exit code for the class of this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            JlcatError::InvalidStyle(_)
+            | JlcatError::InvalidDateFormat(_)
+            | JlcatError::Config(_) => exit_code::USAGE,
+            JlcatError::JsonParse { .. }
+            | JlcatError::BinaryInput(_)
+            | JlcatError::TooManyErrors { .. } => exit_code::PARSE,
+            JlcatError::Io(_) => exit_code::IO,
+            JlcatError::InvalidColumnPath(_)
+            | JlcatError::InvalidFilter(_)
+            | JlcatError::InvalidSortKey(_)
+            | JlcatError::InvalidSelect(_)
+            | JlcatError::InvalidRowRange(_) => exit_code::INVALID_EXPR,
+            #[cfg(feature = "sql")]
+            JlcatError::Sql(_) => exit_code::INVALID_EXPR,
+            #[cfg(feature = "wasm")]
+            JlcatError::Plugin(_) => exit_code::FAILURE,
+            #[cfg(feature = "script")]
+            JlcatError::Script(_) => exit_code::FAILURE,
+            JlcatError::Transform(_) | JlcatError::MemoryLimit(_) | JlcatError::CheckFailed(_) => {
+                exit_code::FAILURE
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_by_class() {
+        let parse = JlcatError::JsonParse {
+            line: 1,
+            message: "x".into(),
+        };
+        assert_eq!(parse.exit_code(), exit_code::PARSE);
+        let io = JlcatError::Io(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(io.exit_code(), exit_code::IO);
+        assert_eq!(
+            JlcatError::InvalidSortKey("x".into()).exit_code(),
+            exit_code::INVALID_EXPR
+        );
+        assert_eq!(
+            JlcatError::InvalidSelect("x".into()).exit_code(),
+            exit_code::INVALID_EXPR
+        );
+        assert_eq!(JlcatError::Config("x".into()).exit_code(), exit_code::USAGE);
+        assert_eq!(
+            JlcatError::Transform("x".into()).exit_code(),
+            exit_code::FAILURE
+        );
+    }
+
+    #[test]
+    fn test_broken_pipe() {
+        let pipe = JlcatError::Io(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+        assert!(pipe.is_broken_pipe());
+        let io = JlcatError::Io(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(!io.is_broken_pipe());
+    }
+}
@@ -4,12 +4,16 @@ use std::fs::File;
 use std::io::{self, Read, Seek};
 
 use super::indexed::IndexedReader;
+use super::rows::{ParsedRow, RowSource};
+use crate::error::Result;
 
 /// A cached reader that combines IndexedReader with RowCache
 /// for efficient random access with caching
 pub struct CachedReader<R: Read + Seek> {
     indexed: IndexedReader<R>,
     cache: RowCache,
+    /// Next row for `RowSource::next_row`
+    cursor: usize,
 }
 
 impl CachedReader<File> {
@@ -27,6 +31,7 @@ impl CachedReader<File> {
         Ok(Self {
             indexed,
             cache: RowCache::new(cache_size),
+            cursor: 0,
         })
     }
 }
@@ -43,9 +48,19 @@ impl<R: Read + Seek> CachedReader<R> {
         Ok(Self {
             indexed,
             cache: RowCache::new(cache_size),
+            cursor: 0,
         })
     }
 
+    /// Cache rows read through an existing index
+    pub fn from_indexed(indexed: IndexedReader<R>, cache_size: usize) -> Self {
+        Self {
+            indexed,
+            cache: RowCache::new(cache_size),
+            cursor: 0,
+        }
+    }
+
     /// Get the total number of rows
     pub fn row_count(&self) -> usize {
         self.indexed.row_count()
@@ -110,6 +125,44 @@ impl<R: Read + Seek> CachedReader<R> {
     }
 }
 
+impl<R: Read + Seek> RowSource for CachedReader<R> {
+    fn next_row(&mut self) -> Result<Option<ParsedRow>> {
+        let row = self.row_at(self.cursor)?;
+        if row.is_some() {
+            self.cursor += 1;
+        }
+        Ok(row)
+    }
+
+    fn position(&self) -> usize {
+        self.cursor
+    }
+
+    /// Text of the row last read from the file (rows served from the cache keep none)
+    fn last_text(&self) -> Option<&str> {
+        self.indexed.last_text()
+    }
+
+    fn row_count_hint(&self) -> Option<usize> {
+        Some(self.row_count())
+    }
+
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn row_at(&mut self, index: usize) -> Result<Option<ParsedRow>> {
+        if let Some(value) = self.cache.get(index) {
+            return Ok(Some(Ok(value.clone())));
+        }
+        let row = self.indexed.row_at(index)?;
+        if let Some(Ok(ref value)) = row {
+            self.cache.insert(index, value.clone());
+        }
+        Ok(row)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,6 +274,23 @@ mod tests {
         assert_eq!(reader.cache_size(), 0);
     }
 
+    #[test]
+    fn test_row_source_fills_cache() {
+        let content = "{\"id\": 1}\nnot json\n{\"id\": 3}\n";
+        let mut reader = create_test_reader(content);
+        assert_eq!(reader.row_count_hint(), Some(3));
+
+        assert_eq!(reader.next_row().unwrap().unwrap().unwrap()["id"], 1);
+        assert!(reader.next_row().unwrap().unwrap().is_err());
+        assert_eq!(reader.last_text(), Some("not json"));
+        assert_eq!(reader.row_at(2).unwrap().unwrap().unwrap()["id"], 3);
+        assert_eq!(reader.next_row().unwrap().unwrap().unwrap()["id"], 3);
+        assert!(reader.next_row().unwrap().is_none());
+
+        // Invalid rows are not cached
+        assert_eq!(reader.cache_size(), 2);
+    }
+
     #[test]
     fn test_row_count() {
         let content = r#"{"id": 1}
@@ -19,6 +19,27 @@ pub fn sniff_format(peek: &[u8]) -> Option<InputFormat> {
     }
 }
 
+/// Bytes read from the start of an input to detect its format
+pub const SNIFF_LEN: usize = 1024;
+
+/// Why the start of an input looks binary rather than text: a NUL byte or
+/// bytes that are not UTF-8, with their offset. A UTF-8 sequence cut off at
+/// the end of `peek` does not count.
+pub fn binary_reason(peek: &[u8]) -> Option<String> {
+    if let Some(offset) = peek.iter().position(|&b| b == 0) {
+        return Some(format!(
+            "NUL byte at offset {}; UTF-16 text without a byte order mark needs --encoding",
+            offset
+        ));
+    }
+    match std::str::from_utf8(peek) {
+        Err(e) if e.error_len().is_some() => {
+            Some(format!("invalid UTF-8 at offset {}", e.valid_up_to()))
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,6 +62,25 @@ mod tests {
         assert_eq!(sniff_format(input), None);
     }
 
+    #[test]
+    fn test_binary_reason() {
+        assert_eq!(binary_reason(b"{\"id\": 1}\n"), None);
+        assert_eq!(binary_reason("{\"名前\": 1}".as_bytes()), None);
+        // A character cut off by the peek is not binary
+        assert_eq!(binary_reason(&"{\"名".as_bytes()[..4]), None);
+        assert_eq!(
+            binary_reason(b"\x89PNG\r\n\x1a\n\0\0"),
+            Some(
+                "NUL byte at offset 8; UTF-16 text without a byte order mark needs --encoding"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            binary_reason(b"{\"a\": \"\xff\"}"),
+            Some("invalid UTF-8 at offset 7".to_string())
+        );
+    }
+
     #[test]
     fn test_sniff_invalid_input() {
         let input = b"not json";
@@ -0,0 +1,165 @@
+use encoding_rs::{Decoder, Encoding, UTF_8};
+use std::io::{self, Read};
+
+/// Bytes of encoded input decoded at a time
+const CHUNK: usize = 8 * 1024;
+
+/// Parse an encoding name such as `utf-16le`, `utf-16be` or `latin1`
+/// (any WHATWG label)
+pub fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+        format!(
+            "unknown encoding '{}' (e.g. utf-8, utf-16le, utf-16be)",
+            label
+        )
+    })
+}
+
+/// The encoding named by a byte order mark at the start of `head`
+pub fn bom_encoding(head: &[u8]) -> Option<&'static Encoding> {
+    Encoding::for_bom(head).map(|(encoding, _)| encoding)
+}
+
+/// Read `reader` as UTF-8 text: a byte order mark is dropped, and UTF-16 (by
+/// its mark, or `encoding` when there is none) or another `encoding` is
+/// transcoded. Without either, bytes pass through untouched.
+pub fn text_reader<'a, R: Read + 'a>(
+    mut reader: R,
+    encoding: Option<&'static Encoding>,
+) -> io::Result<Box<dyn Read + 'a>> {
+    let mut head = Vec::with_capacity(3);
+    (&mut reader).take(3).read_to_end(&mut head)?;
+    let (encoding, bom_len) = match Encoding::for_bom(&head) {
+        Some((encoding, len)) => (Some(encoding), len),
+        None => (encoding, 0),
+    };
+    let reader = io::Cursor::new(head.split_off(bom_len)).chain(reader);
+    Ok(match encoding {
+        Some(encoding) if encoding != UTF_8 => Box::new(Transcoder::new(reader, encoding)),
+        _ => Box::new(reader),
+    })
+}
+
+/// Decodes text in another encoding to UTF-8 as it is read, replacing
+/// malformed sequences with U+FFFD
+struct Transcoder<R: Read> {
+    inner: R,
+    decoder: Decoder,
+    /// Decoded bytes not yet read
+    output: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read> Transcoder<R> {
+    fn new(inner: R, encoding: &'static Encoding) -> Self {
+        Self {
+            inner,
+            decoder: encoding.new_decoder_without_bom_handling(),
+            output: Vec::new(),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    /// Decode the next chunk of input
+    fn fill(&mut self) -> io::Result<()> {
+        let mut input = [0u8; CHUNK];
+        let read = self.inner.read(&mut input)?;
+        let last = read == 0;
+        let max = self
+            .decoder
+            .max_utf8_buffer_length(read)
+            .unwrap_or(read * 3 + 16);
+        self.output.resize(max, 0);
+        let (_, _, written, _) =
+            self.decoder
+                .decode_to_utf8(&input[..read], &mut self.output, last);
+        self.output.truncate(written);
+        self.pos = 0;
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for Transcoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.output.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.fill()?;
+        }
+        let n = buf.len().min(self.output.len() - self.pos);
+        buf[..n].copy_from_slice(&self.output[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_text(bytes: &[u8], encoding: Option<&'static Encoding>) -> String {
+        let mut text = String::new();
+        text_reader(bytes, encoding)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    fn utf16(text: &str, little_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| {
+                if little_endian {
+                    unit.to_le_bytes()
+                } else {
+                    unit.to_be_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_text_reader_strips_utf8_bom() {
+        assert_eq!(
+            read_text(b"\xEF\xBB\xBF{\"id\": 1}\n", None),
+            "{\"id\": 1}\n"
+        );
+        assert_eq!(read_text(b"{}", None), "{}");
+        assert_eq!(read_text(b"", None), "");
+        // Invalid UTF-8 is left for the binary check to report
+        let invalid = b"{\"a\": \"\xff\"}";
+        let mut bytes = Vec::new();
+        text_reader(&invalid[..], None)
+            .unwrap()
+            .read_to_end(&mut bytes)
+            .unwrap();
+        assert_eq!(bytes, invalid);
+    }
+
+    #[test]
+    fn test_text_reader_transcodes_utf16() {
+        let text = "{\"名前\": \"東京\"}\n";
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(utf16(text, true));
+        assert_eq!(read_text(&le, None), text);
+
+        let mut be = vec![0xFE, 0xFF];
+        be.extend(utf16(text, false));
+        assert_eq!(read_text(&be, None), text);
+
+        // Without a byte order mark only --encoding tells
+        let encoding = parse_encoding("utf-16be").unwrap();
+        assert_eq!(read_text(&utf16(text, false), Some(encoding)), text);
+    }
+
+    #[test]
+    fn test_parse_encoding() {
+        assert_eq!(parse_encoding("UTF-16LE").unwrap(), encoding_rs::UTF_16LE);
+        assert_eq!(parse_encoding("latin1").unwrap(), encoding_rs::WINDOWS_1252);
+        assert!(parse_encoding("klingon").is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use super::detector::{sniff_format, InputFormat};
+use std::fmt;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+/// Bytes read from the start of the input to estimate row size
+const SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// Row count above which plain output to a terminal triggers a warning
+pub const LARGE_OUTPUT_ROWS: u64 = 100_000;
+
+/// Approximate number of rows in an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowEstimate {
+    pub rows: u64,
+    /// Total input size in bytes
+    pub bytes: u64,
+    /// True when the whole input was counted rather than sampled
+    pub exact: bool,
+}
+
+impl RowEstimate {
+    /// Whether rendering every row as a plain table is likely to flood a terminal
+    pub fn is_large(&self) -> bool {
+        self.rows > LARGE_OUTPUT_ROWS
+    }
+
+    /// Rows expected in the first `bytes` of the input, taking them to be
+    /// spread evenly
+    pub fn rows_within(&self, bytes: u64) -> u64 {
+        if bytes >= self.bytes {
+            return self.rows;
+        }
+        (self.rows as f64 * bytes as f64 / self.bytes as f64).round() as u64
+    }
+}
+
+impl fmt::Display for RowEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let prefix = if self.exact { "" } else { "~" };
+        write!(f, "{}{} rows ({} bytes)", prefix, self.rows, self.bytes)
+    }
+}
+
+/// Estimate the row count of a seekable input from a sample at its start:
+/// the sample's rows per byte scaled by the total size.
+/// Leaves the reader positioned at the start.
+pub fn estimate_rows<R: Read + Seek>(reader: &mut R) -> io::Result<RowEstimate> {
+    let bytes = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let mut sample = Vec::new();
+    reader
+        .by_ref()
+        .take(SAMPLE_BYTES)
+        .read_to_end(&mut sample)?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let exact = sample.len() as u64 >= bytes;
+    let (rows, sampled) = match sniff_format(&sample) {
+        Some(InputFormat::JsonArray) => (count_array_elements(&sample), sample.len() as u64),
+        Some(InputFormat::JsonLines) => count_lines(&sample, exact),
+        None => (0, sample.len() as u64),
+    };
+
+    let rows = if exact || sampled == 0 {
+        rows
+    } else {
+        // Average row length in the sample × file size
+        (rows as f64 * bytes as f64 / sampled as f64).round() as u64
+    };
+
+    Ok(RowEstimate { rows, bytes, exact })
+}
+
+/// Count the rows of a non-seekable input by reading it to the end
+pub fn count_rows<R: BufRead>(mut reader: R) -> io::Result<RowEstimate> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    let rows = match sniff_format(&data) {
+        Some(InputFormat::JsonArray) => count_array_elements(&data),
+        Some(InputFormat::JsonLines) => count_lines(&data, true).0,
+        None => 0,
+    };
+
+    Ok(RowEstimate {
+        rows,
+        bytes: data.len() as u64,
+        exact: true,
+    })
+}
+
+/// Non-empty lines and the bytes they span. A trailing partial line is
+/// dropped unless the sample is the whole input.
+fn count_lines(sample: &[u8], complete: bool) -> (u64, u64) {
+    let end = if complete {
+        sample.len()
+    } else {
+        match sample.iter().rposition(|&b| b == b'\n') {
+            Some(pos) => pos + 1,
+            None => return (0, 0),
+        }
+    };
+
+    let rows = sample[..end]
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .count();
+
+    (rows as u64, end as u64)
+}
+
+/// Elements opened directly inside the top-level array
+fn count_array_elements(sample: &[u8]) -> u64 {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut count = 0;
+
+    for &b in sample {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                if depth == 1 && b == b'{' {
+                    count += 1;
+                }
+                depth += 1;
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_estimate_small_input_is_exact() {
+        let data = b"{\"id\": 1}\n{\"id\": 2}\n\n{\"id\": 3}\n";
+        let estimate = estimate_rows(&mut Cursor::new(&data[..])).unwrap();
+        assert_eq!(
+            estimate,
+            RowEstimate {
+                rows: 3,
+                bytes: data.len() as u64,
+                exact: true
+            }
+        );
+        assert_eq!(estimate.to_string(), "3 rows (31 bytes)");
+    }
+
+    #[test]
+    fn test_estimate_large_input_is_scaled() {
+        let line = "{\"id\": 12345, \"name\": \"row\"}\n";
+        let data = line.repeat(10_000);
+        let mut cursor = Cursor::new(data.as_bytes());
+
+        let estimate = estimate_rows(&mut cursor).unwrap();
+        assert!(!estimate.exact);
+        assert!((9_900..=10_100).contains(&estimate.rows), "{:?}", estimate);
+        assert!(estimate.to_string().starts_with('~'));
+        // Reader is rewound for the real read
+        assert_eq!(cursor.position(), 0);
+    }
+
+    #[test]
+    fn test_estimate_json_array() {
+        let data = br#"[
+  {"id": 1, "tags": [{"a": "{"}]},
+  {"id": 2, "note": "a \" } quote"}
+]"#;
+        let estimate = estimate_rows(&mut Cursor::new(&data[..])).unwrap();
+        assert_eq!(estimate.rows, 2);
+    }
+
+    #[test]
+    fn test_count_rows() {
+        let data = "{\"id\": 1}\n{\"id\": 2}";
+        let estimate = count_rows(Cursor::new(data)).unwrap();
+        assert_eq!(estimate.rows, 2);
+        assert!(estimate.exact);
+    }
+
+    #[test]
+    fn test_is_large() {
+        let estimate = RowEstimate {
+            rows: LARGE_OUTPUT_ROWS + 1,
+            bytes: 0,
+            exact: false,
+        };
+        assert!(estimate.is_large());
+    }
+
+    #[test]
+    fn test_rows_within() {
+        let estimate = RowEstimate {
+            rows: 100,
+            bytes: 1000,
+            exact: false,
+        };
+        assert_eq!(estimate.rows_within(250), 25);
+        assert_eq!(estimate.rows_within(5000), 100);
+    }
+}
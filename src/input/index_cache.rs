@@ -0,0 +1,229 @@
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3::Xxh3;
+
+/// Extension appended to the input's file name for its sidecar
+const EXTENSION: &str = "jlcatidx";
+
+/// Format version written in the sidecar header
+const VERSION: u64 = 2;
+
+/// Bytes hashed at each end of the file
+const HASH_BLOCK: u64 = 64 * 1024;
+
+/// Offsets checked against the file's line breaks when loading
+const SPOT_CHECKS: usize = 64;
+
+/// Row index of a JSONL file saved in a sidecar next to it (`--index-cache`),
+/// so reopening the file skips the scan for row offsets.
+///
+/// The sidecar is a one-line JSON header, holding the file's size and an
+/// xxh3 hash of its first and last 64 KiB when it was indexed, followed by
+/// the offsets as little-endian `u64`s. It is only used while the size and
+/// hash still match and a sample of the offsets still start lines, so an
+/// edited file is indexed again rather than shown with wrong rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexCache {
+    /// Byte offset of each non-blank line
+    pub offsets: Vec<u64>,
+    /// Columns of every row, recorded when each line held a JSON object
+    pub columns: Option<Vec<String>>,
+}
+
+impl IndexCache {
+    /// Sidecar path for `path`, e.g. `big.jsonl.jlcatidx`
+    pub fn sidecar(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".");
+        name.push(EXTENSION);
+        PathBuf::from(name)
+    }
+
+    /// Load the sidecar of `path`; `None` when there is none, it cannot be
+    /// read, or the file changed since it was written
+    pub fn load(path: &Path) -> Option<Self> {
+        let stamp = Stamp::of(path).ok()?;
+        let mut reader = BufReader::new(File::open(Self::sidecar(path)).ok()?);
+
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let header: Value = serde_json::from_str(&line).ok()?;
+        if header["version"].as_u64() != Some(VERSION) || Stamp::from_header(&header)? != stamp {
+            return None;
+        }
+        let rows = usize::try_from(header["rows"].as_u64()?).ok()?;
+        let columns = match header["columns"] {
+            Value::Array(ref columns) => Some(
+                columns
+                    .iter()
+                    .map(|c| c.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()?,
+            ),
+            _ => None,
+        };
+
+        let mut offsets = Vec::with_capacity(rows);
+        let mut buf = [0u8; 8];
+        for _ in 0..rows {
+            reader.read_exact(&mut buf).ok()?;
+            offsets.push(u64::from_le_bytes(buf));
+        }
+        if !starts_lines(path, &offsets).ok()? {
+            return None;
+        }
+        Some(Self { offsets, columns })
+    }
+
+    /// Write the sidecar of `path`, stamped with the file as it is now
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let stamp = Stamp::of(path)?;
+        let header = json!({
+            "version": VERSION,
+            "size": stamp.size,
+            "hash": stamp.hash,
+            "rows": self.offsets.len(),
+            "columns": self.columns,
+        });
+
+        // Written under a temporary name so a reader never sees half a file
+        let sidecar = Self::sidecar(path);
+        let mut partial = sidecar.clone().into_os_string();
+        partial.push(".tmp");
+        {
+            let mut out = BufWriter::new(File::create(&partial)?);
+            writeln!(out, "{}", header)?;
+            for offset in &self.offsets {
+                out.write_all(&offset.to_le_bytes())?;
+            }
+            out.flush()?;
+        }
+        fs::rename(&partial, &sidecar)
+    }
+}
+
+/// Size and content hash identifying one version of a file
+#[derive(Debug, PartialEq, Eq)]
+struct Stamp {
+    size: u64,
+    /// xxh3 of the first and last `HASH_BLOCK` bytes and the size
+    hash: u64,
+}
+
+impl Stamp {
+    fn of(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let size = file.metadata()?.len();
+
+        let mut hasher = Xxh3::new();
+        let mut block = Vec::new();
+        (&mut file).take(HASH_BLOCK).read_to_end(&mut block)?;
+        hasher.update(&block);
+        if size > HASH_BLOCK {
+            block.clear();
+            file.seek(SeekFrom::Start(
+                size.saturating_sub(HASH_BLOCK).max(HASH_BLOCK),
+            ))?;
+            file.take(HASH_BLOCK).read_to_end(&mut block)?;
+            hasher.update(&block);
+        }
+        hasher.update(&size.to_le_bytes());
+
+        Ok(Self {
+            size,
+            hash: hasher.digest(),
+        })
+    }
+
+    fn from_header(header: &Value) -> Option<Self> {
+        Some(Self {
+            size: header["size"].as_u64()?,
+            hash: header["hash"].as_u64()?,
+        })
+    }
+}
+
+/// Whether evenly spaced offsets still fall at the start of a line, which
+/// catches edits in the middle of the file that the hash does not cover
+fn starts_lines(path: &Path, offsets: &[u64]) -> io::Result<bool> {
+    let mut file = File::open(path)?;
+    let step = (offsets.len() / SPOT_CHECKS).max(1);
+    let mut byte = [0u8; 1];
+    for &offset in offsets.iter().step_by(step).filter(|&&offset| offset > 0) {
+        file.seek(SeekFrom::Start(offset - 1))?;
+        file.read_exact(&mut byte)?;
+        if byte[0] != b'\n' {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn input(dir: &TempDir, content: &str) -> PathBuf {
+        let path = dir.path().join("rows.jsonl");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        assert_eq!(
+            IndexCache::sidecar(Path::new("logs/big.jsonl")),
+            PathBuf::from("logs/big.jsonl.jlcatidx")
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new().unwrap();
+        let path = input(&dir, "{\"a\": 1}\n{\"b\": 2}\n");
+        let cache = IndexCache {
+            offsets: vec![0, 9],
+            columns: Some(vec!["a".to_string(), "b".to_string()]),
+        };
+        cache.save(&path).unwrap();
+
+        assert_eq!(IndexCache::load(&path), Some(cache));
+    }
+
+    #[test]
+    fn test_load_rejects_changed_file() {
+        let dir = TempDir::new().unwrap();
+        let path = input(&dir, "{\"a\": 1}\n");
+        let cache = IndexCache {
+            offsets: vec![0],
+            columns: None,
+        };
+        cache.save(&path).unwrap();
+
+        fs::write(&path, "{\"a\": 1}\n{\"a\": 2}\n").unwrap();
+        assert_eq!(IndexCache::load(&path), None);
+    }
+
+    #[test]
+    fn test_load_rejects_same_size_edit() {
+        let dir = TempDir::new().unwrap();
+        let path = input(&dir, "{\"a\": 1}\n");
+        let cache = IndexCache {
+            offsets: vec![0],
+            columns: Some(vec!["a".to_string()]),
+        };
+        cache.save(&path).unwrap();
+
+        fs::write(&path, "{\"b\": 1}\n").unwrap();
+        assert_eq!(IndexCache::load(&path), None);
+    }
+
+    #[test]
+    fn test_load_without_sidecar() {
+        let dir = TempDir::new().unwrap();
+        let path = input(&dir, "{\"a\": 1}\n");
+        assert_eq!(IndexCache::load(&path), None);
+    }
+}
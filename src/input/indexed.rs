@@ -1,3 +1,6 @@
+use super::parse::{parse_row, DupKeys};
+use super::rows::{ParsedRow, RowSource};
+use crate::error::Result;
 use serde_json::Value;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
@@ -10,6 +13,13 @@ pub struct IndexedReader<R: Read + Seek> {
     offsets: Vec<u64>,
     /// Total number of rows
     row_count: usize,
+    /// Next row for `RowSource::next_row`
+    cursor: usize,
+    /// Text of the row last read
+    text: String,
+    dup_keys: DupKeys,
+    /// Keys repeated within an object, over the rows read so far
+    duplicates: usize,
 }
 
 impl IndexedReader<File> {
@@ -31,9 +41,34 @@ impl<R: Read + Seek> IndexedReader<R> {
             reader: buf_reader,
             offsets,
             row_count,
+            cursor: 0,
+            text: String::new(),
+            dup_keys: DupKeys::default(),
+            duplicates: 0,
         })
     }
 
+    /// Create an IndexedReader from an offset table built earlier for the
+    /// same input, skipping the scan
+    pub fn with_offsets(reader: R, offsets: Vec<u64>) -> Self {
+        let row_count = offsets.len();
+        Self {
+            reader: BufReader::new(reader),
+            offsets,
+            row_count,
+            cursor: 0,
+            text: String::new(),
+            dup_keys: DupKeys::default(),
+            duplicates: 0,
+        }
+    }
+
+    /// Resolve keys repeated within an object by `policy`
+    pub fn with_dup_keys(mut self, policy: DupKeys) -> Self {
+        self.dup_keys = policy;
+        self
+    }
+
     /// Build the offset index by scanning all lines
     fn build_index<T: BufRead + Seek>(reader: &mut T) -> io::Result<Vec<u64>> {
         let mut offsets = Vec::new();
@@ -75,6 +110,18 @@ impl<R: Read + Seek> IndexedReader<R> {
 
     /// Read and parse a specific row by index
     pub fn get_row(&mut self, index: usize) -> io::Result<Option<Value>> {
+        match self.parse_row(index)? {
+            Some(Ok(value)) => Ok(Some(value)),
+            Some(Err(e)) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("JSON parse error at row {}: {}", index, e),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the text of a row and parse it, keeping parse errors per row
+    fn parse_row(&mut self, index: usize) -> io::Result<Option<ParsedRow>> {
         if index >= self.row_count {
             return Ok(None);
         }
@@ -82,16 +129,16 @@ impl<R: Read + Seek> IndexedReader<R> {
         let offset = self.offsets[index];
         self.reader.seek(SeekFrom::Start(offset))?;
 
-        let mut line = String::new();
-        self.reader.read_line(&mut line)?;
+        self.text.clear();
+        self.reader.read_line(&mut self.text)?;
+        let len = self.text.trim_end().len();
+        self.text.truncate(len);
 
-        match serde_json::from_str(&line) {
-            Ok(value) => Ok(Some(value)),
-            Err(e) => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("JSON parse error at row {}: {}", index, e),
-            )),
-        }
+        Ok(Some(parse_row(
+            &self.text,
+            self.dup_keys,
+            &mut self.duplicates,
+        )))
     }
 
     /// Read a range of rows
@@ -117,6 +164,41 @@ impl<R: Read + Seek> IndexedReader<R> {
     }
 }
 
+impl<R: Read + Seek> RowSource for IndexedReader<R> {
+    fn next_row(&mut self) -> Result<Option<ParsedRow>> {
+        let row = self.parse_row(self.cursor)?;
+        if row.is_some() {
+            self.cursor += 1;
+        }
+        Ok(row)
+    }
+
+    /// Row number (1-based, blank lines not counted)
+    fn position(&self) -> usize {
+        self.cursor
+    }
+
+    fn last_text(&self) -> Option<&str> {
+        Some(&self.text)
+    }
+
+    fn duplicate_keys(&self) -> usize {
+        self.duplicates
+    }
+
+    fn row_count_hint(&self) -> Option<usize> {
+        Some(self.row_count)
+    }
+
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn row_at(&mut self, index: usize) -> Result<Option<ParsedRow>> {
+        Ok(self.parse_row(index)?)
+    }
+}
+
 /// Iterator over rows in an IndexedReader
 pub struct IndexedRowIterator<'a, R: Read + Seek> {
     reader: &'a mut IndexedReader<R>,
@@ -234,6 +316,39 @@ mod tests {
         assert_eq!(rows[2]["id"], 3);
     }
 
+    #[test]
+    fn test_row_source() {
+        let content = "{\"id\": 1}\n\nnot json\n{\"id\": 3}\n";
+        let mut reader = create_test_reader(content);
+        assert!(reader.is_seekable());
+        assert_eq!(reader.row_count_hint(), Some(3));
+
+        assert_eq!(reader.next_row().unwrap().unwrap().unwrap()["id"], 1);
+        assert!(reader.next_row().unwrap().unwrap().is_err());
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.last_text(), Some("not json"));
+
+        // Random access does not move the cursor
+        assert_eq!(reader.row_at(0).unwrap().unwrap().unwrap()["id"], 1);
+        assert_eq!(reader.next_row().unwrap().unwrap().unwrap()["id"], 3);
+        assert!(reader.next_row().unwrap().is_none());
+        assert!(reader.row_at(3).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_with_offsets_skips_scan() {
+        let content = "{\"id\": 1}\n\n{\"id\": 2}\n";
+        let offsets = create_test_reader(content).offsets().to_vec();
+        let cursor = Cursor::new(content.as_bytes().to_vec());
+        let mut reader = IndexedReader::with_offsets(cursor, offsets);
+
+        assert_eq!(reader.row_count(), 2);
+        assert_eq!(
+            reader.get_row(1).unwrap(),
+            Some(serde_json::json!({"id": 2}))
+        );
+    }
+
     #[test]
     fn test_empty_input() {
         let reader = create_test_reader("");
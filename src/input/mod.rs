@@ -1,15 +1,31 @@
 #[allow(dead_code)]
 mod cached;
 mod detector;
+mod encoding;
+mod estimate;
+mod index_cache;
 #[allow(dead_code)]
 mod indexed;
+mod parse;
+#[allow(dead_code)]
+mod rows;
 #[allow(dead_code)]
 mod source;
 #[allow(dead_code)]
+mod spilled;
+#[allow(dead_code)]
 mod spooler;
 
 #[allow(unused_imports)]
 pub use cached::CachedReader;
-pub use detector::{sniff_format, InputFormat};
+pub use detector::{binary_reason, sniff_format, InputFormat, SNIFF_LEN};
+pub use encoding::{bom_encoding, parse_encoding, text_reader};
+pub use estimate::{count_rows, estimate_rows, RowEstimate};
+pub use index_cache::IndexCache;
 #[allow(unused_imports)]
 pub use indexed::IndexedReader;
+pub use parse::DupKeys;
+#[allow(unused_imports)]
+pub use rows::{ArrayRows, ByteLimit, LineRows, ParsedRow, RowSource};
+pub use spilled::SpilledRows;
+pub use spooler::SpooledInput;
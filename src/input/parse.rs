@@ -0,0 +1,175 @@
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::map::Entry;
+use serde_json::{Map, Number, Value};
+use std::fmt;
+
+/// Which value wins when a key repeats within one JSON object (`--dup-keys`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DupKeys {
+    First,
+    /// What serde_json does on its own
+    #[default]
+    Last,
+    /// The row is invalid JSON
+    Error,
+}
+
+/// Key serde_json's `arbitrary_precision` feature hands numbers over in, as
+/// a map holding their text
+const NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+/// Parse the text of a row, resolving repeated keys by `policy` and adding
+/// how many there were to `duplicates`
+pub fn parse_row(
+    text: &str,
+    policy: DupKeys,
+    duplicates: &mut usize,
+) -> Result<Value, serde_json::Error> {
+    let mut de = serde_json::Deserializer::from_str(text);
+    let value = ValueSeed { policy, duplicates }.deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Builds a `Value` as serde_json's own deserializer does, except for
+/// repeated keys
+struct ValueSeed<'a> {
+    policy: DupKeys,
+    duplicates: &'a mut usize,
+}
+
+impl ValueSeed<'_> {
+    fn nested(&mut self) -> ValueSeed<'_> {
+        ValueSeed {
+            policy: self.policy,
+            duplicates: &mut *self.duplicates,
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ValueSeed<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ValueSeed<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, b: bool) -> Result<Value, E> {
+        Ok(Value::Bool(b))
+    }
+
+    fn visit_i64<E: de::Error>(self, n: i64) -> Result<Value, E> {
+        Ok(Value::from(n))
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<Value, E> {
+        Ok(Value::from(n))
+    }
+
+    fn visit_f64<E: de::Error>(self, n: f64) -> Result<Value, E> {
+        Ok(Number::from_f64(n).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Value, E> {
+        Ok(Value::String(s.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, s: String) -> Result<Value, E> {
+        Ok(Value::String(s))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element_seed(self.nested())? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Value, A::Error> {
+        let mut object = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            if key == NUMBER_TOKEN && object.is_empty() {
+                let text: String = map.next_value()?;
+                return text
+                    .parse::<Number>()
+                    .map(Value::Number)
+                    .map_err(de::Error::custom);
+            }
+            let value = map.next_value_seed(self.nested())?;
+            match object.entry(key) {
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+                Entry::Occupied(mut entry) => {
+                    *self.duplicates += 1;
+                    match self.policy {
+                        DupKeys::First => {}
+                        DupKeys::Last => {
+                            entry.insert(value);
+                        }
+                        DupKeys::Error => {
+                            return Err(de::Error::custom(format_args!(
+                                "duplicate key `{}`",
+                                entry.key()
+                            )))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(text: &str, policy: DupKeys) -> (Result<Value, serde_json::Error>, usize) {
+        let mut duplicates = 0;
+        let value = parse_row(text, policy, &mut duplicates);
+        (value, duplicates)
+    }
+
+    #[test]
+    fn test_parse_row_matches_serde_json() {
+        let text = r#"{"id": 12345678901234567890123, "price": 1.10, "tags": ["a", null, true], "n": {"x": -1}}"#;
+        let (value, duplicates) = parse(text, DupKeys::Last);
+        assert_eq!(value.unwrap(), serde_json::from_str::<Value>(text).unwrap());
+        assert_eq!(duplicates, 0);
+        assert!(parse("{\"id\": 1} x", DupKeys::Last).0.is_err());
+    }
+
+    #[test]
+    fn test_parse_row_duplicate_policies() {
+        let text = r#"{"a": 1, "b": {"c": 1, "c": 2}, "a": 3}"#;
+
+        let (value, duplicates) = parse(text, DupKeys::Last);
+        assert_eq!(value.unwrap(), json!({"a": 3, "b": {"c": 2}}));
+        assert_eq!(duplicates, 2);
+
+        let (value, _) = parse(text, DupKeys::First);
+        assert_eq!(value.unwrap(), json!({"a": 1, "b": {"c": 1}}));
+
+        let (value, _) = parse(text, DupKeys::Error);
+        assert!(value.unwrap_err().to_string().contains("duplicate key `c`"));
+    }
+}
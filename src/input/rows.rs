@@ -0,0 +1,413 @@
+use super::parse::{parse_row, DupKeys};
+use crate::error::{JlcatError, Result};
+use serde_json::Value;
+use std::io::{self, BufRead};
+
+/// A row as parsed from its source text; `Err` when just this row is not valid JSON
+pub type ParsedRow = std::result::Result<Value, serde_json::Error>;
+
+/// A source of JSON rows, read in order and, where supported, by position.
+///
+/// `next_row` distinguishes two kinds of failure: the outer error means the
+/// source itself failed (I/O, a malformed array), the inner one that only the
+/// current row is invalid and reading can continue.
+pub trait RowSource {
+    /// Read the next row, or `None` at the end
+    fn next_row(&mut self) -> Result<Option<ParsedRow>>;
+
+    /// Line number (or array element number) of the row last returned
+    fn position(&self) -> usize;
+
+    /// Raw text of the row last returned, if the source keeps it
+    fn last_text(&self) -> Option<&str> {
+        None
+    }
+
+    /// Keys repeated within an object, counted over the rows read so far
+    fn duplicate_keys(&self) -> usize {
+        0
+    }
+
+    /// Bytes of input consumed by the rows read so far, where the source
+    /// counts them (`--limit-bytes`)
+    fn bytes_read(&self) -> Option<u64> {
+        None
+    }
+
+    /// Total number of rows, if known without reading them
+    fn row_count_hint(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether rows can be read by index with `row_at`
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    /// Read the row at a 0-based index; `None` past the end or when the
+    /// source cannot seek
+    fn row_at(&mut self, _index: usize) -> Result<Option<ParsedRow>> {
+        Ok(None)
+    }
+}
+
+/// Rows of JSONL input, one per non-blank line
+pub struct LineRows<R: BufRead> {
+    lines: io::Lines<R>,
+    line: usize,
+    text: String,
+    dup_keys: DupKeys,
+    duplicates: usize,
+    bytes: u64,
+}
+
+impl<R: BufRead> LineRows<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: 0,
+            text: String::new(),
+            dup_keys: DupKeys::default(),
+            duplicates: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Resolve keys repeated within an object by `policy`
+    pub fn with_dup_keys(mut self, policy: DupKeys) -> Self {
+        self.dup_keys = policy;
+        self
+    }
+}
+
+impl<R: BufRead> RowSource for LineRows<R> {
+    fn next_row(&mut self) -> Result<Option<ParsedRow>> {
+        for line in self.lines.by_ref() {
+            let line = line?;
+            self.line += 1;
+            // The line break is not part of the line; count it as one byte
+            self.bytes += line.len() as u64 + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            self.text = line;
+            return Ok(Some(parse_row(
+                &self.text,
+                self.dup_keys,
+                &mut self.duplicates,
+            )));
+        }
+        Ok(None)
+    }
+
+    fn position(&self) -> usize {
+        self.line
+    }
+
+    fn last_text(&self) -> Option<&str> {
+        Some(&self.text)
+    }
+
+    fn duplicate_keys(&self) -> usize {
+        self.duplicates
+    }
+
+    fn bytes_read(&self) -> Option<u64> {
+        Some(self.bytes)
+    }
+}
+
+/// Stops a source once it has consumed `max` bytes (`--limit-bytes`); the
+/// row that crosses the limit is still read whole
+pub struct ByteLimit<'a> {
+    inner: &'a mut dyn RowSource,
+    max: u64,
+}
+
+impl<'a> ByteLimit<'a> {
+    pub fn new(inner: &'a mut dyn RowSource, max: u64) -> Self {
+        Self { inner, max }
+    }
+}
+
+impl RowSource for ByteLimit<'_> {
+    fn next_row(&mut self) -> Result<Option<ParsedRow>> {
+        if self.inner.bytes_read().is_some_and(|read| read >= self.max) {
+            return Ok(None);
+        }
+        self.inner.next_row()
+    }
+
+    fn position(&self) -> usize {
+        self.inner.position()
+    }
+
+    fn last_text(&self) -> Option<&str> {
+        self.inner.last_text()
+    }
+
+    fn duplicate_keys(&self) -> usize {
+        self.inner.duplicate_keys()
+    }
+
+    fn bytes_read(&self) -> Option<u64> {
+        self.inner.bytes_read()
+    }
+}
+
+/// Elements of a JSON array, split one at a time without loading the whole array
+pub struct ArrayRows<R: BufRead> {
+    reader: R,
+    started: bool,
+    finished: bool,
+    /// Line of the reader position, for errors in the array structure
+    line: usize,
+    index: usize,
+    text: String,
+    dup_keys: DupKeys,
+    duplicates: usize,
+    bytes: u64,
+}
+
+impl<R: BufRead> ArrayRows<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            started: false,
+            finished: false,
+            line: 1,
+            index: 0,
+            text: String::new(),
+            dup_keys: DupKeys::default(),
+            duplicates: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Resolve keys repeated within an object by `policy`
+    pub fn with_dup_keys(mut self, policy: DupKeys) -> Self {
+        self.dup_keys = policy;
+        self
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>> {
+        let byte = match self.reader.fill_buf()?.first() {
+            Some(&b) => b,
+            None => return Ok(None),
+        };
+        self.reader.consume(1);
+        self.bytes += 1;
+        if byte == b'\n' {
+            self.line += 1;
+        }
+        Ok(Some(byte))
+    }
+
+    /// Next byte that is not whitespace
+    fn next_token(&mut self) -> Result<Option<u8>> {
+        while let Some(b) = self.next_byte()? {
+            if !b.is_ascii_whitespace() {
+                return Ok(Some(b));
+            }
+        }
+        Ok(None)
+    }
+
+    fn malformed(&mut self, message: &str) -> JlcatError {
+        self.finished = true;
+        JlcatError::JsonParse {
+            line: self.line,
+            message: message.to_string(),
+        }
+    }
+
+    /// Raw text of the next element, up to the `,` or `]` that ends it
+    fn next_element(&mut self) -> Result<Option<Vec<u8>>> {
+        if !self.started {
+            self.started = true;
+            if self.next_token()? != Some(b'[') {
+                return Err(self.malformed("expected a JSON array"));
+            }
+        }
+
+        let mut first = match self.next_token()? {
+            Some(b']') if self.index == 0 => {
+                self.finished = true;
+                return Ok(None);
+            }
+            Some(b) => Some(b),
+            None => return Err(self.malformed("unexpected end of JSON array")),
+        };
+
+        let mut element = Vec::new();
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        loop {
+            let b = match first.take() {
+                Some(b) => b,
+                None => match self.next_byte()? {
+                    Some(b) => b,
+                    None => return Err(self.malformed("unexpected end of JSON array")),
+                },
+            };
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_string = false;
+                }
+            } else {
+                match b {
+                    b'"' => in_string = true,
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' if depth > 0 => depth -= 1,
+                    b']' => {
+                        self.finished = true;
+                        break;
+                    }
+                    b',' if depth == 0 => break,
+                    _ => {}
+                }
+            }
+            element.push(b);
+        }
+
+        if element.iter().all(u8::is_ascii_whitespace) {
+            return Err(self.malformed("expected a value in JSON array"));
+        }
+        Ok(Some(element))
+    }
+}
+
+impl<R: BufRead> RowSource for ArrayRows<R> {
+    fn next_row(&mut self) -> Result<Option<ParsedRow>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let Some(element) = self.next_element()? else {
+            return Ok(None);
+        };
+        self.index += 1;
+        self.text = String::from_utf8_lossy(&element).trim().to_string();
+        Ok(Some(parse_row(
+            &self.text,
+            self.dup_keys,
+            &mut self.duplicates,
+        )))
+    }
+
+    fn position(&self) -> usize {
+        self.index
+    }
+
+    fn last_text(&self) -> Option<&str> {
+        Some(&self.text)
+    }
+
+    fn duplicate_keys(&self) -> usize {
+        self.duplicates
+    }
+
+    fn bytes_read(&self) -> Option<u64> {
+        Some(self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn drain(source: &mut dyn RowSource) -> Vec<std::result::Result<Value, String>> {
+        let mut rows = Vec::new();
+        while let Some(row) = source.next_row().unwrap() {
+            rows.push(row.map_err(|_| source.last_text().unwrap().to_string()));
+        }
+        rows
+    }
+
+    #[test]
+    fn test_line_rows() {
+        let mut rows = LineRows::new("{\"id\": 1}\n\nnot json\n".as_bytes());
+        assert_eq!(rows.next_row().unwrap().unwrap().unwrap(), json!({"id": 1}));
+        assert_eq!(rows.position(), 1);
+        assert!(rows.next_row().unwrap().unwrap().is_err());
+        assert_eq!(rows.position(), 3);
+        assert_eq!(rows.last_text(), Some("not json"));
+        assert!(rows.next_row().unwrap().is_none());
+        assert!(!rows.is_seekable());
+    }
+
+    #[test]
+    fn test_array_rows() {
+        let input = r#" [ {"a": "x,]\"}"}, [1, 2], 3 ,{"b": {"c": []}} ] "#;
+        let mut rows = ArrayRows::new(input.as_bytes());
+        assert_eq!(
+            drain(&mut rows),
+            vec![
+                Ok(json!({"a": "x,]\"}"})),
+                Ok(json!([1, 2])),
+                Ok(json!(3)),
+                Ok(json!({"b": {"c": []}})),
+            ]
+        );
+        assert_eq!(rows.position(), 4);
+    }
+
+    #[test]
+    fn test_array_rows_invalid_element_and_empty() {
+        let mut rows = ArrayRows::new(r#"[{"id": 1}, {bad}]"#.as_bytes());
+        assert_eq!(
+            drain(&mut rows),
+            vec![Ok(json!({"id": 1})), Err("{bad}".to_string())]
+        );
+
+        let mut empty = ArrayRows::new(" [ ]".as_bytes());
+        assert!(empty.next_row().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_array_rows_malformed() {
+        let mut rows = ArrayRows::new("[{\"id\": 1},\n".as_bytes());
+        assert!(rows.next_row().unwrap().unwrap().is_ok());
+        assert!(matches!(
+            rows.next_row(),
+            Err(JlcatError::JsonParse { line: 2, .. })
+        ));
+        assert!(rows.next_row().unwrap().is_none());
+
+        assert!(ArrayRows::new("[1,,2]".as_bytes()).nth_err(1));
+        assert!(ArrayRows::new("{}".as_bytes()).next_row().is_err());
+    }
+
+    #[test]
+    fn test_byte_limit() {
+        let input = "{\"id\": 1}\n{\"id\": 2}\n{\"id\": 3}\n";
+        // Each line is 10 bytes with its line break; the second one crosses 15
+        let mut lines = LineRows::new(input.as_bytes());
+        let mut rows = ByteLimit::new(&mut lines, 15);
+        assert_eq!(
+            drain(&mut rows),
+            vec![Ok(json!({"id": 1})), Ok(json!({"id": 2}))]
+        );
+        assert_eq!(rows.bytes_read(), Some(20));
+
+        let mut array = ArrayRows::new(r#"[{"id": 1}, {"id": 2}]"#.as_bytes());
+        assert_eq!(drain(&mut ByteLimit::new(&mut array, 5)).len(), 1);
+    }
+
+    impl<R: BufRead> ArrayRows<R> {
+        /// Whether reading fails at the `n`th row (0-based)
+        fn nth_err(mut self, n: usize) -> bool {
+            for _ in 0..n {
+                if self.next_row().is_err() {
+                    return false;
+                }
+            }
+            self.next_row().is_err()
+        }
+    }
+}
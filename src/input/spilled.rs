@@ -0,0 +1,64 @@
+use super::cached::CachedReader;
+use super::spooler::SpooledInput;
+use serde_json::Value;
+use std::fs::File;
+
+/// Rows left on disk because the input is over `--max-memory`, read back
+/// by position through a byte-offset index and a small row cache
+pub struct SpilledRows {
+    reader: CachedReader<File>,
+    /// Index in the file of each row shown, in order
+    positions: Vec<usize>,
+    /// Temp copy of stdin or of several files, removed when dropped
+    _spool: Option<SpooledInput>,
+}
+
+impl SpilledRows {
+    pub fn new(
+        reader: CachedReader<File>,
+        positions: Vec<usize>,
+        spool: Option<SpooledInput>,
+    ) -> Self {
+        Self {
+            reader,
+            positions,
+            _spool: spool,
+        }
+    }
+
+    /// Number of rows shown
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Row `index` of the rows shown; `None` past the end, or if the file
+    /// can no longer be read
+    pub fn get(&mut self, index: usize) -> Option<Value> {
+        let position = *self.positions.get(index)?;
+        self.reader.get_row(position).ok().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_get_reads_shown_rows_by_position() {
+        let input = b"{\"id\": 1}\n\n{\"id\": 2}\n{\"id\": 3}\n";
+        let spool = SpooledInput::from_reader(Cursor::new(input.to_vec())).unwrap();
+        let reader = CachedReader::from_path(spool.path()).unwrap();
+        let mut rows = SpilledRows::new(reader, vec![0, 2], Some(spool));
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows.get(0), Some(json!({"id": 1})));
+        assert_eq!(rows.get(1), Some(json!({"id": 3})));
+        assert_eq!(rows.get(2), None);
+    }
+}
@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::io::{self, BufReader, Read, Write};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use tempfile::NamedTempFile;
 
@@ -15,6 +15,31 @@ impl SpooledInput {
         Ok(Self { temp_file })
     }
 
+    /// Spool several inputs one after another. Each one that does not end
+    /// with a line break gets one, so its last row cannot run into the next
+    /// input's first.
+    pub fn from_readers<R: Read>(readers: impl IntoIterator<Item = R>) -> io::Result<Self> {
+        let mut temp_file = NamedTempFile::new()?;
+        for mut reader in readers {
+            if io::copy(&mut reader, &mut temp_file)? == 0 {
+                continue;
+            }
+            let mut last = [0];
+            temp_file.seek(SeekFrom::End(-1))?;
+            temp_file.read_exact(&mut last)?;
+            if last != *b"\n" {
+                temp_file.write_all(b"\n")?;
+            }
+        }
+        temp_file.flush()?;
+        Ok(Self { temp_file })
+    }
+
+    /// Keep a temp file written elsewhere, such as sorted output
+    pub fn from_temp_file(temp_file: NamedTempFile) -> Self {
+        Self { temp_file }
+    }
+
     pub fn path(&self) -> &Path {
         self.temp_file.path()
     }
@@ -51,6 +76,17 @@ mod tests {
         assert!(content1.contains("\"id\": 3"));
     }
 
+    #[test]
+    fn test_spool_readers_breaks_lines_between_inputs() {
+        let inputs = [&b"{\"id\": 1}"[..], b"", b"{\"id\": 2}\n", b"{\"id\": 3}"];
+        let spooled = SpooledInput::from_readers(inputs.map(Cursor::new)).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(spooled.path()).unwrap(),
+            "{\"id\": 1}\n{\"id\": 2}\n{\"id\": 3}\n"
+        );
+    }
+
     #[test]
     fn test_spool_reader_method() {
         let input = b"line1\nline2\n";
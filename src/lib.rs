@@ -1,5 +1,42 @@
+//! Render JSON and JSONL rows as tables.
+//!
+//! The items re-exported at the crate root are the supported API for
+//! embedding jlcat: [`TableBuilder`] runs the filter → sort → select → render
+//! steps of the binary, and the types below it expose each step on its own.
+//! The modules remain public for the binary's own tests and may change
+//! between minor versions.
+//!
+//! ```
+//! use jlcat::{CatRenderer, TableData, TableRenderer, TableStyle};
+//! use serde_json::json;
+//!
+//! let rows = vec![json!({"id": 1, "name": "Alice"})];
+//! let table = TableData::from_rows(rows, None);
+//! let text = CatRenderer::new(TableStyle::Plain).render(&table);
+//! assert!(text.contains("Alice"));
+//! ```
+
+mod builder;
 pub mod cli;
+pub mod color;
+pub mod config;
 pub mod core;
 pub mod error;
 pub mod input;
+pub mod pipeline;
+#[cfg(feature = "wasm")]
+pub mod plugin;
 pub mod render;
+#[cfg(feature = "script")]
+pub mod script;
+#[cfg(feature = "sql")]
+pub mod sql;
+
+pub use crate::builder::TableBuilder;
+pub use crate::cli::TableStyle;
+pub use crate::core::{
+    ArrayExpansion, ChildTable, ColumnSelector, FilterExpr, FlatConfig, FlatTableData, Formatter,
+    FullTextSearch, NestedExtractor, Sorter, TableData,
+};
+pub use crate::error::{JlcatError, Result};
+pub use crate::render::{CatRenderer, TableRenderer, VerticalRenderer};
@@ -0,0 +1,156 @@
+use super::decode::SkipStats;
+use crate::core::{display_width, ColumnType, Schema, SchemaInferrer};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// What `--check` found in the input: row counts, the inferred columns and
+/// the value types each one holds
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    pub rows: usize,
+    pub skipped: SkipStats,
+    schema: Schema,
+    /// Rows holding each type, per column, in order of first appearance
+    types: HashMap<String, Vec<(ColumnType, usize)>>,
+}
+
+impl CheckReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a valid row's columns and value types
+    pub fn add(&mut self, row: &Value) {
+        self.rows += 1;
+        SchemaInferrer::infer_streaming(row, &mut self.schema);
+        if let Value::Object(obj) = row {
+            for (key, value) in obj {
+                let counts = self.types.entry(key.clone()).or_default();
+                let col_type = ColumnType::from_value(value);
+                match counts.iter_mut().find(|(t, _)| *t == col_type) {
+                    Some((_, n)) => *n += 1,
+                    None => counts.push((col_type, 1)),
+                }
+            }
+        }
+    }
+
+    /// Columns holding more than one type of value besides null
+    pub fn conflicts(&self) -> Vec<&str> {
+        self.schema
+            .columns()
+            .iter()
+            .filter(|c| self.schema.column_type(c) == Some(ColumnType::Mixed))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Why the check failed, or None when the input is clean
+    pub fn problems(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.skipped.total() > 0 {
+            parts.push(self.skipped.summary());
+        }
+        match self.conflicts().len() {
+            0 => {}
+            1 => parts.push("1 column with conflicting types".to_string()),
+            n => parts.push(format!("{} columns with conflicting types", n)),
+        }
+        match self.skipped.duplicate_keys {
+            0 => {}
+            1 => parts.push("1 duplicate key".to_string()),
+            n => parts.push(format!("{} duplicate keys", n)),
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    /// Type counts of a column, e.g. "number 1180, string 20"
+    fn type_text(&self, column: &str) -> String {
+        let counts = self.types.get(column).map(Vec::as_slice).unwrap_or(&[]);
+        match counts {
+            [(col_type, _)] => col_type.name().to_string(),
+            _ => counts
+                .iter()
+                .map(|(t, n)| format!("{} {}", t.name(), n))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = if self.rows == 1 { "row" } else { "rows" };
+        write!(f, "{} valid {}", self.rows, unit)?;
+        if self.skipped.total() > 0 {
+            write!(f, ", {}", self.skipped.summary())?;
+        }
+        let columns = self.schema.columns();
+        let width = columns.iter().map(|c| display_width(c)).max().unwrap_or(0);
+        for column in columns {
+            let present: usize = self
+                .types
+                .get(column)
+                .map_or(0, |counts| counts.iter().map(|(_, n)| n).sum());
+            write!(
+                f,
+                "\n  {}{}  {}",
+                column,
+                " ".repeat(width - display_width(column)),
+                self.type_text(column)
+            )?;
+            if present < self.rows {
+                write!(f, " (in {} of {} rows)", present, self.rows)?;
+            }
+            if self.schema.column_type(column) == Some(ColumnType::Mixed) {
+                write!(f, "  <- conflicting types")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_report() {
+        let mut report = CheckReport::new();
+        report.add(&json!({"id": 1, "price": 10, "note": null}));
+        report.add(&json!({"id": 2, "price": "12.5"}));
+        report.add(&json!({"id": 3, "price": 9, "note": "sale"}));
+
+        assert_eq!(report.conflicts(), vec!["price"]);
+        assert_eq!(
+            report.problems().as_deref(),
+            Some("1 column with conflicting types")
+        );
+        assert_eq!(
+            report.to_string(),
+            "3 valid rows\n  \
+             id     number\n  \
+             note   null 1, string 1 (in 2 of 3 rows)\n  \
+             price  number 2, string 1  <- conflicting types"
+        );
+    }
+
+    #[test]
+    fn test_check_report_clean_and_skipped() {
+        let mut report = CheckReport::new();
+        report.add(&json!({"id": 1}));
+        assert_eq!(report.problems(), None);
+
+        report.skipped.invalid_json = 2;
+        report.skipped.duplicate_keys = 1;
+        assert_eq!(
+            report.problems().as_deref(),
+            Some("2 lines skipped (2 invalid JSON), 1 duplicate key")
+        );
+        assert!(report
+            .to_string()
+            .starts_with("1 valid row, 2 lines skipped"));
+    }
+}
@@ -0,0 +1,908 @@
+use crate::error::{JlcatError, Result};
+use crate::input::{
+    binary_reason, sniff_format, text_reader, ArrayRows, ByteLimit, DupKeys, InputFormat, LineRows,
+    RowSource, SNIFF_LEN,
+};
+use encoding_rs::Encoding;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+
+/// Paging and strictness options for decoding rows
+#[derive(Debug, Clone, Default)]
+pub struct DecodeOptions {
+    /// Fail on invalid lines instead of skipping them with a warning
+    pub strict: bool,
+    /// In lenient mode, abort once more than this many lines are skipped
+    pub max_errors: Option<usize>,
+    /// In lenient mode, write every skipped line to this file
+    pub rejects: Option<PathBuf>,
+    /// Skip lines without a warning for each (they are still counted)
+    pub quiet: bool,
+    pub skip: usize,
+    pub limit: Option<usize>,
+    pub tail: Option<usize>,
+    /// Stop reading once this many bytes of input are consumed, across all
+    /// inputs (`--limit-bytes`, `--limit N%`)
+    pub limit_bytes: Option<u64>,
+    /// Add a `_source` column naming the input of each row; labels replace the
+    /// input names in order
+    pub tag_source: Option<Vec<String>>,
+    /// Add a `#` column with each row's 1-based position in its input,
+    /// counted before paging
+    pub row_numbers: bool,
+    /// Encoding of inputs without a byte order mark (`--encoding`); UTF-8
+    /// when unset
+    pub encoding: Option<&'static Encoding>,
+    /// Which value a key repeated within an object keeps, or whether the row
+    /// is invalid (`--dup-keys`)
+    pub dup_keys: DupKeys,
+}
+
+/// Column `--tag-source` adds with the input each row came from
+pub const SOURCE_COLUMN: &str = "_source";
+
+/// Column `--row-numbers` adds with each row's position in its input
+pub const ROW_NUMBER_COLUMN: &str = "#";
+
+/// Rows decoded from the input, with what lenient mode skipped on the way
+#[derive(Debug, Clone, Default)]
+pub struct Decoded {
+    pub rows: Vec<Value>,
+    pub skipped: SkipStats,
+}
+
+/// Counts of lines skipped in lenient mode, by reason
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SkipStats {
+    pub invalid_json: usize,
+    pub non_object: usize,
+    /// JSON array input: counts are of array elements rather than lines
+    pub elements: bool,
+    /// Keys repeated within an object in rows that were kept; not skips, but
+    /// reported with them
+    pub duplicate_keys: usize,
+}
+
+impl SkipStats {
+    pub fn total(&self) -> usize {
+        self.invalid_json + self.non_object
+    }
+
+    /// One-line summary, e.g. "13 lines skipped (10 invalid JSON, 3 non-object)"
+    pub fn summary(&self) -> String {
+        let unit = match (self.elements, self.total()) {
+            (false, 1) => "line",
+            (false, _) => "lines",
+            (true, 1) => "array element",
+            (true, _) => "array elements",
+        };
+        format!("{} {} skipped ({})", self.total(), unit, self)
+    }
+
+    /// Note on repeated keys resolved by `policy`; none when there were none
+    /// or the rows holding them were rejected
+    pub fn duplicate_summary(&self, policy: DupKeys) -> Option<String> {
+        let kept = match policy {
+            _ if self.duplicate_keys == 0 => return None,
+            DupKeys::First => "first",
+            DupKeys::Last => "last",
+            DupKeys::Error => return None,
+        };
+        let keys = if self.duplicate_keys == 1 {
+            "key"
+        } else {
+            "keys"
+        };
+        Some(format!(
+            "{} duplicate {} in objects, kept the {} value (--dup-keys first|last|error)",
+            self.duplicate_keys, keys, kept
+        ))
+    }
+}
+
+impl std::fmt::Display for SkipStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            (self.invalid_json, "invalid JSON"),
+            (self.non_object, "non-object"),
+        ]
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, reason)| format!("{} {}", n, reason))
+        .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Why a line was skipped in lenient mode
+#[derive(Debug, Clone, Copy)]
+enum SkipReason {
+    InvalidJson,
+    NonObject,
+}
+
+/// Tracks and warns about skipped lines, enforces `--max-errors` and writes `--rejects`
+#[derive(Debug, Default)]
+struct Skipper {
+    stats: SkipStats,
+    quiet: bool,
+    max_errors: Option<usize>,
+    /// One JSON record per skipped line
+    rejects: Option<BufWriter<File>>,
+    /// Why decoding must stop (threshold exceeded or rejects write failed)
+    failure: Option<JlcatError>,
+    /// Name of the input being read, when there are several
+    input: Option<String>,
+    /// Bytes still to read under `--limit-bytes`, shared by all inputs
+    bytes_left: Option<u64>,
+}
+
+impl Skipper {
+    fn new(options: &DecodeOptions) -> Result<Self> {
+        let rejects = match options.rejects {
+            Some(ref path) => Some(BufWriter::new(File::create(path)?)),
+            None => None,
+        };
+        Ok(Self {
+            max_errors: options.max_errors,
+            rejects,
+            quiet: options.quiet,
+            bytes_left: options.limit_bytes,
+            ..Default::default()
+        })
+    }
+
+    /// Record a skipped line; false once decoding must stop
+    fn skip(&mut self, reason: SkipReason, at: usize, text: &str, message: &str) -> bool {
+        let what = match reason {
+            SkipReason::InvalidJson => {
+                self.stats.invalid_json += 1;
+                "invalid JSON"
+            }
+            SkipReason::NonObject => {
+                self.stats.non_object += 1;
+                "expected JSON object"
+            }
+        };
+        // Array elements are only counted, as before
+        if !self.quiet && !self.stats.elements {
+            let location = match self.input {
+                Some(ref input) => format!("{}: line {}", input, at),
+                None => format!("line {}", at),
+            };
+            crate::color::warn(&format!("{}: {}, skipping", location, what));
+        }
+        if let Some(ref mut out) = self.rejects {
+            let mut record = serde_json::Map::new();
+            if let Some(ref input) = self.input {
+                record.insert("file".to_string(), Value::from(input.as_str()));
+            }
+            let unit = if self.stats.elements {
+                "element"
+            } else {
+                "line"
+            };
+            record.insert(unit.to_string(), Value::from(at));
+            record.insert("error".to_string(), Value::from(message));
+            record.insert("text".to_string(), Value::from(text));
+            if let Err(e) = writeln!(out, "{}", Value::Object(record)) {
+                self.failure = Some(e.into());
+                return false;
+            }
+        }
+        if self.max_errors.is_some_and(|max| self.stats.total() > max) {
+            self.failure = Some(JlcatError::TooManyErrors {
+                max: self.max_errors.unwrap_or_default(),
+                line: at,
+                summary: self.stats.to_string(),
+            });
+            return false;
+        }
+        true
+    }
+
+    /// Error that stopped decoding, if any
+    fn error(&mut self) -> Option<JlcatError> {
+        self.failure.take()
+    }
+
+    /// Flush the rejects file
+    fn finish(&mut self) -> Result<()> {
+        if let Some(ref mut out) = self.rejects {
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode JSON objects from JSONL or a JSON array, detected from the first bytes
+pub fn decode<R: Read>(reader: R, options: &DecodeOptions) -> Result<Decoded> {
+    let mut skipper = Skipper::new(options)?;
+    let rows = decode_with(
+        reader,
+        options,
+        &mut skipper,
+        options.skip,
+        options.limit,
+        options.tail,
+        |_, value| value,
+    )?;
+    skipper.finish()?;
+    Ok(Decoded {
+        rows,
+        skipped: skipper.stats,
+    })
+}
+
+/// Decode several named inputs, such as multiple files, as one row set.
+///
+/// Paging applies to the combined rows and lenient mode counts skipped lines
+/// across all inputs. With `tag_source`, each row gets a `_source` column
+/// holding its input's label or name; with `row_numbers`, a `#` column
+/// numbering it within its input.
+pub fn decode_inputs<R: Read>(
+    inputs: Vec<(String, R)>,
+    options: &DecodeOptions,
+) -> Result<Decoded> {
+    let mut skipper = Skipper::new(options)?;
+    let several = inputs.len() > 1;
+    // Stop reading once --skip and --limit are satisfied, unless --tail needs every row
+    let wanted = match options.tail {
+        Some(_) => None,
+        None => options.limit.map(|limit| options.skip + limit),
+    };
+
+    let mut rows = Vec::new();
+    for (i, (name, reader)) in inputs.into_iter().enumerate() {
+        let remaining = wanted.map(|wanted| wanted.saturating_sub(rows.len()));
+        if remaining == Some(0) || skipper.bytes_left == Some(0) {
+            break;
+        }
+        skipper.input = several.then(|| name.clone());
+        let mut decoded = decode_with(
+            reader,
+            options,
+            &mut skipper,
+            0,
+            remaining,
+            None,
+            |_, value| value,
+        )?;
+        if options.row_numbers {
+            for (n, row) in decoded.iter_mut().enumerate() {
+                if let Some(obj) = row.as_object_mut() {
+                    obj.insert(ROW_NUMBER_COLUMN.to_string(), Value::from(n + 1));
+                }
+            }
+        }
+        if let Some(ref labels) = options.tag_source {
+            let label = labels.get(i).cloned().unwrap_or(name);
+            for row in &mut decoded {
+                if let Some(obj) = row.as_object_mut() {
+                    obj.insert(SOURCE_COLUMN.to_string(), Value::String(label.clone()));
+                }
+            }
+        }
+        rows.extend(decoded);
+    }
+    skipper.finish()?;
+
+    let rows = match options.tail {
+        Some(n) => rows.split_off(rows.len().saturating_sub(n)),
+        None => rows
+            .into_iter()
+            .skip(options.skip)
+            .take(options.limit.unwrap_or(usize::MAX))
+            .collect(),
+    };
+    Ok(Decoded {
+        rows,
+        skipped: skipper.stats,
+    })
+}
+
+/// Read every row of several named inputs without keeping them, for
+/// `--check` and `--infer-schema`: paging options are ignored, and `visit`
+/// sees each object row.
+pub fn scan_inputs<R: Read>(
+    inputs: Vec<(String, R)>,
+    options: &DecodeOptions,
+    mut visit: impl FnMut(&Value),
+) -> Result<SkipStats> {
+    let mut skipper = Skipper::new(options)?;
+    skipper.bytes_left = None;
+    let several = inputs.len() > 1;
+    for (name, reader) in inputs {
+        skipper.input = several.then_some(name);
+        // Rows map to (), so nothing accumulates
+        decode_with(reader, options, &mut skipper, 0, None, None, |_, value| {
+            visit(&value)
+        })?;
+    }
+    skipper.finish()?;
+    Ok(skipper.stats)
+}
+
+/// Decode one input, recording skipped lines in `skipper`; `keep` turns each
+/// row and its position into what is collected
+fn decode_with<R: Read, T>(
+    reader: R,
+    options: &DecodeOptions,
+    skipper: &mut Skipper,
+    skip: usize,
+    limit: Option<usize>,
+    tail: Option<usize>,
+    keep: impl FnMut(usize, Value) -> T,
+) -> Result<Vec<T>> {
+    let reader = text_reader(reader, options.encoding)?;
+    let mut peekable = PeekableReader::new(BufReader::new(reader));
+    let peek = peekable.peek(SNIFF_LEN)?;
+    // Fail once rather than warn about every "line" of a binary file
+    if let Some(reason) = binary_reason(&peek) {
+        return Err(JlcatError::BinaryInput(match skipper.input {
+            Some(ref name) => format!("{}: {}", name, reason),
+            None => reason,
+        }));
+    }
+
+    let reader = BufReader::new(peekable);
+    let mut source: Box<dyn RowSource> = match sniff_format(&peek) {
+        Some(InputFormat::JsonArray) => {
+            skipper.stats.elements = true;
+            Box::new(ArrayRows::new(reader).with_dup_keys(options.dup_keys))
+        }
+        Some(InputFormat::JsonLines) | None => {
+            Box::new(LineRows::new(reader).with_dup_keys(options.dup_keys))
+        }
+    };
+    let Some(max) = skipper.bytes_left else {
+        return read_rows(
+            source.as_mut(),
+            options.strict,
+            skipper,
+            skip,
+            limit,
+            tail,
+            keep,
+        );
+    };
+    let rows = read_rows(
+        &mut ByteLimit::new(source.as_mut(), max),
+        options.strict,
+        skipper,
+        skip,
+        limit,
+        tail,
+        keep,
+    )?;
+    let read = source.bytes_read().unwrap_or_default();
+    skipper.bytes_left = Some(max.saturating_sub(read));
+    Ok(rows)
+}
+
+/// Index the object rows of a seekable source instead of loading them, for
+/// inputs over `--max-memory`. Lenient mode and paging apply as in [`decode`];
+/// the result holds the 0-based positions of the rows kept. `visit` sees each
+/// of them, and with `--tail` also the rows it later drops.
+pub fn index_rows(
+    source: &mut dyn RowSource,
+    options: &DecodeOptions,
+    mut visit: impl FnMut(&Value),
+) -> Result<(Vec<usize>, SkipStats)> {
+    let mut skipper = Skipper::new(options)?;
+    let positions = read_rows(
+        source,
+        options.strict,
+        &mut skipper,
+        options.skip,
+        options.limit,
+        options.tail,
+        |position, value| {
+            visit(&value);
+            position - 1
+        },
+    )?;
+    skipper.finish()?;
+    Ok((positions, skipper.stats))
+}
+
+/// Drain object rows from `source`, applying strictness and paging; `keep`
+/// turns each row and its position into what is collected
+fn read_rows<T>(
+    source: &mut dyn RowSource,
+    strict: bool,
+    skipper: &mut Skipper,
+    skip: usize,
+    limit: Option<usize>,
+    tail: Option<usize>,
+    mut keep: impl FnMut(usize, Value) -> T,
+) -> Result<Vec<T>> {
+    if tail == Some(0) || limit == Some(0) {
+        return Ok(Vec::new());
+    }
+
+    let mut rows = Vec::new();
+    let mut tail_buf: Option<(usize, VecDeque<T>)> = tail.map(|n| (n, VecDeque::with_capacity(n)));
+    let mut skipped = 0usize;
+    let elements = skipper.stats.elements;
+
+    while let Some(row) = source.next_row()? {
+        let at = source.position();
+        let (reason, message) = match row {
+            Ok(value) if value.is_object() => {
+                if let Some((count, buf)) = tail_buf.as_mut() {
+                    if buf.len() == *count {
+                        buf.pop_front();
+                    }
+                    buf.push_back(keep(at, value));
+                } else {
+                    if skipped < skip {
+                        skipped += 1;
+                        continue;
+                    }
+                    rows.push(keep(at, value));
+                    if limit.is_some_and(|max| rows.len() >= max) {
+                        break;
+                    }
+                }
+                continue;
+            }
+            Ok(_) => (SkipReason::NonObject, "expected JSON object".to_string()),
+            Err(e) => (SkipReason::InvalidJson, e.to_string()),
+        };
+
+        if strict {
+            // Array errors are reported against the array, which starts on line 1
+            let (line, message) = match (elements, reason) {
+                (true, SkipReason::NonObject) => {
+                    (1, format!("array element {} is not an object", at))
+                }
+                (true, SkipReason::InvalidJson) => {
+                    (1, format!("array element {}: {}", at, message))
+                }
+                (false, SkipReason::NonObject) => {
+                    (at, "expected JSON object, got non-object value".to_string())
+                }
+                (false, SkipReason::InvalidJson) => (at, message),
+            };
+            return Err(JlcatError::JsonParse { line, message });
+        }
+        let text = source.last_text().unwrap_or_default().to_string();
+        if !skipper.skip(reason, at, &text, &message) {
+            break;
+        }
+    }
+
+    skipper.stats.duplicate_keys += source.duplicate_keys();
+    if let Some(err) = skipper.error() {
+        return Err(err);
+    }
+
+    if let Some((_, buf)) = tail_buf {
+        Ok(buf.into_iter().collect())
+    } else {
+        Ok(rows)
+    }
+}
+
+/// A reader that can peek ahead without consuming bytes
+struct PeekableReader<R: Read> {
+    inner: R,
+    buffer: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> PeekableReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&mut self, n: usize) -> io::Result<Vec<u8>> {
+        // One read may stop short, e.g. at the end of a skipped byte order mark
+        if self.buffer.len() < n {
+            let wanted = (n - self.buffer.len()) as u64;
+            (&mut self.inner)
+                .take(wanted)
+                .read_to_end(&mut self.buffer)?;
+        }
+        Ok(self.buffer.clone())
+    }
+}
+
+impl<R: Read> Read for PeekableReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // First drain the buffer
+        if self.pos < self.buffer.len() {
+            let remaining = &self.buffer[self.pos..];
+            let to_copy = std::cmp::min(remaining.len(), buf.len());
+            buf[..to_copy].copy_from_slice(&remaining[..to_copy]);
+            self.pos += to_copy;
+            if to_copy < buf.len() {
+                // Need more from inner
+                let additional = self.inner.read(&mut buf[to_copy..])?;
+                Ok(to_copy + additional)
+            } else {
+                Ok(to_copy)
+            }
+        } else {
+            self.inner.read(buf)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn options(strict: bool) -> DecodeOptions {
+        DecodeOptions {
+            strict,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_index_rows_keeps_positions_of_object_rows() {
+        let input = "{\"id\": 1}\nnot json\n[1]\n{\"id\": 2}\n{\"id\": 3}\n";
+        let mut source = crate::input::IndexedReader::new(io::Cursor::new(input)).unwrap();
+        let lenient = DecodeOptions {
+            quiet: true,
+            skip: 1,
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        let (positions, skipped) =
+            index_rows(&mut source, &lenient, |row| seen.push(row["id"].clone())).unwrap();
+        assert_eq!(positions, vec![3, 4]);
+        assert_eq!(seen, vec![json!(2), json!(3)]);
+        assert_eq!(skipped.invalid_json, 1);
+        assert_eq!(skipped.non_object, 1);
+
+        let mut source = crate::input::IndexedReader::new(io::Cursor::new(input)).unwrap();
+        assert!(index_rows(&mut source, &options(true), |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_decode_inputs_tags_and_pages_across_inputs() {
+        let inputs = vec![
+            (
+                "a.jsonl".to_string(),
+                "{\"id\": 1}\n{\"id\": 2}\n".as_bytes(),
+            ),
+            (
+                "b.json".to_string(),
+                "[{\"id\": 3}, {\"id\": 4}]".as_bytes(),
+            ),
+        ];
+        let opts = DecodeOptions {
+            skip: 1,
+            limit: Some(2),
+            tag_source: Some(vec!["first".to_string()]),
+            ..options(true)
+        };
+        assert_eq!(
+            decode_inputs(inputs, &opts).unwrap().rows,
+            vec![
+                json!({"id": 2, "_source": "first"}),
+                json!({"id": 3, "_source": "b.json"}),
+            ]
+        );
+
+        let inputs = vec![
+            ("a".to_string(), "{\"id\": 1}\n{\"id\": 2}\n".as_bytes()),
+            ("b".to_string(), "{\"id\": 3}\n".as_bytes()),
+        ];
+        let opts = DecodeOptions {
+            tail: Some(2),
+            ..options(true)
+        };
+        assert_eq!(
+            decode_inputs(inputs, &opts).unwrap().rows,
+            vec![json!({"id": 2}), json!({"id": 3})]
+        );
+    }
+
+    #[test]
+    fn test_decode_inputs_numbers_rows_before_paging() {
+        let inputs = vec![
+            ("a".to_string(), "{\"id\": 1}\n{\"id\": 2}\n".as_bytes()),
+            ("b".to_string(), "{\"id\": 3}\n".as_bytes()),
+        ];
+        let opts = DecodeOptions {
+            skip: 1,
+            row_numbers: true,
+            ..options(true)
+        };
+        assert_eq!(
+            decode_inputs(inputs, &opts).unwrap().rows,
+            vec![json!({"id": 2, "#": 2}), json!({"id": 3, "#": 1})]
+        );
+    }
+
+    #[test]
+    fn test_decode_inputs_limit_bytes_spans_inputs() {
+        let inputs = vec![
+            ("a".to_string(), "{\"id\": 1}\n{\"id\": 2}\n".as_bytes()),
+            ("b".to_string(), "{\"id\": 3}\n{\"id\": 4}\n".as_bytes()),
+        ];
+        // 20 bytes of "a", then the first row of "b" crosses the remaining 5
+        let opts = DecodeOptions {
+            limit_bytes: Some(25),
+            ..options(true)
+        };
+        assert_eq!(
+            decode_inputs(inputs, &opts).unwrap().rows,
+            vec![json!({"id": 1}), json!({"id": 2}), json!({"id": 3})]
+        );
+    }
+
+    #[test]
+    fn test_decode_jsonl() {
+        let input = "{\"id\": 1}\n\n{\"id\": 2}\n";
+        let rows = decode(input.as_bytes(), &options(true)).unwrap().rows;
+        assert_eq!(rows, vec![json!({"id": 1}), json!({"id": 2})]);
+    }
+
+    #[test]
+    fn test_decode_skips_byte_order_mark() {
+        let input = "\u{feff}[{\"id\": 1}]";
+        let rows = decode(input.as_bytes(), &options(true)).unwrap().rows;
+        assert_eq!(rows, vec![json!({"id": 1})]);
+
+        let utf16: Vec<u8> = "\u{feff}{\"id\": 2}\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let rows = decode(&utf16[..], &options(true)).unwrap().rows;
+        assert_eq!(rows, vec![json!({"id": 2})]);
+    }
+
+    #[test]
+    fn test_decode_json_array() {
+        let input = r#"[{"id": 1}, {"id": 2}, {"id": 3}]"#;
+        let opts = DecodeOptions {
+            strict: true,
+            max_errors: None,
+            rejects: None,
+            quiet: false,
+            skip: 1,
+            limit: Some(1),
+            tail: None,
+            limit_bytes: None,
+            tag_source: None,
+            row_numbers: false,
+            encoding: None,
+            dup_keys: DupKeys::Last,
+        };
+        assert_eq!(
+            decode(input.as_bytes(), &opts).unwrap().rows,
+            vec![json!({"id": 2})]
+        );
+    }
+
+    #[test]
+    fn test_decode_tail() {
+        let input = "{\"id\": 1}\n{\"id\": 2}\n{\"id\": 3}\n";
+        let opts = DecodeOptions {
+            tail: Some(2),
+            ..options(true)
+        };
+        assert_eq!(
+            decode(input.as_bytes(), &opts).unwrap().rows,
+            vec![json!({"id": 2}), json!({"id": 3})]
+        );
+    }
+
+    #[test]
+    fn test_decode_strict_and_lenient() {
+        let input = "{\"id\": 1}\nnot json\n";
+        assert!(decode(input.as_bytes(), &options(true)).is_err());
+        assert_eq!(
+            decode(input.as_bytes(), &options(false)).unwrap().rows,
+            vec![json!({"id": 1})]
+        );
+    }
+
+    #[test]
+    fn test_decode_duplicate_keys() {
+        let input = "{\"id\": 1, \"id\": 2}\n{\"id\": 3}\n";
+        for (policy, id) in [(DupKeys::First, 1), (DupKeys::Last, 2)] {
+            let opts = DecodeOptions {
+                dup_keys: policy,
+                ..options(true)
+            };
+            let decoded = decode(input.as_bytes(), &opts).unwrap();
+            assert_eq!(decoded.rows[0], json!({"id": id}));
+            assert_eq!(decoded.skipped.duplicate_keys, 1);
+            assert!(decoded
+                .skipped
+                .duplicate_summary(policy)
+                .unwrap()
+                .starts_with("1 duplicate key in objects"));
+        }
+
+        let opts = DecodeOptions {
+            dup_keys: DupKeys::Error,
+            ..options(true)
+        };
+        let err = decode(input.as_bytes(), &opts).unwrap_err();
+        assert!(err.to_string().contains("duplicate key `id`"), "{}", err);
+
+        let opts = DecodeOptions {
+            strict: false,
+            quiet: true,
+            ..opts
+        };
+        let decoded = decode(input.as_bytes(), &opts).unwrap();
+        assert_eq!(decoded.rows, vec![json!({"id": 3})]);
+        assert_eq!(decoded.skipped.invalid_json, 1);
+    }
+
+    #[test]
+    fn test_scan_inputs_counts_every_row() {
+        let inputs = vec![
+            ("a".to_string(), "{\"id\": 1}\nnot json\n".as_bytes()),
+            (
+                "b".to_string(),
+                "{\"id\": 2}\n[2]\n{\"id\": 3}\n".as_bytes(),
+            ),
+        ];
+        let opts = DecodeOptions {
+            quiet: true,
+            limit: Some(1),
+            ..options(false)
+        };
+        let mut ids = Vec::new();
+        let skipped = scan_inputs(inputs, &opts, |row| ids.push(row["id"].clone())).unwrap();
+        assert_eq!(ids, vec![json!(1), json!(2), json!(3)]);
+        assert_eq!(skipped.invalid_json, 1);
+        assert_eq!(skipped.non_object, 1);
+    }
+
+    #[test]
+    fn test_decode_rejects_binary_input() {
+        let input: &[u8] = b"\x1f\x8b\x08\0\0\0\0\0\0\x03{\"id\": 1}\n";
+        for strict in [true, false] {
+            let err = decode(input, &options(strict)).unwrap_err();
+            assert!(matches!(err, JlcatError::BinaryInput(_)), "{}", err);
+        }
+
+        let inputs = vec![("ok.jsonl", &b"{\"id\": 1}\n"[..]), ("logs.gz", input)];
+        let inputs = inputs
+            .into_iter()
+            .map(|(n, r)| (n.to_string(), r))
+            .collect();
+        let err = decode_inputs(inputs, &options(false)).unwrap_err();
+        assert!(err.to_string().contains("logs.gz: NUL byte at offset 3"));
+    }
+
+    #[test]
+    fn test_decode_max_errors() {
+        let input = "{\"id\": 1}\nnot json\n42\n{\"id\": 2}\n";
+        let opts = DecodeOptions {
+            max_errors: Some(2),
+            ..options(false)
+        };
+        let decoded = decode(input.as_bytes(), &opts).unwrap();
+        assert_eq!(decoded.rows.len(), 2);
+        assert_eq!(
+            decoded.skipped.summary(),
+            "2 lines skipped (1 invalid JSON, 1 non-object)"
+        );
+
+        let opts = DecodeOptions {
+            max_errors: Some(1),
+            ..options(false)
+        };
+        let err = decode(input.as_bytes(), &opts).unwrap_err();
+        assert!(matches!(
+            err,
+            JlcatError::TooManyErrors {
+                max: 1,
+                line: 3,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("1 invalid JSON, 1 non-object"));
+    }
+
+    #[test]
+    fn test_decode_max_errors_json_array() {
+        let opts = DecodeOptions {
+            max_errors: Some(0),
+            ..options(false)
+        };
+        let err = decode(r#"[{"id": 1}, 2]"#.as_bytes(), &opts).unwrap_err();
+        assert!(matches!(err, JlcatError::TooManyErrors { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_decode_json_array_errors() {
+        let input = r#"[{"id": 1}, {bad}, 3, {"id": 4}]"#;
+        let err = decode(input.as_bytes(), &options(true)).unwrap_err();
+        assert!(err.to_string().contains("array element 2"));
+
+        let decoded = decode(input.as_bytes(), &options(false)).unwrap();
+        assert_eq!(decoded.rows, vec![json!({"id": 1}), json!({"id": 4})]);
+        assert_eq!(
+            decoded.skipped.summary(),
+            "2 array elements skipped (1 invalid JSON, 1 non-object)"
+        );
+
+        // A broken array is an error even in lenient mode
+        assert!(decode(r#"[{"id": 1}, "#.as_bytes(), &options(false)).is_err());
+    }
+
+    #[test]
+    fn test_skip_stats_display() {
+        let stats = SkipStats {
+            invalid_json: 10,
+            non_object: 3,
+            elements: false,
+            duplicate_keys: 0,
+        };
+        assert_eq!(stats.to_string(), "10 invalid JSON, 3 non-object");
+        assert_eq!(stats.total(), 13);
+        assert_eq!(
+            stats.summary(),
+            "13 lines skipped (10 invalid JSON, 3 non-object)"
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_file() {
+        let rejects = tempfile::NamedTempFile::new().unwrap();
+        let opts = DecodeOptions {
+            rejects: Some(rejects.path().to_path_buf()),
+            ..options(false)
+        };
+        let input = "{\"id\": 1}\nnot json\n[1]\n";
+        assert_eq!(decode(input.as_bytes(), &opts).unwrap().rows.len(), 1);
+
+        let written = std::fs::read_to_string(rejects.path()).unwrap();
+        let records: Vec<Value> = written
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["line"], json!(2));
+        assert_eq!(records[0]["text"], json!("not json"));
+        assert!(records[0]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("expected"));
+        assert_eq!(
+            records[1],
+            json!({"line": 3, "error": "expected JSON object", "text": "[1]"})
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_json_array_elements() {
+        let rejects = tempfile::NamedTempFile::new().unwrap();
+        let opts = DecodeOptions {
+            rejects: Some(rejects.path().to_path_buf()),
+            ..options(false)
+        };
+        let decoded = decode(r#"[{"id": 1}, "x"]"#.as_bytes(), &opts).unwrap();
+        assert_eq!(
+            decoded.skipped.summary(),
+            "1 array element skipped (1 non-object)"
+        );
+
+        let written = std::fs::read_to_string(rejects.path()).unwrap();
+        let record: Value = serde_json::from_str(written.trim()).unwrap();
+        assert_eq!(
+            record,
+            json!({"element": 2, "error": "expected JSON object", "text": "\"x\""})
+        );
+    }
+}
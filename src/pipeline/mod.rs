@@ -0,0 +1,26 @@
+//! Row processing pipeline: source → decode → transform → filter → sort → project → sink.
+//!
+//! [`Plan::from_cli`] turns command-line options into an ordered set of stages and
+//! [`Plan::run`] executes them. Each stage works on the whole row set, so stages
+//! compose predictably and can be tested without the binary.
+
+mod check;
+mod decode;
+mod plan;
+mod progress;
+mod project;
+mod sink;
+mod source;
+mod spill;
+mod stage;
+
+// decode, Projection and render_cat serve the library's TableBuilder,
+// which the binary does not build
+#[allow(unused_imports)]
+pub use decode::{decode, DecodeOptions};
+pub use plan::Plan;
+#[allow(unused_imports)]
+pub use project::{Projection, View};
+#[allow(unused_imports)]
+pub use sink::render_cat;
+pub use sink::write_stdout;
@@ -0,0 +1,899 @@
+use super::check::CheckReport;
+use super::decode::{
+    decode, decode_inputs, index_rows, scan_inputs, DecodeOptions, SkipStats, ROW_NUMBER_COLUMN,
+};
+use super::progress::Progress;
+use super::project::{Projection, View};
+use super::sink::Sink;
+use super::source::Source;
+use super::spill;
+use super::stage::{Reverse, Shuffle, Stage};
+use crate::cli::{
+    Cli, DupKeysMode, ElideMode, FlatArraysMode, HeaderCaseMode, KeepMode, Limit, NestedMode,
+};
+use crate::color;
+use crate::config::{load_header_map, Config};
+use crate::core::{
+    ArrayExpansion, ChildRows, ColumnSelector, CommandTransform, DisplayZone, DistinctOn, Elision,
+    Explode, ExternalSort, FilterExpr, FlatConfig, FormatRule, FullTextSearch, HeaderCase, Keep,
+    LevelFilter, NestedExtractor, RowRanges, Schema, SchemaInferrer, Select, Sorter, TimeFormat,
+    ValueShape,
+};
+use crate::error::{JlcatError, Result};
+use crate::input::{
+    binary_reason, bom_encoding, estimate_rows, sniff_format, CachedReader, DupKeys, IndexCache,
+    IndexedReader, InputFormat, RowEstimate, SpilledRows, SpooledInput, SNIFF_LEN,
+};
+use crate::render::{CatRenderer, VerticalRenderer};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+/// An ordered set of stages from input to output
+pub struct Plan {
+    source: Source,
+    decode: DecodeOptions,
+    stages: Vec<Box<dyn Stage>>,
+    projection: Projection,
+    sink: Sink,
+    /// Bytes of parsed rows to hold in memory before reading them from disk
+    /// instead (`--max-memory`)
+    max_memory: Option<u64>,
+    /// Keep the row index of a spilled file in a sidecar (`--index-cache`)
+    index_cache: bool,
+}
+
+impl Plan {
+    pub fn new(source: Source, decode: DecodeOptions, projection: Projection, sink: Sink) -> Self {
+        Self {
+            source,
+            decode,
+            stages: Vec::new(),
+            projection,
+            sink,
+            max_memory: None,
+            index_cache: false,
+        }
+    }
+
+    /// Limit the memory taken by loaded rows
+    pub fn with_max_memory(mut self, budget: Option<u64>) -> Self {
+        self.max_memory = budget;
+        self
+    }
+
+    /// Save and reuse the row index of a file read from disk
+    pub fn with_index_cache(mut self, index_cache: bool) -> Self {
+        self.index_cache = index_cache;
+        self
+    }
+
+    /// Add a stage, placed after every stage of the same or an earlier kind
+    pub fn with_stage(mut self, stage: Box<dyn Stage>) -> Self {
+        let pos = self
+            .stages
+            .iter()
+            .position(|s| s.kind() > stage.kind())
+            .unwrap_or(self.stages.len());
+        self.stages.insert(pos, stage);
+        self
+    }
+
+    /// Names of the stages in execution order
+    #[cfg(test)]
+    fn stage_names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    /// Run every stage over the rows
+    pub fn process(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        self.stages
+            .iter_mut()
+            .try_fold(rows, |rows, stage| stage.run(rows))
+    }
+
+    /// Build a plan from command-line options and the config file
+    pub fn from_cli(cli: &Cli, config: &Config) -> Result<Self> {
+        let mut time_format = TimeFormat::parse(&cli.date_format)?
+            .with_epoch_units(&cli.epoch_unit)?
+            .with_relative_columns(&cli.relative_time);
+        if let Some(ref tz) = cli.tz {
+            time_format = time_format.with_zone(DisplayZone::parse(tz)?);
+        }
+        let header_map = match cli.header_map {
+            Some(ref path) => load_header_map(path)?,
+            None => HashMap::new(),
+        };
+        // Rows first, so cell rules win where both apply
+        let highlights = cli
+            .highlight_row
+            .iter()
+            .map(|rule| FormatRule::parse_highlight(rule, true))
+            .chain(
+                cli.highlight
+                    .iter()
+                    .map(|rule| FormatRule::parse_highlight(rule, false)),
+            )
+            .collect::<Result<Vec<_>>>()?;
+        let formatter = config
+            .formatter()?
+            .with_rules(highlights)
+            .with_time_format(time_format)
+            .with_max_width(cli.max_col_width)
+            .with_widths(cli.widths.clone().unwrap_or_default())
+            .with_header_map(header_map.clone())
+            .with_elision(match cli.elide {
+                Some(ElideMode::Middle) => Elision::Middle,
+                Some(ElideMode::End) | None => Elision::End,
+            })
+            .with_inline_nested(match cli.nested {
+                Some(NestedMode::Inline(width)) => Some(width),
+                Some(NestedMode::Placeholder) | None => None,
+            })
+            .with_header_case(cli.header_case.map(|case| match case {
+                HeaderCaseMode::Upper => HeaderCase::Upper,
+                HeaderCaseMode::Lower => HeaderCase::Lower,
+                HeaderCaseMode::Title => HeaderCase::Title,
+            }))
+            .with_raw_control(cli.raw_control)
+            .with_show_types(cli.show_types)
+            .with_null_label(cli.null_label.clone())
+            .with_bool_labels(
+                cli.bool_labels
+                    .clone()
+                    .map(|labels| (labels.true_label, labels.false_label)),
+            );
+        let keymap = config.keymap()?;
+
+        let source = match cli.files.as_slice() {
+            [] => Source::Stdin,
+            [path] => Source::File(path.clone()),
+            paths => Source::Files(paths.to_vec()),
+        };
+        // A percentage limit becomes a byte budget over the input files
+        let limit_bytes = match cli.limit {
+            Some(Limit::Percent(percent)) => {
+                let size = source.size().ok_or_else(|| {
+                    JlcatError::InvalidRowRange(
+                        "--limit N% needs input files, not stdin; use --limit-bytes".to_string(),
+                    )
+                })?;
+                let share = (size as f64 * percent / 100.0).ceil() as u64;
+                Some(cli.limit_bytes.map_or(share, |max| max.min(share)))
+            }
+            _ => cli.limit_bytes,
+        };
+        let decode = DecodeOptions {
+            strict: cli.is_strict(),
+            max_errors: cli.max_errors,
+            rejects: cli.rejects.clone(),
+            quiet: cli.quiet,
+            skip: cli.skip.unwrap_or(0),
+            limit: match cli.limit {
+                Some(Limit::Rows(n)) => Some(n),
+                _ => None,
+            },
+            tail: cli.tail,
+            limit_bytes,
+            tag_source: cli.tag_source.then(|| cli.source_label.clone()),
+            row_numbers: cli.row_numbers,
+            encoding: cli.encoding,
+            dup_keys: match cli.dup_keys {
+                Some(DupKeysMode::First) => DupKeys::First,
+                Some(DupKeysMode::Last) | None => DupKeys::Last,
+                Some(DupKeysMode::Error) => DupKeys::Error,
+            },
+        };
+
+        let selector = match cli.columns {
+            // Row numbers stay in front of the selected columns
+            Some(ref cols) if cli.row_numbers => {
+                let cols = std::iter::once(ROW_NUMBER_COLUMN.to_string())
+                    .chain(cols.iter().cloned())
+                    .collect();
+                Some(ColumnSelector::new(cols)?)
+            }
+            Some(ref cols) => Some(ColumnSelector::new(cols.clone())?),
+            None => None,
+        };
+        let view = if cli.is_flat() {
+            View::Flat(
+                FlatConfig::new(cli.flat_depth(), cli.array_limit).with_arrays(
+                    match cli.flat_arrays {
+                        Some(FlatArraysMode::Index) => ArrayExpansion::Index,
+                        Some(FlatArraysMode::Join) | None => ArrayExpansion::Join,
+                    },
+                ),
+            )
+        } else if cli.recursive {
+            View::Recursive {
+                summary: cli.children_summary,
+                child_rows: ChildRows {
+                    skip: cli.child_skip.unwrap_or(0),
+                    limit: cli.child_limit.as_ref().and_then(|limit| limit.rows),
+                    table_limits: cli
+                        .child_limit
+                        .iter()
+                        .flat_map(|limit| limit.tables.iter().cloned())
+                        .collect(),
+                },
+                extractor: NestedExtractor::default()
+                    .with_parent_key(cli.parent_key.clone())
+                    .with_children(cli.children.clone()),
+            }
+        } else {
+            View::Normal
+        };
+        let projection = Projection {
+            selector,
+            view,
+            auto_layout: cli.auto_layout,
+            array_limit: cli.array_limit,
+        };
+
+        #[cfg(feature = "wasm")]
+        let plugin = match cli.plugin {
+            Some(ref path) => Some(std::rc::Rc::new(std::cell::RefCell::new(
+                crate::plugin::WasmPlugin::from_file(path)?,
+            ))),
+            None => None,
+        };
+
+        // Plugin renderer replaces the built-in table output
+        #[cfg(feature = "wasm")]
+        let plugin_sink = plugin
+            .as_ref()
+            .filter(|p| p.borrow().has_render() && !cli.interactive)
+            .map(|p| Sink::Plugin(p.clone()));
+        #[cfg(not(feature = "wasm"))]
+        let plugin_sink = None;
+
+        let sink = match plugin_sink {
+            Some(sink) => sink,
+            None if cli.interactive => Sink::Tui {
+                formatter: Box::new(formatter),
+                keymap,
+                edit_path: None,
+            },
+            #[cfg(feature = "sql")]
+            None if cli.to_sqlite.is_some() => {
+                let (path, table) =
+                    crate::sql::parse_target(cli.to_sqlite.as_deref().unwrap_or_default());
+                Sink::Sqlite {
+                    path,
+                    table: table.unwrap_or_else(|| "t".to_string()),
+                }
+            }
+            None if cli.out_dir.is_some() => Sink::Export {
+                dir: cli.out_dir.clone().unwrap_or_default(),
+                format: cli.output.unwrap_or_default(),
+                compact: cli.compact,
+                headers: header_map,
+            },
+            None if cli.output.is_some() => Sink::Serialize {
+                format: cli.output.unwrap_or_default(),
+                compact: cli.compact,
+                headers: header_map,
+            },
+            None if cli.pretty => Sink::Pretty {
+                color: color::stdout_enabled(),
+            },
+            None if cli.vertical => Sink::Cat {
+                renderer: Box::new(VerticalRenderer::new().with_formatter(formatter)),
+                hints: cli.hints,
+            },
+            None => Sink::Cat {
+                renderer: Box::new(
+                    CatRenderer::new(cli.style.clone())
+                        .with_formatter(formatter)
+                        .with_wrap(cli.wrap)
+                        .with_transpose(cli.transpose)
+                        .with_header(!cli.no_header)
+                        .with_terminal_width(terminal_width())
+                        .with_color(color::stdout_enabled()),
+                ),
+                hints: cli.hints,
+            },
+        };
+
+        let mut plan = Plan::new(source, decode, projection, sink)
+            .with_max_memory(cli.max_memory)
+            .with_index_cache(cli.index_cache);
+
+        // Positions refer to the rows as read, so this runs first
+        if let Some(ref spec) = cli.rows {
+            plan = plan.with_stage(Box::new(RowRanges::parse(spec)?));
+        }
+
+        if let Some(ref cmd) = cli.transform_cmd {
+            plan = plan.with_stage(Box::new(CommandTransform::new(
+                cmd.as_str(),
+                cli.transform_batch,
+            )));
+        }
+
+        #[cfg(feature = "wasm")]
+        if let Some(p) = plugin.filter(|p| p.borrow().has_transform()) {
+            plan = plan.with_stage(Box::new(super::stage::PluginTransform(p)));
+        }
+
+        #[cfg(feature = "script")]
+        match (&cli.script, &cli.eval) {
+            (Some(path), _) => {
+                plan = plan.with_stage(Box::new(crate::script::RowScript::from_file(path)?));
+            }
+            (None, Some(source)) => {
+                plan = plan.with_stage(Box::new(crate::script::RowScript::compile(source)?));
+            }
+            (None, None) => {}
+        }
+
+        #[cfg(feature = "sql")]
+        if let Some(ref query) = cli.sql {
+            plan = plan.with_stage(Box::new(crate::sql::SqlQuery::new(query)));
+        }
+
+        if let Some(ref expr) = cli.select {
+            plan = plan.with_stage(Box::new(Select::parse(expr)?));
+        }
+
+        for path in &cli.explode {
+            plan = plan.with_stage(Box::new(Explode::parse(path)?));
+        }
+
+        if let Some(ref spec) = cli.level {
+            let mut filter = LevelFilter::parse(spec)?;
+            if let Some(ref field) = cli.level_field {
+                filter = filter.with_field(field)?;
+            }
+            plan = plan.with_stage(Box::new(filter));
+        }
+
+        for expr in &cli.filter {
+            plan = plan.with_stage(Box::new(FilterExpr::parse(expr)?.whole_word(cli.word)));
+        }
+
+        if let Some(ref text) = cli.grep {
+            plan = plan.with_stage(Box::new(FullTextSearch::new(text).whole_word(cli.word)));
+        }
+
+        if let Some(ref sort_keys) = cli.sort {
+            plan = plan.with_stage(Box::new(Sorter::parse(sort_keys)?));
+        }
+
+        if cli.reverse {
+            plan = plan.with_stage(Box::new(Reverse));
+        }
+
+        if cli.shuffle {
+            plan = plan.with_stage(Box::new(Shuffle::new(cli.seed)));
+        }
+
+        if let Some(ref columns) = cli.distinct_on {
+            let keep = match cli.keep {
+                Some(KeepMode::Last) => Keep::Last,
+                Some(KeepMode::First) | None => Keep::First,
+            };
+            plan = plan.with_stage(Box::new(DistinctOn::new(columns, keep)?));
+        }
+
+        Ok(plan)
+    }
+
+    /// Estimate how many rows the source holds
+    pub fn estimate(&self) -> Result<RowEstimate> {
+        self.source.estimate()
+    }
+
+    /// The estimate behind sizing decisions: sampled from the files or the
+    /// spooled stdin, never by reading stdin itself. None when the input
+    /// can't be sampled, e.g. a named pipe.
+    fn sample_estimate(&self, spool: Option<&SpooledInput>) -> Option<RowEstimate> {
+        match spool {
+            Some(spool) => estimate_rows(&mut File::open(spool.path()).ok()?).ok(),
+            None if matches!(self.source, Source::Stdin) => None,
+            None => self.estimate().ok(),
+        }
+    }
+
+    /// Read the whole input without showing it (`--check`): count valid and
+    /// invalid rows and infer the columns and their value types. Invalid lines
+    /// are counted whatever the strictness.
+    pub fn check(&self) -> Result<CheckReport> {
+        let lenient = DecodeOptions {
+            strict: false,
+            ..self.decode.clone()
+        };
+        let mut report = CheckReport::new();
+        report.skipped = self.scan(&lenient, |row| report.add(row))?;
+        Ok(report)
+    }
+
+    /// Draft JSON Schema describing every row of the input, nested values
+    /// included (`--infer-schema`)
+    pub fn infer_schema(&self) -> Result<Value> {
+        let mut shape = ValueShape::new();
+        let skipped = self.scan(&self.decode, |row| shape.add(row))?;
+        if skipped.total() > 0 {
+            color::note(&skipped.summary());
+        }
+        Ok(shape.to_json_schema())
+    }
+
+    /// Read every row of every input without keeping them
+    fn scan(&self, options: &DecodeOptions, visit: impl FnMut(&Value)) -> Result<SkipStats> {
+        let expected = self.sample_estimate(None).map(|e| e.rows);
+        let progress =
+            Progress::new(self.source.size(), options.quiet).with_expected_lines(expected);
+        let inputs = self
+            .source
+            .open_each()?
+            .into_iter()
+            .map(|(name, reader)| (name, progress.wrap(reader)))
+            .collect();
+        let skipped = scan_inputs(inputs, options, visit)?;
+        progress.finish();
+        Ok(skipped)
+    }
+
+    /// Read, process and render the rows
+    pub fn run(mut self) -> Result<()> {
+        // Stdin is copied to disk when a budget needs its size
+        let spool = match (self.max_memory, &self.source) {
+            (Some(_), Source::Stdin) => Some(SpooledInput::from_reader(self.source.open()?)?),
+            _ => None,
+        };
+        let estimate = self.sample_estimate(spool.as_ref());
+        if let Some(estimate) = estimate {
+            if estimate.is_large() && atty::is(atty::Stream::Stdout) && self.prints_everything() {
+                color::warn(&format!(
+                    "input has about {} rows; consider --limit, --tail or -i",
+                    estimate.rows
+                ));
+            }
+        }
+
+        let size = match spool {
+            Some(ref spool) => Some(std::fs::metadata(spool.path())?.len()),
+            None => self.source.size(),
+        };
+        // Only the first --limit-bytes of the input are loaded
+        let loaded = size.map(|size| self.decode.limit_bytes.map_or(size, |max| size.min(max)));
+        let rows = loaded.and_then(|loaded| estimate.map(|e| e.rows_within(loaded)));
+        if let (Some(budget), Some(size)) = (self.max_memory, loaded) {
+            let rows = rows.unwrap_or(0);
+            if spill::footprint(size, rows) > budget {
+                return self.run_spilled(spool, size, rows, budget);
+            }
+        }
+
+        let progress = Progress::new(size, self.decode.quiet).with_expected_lines(rows);
+        let mut inputs = match spool {
+            Some(spool) => vec![(
+                "stdin".to_string(),
+                Box::new(spool.into_reader()?) as Box<dyn Read>,
+            )],
+            None => self.source.open_each()?,
+        };
+        let decoded = if self.decode.tag_source.is_some()
+            || self.decode.row_numbers
+            || matches!(self.source, Source::Files(_))
+        {
+            let inputs = inputs
+                .into_iter()
+                .map(|(name, reader)| (name, progress.wrap(reader)))
+                .collect();
+            decode_inputs(inputs, &self.decode)?
+        } else {
+            let (_, reader) = inputs.remove(0);
+            decode(progress.wrap(reader), &self.decode)?
+        };
+        progress.finish();
+        let rows = self.process(decoded.rows)?;
+
+        if !rows.is_empty() {
+            let width = terminal_width();
+            let projection =
+                self.projection
+                    .resolve(&rows, width, self.sink.supports_child_tables());
+
+            let target = edit_target(
+                &self.source,
+                &self.stages,
+                &self.decode,
+                &projection,
+                &decoded.skipped,
+            );
+            if let Some(path) = target {
+                self.sink.allow_edits(path);
+            }
+            self.sink.write(rows, projection)?;
+        }
+
+        // Per-line warnings scroll away; repeat the totals after the output
+        if decoded.skipped.total() > 0 {
+            color::note(&decoded.skipped.summary());
+        }
+        if let Some(note) = decoded.skipped.duplicate_summary(self.decode.dup_keys) {
+            color::warn(&note);
+        }
+        Ok(())
+    }
+
+    /// Show an input over `--max-memory` without loading it: its rows are
+    /// indexed by byte offset and read back from disk as they are shown
+    fn run_spilled(
+        self,
+        spool: Option<SpooledInput>,
+        size: u64,
+        rows: u64,
+        budget: u64,
+    ) -> Result<()> {
+        let over_budget = |reason: &str| {
+            JlcatError::MemoryLimit(format!(
+                "input needs about {} in memory, over --max-memory {}, and {} needs every row loaded",
+                spill::format_size(spill::footprint(size, rows)),
+                spill::format_size(budget),
+                reason
+            ))
+        };
+        if let Some(reason) = self.spill_blocker() {
+            return Err(over_budget(&reason));
+        }
+
+        // Several files are read back as one
+        let spool = match (spool, &self.source) {
+            (None, Source::Files(_)) => Some(SpooledInput::from_readers(
+                self.source
+                    .open_each()?
+                    .into_iter()
+                    .map(|(_, reader)| reader),
+            )?),
+            (spool, _) => spool,
+        };
+        let mut file = match (&spool, &self.source) {
+            (Some(spool), _) => File::open(spool.path())?,
+            (None, Source::File(path)) => File::open(path)?,
+            (None, _) => unreachable!("stdin and multiple files are spooled"),
+        };
+        let mut head = Vec::new();
+        (&mut file).take(SNIFF_LEN as u64).read_to_end(&mut head)?;
+        // Rows are read back by byte offset, so the file must be plain UTF-8
+        if bom_encoding(&head).is_some() {
+            return Err(over_budget("input with a byte order mark"));
+        }
+        if let Some(reason) = binary_reason(&head) {
+            return Err(JlcatError::BinaryInput(reason));
+        }
+        if sniff_format(&head) == Some(InputFormat::JsonArray) {
+            return Err(over_budget("JSON array input"));
+        }
+
+        // A saved index skips the offset scan, and also the row scan when it
+        // recorded the columns and no paging applies
+        let cache_path = match self.source {
+            Source::File(ref path) if self.index_cache => Some(path.as_path()),
+            _ => None,
+        };
+        let cache = cache_path.and_then(IndexCache::load);
+        let save_cache = cache_path.filter(|_| cache.is_none());
+        let paged =
+            self.decode.skip > 0 || self.decode.limit.is_some() || self.decode.tail.is_some();
+        let (mut indexed, known_columns) = match cache {
+            Some(cache) => (
+                IndexedReader::with_offsets(file, cache.offsets)
+                    .with_dup_keys(self.decode.dup_keys),
+                cache.columns.filter(|_| !paged),
+            ),
+            None => (
+                IndexedReader::new(file)?.with_dup_keys(self.decode.dup_keys),
+                None,
+            ),
+        };
+        let lines = indexed.row_count();
+        let (positions, skipped, all_columns) = match known_columns {
+            Some(columns) => ((0..lines).collect(), SkipStats::default(), columns),
+            None => {
+                let mut schema = Schema::new();
+                let (positions, skipped) = index_rows(&mut indexed, &self.decode, |row| {
+                    SchemaInferrer::infer_streaming(row, &mut schema)
+                })?;
+                (positions, skipped, schema.columns().to_vec())
+            }
+        };
+        if let Some(path) = save_cache {
+            let cache = IndexCache {
+                offsets: indexed.offsets().to_vec(),
+                columns: (!paged && skipped.total() == 0).then(|| all_columns.clone()),
+            };
+            if let Err(e) = cache.save(path) {
+                color::warn(&format!("could not save the index cache: {}", e));
+            }
+        }
+        let columns = match self.projection.selector {
+            Some(ref selector) => selector.columns().iter().map(|c| c.to_string()).collect(),
+            None => all_columns,
+        };
+        let chunk = spill::chunk_rows(budget, size, lines);
+
+        // A sort runs on disk, in runs of `chunk` rows, into a new temp file
+        let (indexed, positions, spool) = match self.stages.first().and_then(|s| s.sorter()) {
+            Some(sorter) => {
+                let mut sort = ExternalSort::new(sorter.clone(), chunk);
+                for &position in &positions {
+                    if let Some(row) = indexed.get_row(position)? {
+                        sort.push(row)?;
+                    }
+                }
+                let mut sorted = NamedTempFile::new()?;
+                let count = sort.finish(sorted.as_file_mut())?;
+                let sorted = SpooledInput::from_temp_file(sorted);
+                let indexed = IndexedReader::from_path(sorted.path())?;
+                (indexed, (0..count).collect(), Some(sorted))
+            }
+            None => (indexed, positions, spool),
+        };
+
+        let rows = SpilledRows::new(
+            CachedReader::from_indexed(indexed, spill::CACHED_ROWS),
+            positions,
+            spool,
+        );
+        self.sink
+            .write_spilled(rows, columns, self.projection.selector, chunk)?;
+
+        if skipped.total() > 0 {
+            color::note(&skipped.summary());
+        }
+        if let Some(note) = skipped.duplicate_summary(self.decode.dup_keys) {
+            color::warn(&note);
+        }
+        Ok(())
+    }
+
+    /// What needs every row in memory, ruling out `--max-memory` spilling;
+    /// a sort on its own can run on disk
+    fn spill_blocker(&self) -> Option<String> {
+        if let Some(stage) = self.stages.iter().find(|s| s.sorter().is_none()) {
+            return Some(format!("--{}", stage.name()));
+        }
+        let option = if self.decode.tag_source.is_some() {
+            "--tag-source"
+        } else if self.decode.limit_bytes.is_some() {
+            "--limit-bytes"
+        } else if self
+            .decode
+            .encoding
+            .is_some_and(|e| e != encoding_rs::UTF_8)
+        {
+            "--encoding"
+        } else if self.decode.row_numbers {
+            "--row-numbers"
+        } else if self.projection.auto_layout {
+            "--auto-layout"
+        } else if matches!(self.projection.view, View::Flat(_)) {
+            "--flat"
+        } else if matches!(self.projection.view, View::Recursive { .. }) {
+            "--recursive"
+        } else if !self.sink.supports_spill() {
+            "this output mode"
+        } else {
+            return None;
+        };
+        Some(option.to_string())
+    }
+
+    /// Whether every row of a seekable file ends up in plain table output
+    fn prints_everything(&self) -> bool {
+        matches!(self.source, Source::File(_) | Source::Files(_))
+            && matches!(
+                self.sink,
+                Sink::Cat { .. } | Sink::Serialize { .. } | Sink::Pretty { .. }
+            )
+            && self.decode.limit.is_none()
+            && self.decode.limit_bytes.is_none()
+            && self.decode.tail.is_none()
+    }
+}
+
+/// The file the TUI may save cell edits to: a single UTF-8 JSONL file, read
+/// without transcoding, whose rows are all shown, in file order, with their
+/// own columns. Takes the plan's
+/// parts rather than the plan, whose projection is consumed by then.
+fn edit_target(
+    source: &Source,
+    stages: &[Box<dyn Stage>],
+    decode: &DecodeOptions,
+    projection: &Projection,
+    skipped: &SkipStats,
+) -> Option<PathBuf> {
+    let Source::File(ref path) = source else {
+        return None;
+    };
+    let row_for_row = stages.is_empty()
+        && decode.skip == 0
+        && decode.limit.is_none()
+        && decode.limit_bytes.is_none()
+        && decode.tail.is_none()
+        && decode.tag_source.is_none()
+        && !decode.row_numbers
+        && skipped.total() == 0
+        && skipped.duplicate_keys == 0
+        && !skipped.elements
+        && decode.encoding.is_none()
+        && projection.selector.is_none()
+        && matches!(projection.view, View::Normal)
+        && !has_bom(path);
+    row_for_row.then(|| path.clone())
+}
+
+/// Whether the file starts with a byte order mark, which decoding drops and
+/// a write back would not; unreadable files count as marked
+fn has_bom(path: &Path) -> bool {
+    let mut head = Vec::new();
+    match File::open(path).and_then(|file| file.take(3).read_to_end(&mut head)) {
+        Ok(_) => bom_encoding(&head).is_some(),
+        Err(_) => true,
+    }
+}
+
+/// Width of the terminal stdout is attached to, if any
+fn terminal_width() -> Option<usize> {
+    if atty::is(atty::Stream::Stdout) {
+        crossterm::terminal::size().ok().map(|(w, _)| w as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::TableStyle;
+    use serde_json::json;
+
+    fn plan() -> Plan {
+        let projection = Projection {
+            selector: None,
+            view: View::Normal,
+            auto_layout: false,
+            array_limit: 3,
+        };
+        let sink = Sink::Cat {
+            renderer: Box::new(CatRenderer::new(TableStyle::Plain)),
+            hints: false,
+        };
+        Plan::new(Source::Stdin, DecodeOptions::default(), projection, sink)
+    }
+
+    #[test]
+    fn test_stages_ordered_by_kind() {
+        let plan = plan()
+            .with_stage(Box::new(
+                DistinctOn::new(&["id".to_string()], Keep::Last).unwrap(),
+            ))
+            .with_stage(Box::new(Sorter::parse(&["id".to_string()]).unwrap()))
+            .with_stage(Box::new(LevelFilter::parse("warn").unwrap()))
+            .with_stage(Box::new(CommandTransform::new("cat", 1)));
+
+        assert_eq!(
+            plan.stage_names(),
+            vec!["level", "transform-cmd", "sort", "distinct-on"]
+        );
+    }
+
+    #[test]
+    fn test_edits_need_untranscoded_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("plain.jsonl");
+        let marked = dir.path().join("marked.jsonl");
+        std::fs::write(&plain, "{\"id\":1}\n").unwrap();
+        std::fs::write(&marked, "\u{feff}{\"id\":1}\n").unwrap();
+        let plan = plan();
+        let target = |path: &PathBuf, decode: &DecodeOptions| {
+            edit_target(
+                &Source::File(path.clone()),
+                &plan.stages,
+                decode,
+                &plan.projection,
+                &SkipStats::default(),
+            )
+        };
+
+        assert_eq!(target(&plain, &plan.decode), Some(plain.clone()));
+        assert_eq!(target(&marked, &plan.decode), None);
+        let latin1 = DecodeOptions {
+            encoding: Some(encoding_rs::WINDOWS_1252),
+            ..DecodeOptions::default()
+        };
+        assert_eq!(target(&plain, &latin1), None);
+    }
+
+    #[test]
+    fn test_level_runs_before_transforms() {
+        // Same order main.rs used before stages existed: level, then
+        // transform-cmd, plugin and script, then sort
+        let plan = plan()
+            .with_stage(Box::new(Sorter::parse(&["id".to_string()]).unwrap()))
+            .with_stage(Box::new(CommandTransform::new("cat", 1)))
+            .with_stage(Box::new(Select::parse(".id").unwrap()))
+            .with_stage(Box::new(LevelFilter::parse("warn").unwrap()));
+
+        assert_eq!(
+            plan.stage_names(),
+            vec!["level", "transform-cmd", "select", "sort"]
+        );
+    }
+
+    #[test]
+    fn test_process_filters_then_sorts() {
+        let mut plan = plan()
+            .with_stage(Box::new(Sorter::parse(&["-id".to_string()]).unwrap()))
+            .with_stage(Box::new(LevelFilter::parse("warn+").unwrap()));
+
+        let rows = vec![
+            json!({"id": 1, "level": "error"}),
+            json!({"id": 2, "level": "debug"}),
+            json!({"id": 3, "level": "warn"}),
+        ];
+        let rows = plan.process(rows).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![
+                json!({"id": 3, "level": "warn"}),
+                json!({"id": 1, "level": "error"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shuffle_is_seeded() {
+        let rows: Vec<Value> = (0..20).map(|id| json!({ "id": id })).collect();
+        let shuffled = |seed| {
+            plan()
+                .with_stage(Box::new(Shuffle::new(Some(seed))))
+                .process(rows.clone())
+                .unwrap()
+        };
+        let once = shuffled(7);
+        assert_eq!(once, shuffled(7));
+        assert_ne!(once, rows);
+        assert_ne!(once, shuffled(8));
+
+        let mut ids: Vec<u64> = once.iter().filter_map(|row| row["id"].as_u64()).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_reverse_runs_after_sort() {
+        let mut plan = plan()
+            .with_stage(Box::new(Sorter::parse(&["id".to_string()]).unwrap()))
+            .with_stage(Box::new(Reverse))
+            .with_stage(Box::new(LevelFilter::parse("warn+").unwrap()));
+        assert_eq!(plan.stage_names(), vec!["level", "sort", "reverse"]);
+
+        let rows = vec![
+            json!({"id": 2, "level": "warn"}),
+            json!({"id": 3, "level": "error"}),
+            json!({"id": 1, "level": "error"}),
+        ];
+        let ids: Vec<Value> = plan
+            .process(rows)
+            .unwrap()
+            .into_iter()
+            .map(|row| row["id"].clone())
+            .collect();
+        assert_eq!(ids, vec![json!(3), json!(2), json!(1)]);
+    }
+
+    #[test]
+    fn test_process_without_stages() {
+        let rows = vec![json!({"id": 1})];
+        assert_eq!(plan().process(rows.clone()).unwrap(), rows);
+    }
+}
@@ -0,0 +1,115 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::cell::Cell;
+use std::io::{self, Read};
+use std::rc::Rc;
+
+/// Inputs smaller than this load too quickly for a progress bar to help
+const MIN_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Progress bar on stderr while a large input loads: bytes read and lines seen
+pub struct Progress {
+    bar: ProgressBar,
+    lines: Rc<Cell<u64>>,
+    expected: Option<u64>,
+}
+
+impl Progress {
+    /// A bar for reading `total` bytes, drawn only if stderr is a terminal,
+    /// the input is large and `quiet` is off; hidden otherwise
+    pub fn new(total: Option<u64>, quiet: bool) -> Self {
+        let bar = match total {
+            Some(total) if total >= MIN_BYTES && !quiet && atty::is(atty::Stream::Stderr) => {
+                ProgressBar::new(total).with_style(
+                    ProgressStyle::with_template(
+                        "{bar:30} {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}",
+                    )
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+                )
+            }
+            _ => ProgressBar::hidden(),
+        };
+        Self {
+            bar,
+            lines: Rc::new(Cell::new(0)),
+            expected: None,
+        }
+    }
+
+    /// Show the lines read against an estimated total
+    pub fn with_expected_lines(mut self, expected: Option<u64>) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    /// Count what is read from `reader` towards the bar
+    pub fn wrap<'a>(&self, reader: Box<dyn Read + 'a>) -> Box<dyn Read + 'a> {
+        if self.bar.is_hidden() {
+            return reader;
+        }
+        Box::new(ProgressReader {
+            inner: reader,
+            bar: self.bar.clone(),
+            lines: self.lines.clone(),
+            expected: self.expected,
+        })
+    }
+
+    /// Remove the bar before output is printed
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+struct ProgressReader<R> {
+    inner: R,
+    bar: ProgressBar,
+    lines: Rc<Cell<u64>>,
+    expected: Option<u64>,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let lines = self.lines.get() + buf[..n].iter().filter(|&&b| b == b'\n').count() as u64;
+        self.lines.set(lines);
+        self.bar.inc(n as u64);
+        self.bar.set_message(lines_message(lines, self.expected));
+        Ok(n)
+    }
+}
+
+fn lines_message(lines: u64, expected: Option<u64>) -> String {
+    match expected {
+        Some(expected) => format!("{} of ~{} lines", lines, expected.max(lines)),
+        None => format!("{} lines", lines),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_or_quiet_inputs_are_not_tracked() {
+        assert!(Progress::new(Some(10), false).bar.is_hidden());
+        assert!(Progress::new(Some(MIN_BYTES), true).bar.is_hidden());
+        assert!(Progress::new(None, false).bar.is_hidden());
+    }
+
+    #[test]
+    fn test_lines_message() {
+        assert_eq!(lines_message(5, None), "5 lines");
+        assert_eq!(lines_message(5, Some(40)), "5 of ~40 lines");
+        // An estimate that fell short grows with the count
+        assert_eq!(lines_message(50, Some(40)), "50 of ~50 lines");
+    }
+
+    #[test]
+    fn test_wrapped_reader_passes_data_through() {
+        let progress = Progress::new(None, false);
+        let mut reader = progress.wrap(Box::new("a\nb\n".as_bytes()));
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "a\nb\n");
+    }
+}
@@ -0,0 +1,105 @@
+use crate::core::{choose_layout, ChildRows, ColumnSelector, FlatConfig, Layout, NestedExtractor};
+use serde_json::Value;
+
+/// How rows are laid out as tables
+#[derive(Debug, Clone)]
+pub enum View {
+    /// One table, with nested values as placeholders
+    Normal,
+    /// Nested objects expanded into dot-notation columns
+    Flat(FlatConfig),
+    /// A parent table plus one child table per nested field
+    Recursive {
+        summary: bool,
+        child_rows: ChildRows,
+        /// Which child tables to extract and how they reference their parents
+        extractor: NestedExtractor,
+    },
+}
+
+/// Column selection and table layout applied before rendering
+#[derive(Debug, Clone)]
+pub struct Projection {
+    pub selector: Option<ColumnSelector>,
+    pub view: View,
+    /// Choose the view from the shape of the data (`--auto-layout`)
+    pub auto_layout: bool,
+    /// Array elements shown per cell when auto-layout picks flat mode
+    pub array_limit: usize,
+}
+
+impl Projection {
+    /// Settle the view for these rows, applying `--auto-layout` if requested.
+    /// `child_tables` is false for sinks that cannot show child tables.
+    pub fn resolve(
+        mut self,
+        rows: &[Value],
+        terminal_width: Option<usize>,
+        child_tables: bool,
+    ) -> Self {
+        if !self.auto_layout {
+            return self;
+        }
+
+        self.view = match choose_layout(rows, terminal_width) {
+            Layout::Flat => View::Flat(FlatConfig::new(None, self.array_limit)),
+            Layout::Summary if child_tables => View::Recursive {
+                summary: true,
+                child_rows: ChildRows::default(),
+                extractor: NestedExtractor::default(),
+            },
+            Layout::Summary | Layout::Normal => View::Normal,
+        };
+        self.auto_layout = false;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn auto() -> Projection {
+        Projection {
+            selector: None,
+            view: View::Normal,
+            auto_layout: true,
+            array_limit: 3,
+        }
+    }
+
+    #[test]
+    fn test_resolve_without_auto_layout_keeps_view() {
+        let projection = Projection {
+            auto_layout: false,
+            ..auto()
+        };
+        let rows = vec![json!({"user": {"name": "Alice"}})];
+        assert!(matches!(
+            projection.resolve(&rows, Some(80), true).view,
+            View::Normal
+        ));
+    }
+
+    #[test]
+    fn test_resolve_auto_layout() {
+        let rows = vec![json!({"user": {"name": "Alice"}})];
+        assert!(matches!(
+            auto().resolve(&rows, Some(80), true).view,
+            View::Flat(_)
+        ));
+
+        let orders: Vec<Value> = (0..10).map(|i| json!({"n": i})).collect();
+        let rows = vec![json!({"orders": orders})];
+        assert!(matches!(
+            auto().resolve(&rows, Some(80), true).view,
+            View::Recursive { summary: true, .. }
+        ));
+        // Sinks without child tables fall back to placeholders
+        assert!(matches!(
+            auto().resolve(&rows, Some(80), false).view,
+            View::Normal
+        ));
+    }
+}
@@ -0,0 +1,524 @@
+use super::project::{Projection, View};
+use crate::cli::OutputFormat;
+use crate::core::{
+    rename_columns, ChildTable, ColumnSelector, FlatConfig, FlatTableData, Formatter,
+    NestedExtractor, TableData,
+};
+use crate::error::Result;
+use crate::input::SpilledRows;
+use crate::render::tui::Keymap;
+use crate::render::{self, export, TableRenderer};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the parent table, used as its file name by `--out-dir`
+const ROOT_TABLE: &str = "_root";
+
+/// Final destination of the rows
+pub enum Sink {
+    /// Tables printed to stdout
+    Cat {
+        renderer: Box<dyn TableRenderer>,
+        hints: bool,
+    },
+    /// Interactive TUI
+    Tui {
+        formatter: Box<Formatter>,
+        keymap: Keymap,
+        /// JSONL file that cell edits are saved to, set when the rows are
+        /// shown exactly as they are in it
+        edit_path: Option<PathBuf>,
+    },
+    /// Tables serialized as CSV, JSON, JSONL or Markdown on stdout (`--output`)
+    Serialize {
+        format: OutputFormat,
+        compact: bool,
+        /// Labels written in place of column names (`--header-map`)
+        headers: HashMap<String, String>,
+    },
+    /// One file per table in a directory (`--out-dir`)
+    Export {
+        dir: PathBuf,
+        format: OutputFormat,
+        compact: bool,
+        headers: HashMap<String, String>,
+    },
+    /// Each row pretty-printed as JSON (`--pretty`)
+    Pretty { color: bool },
+    /// Every table written to a SQLite database (`--to-sqlite`), the parent
+    /// table named `table`
+    #[cfg(feature = "sql")]
+    Sqlite { path: PathBuf, table: String },
+    /// A plugin's `render` export replaces the built-in tables
+    #[cfg(feature = "wasm")]
+    Plugin(std::rc::Rc<std::cell::RefCell<crate::plugin::WasmPlugin>>),
+}
+
+impl Sink {
+    /// Whether the sink can show recursive child tables
+    pub fn supports_child_tables(&self) -> bool {
+        match self {
+            Sink::Cat { .. } | Sink::Serialize { .. } | Sink::Export { .. } => true,
+            #[cfg(feature = "sql")]
+            Sink::Sqlite { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the sink can show rows read back from disk (`--max-memory`)
+    pub fn supports_spill(&self) -> bool {
+        matches!(
+            self,
+            Sink::Tui { .. }
+                | Sink::Cat { .. }
+                | Sink::Serialize {
+                    format: OutputFormat::Jsonl,
+                    ..
+                }
+        )
+    }
+
+    /// Write rows left on disk, shown under `columns`. The TUI reads them as
+    /// they scroll into view; other sinks load `chunk` rows at a time, and cat
+    /// mode prints each chunk as a table of its own.
+    pub fn write_spilled(
+        self,
+        mut rows: SpilledRows,
+        columns: Vec<String>,
+        selector: Option<ColumnSelector>,
+        chunk: usize,
+    ) -> Result<()> {
+        if let Sink::Tui {
+            formatter, keymap, ..
+        } = self
+        {
+            return render::tui::run_spilled(rows, columns, selector, *formatter, keymap);
+        }
+
+        let chunk = chunk.max(1);
+        for start in (0..rows.len()).step_by(chunk) {
+            let end = (start + chunk).min(rows.len());
+            let cells: Vec<Vec<Value>> = (start..end)
+                .filter_map(|i| rows.get(i))
+                .map(|row| TableData::row_values(&row, &columns, selector.as_ref()))
+                .collect();
+            match self {
+                Sink::Cat { ref renderer, .. } => {
+                    let table = TableData::from_flat_columns_rows(columns.clone(), cells);
+                    write_stdout(&format!("{}\n", renderer.render(&table)))?;
+                }
+                Sink::Serialize {
+                    format,
+                    compact,
+                    ref headers,
+                } => {
+                    let columns = rename_columns(&columns, headers);
+                    write_stdout(&export::serialize(format, compact, &columns, &cells))?;
+                }
+                _ => unreachable!("sink does not support spilled rows"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Let the TUI save cell edits to `path`
+    pub fn allow_edits(&mut self, path: PathBuf) {
+        if let Sink::Tui {
+            ref mut edit_path, ..
+        } = self
+        {
+            *edit_path = Some(path);
+        }
+    }
+
+    pub fn write(self, rows: Vec<Value>, projection: Projection) -> Result<()> {
+        match self {
+            Sink::Cat { renderer, hints } => {
+                write_stdout(&render_cat(renderer.as_ref(), rows, projection, hints))
+            }
+            Sink::Tui {
+                formatter,
+                keymap,
+                edit_path,
+            } => match projection.view {
+                View::Flat(config) => {
+                    let flat_table =
+                        flat_table(&rows, config.clone(), projection.selector.as_ref());
+                    let layout = (projection.selector, Some(config));
+                    render::tui::run_flat(flat_table, rows, *formatter, keymap, layout)
+                }
+                View::Normal | View::Recursive { .. } => {
+                    let table_data =
+                        TableData::from_rows(rows.clone(), projection.selector.clone());
+                    let layout = (projection.selector, None);
+                    render::tui::run(table_data, rows, *formatter, keymap, layout, edit_path)
+                }
+            },
+            Sink::Serialize {
+                format,
+                compact,
+                headers,
+            } => write_stdout(&serialize_tables(
+                format, compact, &headers, rows, projection,
+            )),
+            Sink::Export {
+                dir,
+                format,
+                compact,
+                headers,
+            } => export_tables(&dir, format, compact, &headers, rows, projection),
+            Sink::Pretty { color } => {
+                let rows = match projection.selector {
+                    Some(selector) => selected_rows(rows, selector),
+                    None => rows,
+                };
+                write_stdout(&render::render_pretty(&rows, color))
+            }
+            #[cfg(feature = "sql")]
+            Sink::Sqlite { path, table } => {
+                let mut export = crate::sql::SqliteExport::create(&path)?;
+                for named in named_tables(rows, projection) {
+                    let name = if named.name == ROOT_TABLE {
+                        &table
+                    } else {
+                        &named.name
+                    };
+                    export.write_table(name, &named.columns, &named.rows)?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "wasm")]
+            Sink::Plugin(plugin) => write_stdout(&plugin.borrow_mut().render(&rows)?),
+        }
+    }
+}
+
+/// Write output text to stdout. Unlike `print!`, a closed pipe is returned as
+/// an error rather than a panic, so spilled output stops reading early and
+/// `main` can exit quietly.
+pub fn write_stdout(text: &str) -> Result<()> {
+    let mut out = io::stdout().lock();
+    out.write_all(text.as_bytes())?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Render rows as the text printed in cat mode
+pub fn render_cat(
+    renderer: &dyn TableRenderer,
+    rows: Vec<Value>,
+    projection: Projection,
+    hints: bool,
+) -> String {
+    let mut out = String::new();
+    let mut println = |text: &str| {
+        out.push_str(text);
+        out.push('\n');
+    };
+
+    match projection.view {
+        View::Flat(config) => {
+            // Flat mode - expand nested objects
+            let flat_table = flat_table(&rows, config, projection.selector.as_ref());
+            println(&renderer.render_flat(&flat_table));
+        }
+        View::Recursive {
+            summary,
+            child_rows,
+            extractor,
+        } => {
+            // Extract nested structures
+            let children = extractor.extract_children(&rows);
+
+            let parent_table = parent_table(rows, projection.selector);
+            println(&renderer.render(&parent_table));
+
+            // Render child tables
+            for (name, child) in child_tables(children, summary) {
+                println(&format!("\n## {}\n", name));
+
+                let (shown, rest) = child_rows.apply(&name, &child);
+                println(&renderer.render(&child_table_to_table_data(&shown)));
+                if rest > 0 {
+                    println(&format!("…and {} more", rest));
+                }
+            }
+        }
+        View::Normal => {
+            // Normal mode - render all data as single table
+            let table_data = TableData::from_rows(rows, projection.selector);
+            println(&renderer.render(&table_data));
+
+            if hints {
+                let hints = render::drill_down_hints(&table_data);
+                if !hints.is_empty() {
+                    println("\nNested values:");
+                    for hint in hints {
+                        println(&format!("  {}", hint));
+                    }
+                }
+            }
+        }
+    }
+
+    out
+}
+
+/// A table ready for serialization, named after its path
+struct NamedTable {
+    name: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl NamedTable {
+    fn new(name: &str, columns: Vec<String>, rows: Vec<Vec<Value>>) -> Self {
+        Self {
+            name: name.to_string(),
+            columns,
+            rows,
+        }
+    }
+
+    fn serialize(
+        &self,
+        format: OutputFormat,
+        compact: bool,
+        headers: &HashMap<String, String>,
+    ) -> String {
+        let columns = rename_columns(&self.columns, headers);
+        export::serialize(format, compact, &columns, &self.rows)
+    }
+}
+
+/// Every table of the view: the parent table named `_root`, then child
+/// tables sorted by path (e.g. `orders.shipping`)
+fn named_tables(rows: Vec<Value>, projection: Projection) -> Vec<NamedTable> {
+    match projection.view {
+        View::Recursive {
+            summary,
+            child_rows,
+            extractor,
+        } => {
+            let children = extractor.extract_children(&rows);
+            let parent = parent_table(rows, projection.selector);
+            let mut tables = vec![NamedTable::new(
+                ROOT_TABLE,
+                parent.columns().to_vec(),
+                parent.rows().to_vec(),
+            )];
+
+            for (name, child) in child_tables(children, summary) {
+                let (child, _) = child_rows.apply(&name, &child);
+                tables.push(NamedTable::new(
+                    &name,
+                    child.columns_with_parent(),
+                    child.rows_with_parent(),
+                ));
+            }
+            tables
+        }
+        View::Flat(config) => {
+            let table = flat_table(&rows, config, projection.selector.as_ref());
+            vec![NamedTable::new(
+                ROOT_TABLE,
+                table.columns(),
+                table.rows().to_vec(),
+            )]
+        }
+        View::Normal => {
+            let table = TableData::from_rows(rows, projection.selector);
+            vec![NamedTable::new(
+                ROOT_TABLE,
+                table.columns().to_vec(),
+                table.rows().to_vec(),
+            )]
+        }
+    }
+}
+
+/// Serialize every table for stdout, introducing child tables with their
+/// `## path` section heading as in cat mode
+fn serialize_tables(
+    format: OutputFormat,
+    compact: bool,
+    headers: &HashMap<String, String>,
+    rows: Vec<Value>,
+    projection: Projection,
+) -> String {
+    let mut out = String::new();
+    for (i, table) in named_tables(rows, projection).into_iter().enumerate() {
+        if i > 0 {
+            out.push_str(&format!("\n## {}\n\n", table.name));
+        }
+        out.push_str(&table.serialize(format, compact, headers));
+    }
+    out
+}
+
+/// Write every table of the view to `dir` as `<table>.<ext>`
+fn export_tables(
+    dir: &Path,
+    format: OutputFormat,
+    compact: bool,
+    headers: &HashMap<String, String>,
+    rows: Vec<Value>,
+    projection: Projection,
+) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for table in named_tables(rows, projection) {
+        // Keep odd field names from escaping the directory
+        let name = table.name.replace(['/', '\\'], "_");
+        let path = dir.join(format!("{}.{}", name, export::extension(format)));
+        std::fs::write(path, table.serialize(format, compact, headers))?;
+    }
+    Ok(())
+}
+
+/// Parent table of recursive mode.
+/// - If column selector is provided, use original rows so nested paths resolve
+/// - Otherwise, flatten rows to show placeholders for nested structures
+fn parent_table(rows: Vec<Value>, selector: Option<ColumnSelector>) -> TableData {
+    if selector.is_some() {
+        // Column selection: use original rows so paths like "address.city" work
+        TableData::from_rows(rows, selector)
+    } else {
+        // No column selection: flatten to show placeholders
+        let flat_rows: Vec<Value> = rows.iter().map(NestedExtractor::flatten_row).collect();
+        TableData::from_rows(flat_rows, None)
+    }
+}
+
+/// Non-empty child tables sorted by path, summarized if requested
+fn child_tables(children: HashMap<String, ChildTable>, summary: bool) -> Vec<(String, ChildTable)> {
+    let mut tables: Vec<_> = children
+        .into_iter()
+        .filter(|(_, child)| !child.is_empty())
+        .map(|(name, child)| {
+            let child = if summary { child.summarize() } else { child };
+            (name, child)
+        })
+        .collect();
+    tables.sort_by(|a, b| a.0.cmp(&b.0)); // Consistent ordering
+    tables
+}
+
+/// Rows cut down to the `-c` columns, keyed by the column names
+fn selected_rows(rows: Vec<Value>, selector: ColumnSelector) -> Vec<Value> {
+    let table = TableData::from_rows(rows, Some(selector));
+    table
+        .rows()
+        .iter()
+        .map(|row| {
+            let object = table.columns().iter().cloned().zip(row.iter().cloned());
+            Value::Object(object.collect())
+        })
+        .collect()
+}
+
+/// Flatten rows, keeping only the selected columns if `-c` was given
+fn flat_table(
+    rows: &[Value],
+    config: FlatConfig,
+    selector: Option<&ColumnSelector>,
+) -> FlatTableData {
+    let table = FlatTableData::from_rows(rows, config);
+    match selector {
+        Some(selector) => table.select(selector),
+        None => table,
+    }
+}
+
+/// Convert a ChildTable to TableData for rendering
+fn child_table_to_table_data(child: &ChildTable) -> TableData {
+    let columns = child.columns_with_parent();
+    let rows = child.rows_with_parent();
+
+    // Convert to JSON objects for TableData
+    let json_rows: Vec<Value> = rows
+        .into_iter()
+        .map(|values| {
+            let mut obj = serde_json::Map::new();
+            for (col, val) in columns.iter().zip(values) {
+                obj.insert(col.clone(), val);
+            }
+            Value::Object(obj)
+        })
+        .collect();
+
+    TableData::from_rows(json_rows, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::TableStyle;
+    use crate::core::ChildRows;
+    use crate::render::CatRenderer;
+    use serde_json::json;
+
+    fn projection(view: View) -> Projection {
+        Projection {
+            selector: None,
+            view,
+            auto_layout: false,
+            array_limit: 3,
+        }
+    }
+
+    fn renderer() -> CatRenderer {
+        CatRenderer::new(TableStyle::Plain)
+    }
+
+    #[test]
+    fn test_render_cat_normal() {
+        let rows = vec![json!({"id": 1, "name": "Alice"})];
+        let out = render_cat(&renderer(), rows, projection(View::Normal), false);
+        assert!(out.contains("Alice"));
+        assert!(out.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_render_cat_recursive_with_limit() {
+        let rows = vec![json!({"id": 1, "items": [{"n": 11}, {"n": 12}, {"n": 13}]})];
+        let view = View::Recursive {
+            summary: false,
+            child_rows: ChildRows {
+                limit: Some(1),
+                ..Default::default()
+            },
+            extractor: NestedExtractor::default(),
+        };
+        let out = render_cat(&renderer(), rows, projection(view), false);
+
+        assert!(out.contains("\n## items\n"));
+        assert!(out.contains("11"));
+        assert!(!out.contains("12"));
+        assert!(out.contains("…and 2 more"));
+    }
+
+    #[test]
+    fn test_render_cat_hints() {
+        let rows = vec![json!({"id": 1, "tags": ["a"]})];
+        let out = render_cat(&renderer(), rows, projection(View::Normal), true);
+        assert!(out.contains("Nested values:"));
+        assert!(out.contains("tags: [1 item]"));
+    }
+
+    #[test]
+    fn test_render_cat_flat_with_selector() {
+        let rows = vec![json!({"id": 1, "user": {"name": "Alice", "age": 30}})];
+        let projection = Projection {
+            selector: Some(ColumnSelector::new(vec!["user.name".into()]).unwrap()),
+            ..projection(View::Flat(FlatConfig::default()))
+        };
+
+        let out = render_cat(&renderer(), rows, projection, false);
+
+        assert!(out.contains("user.name"));
+        assert!(!out.contains("user.age"));
+        assert!(!out.contains("id"));
+    }
+}
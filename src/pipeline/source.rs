@@ -0,0 +1,80 @@
+use crate::error::Result;
+use crate::input::{count_rows, estimate_rows, RowEstimate};
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// Where input rows are read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Stdin,
+    File(PathBuf),
+    /// Several files read one after another
+    Files(Vec<PathBuf>),
+}
+
+impl Source {
+    pub fn open(&self) -> Result<Box<dyn Read>> {
+        match self {
+            Source::Stdin => Ok(Box::new(io::stdin().lock())),
+            Source::File(path) => Ok(Box::new(std::fs::File::open(path)?)),
+            Source::Files(paths) => {
+                let mut reader: Box<dyn Read> = Box::new(io::empty());
+                for path in paths {
+                    reader = Box::new(reader.chain(std::fs::File::open(path)?));
+                }
+                Ok(reader)
+            }
+        }
+    }
+
+    /// Open every input separately, named by its path (or "stdin")
+    pub fn open_each(&self) -> Result<Vec<(String, Box<dyn Read>)>> {
+        let paths = match self {
+            Source::Stdin => return Ok(vec![("stdin".to_string(), self.open()?)]),
+            Source::File(path) => std::slice::from_ref(path),
+            Source::Files(paths) => paths.as_slice(),
+        };
+        paths
+            .iter()
+            .map(|path| {
+                let file: Box<dyn Read> = Box::new(std::fs::File::open(path)?);
+                Ok((path.display().to_string(), file))
+            })
+            .collect()
+    }
+
+    /// Total size of the input files in bytes; unknown for stdin
+    pub fn size(&self) -> Option<u64> {
+        let paths = match self {
+            Source::Stdin => return None,
+            Source::File(path) => std::slice::from_ref(path),
+            Source::Files(paths) => paths.as_slice(),
+        };
+        paths
+            .iter()
+            .map(|path| std::fs::metadata(path).ok().map(|m| m.len()))
+            .sum()
+    }
+
+    /// Estimate the row count; files are sampled, stdin is read to the end
+    pub fn estimate(&self) -> Result<RowEstimate> {
+        match self {
+            Source::Stdin => Ok(count_rows(io::stdin().lock())?),
+            Source::File(path) => Ok(estimate_rows(&mut std::fs::File::open(path)?)?),
+            Source::Files(paths) => {
+                let mut total = RowEstimate {
+                    rows: 0,
+                    bytes: 0,
+                    exact: true,
+                };
+                for path in paths {
+                    let estimate = estimate_rows(&mut std::fs::File::open(path)?)?;
+                    total.rows += estimate.rows;
+                    total.bytes += estimate.bytes;
+                    total.exact &= estimate.exact;
+                }
+                Ok(total)
+            }
+        }
+    }
+}
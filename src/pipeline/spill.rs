@@ -0,0 +1,67 @@
+//! Sizing for `--max-memory`: whether an input fits once parsed, and how
+//! many rows to load at a time when it does not.
+
+/// Parsed rows take several times the space of their JSON text
+const VALUE_OVERHEAD: u64 = 4;
+
+/// Each parsed row also holds a map node, allocated whole however few keys
+/// the row has
+const ROW_OVERHEAD: u64 = 256;
+
+/// Rows read back from disk kept parsed at a time
+pub const CACHED_ROWS: usize = 1000;
+
+/// Memory `rows` rows in `bytes` of JSON text are expected to take once parsed
+pub fn footprint(bytes: u64, rows: u64) -> u64 {
+    bytes
+        .saturating_mul(VALUE_OVERHEAD)
+        .saturating_add(rows.saturating_mul(ROW_OVERHEAD))
+}
+
+/// Rows of an input of `rows` rows in `bytes` that fit in `budget` at once
+pub fn chunk_rows(budget: u64, bytes: u64, rows: usize) -> usize {
+    let per_row = footprint(bytes, rows as u64) / rows.max(1) as u64;
+    usize::try_from(budget / per_row.max(1))
+        .unwrap_or(usize::MAX)
+        .max(1)
+}
+
+/// A byte count in the units `--max-memory` takes, e.g. "1.5G"
+pub fn format_size(bytes: u64) -> String {
+    let units = [(40, 'T'), (30, 'G'), (20, 'M'), (10, 'K')];
+    match units.iter().find(|(shift, _)| bytes >= 1 << shift) {
+        Some(&(shift, unit)) => {
+            let size = bytes as f64 / (1u64 << shift) as f64;
+            let text = format!("{:.1}", size);
+            format!("{}{}", text.trim_end_matches(".0"), unit)
+        }
+        None => bytes.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_rows_fills_budget() {
+        // 100 rows of 10 bytes take about 40 bytes each once parsed, plus
+        // the row's map
+        assert_eq!(chunk_rows(2960, 1000, 100), 10);
+        assert_eq!(chunk_rows(10, 1000, 100), 1);
+        assert_eq!(chunk_rows(400, 0, 0), 400);
+    }
+
+    #[test]
+    fn test_footprint_counts_rows() {
+        assert_eq!(footprint(1000, 0), 4000);
+        assert!(footprint(1000, 100) > footprint(1000, 10));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512");
+        assert_eq!(format_size(512 << 20), "512M");
+        assert_eq!(format_size(3 << 29), "1.5G");
+    }
+}
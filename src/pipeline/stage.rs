@@ -0,0 +1,280 @@
+use crate::core::{
+    CommandTransform, DistinctOn, Explode, FilterExpr, FullTextSearch, LevelFilter, RowRanges,
+    Select, Sorter,
+};
+use crate::error::Result;
+use serde_json::Value;
+
+/// Where a stage runs; the planner orders stages by kind, keeping insertion
+/// order within a kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StageKind {
+    /// Picks rows as read, before any transform sees them
+    Input,
+    /// Rewrites rows (external commands, plugins, scripts)
+    Transform,
+    /// Drops rows
+    Filter,
+    /// Reorders rows
+    Sort,
+    /// Drops rows depending on their order, after sorting
+    Distinct,
+}
+
+/// A step that rewrites the row set between decoding and projection
+pub trait Stage {
+    /// Short name used when describing a plan
+    fn name(&self) -> &'static str;
+
+    fn kind(&self) -> StageKind;
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>>;
+
+    /// Sort order of a stage that only reorders rows, so it can also run
+    /// as an external merge sort on inputs over `--max-memory`
+    fn sorter(&self) -> Option<&Sorter> {
+        None
+    }
+}
+
+impl Stage for CommandTransform {
+    fn name(&self) -> &'static str {
+        "transform-cmd"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Transform
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        self.apply(rows)
+    }
+}
+
+impl Stage for RowRanges {
+    fn name(&self) -> &'static str {
+        "rows"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Input
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        Ok(self.apply(rows))
+    }
+}
+
+impl Stage for Explode {
+    fn name(&self) -> &'static str {
+        "explode"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Transform
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        Ok(self.apply(rows))
+    }
+}
+
+impl Stage for DistinctOn {
+    fn name(&self) -> &'static str {
+        "distinct-on"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Distinct
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        Ok(self.apply(rows))
+    }
+}
+
+impl Stage for Select {
+    fn name(&self) -> &'static str {
+        "select"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Transform
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        Ok(self.apply(rows))
+    }
+}
+
+impl Stage for LevelFilter {
+    fn name(&self) -> &'static str {
+        "level"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Input
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        Ok(self.apply(rows))
+    }
+}
+
+impl Stage for FilterExpr {
+    fn name(&self) -> &'static str {
+        "filter"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Filter
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        Ok(rows.into_iter().filter(|row| self.matches(row)).collect())
+    }
+}
+
+impl Stage for FullTextSearch {
+    fn name(&self) -> &'static str {
+        "grep"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Filter
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        Ok(rows.into_iter().filter(|row| self.matches(row)).collect())
+    }
+}
+
+impl Stage for Sorter {
+    fn name(&self) -> &'static str {
+        "sort"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Sort
+    }
+
+    fn run(&mut self, mut rows: Vec<Value>) -> Result<Vec<Value>> {
+        self.sort(&mut rows);
+        Ok(rows)
+    }
+
+    fn sorter(&self) -> Option<&Sorter> {
+        Some(self)
+    }
+}
+
+#[cfg(feature = "script")]
+impl Stage for crate::script::RowScript {
+    fn name(&self) -> &'static str {
+        "script"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Transform
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        self.apply(rows)
+    }
+}
+
+#[cfg(feature = "sql")]
+impl Stage for crate::sql::SqlQuery {
+    fn name(&self) -> &'static str {
+        "sql"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Transform
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        self.apply(rows)
+    }
+}
+
+/// Reverses the row order; added after any sort, so it flips the sorted order
+pub struct Reverse;
+
+impl Stage for Reverse {
+    fn name(&self) -> &'static str {
+        "reverse"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Sort
+    }
+
+    fn run(&mut self, mut rows: Vec<Value>) -> Result<Vec<Value>> {
+        rows.reverse();
+        Ok(rows)
+    }
+}
+
+/// Randomizes the row order (`--shuffle`); the same seed gives the same order
+pub struct Shuffle {
+    seed: u64,
+}
+
+impl Shuffle {
+    /// Shuffle with `seed`, or one taken from the clock
+    pub fn new(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos() as u64)
+        });
+        Self { seed }
+    }
+}
+
+impl Stage for Shuffle {
+    fn name(&self) -> &'static str {
+        "shuffle"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Sort
+    }
+
+    fn run(&mut self, mut rows: Vec<Value>) -> Result<Vec<Value>> {
+        // Fisher-Yates with splitmix64, which is plenty for eyeballing rows
+        let mut state = self.seed;
+        let mut next = || {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        for i in (1..rows.len()).rev() {
+            let j = (next() % (i as u64 + 1)) as usize;
+            rows.swap(i, j);
+        }
+        Ok(rows)
+    }
+}
+
+/// Runs a plugin's `transform` export; the plugin is shared with the sink for `render`
+#[cfg(feature = "wasm")]
+pub struct PluginTransform(pub std::rc::Rc<std::cell::RefCell<crate::plugin::WasmPlugin>>);
+
+#[cfg(feature = "wasm")]
+impl Stage for PluginTransform {
+    fn name(&self) -> &'static str {
+        "plugin"
+    }
+
+    fn kind(&self) -> StageKind {
+        StageKind::Transform
+    }
+
+    fn run(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        self.0.borrow_mut().transform_rows(rows)
+    }
+}
@@ -0,0 +1,293 @@
+//! Sandboxed WebAssembly plugins for row transforms and custom renderers.
+//!
+//! A plugin is a `.wasm` module with no imports that exports:
+//!
+//! - `memory`: the linear memory used to exchange data
+//! - `alloc(len: i32) -> i32`: reserve `len` bytes and return a pointer
+//! - `transform(ptr: i32, len: i32) -> i64` (optional): receive one row as JSON and
+//!   return a JSON object (the new row) or `null` (drop the row)
+//! - `render(ptr: i32, len: i32) -> i64` (optional): receive all rows as a JSON array
+//!   and return UTF-8 text to print instead of a table
+//! - `dealloc(ptr: i32, len: i32)` (optional): release memory returned by the plugin
+//!
+//! Results are packed as `(ptr << 32) | len`. Each call may run at most
+//! [`FUEL_PER_CALL`] instructions, so a plugin stuck in a loop fails instead
+//! of hanging.
+
+use crate::error::{JlcatError, Result};
+use serde_json::Value;
+use std::path::Path;
+use wasmtime::{Config, Engine, Instance, Memory, Module, Store, Trap, TypedFunc};
+
+/// Fuel (roughly, WebAssembly instructions) one call into a plugin may use
+pub const FUEL_PER_CALL: u64 = 5_000_000_000;
+
+pub struct WasmPlugin {
+    store: Store<()>,
+    fuel: u64,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    dealloc: Option<TypedFunc<(i32, i32), ()>>,
+    transform: Option<TypedFunc<(i32, i32), i64>>,
+    render: Option<TypedFunc<(i32, i32), i64>>,
+}
+
+impl WasmPlugin {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let engine = metered_engine()?;
+        let module = Module::from_file(&engine, path).map_err(plugin_error)?;
+        Self::from_module(&engine, &module)
+    }
+
+    #[allow(dead_code)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let engine = metered_engine()?;
+        let module = Module::new(&engine, bytes).map_err(plugin_error)?;
+        Self::from_module(&engine, &module)
+    }
+
+    /// Allow each call `fuel` instead of [`FUEL_PER_CALL`]
+    #[allow(dead_code)]
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    fn from_module(engine: &Engine, module: &Module) -> Result<Self> {
+        let mut store = Store::new(engine, ());
+        store.set_fuel(FUEL_PER_CALL).map_err(plugin_error)?;
+        // No imports are provided, so plugins cannot touch the host
+        let instance = Instance::new(&mut store, module, &[]).map_err(plugin_error)?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| JlcatError::Plugin("missing 'memory' export".into()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "alloc")
+            .map_err(plugin_error)?;
+        let dealloc = instance
+            .get_typed_func::<(i32, i32), ()>(&mut store, "dealloc")
+            .ok();
+        let transform = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, "transform")
+            .ok();
+        let render = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, "render")
+            .ok();
+
+        if transform.is_none() && render.is_none() {
+            return Err(JlcatError::Plugin(
+                "plugin must export 'transform' or 'render'".into(),
+            ));
+        }
+
+        Ok(Self {
+            store,
+            fuel: FUEL_PER_CALL,
+            memory,
+            alloc,
+            dealloc,
+            transform,
+            render,
+        })
+    }
+
+    pub fn has_transform(&self) -> bool {
+        self.transform.is_some()
+    }
+
+    pub fn has_render(&self) -> bool {
+        self.render.is_some()
+    }
+
+    /// Run the plugin's `transform` export over every row
+    pub fn transform_rows(&mut self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        let func = match self.transform.clone() {
+            Some(func) => func,
+            None => return Ok(rows),
+        };
+
+        let mut result = Vec::with_capacity(rows.len());
+        for (idx, row) in rows.iter().enumerate() {
+            let input = serde_json::to_vec(row)
+                .map_err(|e| JlcatError::Plugin(format!("row {}: {}", idx + 1, e)))?;
+            let output = self.call(&func, &input)?;
+            match serde_json::from_slice::<Value>(&output) {
+                Ok(value @ Value::Object(_)) => result.push(value),
+                Ok(Value::Null) => {}
+                Ok(_) => {
+                    return Err(JlcatError::Plugin(format!(
+                        "row {}: transform must return an object or null",
+                        idx + 1
+                    )))
+                }
+                Err(e) => return Err(JlcatError::Plugin(format!("row {}: {}", idx + 1, e))),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Render all rows with the plugin's `render` export
+    pub fn render(&mut self, rows: &[Value]) -> Result<String> {
+        let func = self
+            .render
+            .clone()
+            .ok_or_else(|| JlcatError::Plugin("plugin has no 'render' export".into()))?;
+
+        let input = serde_json::to_vec(rows).map_err(|e| JlcatError::Plugin(e.to_string()))?;
+        let output = self.call(&func, &input)?;
+        String::from_utf8(output)
+            .map_err(|_| JlcatError::Plugin("render output is not valid UTF-8".into()))
+    }
+
+    /// Copy `input` into plugin memory, call `func`, and copy the packed result back out
+    fn call(&mut self, func: &TypedFunc<(i32, i32), i64>, input: &[u8]) -> Result<Vec<u8>> {
+        let len =
+            i32::try_from(input.len()).map_err(|_| JlcatError::Plugin("input too large".into()))?;
+        // The budget covers the whole exchange, alloc and dealloc included
+        self.store.set_fuel(self.fuel).map_err(plugin_error)?;
+        let ptr = self
+            .alloc
+            .call(&mut self.store, len)
+            .map_err(plugin_error)?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input)
+            .map_err(|e| JlcatError::Plugin(e.to_string()))?;
+
+        let packed = func
+            .call(&mut self.store, (ptr, len))
+            .map_err(plugin_error)?;
+        let out_ptr = (packed >> 32) as u32 as usize;
+        let out_len = (packed & 0xffff_ffff) as u32 as usize;
+
+        let mut output = vec![0u8; out_len];
+        self.memory
+            .read(&self.store, out_ptr, &mut output)
+            .map_err(|e| JlcatError::Plugin(e.to_string()))?;
+
+        if let Some(dealloc) = self.dealloc.clone() {
+            dealloc
+                .call(&mut self.store, (ptr, len))
+                .map_err(plugin_error)?;
+            // A plugin may hand back its input buffer; free it only once
+            if out_ptr as i32 != ptr {
+                dealloc
+                    .call(&mut self.store, (out_ptr as i32, out_len as i32))
+                    .map_err(plugin_error)?;
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// An engine that meters plugin calls with fuel
+fn metered_engine() -> Result<Engine> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).map_err(plugin_error)
+}
+
+fn plugin_error(e: wasmtime::Error) -> JlcatError {
+    match e.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => {
+            JlcatError::Plugin("plugin ran out of fuel (stuck in a loop?)".into())
+        }
+        _ => JlcatError::Plugin(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const IDENTITY: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (global $next (mut i32) (i32.const 1024))
+          (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            global.get $next
+            local.set $ptr
+            global.get $next
+            local.get $len
+            i32.add
+            global.set $next
+            local.get $ptr)
+          ;; Traps when a buffer is freed twice in a row
+          (global $freed (mut i32) (i32.const -1))
+          (func (export "dealloc") (param $ptr i32) (param $len i32)
+            local.get $ptr
+            global.get $freed
+            i32.eq
+            if
+              unreachable
+            end
+            local.get $ptr
+            global.set $freed)
+          (func (export "transform") (param $ptr i32) (param $len i32) (result i64)
+            local.get $ptr
+            i64.extend_i32_u
+            i64.const 32
+            i64.shl
+            local.get $len
+            i64.extend_i32_u
+            i64.or))
+    "#;
+
+    const RENDER_HELLO: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (data (i32.const 0) "hello")
+          (func (export "alloc") (param $len i32) (result i32)
+            i32.const 1024)
+          (func (export "render") (param $ptr i32) (param $len i32) (result i64)
+            i64.const 5))
+    "#;
+
+    #[test]
+    fn test_identity_transform() {
+        let mut plugin = WasmPlugin::from_bytes(IDENTITY.as_bytes()).unwrap();
+        assert!(plugin.has_transform());
+        assert!(!plugin.has_render());
+
+        let rows = vec![json!({"id": 1}), json!({"id": 2})];
+        let result = plugin.transform_rows(rows.clone()).unwrap();
+        assert_eq!(result, rows);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut plugin = WasmPlugin::from_bytes(RENDER_HELLO.as_bytes()).unwrap();
+        assert!(plugin.has_render());
+
+        let output = plugin.render(&[json!({"id": 1})]).unwrap();
+        assert_eq!(output, "hello");
+    }
+
+    #[test]
+    fn test_looping_plugin_runs_out_of_fuel() {
+        let wat = r#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "alloc") (param $len i32) (result i32)
+                i32.const 0)
+              (func (export "transform") (param $ptr i32) (param $len i32) (result i64)
+                (loop $forever (br $forever))
+                i64.const 0))
+        "#;
+        let mut plugin = WasmPlugin::from_bytes(wat.as_bytes())
+            .unwrap()
+            .with_fuel(100_000);
+        let err = plugin.transform_rows(vec![json!({"id": 1})]).unwrap_err();
+        assert!(err.to_string().contains("out of fuel"), "{}", err);
+    }
+
+    #[test]
+    fn test_missing_exports_rejected() {
+        let wat = r#"(module (memory (export "memory") 1))"#;
+        assert!(WasmPlugin::from_bytes(wat.as_bytes()).is_err());
+    }
+}
@@ -1,22 +1,93 @@
+use super::TableRenderer;
 use crate::cli::TableStyle;
-use crate::core::{FlatTableData, TableData};
-use comfy_table::{presets, ContentArrangement, Table};
+use crate::core::{
+    display_width, placeholder, CellStyle, FlatTableData, Formatter, SchemaInferrer, StyleColor,
+    TableData,
+};
+use comfy_table::{
+    presets, Attribute, Cell, Color, ColumnConstraint, ContentArrangement, Table, Width,
+};
 use serde_json::Value;
 
+/// Table width used for `--wrap` when output is not a terminal and no column cap is set
+const DEFAULT_WRAP_WIDTH: u16 = 120;
+
+/// Narrowest width a column is shrunk to before columns are dropped
+const MIN_COLUMN_WIDTH: usize = 8;
+
+/// Header of the field-name column in transposed tables
+const TRANSPOSED_FIELD_HEADER: &str = "field";
+
+/// Cell texts of a table body, each with its style
+type StyledRows = Vec<Vec<(String, CellStyle)>>;
+
+/// comfy-table presets for styles it doesn't ship
+const PSQL: &str = "     -+ |          ";
+const ORG: &str = "||  |-+||          ";
+const GRID: &str = "││──╞═╪╡│─┼├┤┬┴┌┐└┘";
+
 pub struct CatRenderer {
     style: TableStyle,
+    formatter: Formatter,
+    wrap: bool,
+    color: bool,
+    transpose: bool,
+    header: bool,
+    terminal_width: Option<usize>,
 }
 
 impl CatRenderer {
     pub fn new(style: TableStyle) -> Self {
-        Self { style }
+        Self {
+            style,
+            formatter: Formatter::default(),
+            wrap: false,
+            color: false,
+            transpose: false,
+            header: true,
+            terminal_width: None,
+        }
     }
 
-    pub fn render(&self, table_data: &TableData) -> String {
-        if table_data.is_empty() {
-            return String::new();
-        }
+    /// Print the header row; off for piping rows into other tools
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Fit tables within this many terminal columns, truncating and dropping
+    /// columns that don't fit. Ignored with `--wrap` and markup styles.
+    pub fn with_terminal_width(mut self, width: Option<usize>) -> Self {
+        self.terminal_width = width;
+        self
+    }
+
+    /// Swap rows and columns: one row per field, one column per record
+    pub fn with_transpose(mut self, transpose: bool) -> Self {
+        self.transpose = transpose;
+        self
+    }
+
+    /// Emit cell styles as ANSI colors. Styles are dropped when disabled.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
 
+    /// Wrap long cells onto multiple lines instead of truncating them.
+    /// Cells wrap at `--max-col-width` if set, otherwise to fit the table width.
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Apply conditional formatting rules to rendered cells
+    pub fn with_formatter(mut self, formatter: Formatter) -> Self {
+        self.formatter = formatter;
+        self
+    }
+
+    fn render_table(&self, columns: &[String], rows: &[Vec<Value>]) -> String {
         let mut table = Table::new();
 
         // Apply style
@@ -25,58 +96,445 @@ impl CatRenderer {
             TableStyle::Rounded => table.load_preset(presets::UTF8_FULL),
             TableStyle::Markdown => table.load_preset(presets::ASCII_MARKDOWN),
             TableStyle::Plain => table.load_preset(presets::NOTHING),
+            TableStyle::Psql => table.load_preset(PSQL),
+            TableStyle::Grid => table.load_preset(GRID),
+            TableStyle::Org => table.load_preset(ORG),
+            // Jira headers use a different separator than rows
+            TableStyle::Jira => return self.render_jira(columns, rows),
+        };
+
+        let header = self.header_texts(columns, rows);
+        let styled = self.color && !self.formatter.is_empty();
+        let body: StyledRows = rows
+            .iter()
+            .map(|row| {
+                let styles = if styled {
+                    self.formatter.cell_styles(columns, row)
+                } else {
+                    vec![CellStyle::default(); columns.len()]
+                };
+                self.row_texts(columns, row).zip(styles).collect()
+            })
+            .collect();
+        let (header, body) = if self.transpose {
+            transpose(header, body, |text| (text, CellStyle::default()))
+        } else {
+            (header, body)
+        };
+
+        // Shrink or drop columns so rows don't wrap across terminal lines;
+        // widths pinned with --widths are kept as given
+        let (header, body, hidden) = match self.terminal_width {
+            Some(width)
+                if !self.wrap && !self.is_markup() && !self.formatter.has_pinned_widths() =>
+            {
+                self.fit(header, body, width)
+            }
+            _ => (header, body, 0),
         };
 
-        table.set_content_arrangement(ContentArrangement::Dynamic);
+        // Markup rows must stay on one line, so cells are never wrapped
+        let markup = self.is_markup();
+        table.set_content_arrangement(if markup {
+            ContentArrangement::Disabled
+        } else {
+            ContentArrangement::Dynamic
+        });
+        if self.color {
+            // Color was already decided by the central policy, even if stdout isn't a TTY
+            table.enforce_styling();
+        }
 
         // Add header
-        table.set_header(table_data.columns());
+        let column_count = header.len();
+        if self.header {
+            table.set_header(header);
+        }
+
+        if markup {
+            // Values are already truncated to --max-col-width
+        } else if let Some(max) = self.formatter.max_width() {
+            // Leave room for the cell padding on both sides
+            let width = Width::Fixed(max.saturating_add(2).min(u16::MAX as usize) as u16);
+            table.set_constraints(vec![ColumnConstraint::UpperBoundary(width); column_count]);
+        } else if self.wrap && table.width().is_none() {
+            table.set_width(DEFAULT_WRAP_WIDTH);
+        }
 
         // Add rows
-        for row in table_data.rows() {
-            let cells: Vec<String> = row.iter().map(|v| self.format_value(v)).collect();
+        for row in body {
+            let cells: Vec<Cell> = row
+                .into_iter()
+                .map(|(text, style)| styled_cell(text, &style))
+                .collect();
             table.add_row(cells);
         }
 
-        table.to_string()
+        // Pinned widths are exact, padding aside; transposed tables have a
+        // column per record instead
+        if !markup && !self.transpose {
+            for (i, column) in columns.iter().enumerate().take(column_count) {
+                let Some(pinned) = self.formatter.pinned_width(column) else {
+                    continue;
+                };
+                let width = Width::Fixed(pinned.saturating_add(2).min(u16::MAX as usize) as u16);
+                if let Some(col) = table.column_mut(i) {
+                    col.set_constraint(ColumnConstraint::Absolute(width));
+                }
+            }
+        }
+
+        let mut output = table.to_string();
+        if hidden > 0 {
+            let noun = if hidden == 1 { "column" } else { "columns" };
+            output.push_str(&format!("\n+{} more {}", hidden, noun));
+        }
+        output
+    }
+
+    /// Fit the table within `width` terminal columns: long columns are truncated
+    /// to a fair share of the width, and trailing columns are dropped when even
+    /// the narrowest layout doesn't fit. Returns the number of dropped columns.
+    fn fit(
+        &self,
+        mut header: Vec<String>,
+        mut body: StyledRows,
+        width: usize,
+    ) -> (Vec<String>, StyledRows, usize) {
+        let natural: Vec<usize> = header
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                body.iter()
+                    .map(|row| text_width(&row[i].0))
+                    .chain(self.header.then(|| text_width(name)))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let widths = fit_columns(&natural, width, |n| self.frame_width(n));
+        let hidden = header.len() - widths.len();
+
+        header.truncate(widths.len());
+        for (name, &max) in header.iter_mut().zip(&widths) {
+            *name = self.truncate_lines(std::mem::take(name), max);
+        }
+        for row in &mut body {
+            row.truncate(widths.len());
+            for ((text, _), &max) in row.iter_mut().zip(&widths) {
+                *text = self.truncate_lines(std::mem::take(text), max);
+            }
+        }
+
+        (header, body, hidden)
+    }
+
+    /// Characters taken by borders, separators and padding for `columns` columns
+    fn frame_width(&self, columns: usize) -> usize {
+        let lines = match self.style {
+            TableStyle::Ascii | TableStyle::Rounded | TableStyle::Grid | TableStyle::Org => {
+                columns + 1
+            }
+            TableStyle::Psql => columns.saturating_sub(1),
+            TableStyle::Plain | TableStyle::Markdown | TableStyle::Jira => 0,
+        };
+        lines + columns * 2
+    }
+
+    fn truncate_lines(&self, text: String, max: usize) -> String {
+        if text_width(&text) <= max {
+            return text;
+        }
+        text.lines()
+            .map(|line| self.formatter.truncate_to(line.to_string(), max))
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 
-    pub fn render_flat(&self, table_data: &FlatTableData) -> String {
+    /// Jira wiki markup: `||` separates header cells and `|` separates row cells
+    fn render_jira(&self, columns: &[String], rows: &[Vec<Value>]) -> String {
+        let header = self.header_texts(columns, rows);
+        let body: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| self.row_texts(columns, row).collect())
+            .collect();
+        let (header, body) = if self.transpose {
+            transpose(header, body, |text| text)
+        } else {
+            (header, body)
+        };
+
+        let mut lines = Vec::new();
+        if self.header {
+            lines.push(format!("|| {} ||", header.join(" || ")));
+        }
+        for cells in body {
+            lines.push(format!("| {} |", cells.join(" | ")));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Displayed, escaped text of each column header, typed by the cells
+    /// below it with `--show-types`
+    fn header_texts(&self, columns: &[String], rows: &[Vec<Value>]) -> Vec<String> {
+        let schema = self
+            .formatter
+            .shows_types()
+            .then(|| SchemaInferrer::infer_cells(columns, rows));
+        columns
+            .iter()
+            .map(|c| self.escape(&self.formatter.typed_header_text(c, schema.as_ref())))
+            .collect()
+    }
+
+    /// Displayed, escaped text of each cell in a row
+    fn row_texts<'a>(
+        &'a self,
+        columns: &'a [String],
+        row: &'a [Value],
+    ) -> impl Iterator<Item = String> + 'a {
+        columns
+            .iter()
+            .zip(row.iter())
+            .map(|(col, v)| self.escape(&self.cell_text(col, v)))
+    }
+
+    /// Styles that produce line-based markup for pasting into other tools
+    fn is_markup(&self) -> bool {
+        matches!(
+            self.style,
+            TableStyle::Markdown | TableStyle::Org | TableStyle::Jira
+        )
+    }
+
+    /// Escape cell separators and line breaks for markup styles
+    fn escape(&self, text: &str) -> String {
+        match self.style {
+            TableStyle::Markdown => escape_markdown(text),
+            TableStyle::Org => escape_org(text),
+            TableStyle::Jira => escape_jira(text),
+            TableStyle::Ascii
+            | TableStyle::Rounded
+            | TableStyle::Plain
+            | TableStyle::Psql
+            | TableStyle::Grid => text.to_string(),
+        }
+    }
+
+    fn cell_text(&self, column: &str, value: &Value) -> String {
+        let text = self
+            .formatter
+            .cell_text(column, value)
+            .unwrap_or_else(|| format_value(value));
+        let text = self.formatter.sanitize(text);
+        if self.wrap && !self.is_markup() {
+            // comfy-table wraps the full text within the column constraints
+            text
+        } else {
+            self.formatter.truncate_cell(column, text)
+        }
+    }
+}
+
+impl TableRenderer for CatRenderer {
+    fn render(&self, table_data: &TableData) -> String {
         if table_data.is_empty() {
             return String::new();
         }
 
-        let mut table = Table::new();
+        self.render_table(table_data.columns(), table_data.rows())
+    }
 
-        match self.style {
-            TableStyle::Ascii => table.load_preset(presets::ASCII_FULL),
-            TableStyle::Rounded => table.load_preset(presets::UTF8_FULL),
-            TableStyle::Markdown => table.load_preset(presets::ASCII_MARKDOWN),
-            TableStyle::Plain => table.load_preset(presets::NOTHING),
-        };
+    fn render_flat(&self, table_data: &FlatTableData) -> String {
+        if table_data.is_empty() {
+            return String::new();
+        }
 
-        table.set_content_arrangement(ContentArrangement::Dynamic);
+        self.render_table(&table_data.columns(), table_data.rows())
+    }
+}
 
-        table.set_header(table_data.columns());
+/// Cell text for a value, with placeholders for nested structures
+pub(super) fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(_) | Value::Object(_) => placeholder(value),
+    }
+}
 
-        for row in table_data.rows() {
-            let cells: Vec<String> = row.iter().map(|v| self.format_value(v)).collect();
-            table.add_row(cells);
+/// Displayed width of the widest line of a cell
+fn text_width(text: &str) -> usize {
+    text.lines().map(display_width).max().unwrap_or(0)
+}
+
+/// Column widths that fit `natural` widths within `available` characters, given
+/// the frame width for a column count. Narrow columns keep their natural width
+/// and the rest share what's left; trailing columns are dropped if needed, so
+/// the result may be shorter than `natural`.
+fn fit_columns(natural: &[usize], available: usize, frame: impl Fn(usize) -> usize) -> Vec<usize> {
+    for kept in (1..=natural.len()).rev() {
+        let natural = &natural[..kept];
+        let budget = available.saturating_sub(frame(kept));
+        if natural.iter().sum::<usize>() <= budget {
+            return natural.to_vec();
+        }
+
+        let minimums: usize = natural.iter().map(|&w| w.min(MIN_COLUMN_WIDTH)).sum();
+        if minimums > budget && kept > 1 {
+            continue;
         }
 
-        table.to_string()
+        // Hand out the budget from the narrowest column up
+        let mut order: Vec<usize> = (0..kept).collect();
+        order.sort_by_key(|&i| natural[i]);
+        let mut widths = vec![0; kept];
+        let mut remaining = budget;
+        for (placed, &i) in order.iter().enumerate() {
+            let share = remaining / (kept - placed);
+            let width = natural[i].min(share).max(natural[i].min(MIN_COLUMN_WIDTH));
+            widths[i] = width;
+            remaining = remaining.saturating_sub(width);
+        }
+        return widths;
+    }
+    Vec::new()
+}
+
+/// Swap the rows and columns of a table. Field names become the first column
+/// and records are numbered from 1.
+fn transpose<T>(
+    header: Vec<String>,
+    body: Vec<Vec<T>>,
+    cell: impl Fn(String) -> T,
+) -> (Vec<String>, Vec<Vec<T>>) {
+    let transposed_header = std::iter::once(TRANSPOSED_FIELD_HEADER.to_string())
+        .chain((1..=body.len()).map(|i| i.to_string()))
+        .collect();
+
+    let mut records: Vec<_> = body.into_iter().map(Vec::into_iter).collect();
+    let transposed_body = header
+        .into_iter()
+        .map(|field| {
+            std::iter::once(cell(field))
+                .chain(records.iter_mut().filter_map(Iterator::next))
+                .collect()
+        })
+        .collect();
+
+    (transposed_header, transposed_body)
+}
+
+/// Escape text for a GitHub-flavored Markdown table cell: backslashes and pipes
+/// are backslash-escaped and line breaks become `<br>`
+pub(super) fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '|' => escaped.push_str("\\|"),
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                escaped.push_str("<br>");
+            }
+            '\n' => escaped.push_str("<br>"),
+            _ => escaped.push(c),
+        }
     }
+    escaped
+}
 
-    fn format_value(&self, value: &Value) -> String {
-        match value {
-            Value::Null => "null".to_string(),
-            Value::Bool(b) => b.to_string(),
-            Value::Number(n) => n.to_string(),
-            Value::String(s) => s.clone(),
-            Value::Array(_) => "[...]".to_string(),
-            Value::Object(_) => "{...}".to_string(),
+/// Escape text for an org-mode table cell, which has no line breaks or literal pipes
+fn escape_org(text: &str) -> String {
+    join_lines(text, " ").replace('|', "\\vert{}")
+}
+
+/// Escape text for a Jira table cell: pipes are backslash-escaped and line breaks become `\\`
+fn escape_jira(text: &str) -> String {
+    join_lines(&text.replace('|', "\\|"), " \\\\ ")
+}
+
+fn join_lines(text: &str, separator: &str) -> String {
+    text.lines().collect::<Vec<_>>().join(separator)
+}
+
+/// Suggest flags for viewing the nested values behind `{3 keys}` / `[12 items]` placeholders,
+/// one hint per column that holds them
+pub fn drill_down_hints(table_data: &TableData) -> Vec<String> {
+    let mut hints = Vec::new();
+
+    for (idx, column) in table_data.columns().iter().enumerate() {
+        let nested = table_data
+            .rows()
+            .iter()
+            .filter_map(|row| row.get(idx))
+            .find(|v| v.is_object() || v.is_array());
+
+        // Shown as the column's first nested cell reads in the table
+        match nested {
+            Some(value @ Value::Object(obj)) => match obj.keys().next() {
+                Some(key) => hints.push(format!(
+                    "{}: {} (try: --flat, or -c '{}.{}')",
+                    column,
+                    placeholder(value),
+                    column,
+                    key
+                )),
+                None => hints.push(format!("{}: {} (try: --flat)", column, placeholder(value))),
+            },
+            Some(value @ Value::Array(_)) => hints.push(format!(
+                "{}: {} (try: -c '{}' -r)",
+                column,
+                placeholder(value),
+                column
+            )),
+            _ => {}
         }
     }
+
+    hints
+}
+
+fn styled_cell(text: String, style: &CellStyle) -> Cell {
+    let mut cell = Cell::new(text);
+    if let Some(fg) = style.fg {
+        cell = cell.fg(to_color(fg));
+    }
+    if let Some(bg) = style.bg {
+        cell = cell.bg(to_color(bg));
+    }
+    if style.bold {
+        cell = cell.add_attribute(Attribute::Bold);
+    }
+    if style.italic {
+        cell = cell.add_attribute(Attribute::Italic);
+    }
+    if style.underline {
+        cell = cell.add_attribute(Attribute::Underlined);
+    }
+    if style.dim {
+        cell = cell.add_attribute(Attribute::Dim);
+    }
+    cell
+}
+
+fn to_color(color: StyleColor) -> Color {
+    match color {
+        StyleColor::Black => Color::Black,
+        StyleColor::Red => Color::Red,
+        StyleColor::Green => Color::Green,
+        StyleColor::Yellow => Color::Yellow,
+        StyleColor::Blue => Color::Blue,
+        StyleColor::Magenta => Color::Magenta,
+        StyleColor::Cyan => Color::Cyan,
+        StyleColor::White => Color::White,
+        StyleColor::Gray => Color::Grey,
+    }
 }
 
 #[cfg(test)]
@@ -84,6 +542,36 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_render_without_header() {
+        let rows = vec![json!({"id": 1, "name": "Alice"})];
+        let table_data = TableData::from_rows(rows, None);
+        let formatter = Formatter::default().with_header_case(Some(crate::core::HeaderCase::Upper));
+
+        let output = CatRenderer::new(TableStyle::Plain)
+            .with_formatter(formatter.clone())
+            .render(&table_data);
+        assert!(output.contains("NAME"));
+
+        let output = CatRenderer::new(TableStyle::Plain)
+            .with_formatter(formatter)
+            .with_header(false)
+            .render(&table_data);
+        assert!(!output.contains("NAME"));
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("Alice"));
+    }
+
+    #[test]
+    fn test_render_jira_without_header() {
+        let rows = vec![json!({"id": 1})];
+        let table_data = TableData::from_rows(rows, None);
+        let output = CatRenderer::new(TableStyle::Jira)
+            .with_header(false)
+            .render(&table_data);
+        assert_eq!(output, "| 1 |");
+    }
+
     #[test]
     fn test_render_simple_table() {
         let rows = vec![
@@ -120,7 +608,7 @@ mod tests {
 
         let output = renderer.render(&table_data);
 
-        assert!(output.contains("{...}"));
+        assert!(output.contains("{1 key}"));
     }
 
     #[test]
@@ -131,7 +619,7 @@ mod tests {
 
         let output = renderer.render(&table_data);
 
-        assert!(output.contains("[...]"));
+        assert!(output.contains("[3 items]"));
     }
 
     #[test]
@@ -157,6 +645,137 @@ mod tests {
         assert!(output.contains("-"));
     }
 
+    #[test]
+    fn test_drill_down_hints() {
+        let rows = vec![json!({"id": 1, "user": {"name": "Alice"}, "orders": [1, 2], "meta": {}})];
+        let table_data = TableData::from_rows(rows, None);
+
+        let hints = drill_down_hints(&table_data);
+
+        // Hints follow the column order, which is by key
+        assert_eq!(
+            hints,
+            vec![
+                "meta: {} (try: --flat)",
+                "orders: [2 items] (try: -c 'orders' -r)",
+                "user: {1 key} (try: --flat, or -c 'user.name')",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drill_down_hints_none() {
+        let rows = vec![json!({"id": 1, "name": "Alice"})];
+        let table_data = TableData::from_rows(rows, None);
+        assert!(drill_down_hints(&table_data).is_empty());
+    }
+
+    #[test]
+    fn test_render_with_formatter() {
+        use crate::core::FormatRule;
+
+        let rows = vec![
+            json!({"id": 1, "latency": 1500}),
+            json!({"id": 2, "latency": 20}),
+        ];
+        let table_data = TableData::from_rows(rows, None);
+        let formatter = Formatter::new(vec![FormatRule::parse(
+            "latency>1000",
+            "bold red",
+            vec!["latency".into()],
+        )
+        .unwrap()]);
+        let renderer = CatRenderer::new(TableStyle::Rounded).with_formatter(formatter);
+
+        let output = renderer.render(&table_data);
+
+        assert!(output.contains("1500"));
+        assert!(output.contains("20"));
+    }
+
+    #[test]
+    fn test_render_color_policy() {
+        use crate::core::FormatRule;
+
+        let rows = vec![json!({"id": 1, "latency": 1500})];
+        let table_data = TableData::from_rows(rows, None);
+        let formatter = Formatter::new(vec![
+            FormatRule::parse("latency>1000", "red", vec![]).unwrap()
+        ]);
+
+        let plain = CatRenderer::new(TableStyle::Rounded)
+            .with_formatter(formatter.clone())
+            .render(&table_data);
+        assert!(!plain.contains('\x1b'));
+
+        let colored = CatRenderer::new(TableStyle::Rounded)
+            .with_formatter(formatter)
+            .with_color(true)
+            .render(&table_data);
+        assert!(colored.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_render_with_max_width() {
+        let rows = vec![json!({"id": 1, "message": "a very long log message indeed"})];
+        let table_data = TableData::from_rows(rows, None);
+        let formatter = Formatter::default().with_max_width(Some(10));
+        let renderer = CatRenderer::new(TableStyle::Rounded).with_formatter(formatter);
+
+        let output = renderer.render(&table_data);
+
+        assert!(output.contains("a very lo…"));
+        assert!(!output.contains("indeed"));
+    }
+
+    #[test]
+    fn test_render_escapes_control_characters() {
+        let rows = vec![json!({"message": "\u{1b}[2Jwiped\rover"})];
+        let table_data = TableData::from_rows(rows, None);
+
+        let output = CatRenderer::new(TableStyle::Rounded).render(&table_data);
+        assert!(output.contains("\\u{1b}[2Jwiped\\rover"));
+        assert!(!output.contains('\u{1b}'));
+
+        let raw = CatRenderer::new(TableStyle::Rounded)
+            .with_formatter(Formatter::default().with_raw_control(true))
+            .render(&table_data);
+        assert!(raw.contains("\u{1b}[2J"));
+    }
+
+    #[test]
+    fn test_render_with_wrap() {
+        let rows = vec![json!({"id": 1, "message": "alpha beta gamma delta epsilon zeta"})];
+        let table_data = TableData::from_rows(rows, None);
+        let formatter = Formatter::default().with_max_width(Some(12));
+        let renderer = CatRenderer::new(TableStyle::Rounded)
+            .with_formatter(formatter)
+            .with_wrap(true);
+
+        let output = renderer.render(&table_data);
+
+        // Nothing is cut off; the message spans several lines instead
+        assert!(!output.contains('…'));
+        assert!(output.contains("zeta"));
+        assert!(output.lines().count() > 5);
+    }
+
+    #[test]
+    fn test_render_with_date_format() {
+        use crate::core::TimeFormat;
+
+        let rows = vec![json!({"id": 1, "ts": "2024-01-15T10:30:00Z"})];
+        let table_data = TableData::from_rows(rows, None);
+        let formatter = Formatter::default()
+            .with_time_format(TimeFormat::parse(&["%d.%m.%Y".to_string()]).unwrap());
+        let renderer = CatRenderer::new(TableStyle::Plain).with_formatter(formatter);
+
+        let output = renderer.render(&table_data);
+
+        assert!(output.contains("15.01.2024"));
+        assert!(!output.contains("10:30"));
+    }
+
     #[test]
     fn test_render_markdown_style() {
         let rows = vec![json!({"id": 1})];
@@ -167,4 +786,217 @@ mod tests {
 
         assert!(output.contains("|"));
     }
+
+    #[test]
+    fn test_render_markdown_escapes_pipes_and_newlines() {
+        let rows =
+            vec![json!({"a|b": "x | y", "note": "line1\nline2\r\nline3", "path": "C:\\tmp"})];
+        let table_data = TableData::from_rows(rows, None);
+        let renderer = CatRenderer::new(TableStyle::Markdown);
+
+        let output = renderer.render(&table_data);
+
+        assert!(output.contains(r"a\|b"));
+        assert!(output.contains(r"x \| y"));
+        assert!(output.contains("line1<br>line2<br>line3"));
+        assert!(output.contains(r"C:\\tmp"));
+        // Header, separator and one data row
+        assert_eq!(output.lines().count(), 3);
+        for line in output.lines() {
+            let unescaped_pipes = line
+                .replace(r"\\", "")
+                .replace(r"\|", "")
+                .matches('|')
+                .count();
+            assert_eq!(unescaped_pipes, 4, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_escape_markdown() {
+        assert_eq!(escape_markdown("plain"), "plain");
+        assert_eq!(escape_markdown(r"a\|b"), r"a\\\|b");
+        assert_eq!(escape_markdown("a\rb"), "a<br>b");
+    }
+
+    fn styled_output(style: TableStyle) -> String {
+        let rows = vec![
+            json!({"id": 1, "name": "Alice"}),
+            json!({"id": 2, "name": "Bob"}),
+        ];
+        let table_data = TableData::from_rows(rows, None);
+        CatRenderer::new(style).render(&table_data)
+    }
+
+    #[test]
+    fn test_render_psql_style() {
+        let output = styled_output(TableStyle::Psql);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("id | name"));
+        assert!(lines[1].starts_with("----+"));
+        assert!(!lines[2].starts_with('|'));
+    }
+
+    #[test]
+    fn test_render_grid_style() {
+        let output = styled_output(TableStyle::Grid);
+
+        assert!(output.starts_with('┌'));
+        assert!(output.contains('╪'));
+        assert!(output.contains('┼'));
+    }
+
+    #[test]
+    fn test_render_org_style() {
+        let output = styled_output(TableStyle::Org);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("|----+"));
+        assert!(lines[1].ends_with('|'));
+        assert!(lines[2].starts_with("| 1 "));
+    }
+
+    #[test]
+    fn test_render_jira_style() {
+        let rows = vec![json!({"id": 1, "name": "a|b\nc"})];
+        let table_data = TableData::from_rows(rows, None);
+        let output = CatRenderer::new(TableStyle::Jira).render(&table_data);
+
+        assert_eq!(output, "|| id || name ||\n| 1 | a\\|b \\\\ c |");
+    }
+
+    #[test]
+    fn test_render_show_types() {
+        let rows = vec![
+            json!({"age": 30, "name": "Alice"}),
+            json!({"age": 41, "tags": ["a"]}),
+        ];
+        let table_data = TableData::from_rows(rows, None);
+        let formatter = Formatter::default().with_show_types(true);
+        let output = CatRenderer::new(TableStyle::Jira)
+            .with_formatter(formatter)
+            .render(&table_data);
+
+        assert!(
+            output.starts_with("|| age:number || name:string? || tags:array? ||"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_escape_org() {
+        assert_eq!(escape_org("a|b\nc"), r"a\vert{}b c");
+    }
+
+    #[test]
+    fn test_render_transposed() {
+        let rows = vec![
+            json!({"id": 1, "name": "Alice"}),
+            json!({"id": 2, "name": "Bob"}),
+        ];
+        let table_data = TableData::from_rows(rows, None);
+        let renderer = CatRenderer::new(TableStyle::Jira).with_transpose(true);
+
+        let output = renderer.render(&table_data);
+
+        assert_eq!(
+            output,
+            "|| field || 1 || 2 ||\n| id | 1 | 2 |\n| name | Alice | Bob |"
+        );
+    }
+
+    #[test]
+    fn test_render_transposed_keeps_column_formatting() {
+        use crate::core::TimeFormat;
+
+        let rows = vec![json!({"created_at": "2024-01-15T10:30:00Z"})];
+        let table_data = TableData::from_rows(rows, None);
+        let formatter = Formatter::default()
+            .with_time_format(TimeFormat::parse(&["%d.%m.%Y".to_string()]).unwrap());
+        let renderer = CatRenderer::new(TableStyle::Plain)
+            .with_formatter(formatter)
+            .with_transpose(true);
+
+        let output = renderer.render(&table_data);
+
+        assert!(output.contains("created_at"));
+        assert!(output.contains("15.01.2024"));
+    }
+
+    fn frame(columns: usize) -> usize {
+        columns * 3 + 1
+    }
+
+    #[test]
+    fn test_fit_columns_keeps_natural_widths_when_they_fit() {
+        assert_eq!(fit_columns(&[2, 5, 10], 40, frame), vec![2, 5, 10]);
+    }
+
+    #[test]
+    fn test_fit_columns_shrinks_wide_columns() {
+        // 40 - frame(3) = 30 characters for content
+        let widths = fit_columns(&[2, 50, 60], 40, frame);
+        assert_eq!(widths[0], 2);
+        assert_eq!(widths.iter().sum::<usize>(), 30);
+        assert!(widths[1] >= MIN_COLUMN_WIDTH && widths[2] >= MIN_COLUMN_WIDTH);
+    }
+
+    #[test]
+    fn test_fit_columns_drops_trailing_columns() {
+        // Five columns can't all get MIN_COLUMN_WIDTH in 30 characters
+        let widths = fit_columns(&[20; 5], 30, frame);
+        assert_eq!(widths.len(), 2);
+        assert!(widths.iter().sum::<usize>() + frame(2) <= 30);
+    }
+
+    #[test]
+    fn test_render_pinned_widths() {
+        use crate::core::ColumnWidths;
+
+        let rows = vec![json!({"id": 1, "msg": "hello world, a long message"})];
+        let table_data = TableData::from_rows(rows, None);
+        let formatter = Formatter::default().with_widths(ColumnWidths::parse("msg=8").unwrap());
+        let output = CatRenderer::new(TableStyle::Ascii)
+            .with_formatter(formatter)
+            .with_terminal_width(Some(20))
+            .render(&table_data);
+
+        assert_eq!(output.lines().next(), Some("+----+----------+"));
+        assert!(output.contains("hello w…"), "{}", output);
+    }
+
+    #[test]
+    fn test_render_fits_terminal_width() {
+        let long = "x".repeat(100);
+        let rows = vec![json!({"id": 1, "a": long, "b": long, "c": long, "d": long, "e": long})];
+        let table_data = TableData::from_rows(rows, None);
+        let renderer = CatRenderer::new(TableStyle::Rounded).with_terminal_width(Some(40));
+
+        let output = renderer.render(&table_data);
+        let (table, note) = output.rsplit_once('\n').unwrap();
+
+        for line in table.lines() {
+            assert!(line.chars().count() <= 40, "{}", line);
+        }
+        assert!(table.contains('…'));
+        assert_eq!(note, "+3 more columns");
+    }
+
+    #[test]
+    fn test_render_ignores_terminal_width_when_wrapping() {
+        let rows = vec![json!({"a": "x".repeat(100)})];
+        let table_data = TableData::from_rows(rows, None);
+        let renderer = CatRenderer::new(TableStyle::Plain)
+            .with_terminal_width(Some(40))
+            .with_wrap(true);
+
+        let output = renderer.render(&table_data);
+
+        assert!(!output.contains('…'));
+        assert!(!output.contains("more column"));
+    }
 }
@@ -0,0 +1,225 @@
+use super::cat::escape_markdown;
+use crate::cli::OutputFormat;
+use serde_json::Value;
+
+/// File extension for tables written in `format`
+pub fn extension(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Csv => "csv",
+        OutputFormat::Json => "json",
+        OutputFormat::Jsonl => "jsonl",
+        OutputFormat::Markdown => "md",
+    }
+}
+
+/// Format of a file with extension `ext`, the reverse of [`extension`]
+pub fn format_for_extension(ext: &str) -> Option<OutputFormat> {
+    match ext.to_ascii_lowercase().as_str() {
+        "csv" => Some(OutputFormat::Csv),
+        "json" => Some(OutputFormat::Json),
+        "jsonl" | "ndjson" => Some(OutputFormat::Jsonl),
+        "md" | "markdown" => Some(OutputFormat::Markdown),
+        _ => None,
+    }
+}
+
+/// Serialize a table in `format`; `compact` puts a JSON array on one line
+pub fn serialize(
+    format: OutputFormat,
+    compact: bool,
+    columns: &[String],
+    rows: &[Vec<Value>],
+) -> String {
+    match format {
+        OutputFormat::Csv => to_csv(columns, rows),
+        OutputFormat::Json => to_json(columns, rows, compact),
+        OutputFormat::Jsonl => to_jsonl(columns, rows),
+        OutputFormat::Markdown => to_markdown(columns, rows),
+    }
+}
+
+/// Serialize a table as CSV (RFC 4180), with a header line
+pub fn to_csv(columns: &[String], rows: &[Vec<Value>]) -> String {
+    let mut out = String::new();
+    push_csv_line(&mut out, columns.iter().map(String::as_str));
+    for row in rows {
+        let fields: Vec<String> = (0..columns.len())
+            .map(|i| text_field(row.get(i).unwrap_or(&Value::Null)))
+            .collect();
+        push_csv_line(&mut out, fields.iter().map(String::as_str));
+    }
+    out
+}
+
+/// Serialize a table as one JSON object per line, keys in column order
+pub fn to_jsonl(columns: &[String], rows: &[Vec<Value>]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        out.push_str(&json_object(columns, row));
+        out.push('\n');
+    }
+    out
+}
+
+/// Serialize a table as a JSON array of objects, keys in column order,
+/// indented two spaces per level unless `compact`
+pub fn to_json(columns: &[String], rows: &[Vec<Value>], compact: bool) -> String {
+    if compact {
+        let objects: Vec<String> = rows.iter().map(|row| json_object(columns, row)).collect();
+        return format!("[{}]\n", objects.join(","));
+    }
+    if rows.is_empty() {
+        return "[]\n".to_string();
+    }
+
+    let objects: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            if columns.is_empty() {
+                return "  {}".to_string();
+            }
+            let fields: Vec<String> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    let value = row.get(i).unwrap_or(&Value::Null);
+                    let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+                    format!(
+                        "    {}: {}",
+                        Value::String(column.clone()),
+                        pretty.replace('\n', "\n    ")
+                    )
+                })
+                .collect();
+            format!("  {{\n{}\n  }}", fields.join(",\n"))
+        })
+        .collect();
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
+
+/// One row as a compact JSON object, keys in column order
+fn json_object(columns: &[String], row: &[Value]) -> String {
+    let mut out = String::from("{");
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&Value::String(column.clone()).to_string());
+        out.push(':');
+        out.push_str(&row.get(i).unwrap_or(&Value::Null).to_string());
+    }
+    out.push('}');
+    out
+}
+
+/// Serialize a table as a GitHub-flavored Markdown table
+pub fn to_markdown(columns: &[String], rows: &[Vec<Value>]) -> String {
+    let mut out = String::new();
+    push_markdown_line(&mut out, columns.iter().map(|c| escape_markdown(c)));
+    push_markdown_line(&mut out, columns.iter().map(|_| "---".to_string()));
+    for row in rows {
+        push_markdown_line(
+            &mut out,
+            (0..columns.len())
+                .map(|i| escape_markdown(&text_field(row.get(i).unwrap_or(&Value::Null)))),
+        );
+    }
+    out
+}
+
+fn push_markdown_line(out: &mut String, cells: impl Iterator<Item = String>) {
+    out.push('|');
+    for cell in cells {
+        out.push(' ');
+        out.push_str(&cell);
+        out.push_str(" |");
+    }
+    out.push('\n');
+}
+
+fn push_csv_line<'a>(out: &mut String, fields: impl Iterator<Item = &'a str>) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push('\n');
+}
+
+/// Cell text for CSV and Markdown: strings unquoted, null empty, everything else as JSON
+fn text_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn columns() -> Vec<String> {
+        vec!["id".to_string(), "note".to_string()]
+    }
+
+    #[test]
+    fn test_to_csv_quotes_special_characters() {
+        let rows = vec![
+            vec![json!(1), json!("plain")],
+            vec![json!(2), json!("a, \"b\"\nc")],
+            vec![json!(3), Value::Null],
+        ];
+
+        assert_eq!(
+            to_csv(&columns(), &rows),
+            "id,note\n1,plain\n2,\"a, \"\"b\"\"\nc\"\n3,\n"
+        );
+    }
+
+    #[test]
+    fn test_to_markdown_escapes_cells() {
+        let rows = vec![vec![json!(1), json!("a|b\nc")], vec![json!(2), Value::Null]];
+
+        assert_eq!(
+            to_markdown(&columns(), &rows),
+            "| id | note |\n| --- | --- |\n| 1 | a\\|b<br>c |\n| 2 |  |\n"
+        );
+    }
+
+    #[test]
+    fn test_to_json_pretty_and_compact() {
+        let columns = vec!["z".to_string(), "a".to_string()];
+        let rows = vec![vec![json!(1), json!({"k": [true]})], vec![json!(2)]];
+
+        assert_eq!(
+            to_json(&columns, &rows, false),
+            "[\n  {\n    \"z\": 1,\n    \"a\": {\n      \"k\": [\n        true\n      ]\n    }\n  },\n  {\n    \"z\": 2,\n    \"a\": null\n  }\n]\n"
+        );
+        assert_eq!(
+            to_json(&columns, &rows, true),
+            "[{\"z\":1,\"a\":{\"k\":[true]}},{\"z\":2,\"a\":null}]\n"
+        );
+        assert_eq!(to_json(&columns, &[], false), "[]\n");
+        assert_eq!(to_json(&columns, &[], true), "[]\n");
+    }
+
+    #[test]
+    fn test_to_jsonl_keeps_column_order() {
+        let columns = vec!["z".to_string(), "a".to_string()];
+        let rows = vec![vec![json!(1), json!({"k": true})], vec![json!(2)]];
+
+        assert_eq!(
+            to_jsonl(&columns, &rows),
+            "{\"z\":1,\"a\":{\"k\":true}}\n{\"z\":2,\"a\":null}\n"
+        );
+    }
+}
@@ -1,4 +1,18 @@
 mod cat;
+pub mod export;
+mod pretty;
 pub mod tui;
+mod vertical;
 
-pub use cat::CatRenderer;
+use crate::core::{FlatTableData, TableData};
+
+pub use cat::{drill_down_hints, CatRenderer};
+pub use pretty::render_pretty;
+pub use vertical::VerticalRenderer;
+
+/// Renders tables as text for cat mode
+pub trait TableRenderer {
+    fn render(&self, table_data: &TableData) -> String;
+
+    fn render_flat(&self, table_data: &FlatTableData) -> String;
+}
@@ -0,0 +1,57 @@
+use super::tui::highlight::highlight_ansi;
+use super::vertical::divider;
+use crate::core::display_width;
+use serde_json::Value;
+
+/// Pretty-print each row as JSON under a record divider, like `jq .` with
+/// the dividers of `--vertical`; colored with ANSI escapes when `color`
+pub fn render_pretty(rows: &[Value], color: bool) -> String {
+    let plain: Vec<String> = rows
+        .iter()
+        .map(|row| serde_json::to_string_pretty(row).unwrap_or_default())
+        .collect();
+
+    // Dividers span the widest line, measured without escapes
+    let width = plain
+        .iter()
+        .flat_map(|text| text.lines())
+        .map(display_width)
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for (i, (row, text)) in rows.iter().zip(plain).enumerate() {
+        out.push_str(&divider(i + 1, width));
+        out.push('\n');
+        if color {
+            out.push_str(&highlight_ansi(row));
+        } else {
+            out.push_str(&text);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_pretty_plain() {
+        let rows = vec![json!({"id": 1, "tags": ["a"]}), json!({"id": 2})];
+        assert_eq!(
+            render_pretty(&rows, false),
+            "-[ RECORD 1 ]-\n{\n  \"id\": 1,\n  \"tags\": [\n    \"a\"\n  ]\n}\n\
+             -[ RECORD 2 ]-\n{\n  \"id\": 2\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_render_pretty_color() {
+        let out = render_pretty(&[json!({"name": "Alice"})], true);
+        assert!(out.contains("\x1b[32m\"Alice\"\x1b[0m"));
+        assert!(out.starts_with("-[ RECORD 1 ]"));
+    }
+}
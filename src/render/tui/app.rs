@@ -1,28 +1,110 @@
-use crate::core::{FilterExpr, FlatTableData, FullTextSearch, TableData};
+use super::edit;
+use super::finder::ColumnFinder;
+use super::input::Keymap;
+use crate::core::{
+    ColumnSelector, FilterExpr, FlatConfig, FlatTableData, Formatter, FullTextSearch, Schema,
+    SchemaInferrer, Sorter, TableData,
+};
+use crate::input::SpilledRows;
+use crate::render::export;
 use serde_json::Value;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Where the rows of the table come from
+enum Rows {
+    /// Every row loaded and turned into cells up front
+    Loaded {
+        /// The table data to display
+        table_data: TableData,
+        /// Original JSON records (before flattening)
+        source_records: Vec<Value>,
+    },
+    /// Rows over `--max-memory`, read back from disk and turned into cells
+    /// as they are shown
+    Spilled {
+        columns: Vec<String>,
+        selector: Option<ColumnSelector>,
+        records: RefCell<SpilledRows>,
+    },
+}
+
+/// View changes that can be undone
+const HISTORY_LIMIT: usize = 100;
+
+/// Settings that decide which rows and columns are shown and in what order
+#[derive(Clone)]
+struct ViewState {
+    search_query: String,
+    filter_expr: Option<FilterExpr>,
+    whole_word: bool,
+    sorter: Option<Sorter>,
+    selector: Option<ColumnSelector>,
+    flat: bool,
+}
+
+/// Outcome of a `:` command: a message to show, or why it failed
+type CommandResult = std::result::Result<Option<String>, String>;
 
 /// Application state for TUI mode
 pub struct App {
-    /// The table data to display
-    table_data: TableData,
-    /// Original JSON records (before flattening)
-    source_records: Vec<Value>,
+    /// The rows to display
+    rows: Rows,
     /// Current scroll offset (first visible row)
     scroll_offset: usize,
     /// Currently selected row index (in filtered view)
     selected_row: usize,
+    /// Currently selected column index
+    selected_column: usize,
+    /// First column shown when not every column fits
+    column_offset: usize,
     /// Current input mode
     pub mode: InputMode,
     /// Search query (full text)
     search_query: String,
     /// Filter expression
     filter_expr: Option<FilterExpr>,
+    /// Search and `~` filters match whole words only
+    whole_word: bool,
     /// Indices of rows matching current filter/search
     filtered_indices: Vec<usize>,
+    /// Indices of rows marked for deletion, left out by `:write`
+    deleted: HashSet<usize>,
+    /// Order of the rows shown (`:sort`)
+    sorter: Option<Sorter>,
+    /// Columns of loaded rows (`-c`, `:columns`)
+    selector: Option<ColumnSelector>,
+    /// Loaded rows shown in flat mode (`--flat`, `:flat`)
+    flat: bool,
+    flat_config: FlatConfig,
+    /// Views before each change, most recent last (`u`)
+    undo: Vec<ViewState>,
+    /// Views undone, most recent last (`Ctrl-r`)
+    redo: Vec<ViewState>,
     /// Input buffer for search/filter
     pub input_buffer: String,
     /// State for detail view modal (when in Detail mode)
     detail_state: Option<DetailViewState>,
+    /// Most frequent values of a column (when in TopValues mode)
+    top_values: Option<TopValuesState>,
+    /// Column names Tab is cycling through in the filter prompt
+    completion: Option<Completion>,
+    /// Fuzzy column search (when in FindColumn mode)
+    column_finder: Option<ColumnFinder>,
+    /// Conditional formatting rules for table cells
+    formatter: Formatter,
+    /// Types of the loaded columns, for `--show-types` headers
+    column_types: Option<Schema>,
+    /// Extra key bindings
+    keymap: Keymap,
+    /// JSONL file that cell edits are written back to, when the rows are
+    /// shown exactly as they are in it
+    edit_path: Option<PathBuf>,
+    /// Result of the last action, shown in the footer until the next key
+    status: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,6 +113,47 @@ pub enum InputMode {
     Search,
     Filter,
     Detail,
+    Edit,
+    Command,
+    TopValues,
+    FindColumn,
+}
+
+/// Values shown by the top-values popup
+const TOP_VALUES: usize = 20;
+
+/// Most frequent values of one column among the rows shown
+#[derive(Debug, Clone)]
+pub struct TopValuesState {
+    pub column: String,
+    /// Values with their row counts, most frequent first
+    pub values: Vec<(Value, usize)>,
+    /// Number of different values in the column
+    pub distinct: usize,
+    /// Rows counted
+    pub rows: usize,
+    pub selected: usize,
+}
+
+impl TopValuesState {
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.values.len() {
+            self.selected += 1;
+        }
+    }
+}
+
+/// Column names that complete the word being typed in the filter prompt
+#[derive(Debug, Clone)]
+struct Completion {
+    /// Byte offset in the input where the column name starts
+    start: usize,
+    candidates: Vec<String>,
+    index: usize,
 }
 
 /// State for the detail view modal
@@ -77,47 +200,134 @@ impl DetailViewState {
 
 impl App {
     pub fn new(table_data: TableData, source_records: Vec<Value>) -> Self {
-        let row_count = table_data.rows().len();
-        let filtered_indices: Vec<usize> = (0..row_count).collect();
-
-        Self {
+        Self::with_rows(Rows::Loaded {
             table_data,
             source_records,
-            scroll_offset: 0,
-            selected_row: 0,
-            mode: InputMode::Normal,
-            search_query: String::new(),
-            filter_expr: None,
-            filtered_indices,
-            input_buffer: String::new(),
-            detail_state: None,
-        }
+        })
     }
 
     /// Create App from flat table data (for flat mode TUI)
     pub fn from_flat(flat_data: FlatTableData, source_records: Vec<Value>) -> Self {
         let columns = flat_data.columns();
         let rows: Vec<Vec<Value>> = flat_data.rows().to_vec();
-        let row_count = rows.len();
-        let filtered_indices: Vec<usize> = (0..row_count).collect();
 
-        Self {
+        Self::with_rows(Rows::Loaded {
             table_data: TableData::from_flat_columns_rows(columns, rows),
             source_records,
+        })
+    }
+
+    /// Create App over rows left on disk, shown under `columns`
+    pub fn from_spilled(
+        records: SpilledRows,
+        columns: Vec<String>,
+        selector: Option<ColumnSelector>,
+    ) -> Self {
+        Self::with_rows(Rows::Spilled {
+            columns,
+            selector,
+            records: RefCell::new(records),
+        })
+    }
+
+    fn with_rows(rows: Rows) -> Self {
+        let mut app = Self {
+            rows,
             scroll_offset: 0,
             selected_row: 0,
+            selected_column: 0,
+            column_offset: 0,
             mode: InputMode::Normal,
             search_query: String::new(),
             filter_expr: None,
-            filtered_indices,
+            whole_word: false,
+            filtered_indices: Vec::new(),
+            deleted: HashSet::new(),
+            sorter: None,
+            selector: None,
+            flat: false,
+            flat_config: FlatConfig::default(),
+            undo: Vec::new(),
+            redo: Vec::new(),
             input_buffer: String::new(),
             detail_state: None,
-        }
+            top_values: None,
+            completion: None,
+            column_finder: None,
+            formatter: Formatter::default(),
+            column_types: None,
+            keymap: Keymap::default(),
+            edit_path: None,
+            status: None,
+        };
+        app.filtered_indices = (0..app.row_count()).collect();
+        app
+    }
+
+    /// Apply conditional formatting rules to table cells
+    pub fn with_formatter(mut self, formatter: Formatter) -> Self {
+        self.formatter = formatter;
+        self.infer_column_types();
+        self
+    }
+
+    /// Extra key bindings from the config file
+    pub fn with_keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Record how the loaded table was laid out, so `:columns` and `:flat`
+    /// can lay it out again
+    pub fn with_layout(
+        mut self,
+        selector: Option<ColumnSelector>,
+        flat: Option<FlatConfig>,
+    ) -> Self {
+        self.selector = selector;
+        self.flat = flat.is_some();
+        self.flat_config = flat.unwrap_or_default();
+        self
+    }
+
+    /// Allow editing cells, saving each edit to `path`. Only valid when row
+    /// `i` of the table is row `i` of the file.
+    pub fn with_edit_path(mut self, path: Option<PathBuf>) -> Self {
+        self.edit_path = path;
+        self
     }
 
     // Getters
+    pub fn formatter(&self) -> &Formatter {
+        &self.formatter
+    }
+
+    /// Display text of a table header, with its type under `--show-types`
+    pub fn header_text(&self, column: &str) -> String {
+        self.formatter
+            .typed_header_text(column, self.column_types.as_ref())
+    }
+
+    /// Infer the loaded columns' types once rather than on every frame. Rows
+    /// read from disk are not scanned, so their headers go untyped.
+    fn infer_column_types(&mut self) {
+        self.column_types = match self.rows {
+            Rows::Loaded { ref table_data, .. } if self.formatter.shows_types() => Some(
+                SchemaInferrer::infer_cells(table_data.columns(), table_data.rows()),
+            ),
+            _ => None,
+        };
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
     pub fn columns(&self) -> &[String] {
-        self.table_data.columns()
+        match self.rows {
+            Rows::Loaded { ref table_data, .. } => table_data.columns(),
+            Rows::Spilled { ref columns, .. } => columns,
+        }
     }
 
     pub fn visible_row_count(&self) -> usize {
@@ -128,10 +338,26 @@ impl App {
         self.selected_row
     }
 
+    pub fn selected_column(&self) -> usize {
+        self.selected_column
+    }
+
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    pub fn clear_status(&mut self) {
+        self.status = None;
+    }
+
     pub fn scroll_offset(&self) -> usize {
         self.scroll_offset
     }
 
+    pub fn whole_word(&self) -> bool {
+        self.whole_word
+    }
+
     pub fn search_query(&self) -> &str {
         &self.search_query
     }
@@ -172,22 +398,83 @@ impl App {
     }
 
     /// Get the row at the given visible index
-    pub fn get_visible_row(&self, visible_idx: usize) -> Option<&[Value]> {
-        self.filtered_indices
-            .get(visible_idx)
-            .and_then(|&actual_idx| self.table_data.rows().get(actual_idx))
-            .map(|v| v.as_slice())
+    pub fn get_visible_row(&self, visible_idx: usize) -> Option<Cow<'_, [Value]>> {
+        let actual_idx = *self.filtered_indices.get(visible_idx)?;
+        self.row(actual_idx)
     }
 
     /// Get the currently selected row's values
-    pub fn get_selected_row(&self) -> Option<&[Value]> {
+    pub fn get_selected_row(&self) -> Option<Cow<'_, [Value]>> {
         self.get_visible_row(self.selected_row)
     }
 
     /// Get the original JSON for the currently selected row
-    pub fn get_selected_source(&self) -> Option<&Value> {
+    pub fn get_selected_source(&self) -> Option<Cow<'_, Value>> {
         let actual_idx = *self.filtered_indices.get(self.selected_row)?;
-        self.source_records.get(actual_idx)
+        self.source(actual_idx)
+    }
+
+    /// Original JSON of the row at an index into all rows
+    fn source(&self, actual_idx: usize) -> Option<Cow<'_, Value>> {
+        match self.rows {
+            Rows::Loaded {
+                ref source_records, ..
+            } => source_records.get(actual_idx).map(Cow::Borrowed),
+            Rows::Spilled { ref records, .. } => {
+                records.borrow_mut().get(actual_idx).map(Cow::Owned)
+            }
+        }
+    }
+
+    /// Whether the row at a visible index is marked for deletion
+    pub fn is_deleted(&self, visible_idx: usize) -> bool {
+        self.filtered_indices
+            .get(visible_idx)
+            .is_some_and(|idx| self.deleted.contains(idx))
+    }
+
+    pub fn deleted_count(&self) -> usize {
+        self.deleted.len()
+    }
+
+    /// Mark the selected row for deletion, or unmark it, and move down
+    pub fn toggle_deleted(&mut self) {
+        let Some(&actual_idx) = self.filtered_indices.get(self.selected_row) else {
+            return;
+        };
+        if !self.deleted.remove(&actual_idx) {
+            self.deleted.insert(actual_idx);
+        }
+        self.move_down();
+    }
+
+    /// Cells of the row at an index into all rows
+    fn row(&self, actual_idx: usize) -> Option<Cow<'_, [Value]>> {
+        match self.rows {
+            Rows::Loaded { ref table_data, .. } => table_data
+                .rows()
+                .get(actual_idx)
+                .map(|v| Cow::Borrowed(v.as_slice())),
+            Rows::Spilled {
+                ref columns,
+                ref selector,
+                ref records,
+            } => {
+                let record = records.borrow_mut().get(actual_idx)?;
+                Some(Cow::Owned(TableData::row_values(
+                    &record,
+                    columns,
+                    selector.as_ref(),
+                )))
+            }
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        match self.rows {
+            Rows::Loaded { ref table_data, .. } => table_data.rows().len(),
+            Rows::Spilled { ref records, .. } => records.borrow().len(),
+        }
     }
 
     /// Get the detail view state (if in Detail mode)
@@ -212,6 +499,93 @@ impl App {
         self.detail_state = None;
     }
 
+    pub fn top_values(&self) -> Option<&TopValuesState> {
+        self.top_values.as_ref()
+    }
+
+    pub fn top_values_mut(&mut self) -> Option<&mut TopValuesState> {
+        self.top_values.as_mut()
+    }
+
+    /// Count the values of the selected column among the rows shown and
+    /// open the popup listing the most frequent
+    pub fn show_top_values(&mut self) {
+        let Some(column) = self.columns().get(self.selected_column).cloned() else {
+            return;
+        };
+        // Keyed by JSON text, so 1 and "1" are told apart
+        let mut counts: HashMap<String, (Value, usize)> = HashMap::new();
+        for &idx in &self.filtered_indices {
+            let Some(value) = self
+                .row(idx)
+                .and_then(|row| row.get(self.selected_column).cloned())
+            else {
+                continue;
+            };
+            counts
+                .entry(value.to_string())
+                .or_insert_with(|| (value, 0))
+                .1 += 1;
+        }
+
+        let distinct = counts.len();
+        let mut values: Vec<(String, (Value, usize))> = counts.into_iter().collect();
+        values.sort_by(|(a_key, (_, a)), (b_key, (_, b))| b.cmp(a).then_with(|| a_key.cmp(b_key)));
+        values.truncate(TOP_VALUES);
+
+        self.top_values = Some(TopValuesState {
+            column,
+            values: values.into_iter().map(|(_, counted)| counted).collect(),
+            distinct,
+            rows: self.filtered_indices.len(),
+            selected: 0,
+        });
+        self.mode = InputMode::TopValues;
+    }
+
+    pub fn close_top_values(&mut self) {
+        self.mode = InputMode::Normal;
+        self.top_values = None;
+    }
+
+    /// Close the popup and add `column=value` for the selected value to the
+    /// filter
+    pub fn filter_top_value(&mut self) {
+        let Some(state) = self.top_values.take() else {
+            return;
+        };
+        self.mode = InputMode::Normal;
+        let Some((value, _)) = state.values.get(state.selected) else {
+            return;
+        };
+        let text = match value {
+            Value::String(s) => s.clone(),
+            Value::Array(_) | Value::Object(_) => {
+                self.status = Some("Only scalar values can be filtered on".to_string());
+                return;
+            }
+            other => other.to_string(),
+        };
+
+        let condition = format!(
+            "{}={}",
+            escape_column(&state.column),
+            Self::quote_if_needed(&text)
+        );
+        match FilterExpr::parse(&condition) {
+            Ok(expr) => {
+                self.remember();
+                let expr = expr.whole_word(self.whole_word);
+                match self.filter_expr {
+                    Some(ref mut filter) => filter.conditions.extend(expr.conditions),
+                    None => self.filter_expr = Some(expr),
+                }
+                self.apply_filters();
+            }
+            Err(e) => self.status = Some(e.to_string()),
+        }
+    }
+
     // Navigation
     pub fn move_up(&mut self) {
         if self.selected_row > 0 {
@@ -227,6 +601,16 @@ impl App {
         }
     }
 
+    pub fn move_left(&mut self) {
+        self.selected_column = self.selected_column.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        if self.selected_column + 1 < self.columns().len() {
+            self.selected_column += 1;
+        }
+    }
+
     pub fn page_up(&mut self, page_size: usize) {
         self.selected_row = self.selected_row.saturating_sub(page_size);
         self.ensure_visible();
@@ -264,6 +648,63 @@ impl App {
         }
     }
 
+    pub fn column_offset(&self) -> usize {
+        self.column_offset
+    }
+
+    /// Scroll columns so the selected one is among the `width` shown
+    pub fn ensure_column_visible(&mut self, width: usize) {
+        let width = width.max(1);
+        let max_offset = self.columns().len().saturating_sub(width);
+        if self.selected_column < self.column_offset {
+            self.column_offset = self.selected_column;
+        } else if self.selected_column >= self.column_offset + width {
+            self.column_offset = self.selected_column + 1 - width;
+        }
+        self.column_offset = self.column_offset.min(max_offset);
+    }
+
+    pub fn column_finder(&self) -> Option<&ColumnFinder> {
+        self.column_finder.as_ref()
+    }
+
+    pub fn column_finder_mut(&mut self) -> Option<&mut ColumnFinder> {
+        self.column_finder.as_mut()
+    }
+
+    /// Open the fuzzy search over column names
+    pub fn open_column_finder(&mut self) {
+        self.column_finder = Some(ColumnFinder::new(self.columns()));
+        self.mode = InputMode::FindColumn;
+    }
+
+    pub fn close_column_finder(&mut self) {
+        self.column_finder = None;
+        self.mode = InputMode::Normal;
+    }
+
+    pub fn find_column_char(&mut self, c: char) {
+        if let Some(mut finder) = self.column_finder.take() {
+            finder.input_char(c, self.columns());
+            self.column_finder = Some(finder);
+        }
+    }
+
+    pub fn find_column_backspace(&mut self) {
+        if let Some(mut finder) = self.column_finder.take() {
+            finder.input_backspace(self.columns());
+            self.column_finder = Some(finder);
+        }
+    }
+
+    /// Select the column chosen in the finder, scrolling it into view
+    pub fn jump_to_column(&mut self) {
+        if let Some(column) = self.column_finder.as_ref().and_then(ColumnFinder::chosen) {
+            self.selected_column = column;
+        }
+        self.close_column_finder();
+    }
+
     // Mode switching
     pub fn enter_search_mode(&mut self) {
         self.mode = InputMode::Search;
@@ -275,41 +716,459 @@ impl App {
         self.input_buffer = self.filter_text();
     }
 
+    /// Start editing the selected cell
+    pub fn enter_edit_mode(&mut self) {
+        if self.edit_path.is_none() {
+            self.status = Some(
+                "Editing needs one JSONL file shown row for row (no -c, filters, sorting or paging)"
+                    .to_string(),
+            );
+            return;
+        }
+        if self.selector.is_some() || self.flat {
+            self.status =
+                Some("Editing needs the file's own columns (not :columns or :flat)".to_string());
+            return;
+        }
+        let Some(value) = self
+            .get_selected_row()
+            .and_then(|row| row.get(self.selected_column).cloned())
+        else {
+            return;
+        };
+        match edit::edit_text(&value) {
+            Some(text) => {
+                self.mode = InputMode::Edit;
+                self.input_buffer = text;
+            }
+            None => self.status = Some("Only scalar values can be edited".to_string()),
+        }
+    }
+
+    pub fn enter_command_mode(&mut self) {
+        self.mode = InputMode::Command;
+        self.input_buffer.clear();
+    }
+
     pub fn cancel_input(&mut self) {
         self.mode = InputMode::Normal;
         self.input_buffer.clear();
+        self.completion = None;
     }
 
     pub fn confirm_input(&mut self) {
         match self.mode {
             InputMode::Search => {
+                self.remember();
                 self.search_query = self.input_buffer.clone();
                 self.apply_filters();
             }
             InputMode::Filter => {
                 if self.input_buffer.is_empty() {
+                    self.remember();
                     self.filter_expr = None;
                 } else if let Ok(expr) = FilterExpr::parse(&self.input_buffer) {
-                    self.filter_expr = Some(expr);
+                    self.remember();
+                    self.filter_expr = Some(expr.whole_word(self.whole_word));
                 }
                 self.apply_filters();
             }
-            InputMode::Normal | InputMode::Detail => {}
+            InputMode::Edit => self.save_edit(),
+            InputMode::Command => self.run_command(),
+            InputMode::Normal
+            | InputMode::Detail
+            | InputMode::TopValues
+            | InputMode::FindColumn => {}
         }
         self.mode = InputMode::Normal;
         self.input_buffer.clear();
+        self.completion = None;
     }
 
     pub fn input_char(&mut self, c: char) {
         self.input_buffer.push(c);
+        self.completion = None;
     }
 
     pub fn input_backspace(&mut self) {
         self.input_buffer.pop();
+        self.completion = None;
+    }
+
+    /// Complete the column name being typed in the filter prompt; pressed
+    /// again, move on to the next column that matches
+    pub fn complete_column(&mut self) {
+        if let Some(completion) = &mut self.completion {
+            completion.index = (completion.index + 1) % completion.candidates.len();
+        } else {
+            let Some(start) = self.column_word_start() else {
+                return;
+            };
+            let candidates = self.columns_matching(&self.input_buffer[start..]);
+            if candidates.is_empty() {
+                return;
+            }
+            self.completion = Some(Completion {
+                start,
+                candidates,
+                index: 0,
+            });
+        }
+        if let Some(completion) = &self.completion {
+            self.input_buffer.truncate(completion.start);
+            self.input_buffer
+                .push_str(&completion.candidates[completion.index]);
+        }
+    }
+
+    /// Columns that complete the name being typed in the filter prompt, with
+    /// the one Tab last put in the input
+    pub fn column_completions(&self) -> (Vec<String>, Option<usize>) {
+        if let Some(completion) = &self.completion {
+            return (completion.candidates.clone(), Some(completion.index));
+        }
+        let candidates = match self.column_word_start() {
+            Some(start) if start < self.input_buffer.len() => {
+                self.columns_matching(&self.input_buffer[start..])
+            }
+            _ => Vec::new(),
+        };
+        (candidates, None)
+    }
+
+    /// Where the last condition of the filter input starts, unless its column
+    /// name is already followed by an operator
+    fn column_word_start(&self) -> Option<usize> {
+        let mut start = 0;
+        let mut escaped = false;
+        let mut in_name = true;
+        let mut quote = None;
+        for (i, c) in self.input_buffer.char_indices() {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if quote.is_some() {
+                if quote == Some(c) {
+                    quote = None;
+                }
+            } else if !in_name && matches!(c, '"' | '\'') {
+                quote = Some(c);
+            } else if c == ' ' {
+                start = i + 1;
+                in_name = true;
+            } else if matches!(c, '=' | '!' | '>' | '<' | '~') {
+                in_name = false;
+            }
+        }
+        in_name.then_some(start)
+    }
+
+    /// Column names, written for a filter, that start with `prefix`
+    /// (ignoring case) and are not `prefix` itself
+    fn columns_matching(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        self.columns()
+            .iter()
+            .map(|column| escape_column(column))
+            .filter(|column| column.to_lowercase().starts_with(&prefix) && *column != prefix)
+            .collect()
+    }
+
+    /// Run a `:` command: `write FILE` (or `w`) saves the rows shown, without
+    /// those marked for deletion; `write!` replaces an existing file
+    ///
+    /// The other commands take the same values as the matching flags:
+    /// `sort KEYS`, `filter EXPR`, `search TEXT`, `columns COLUMNS` (without a
+    /// value they clear what they set), `flat` to switch flat mode on or off,
+    /// and `export FILE` to save the table shown as CSV, JSON, JSONL or
+    /// Markdown, chosen by the file extension.
+    fn run_command(&mut self) {
+        let input = self.input_buffer.trim().to_string();
+        let (command, arg) = input
+            .split_once(char::is_whitespace)
+            .map(|(command, arg)| (command, arg.trim()))
+            .unwrap_or((input.as_str(), ""));
+        let (name, force) = match command.strip_suffix('!') {
+            Some(name) => (name, true),
+            None => (command, false),
+        };
+        let result = match name {
+            "" => return,
+            "w" | "write" | "export" if arg.is_empty() => Err(format!("Usage: :{} FILE", name)),
+            "w" | "write" => self.write_rows(Path::new(arg), force),
+            "export" => self.export_table(Path::new(arg), force),
+            "sort" => self.set_sort(arg),
+            "filter" => self.set_filter(arg),
+            "search" => {
+                self.remember();
+                self.search_query = arg.to_string();
+                self.apply_filters();
+                Ok(None)
+            }
+            "columns" => self.set_columns(arg),
+            "flat" => self.toggle_flat(),
+            _ => Err(format!("Unknown command: {}", command)),
+        };
+        self.status = match result {
+            Ok(message) => message,
+            Err(message) => Some(message),
+        };
+    }
+
+    /// Save the rows shown, except those marked for deletion, as JSONL
+    fn write_rows(&self, path: &Path, overwrite: bool) -> CommandResult {
+        check_overwrite(path, overwrite, "write")?;
+        let rows = self
+            .filtered_indices
+            .iter()
+            .filter(|idx| !self.deleted.contains(idx))
+            .filter_map(|&idx| self.source(idx));
+        match edit::save_rows(path, rows) {
+            Ok(count) => Ok(Some(format!("Wrote {} rows to {}", count, path.display()))),
+            Err(e) => Err(format!("Not written: {}", e)),
+        }
+    }
+
+    /// Save the table shown, except rows marked for deletion, in the format
+    /// of the file extension
+    fn export_table(&self, path: &Path, overwrite: bool) -> CommandResult {
+        let format = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(export::format_for_extension)
+            .ok_or_else(|| {
+                format!(
+                    "Cannot tell the format of {} (use .csv, .json, .jsonl or .md)",
+                    path.display()
+                )
+            })?;
+        check_overwrite(path, overwrite, "export")?;
+        let rows: Vec<Vec<Value>> = self
+            .filtered_indices
+            .iter()
+            .filter(|idx| !self.deleted.contains(idx))
+            .filter_map(|&idx| self.row(idx).map(Cow::into_owned))
+            .collect();
+        let columns = self.formatter.export_columns(self.columns());
+        let text = export::serialize(format, false, &columns, &rows);
+        match edit::save_file(path, text.as_bytes()) {
+            Ok(()) => Ok(Some(format!(
+                "Exported {} rows to {}",
+                rows.len(),
+                path.display()
+            ))),
+            Err(e) => Err(format!("Not exported: {}", e)),
+        }
+    }
+
+    /// Order the rows shown by comma-separated sort keys, as with `--sort`
+    fn set_sort(&mut self, keys: &str) -> CommandResult {
+        let keys = split_list(keys);
+        let sorter = if keys.is_empty() {
+            None
+        } else {
+            Some(Sorter::parse(&keys).map_err(|e| e.to_string())?)
+        };
+        self.remember();
+        self.sorter = sorter;
+        self.apply_filters();
+        Ok(None)
+    }
+
+    /// Show rows matching a filter expression, as with `--filter`
+    fn set_filter(&mut self, expr: &str) -> CommandResult {
+        let filter_expr = if expr.is_empty() {
+            None
+        } else {
+            let expr = FilterExpr::parse(expr).map_err(|e| e.to_string())?;
+            Some(expr.whole_word(self.whole_word))
+        };
+        self.remember();
+        self.filter_expr = filter_expr;
+        self.apply_filters();
+        Ok(None)
+    }
+
+    /// Show comma-separated columns, as with `--columns`
+    fn set_columns(&mut self, columns: &str) -> CommandResult {
+        self.loaded_only("columns")?;
+        let columns = split_list(columns);
+        let selector = if columns.is_empty() {
+            None
+        } else {
+            Some(ColumnSelector::new(columns).map_err(|e| e.to_string())?)
+        };
+        self.remember();
+        self.selector = selector;
+        self.rebuild_table();
+        Ok(None)
+    }
+
+    /// Switch flat mode on or off, as with `--flat`
+    fn toggle_flat(&mut self) -> CommandResult {
+        self.loaded_only("flat")?;
+        self.remember();
+        self.flat = !self.flat;
+        self.rebuild_table();
+        Ok(None)
+    }
+
+    /// Save the view as it is before a change, for `undo`
+    fn remember(&mut self) {
+        let state = self.view_state();
+        self.undo.push(state);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Go back to the view before the last change
+    pub fn undo(&mut self) {
+        match self.undo.pop() {
+            Some(state) => {
+                let current = self.view_state();
+                self.redo.push(current);
+                self.restore(state);
+            }
+            None => self.status = Some("Nothing to undo".to_string()),
+        }
+    }
+
+    /// Reapply the last change undone
+    pub fn redo(&mut self) {
+        match self.redo.pop() {
+            Some(state) => {
+                let current = self.view_state();
+                self.undo.push(current);
+                self.restore(state);
+            }
+            None => self.status = Some("Nothing to redo".to_string()),
+        }
+    }
+
+    fn view_state(&self) -> ViewState {
+        ViewState {
+            search_query: self.search_query.clone(),
+            filter_expr: self.filter_expr.clone(),
+            whole_word: self.whole_word,
+            sorter: self.sorter.clone(),
+            selector: self.selector.clone(),
+            flat: self.flat,
+        }
+    }
+
+    fn restore(&mut self, state: ViewState) {
+        let columns =
+            |selector: &Option<ColumnSelector>| selector.as_ref().map(|s| s.columns().join(","));
+        let relayout =
+            state.flat != self.flat || columns(&state.selector) != columns(&self.selector);
+
+        self.search_query = state.search_query;
+        self.filter_expr = state.filter_expr;
+        self.whole_word = state.whole_word;
+        self.sorter = state.sorter;
+        self.selector = state.selector;
+        self.flat = state.flat;
+        if relayout {
+            self.rebuild_table();
+        } else {
+            self.apply_filters();
+        }
+    }
+
+    fn loaded_only(&self, command: &str) -> CommandResult {
+        match self.rows {
+            Rows::Loaded { .. } => Ok(None),
+            Rows::Spilled { .. } => Err(format!(
+                ":{} is not available for rows read from disk (--max-memory)",
+                command
+            )),
+        }
+    }
+
+    /// Lay out the loaded rows again after `:columns` or `:flat`
+    fn rebuild_table(&mut self) {
+        let Rows::Loaded {
+            ref mut table_data,
+            ref source_records,
+        } = self.rows
+        else {
+            return;
+        };
+        *table_data = if self.flat {
+            let flat = FlatTableData::from_rows(source_records, self.flat_config.clone());
+            let flat = match self.selector {
+                Some(ref selector) => flat.select(selector),
+                None => flat,
+            };
+            TableData::from_flat_columns_rows(flat.columns(), flat.rows().to_vec())
+        } else {
+            TableData::from_rows(source_records.clone(), self.selector.clone())
+        };
+        self.infer_column_types();
+        self.selected_column = self
+            .selected_column
+            .min(self.columns().len().saturating_sub(1));
+        self.apply_filters();
+    }
+
+    /// Write the edited cell back to the file, then update the table
+    fn save_edit(&mut self) {
+        let Some(&actual_idx) = self.filtered_indices.get(self.selected_row) else {
+            return;
+        };
+        let Some(column) = self.columns().get(self.selected_column).cloned() else {
+            return;
+        };
+        let (
+            Some(path),
+            Rows::Loaded {
+                table_data,
+                source_records,
+            },
+        ) = (self.edit_path.as_deref(), &mut self.rows)
+        else {
+            return;
+        };
+        let Some(before) = source_records.get(actual_idx) else {
+            return;
+        };
+
+        let value = edit::parse_edit(
+            &self.input_buffer,
+            before.get(&column).unwrap_or(&Value::Null),
+        );
+        match edit::write_back(path, actual_idx, before, &column, &value) {
+            Ok(()) => {
+                let mut after = before.clone();
+                if let Some(fields) = after.as_object_mut() {
+                    fields.insert(column, value.clone());
+                }
+                source_records[actual_idx] = after;
+                table_data.set_cell(actual_idx, self.selected_column, value);
+                self.status = Some(format!("Saved {}", path.display()));
+            }
+            Err(e) => self.status = Some(format!("Not saved: {}", e)),
+        }
+        self.infer_column_types();
+    }
+
+    /// Switch search and `~` filters between substring and whole-word matching
+    pub fn toggle_whole_word(&mut self) {
+        self.remember();
+        self.whole_word = !self.whole_word;
+        self.filter_expr = self
+            .filter_expr
+            .take()
+            .map(|expr| expr.whole_word(self.whole_word));
+        self.apply_filters();
     }
 
     /// Clear search and filter
     pub fn clear_filters(&mut self) {
+        self.remember();
         self.search_query.clear();
         self.filter_expr = None;
         self.apply_filters();
@@ -317,12 +1176,13 @@ impl App {
 
     /// Apply search and filter to update filtered_indices
     fn apply_filters(&mut self) {
-        let rows = self.table_data.rows();
-        let columns = self.table_data.columns();
+        let columns = self.columns();
 
-        self.filtered_indices = (0..rows.len())
+        let mut filtered_indices: Vec<usize> = (0..self.row_count())
             .filter(|&idx| {
-                let row = &rows[idx];
+                let Some(row) = self.row(idx) else {
+                    return false;
+                };
 
                 // Build a JSON object for filtering
                 let row_obj: Value = {
@@ -337,7 +1197,8 @@ impl App {
 
                 // Check search query
                 if !self.search_query.is_empty() {
-                    let search = FullTextSearch::new(&self.search_query);
+                    let search =
+                        FullTextSearch::new(&self.search_query).whole_word(self.whole_word);
                     if !search.matches(&row_obj) {
                         return false;
                     }
@@ -354,6 +1215,15 @@ impl App {
             })
             .collect();
 
+        // Sort keys address the original JSON, as with `--sort`
+        if let Some(ref sorter) = self.sorter {
+            filtered_indices.sort_by(|&a, &b| match (self.source(a), self.source(b)) {
+                (Some(a), Some(b)) => sorter.compare(&a, &b),
+                _ => Ordering::Equal,
+            });
+        }
+        self.filtered_indices = filtered_indices;
+
         // Reset selection if it's now out of bounds
         if self.selected_row >= self.filtered_indices.len() {
             self.selected_row = self.filtered_indices.len().saturating_sub(1);
@@ -362,9 +1232,288 @@ impl App {
     }
 }
 
+/// Refuse to replace `path` unless the command ended in `!`
+fn check_overwrite(path: &Path, overwrite: bool, command: &str) -> CommandResult {
+    if !overwrite && path.exists() {
+        return Err(format!(
+            "{} exists (use :{}! to replace it)",
+            path.display(),
+            command
+        ));
+    }
+    Ok(None)
+}
+
+/// A column name written so a filter reads it back whole
+fn escape_column(column: &str) -> String {
+    let mut escaped = String::with_capacity(column.len());
+    for c in column.chars() {
+        if matches!(c, '=' | '!' | '>' | '<' | '~' | ' ' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Comma-separated values, split as the matching command-line flags split them
+fn split_list(arg: &str) -> Vec<String> {
+    arg.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
+
+    fn edit_cell(app: &mut App, column: &str, text: &str) {
+        app.go_to_top();
+        while app.columns()[app.selected_column()] != column {
+            app.move_right();
+        }
+        app.enter_edit_mode();
+        app.input_buffer = text.to_string();
+        app.confirm_input();
+    }
+
+    #[test]
+    fn test_edit_saves_cell_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rows.jsonl");
+        std::fs::write(&path, "{\"id\":1,\"name\":\"a\"}\n").unwrap();
+        let rows = vec![json!({"id": 1, "name": "a"})];
+        let mut app = App::new(TableData::from_rows(rows.clone(), None), rows)
+            .with_edit_path(Some(path.clone()));
+
+        edit_cell(&mut app, "name", "b");
+        assert_eq!(app.mode, InputMode::Normal);
+        assert!(app.status().unwrap().starts_with("Saved"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"id\":1,\"name\":\"b\"}\n"
+        );
+        assert_eq!(app.get_selected_source().unwrap()["name"], json!("b"));
+    }
+
+    #[test]
+    fn test_write_skips_deleted_and_filtered_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+        let rows: Vec<Value> = (1..=4).map(|id| json!({"id": id})).collect();
+        let mut app = App::new(TableData::from_rows(rows.clone(), None), rows);
+
+        app.toggle_deleted();
+        assert!(app.is_deleted(0));
+        assert_eq!(app.selected_row(), 1);
+        app.enter_filter_mode();
+        app.input_buffer = "id<4".to_string();
+        app.confirm_input();
+
+        app.enter_command_mode();
+        app.input_buffer = format!("write {}", path.display());
+        app.confirm_input();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"id\":2}\n{\"id\":3}\n"
+        );
+
+        // An existing file is only replaced with `write!`
+        app.enter_command_mode();
+        app.input_buffer = format!("w {}", path.display());
+        app.confirm_input();
+        assert!(app.status().unwrap().contains("exists"));
+    }
+
+    fn run(app: &mut App, command: &str) {
+        app.enter_command_mode();
+        app.input_buffer = command.to_string();
+        app.confirm_input();
+    }
+
+    fn shown_ids(app: &App) -> Vec<i64> {
+        let id = app.columns().iter().position(|c| c == "id").unwrap();
+        (0..app.visible_row_count())
+            .map(|i| app.get_visible_row(i).unwrap()[id].as_i64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_commands_sort_filter_and_pick_columns() {
+        let rows = vec![
+            json!({"id": 1, "age": 30, "user": {"name": "a"}}),
+            json!({"id": 2, "age": 25, "user": {"name": "b"}}),
+            json!({"id": 3, "age": 35, "user": {"name": "c"}}),
+        ];
+        let mut app = App::new(TableData::from_rows(rows.clone(), None), rows);
+
+        run(&mut app, "sort -age");
+        assert_eq!(shown_ids(&app), vec![3, 1, 2]);
+        run(&mut app, "filter age<35");
+        assert_eq!(shown_ids(&app), vec![1, 2]);
+        run(&mut app, "filter");
+        run(&mut app, "columns id,user.name");
+        assert_eq!(app.columns(), ["id", "user.name"]);
+        assert_eq!(shown_ids(&app), vec![3, 1, 2]);
+
+        run(&mut app, "columns");
+        run(&mut app, "flat");
+        assert!(app.columns().iter().any(|c| c == "user.name"));
+
+        run(&mut app, "sort -");
+        assert!(app.status().is_some());
+        run(&mut app, "frobnicate");
+        assert_eq!(app.status(), Some("Unknown command: frobnicate"));
+    }
+
+    #[test]
+    fn test_export_command_writes_table_shown() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.csv");
+        let rows = vec![json!({"id": 1, "name": "a"}), json!({"id": 2, "name": "b"})];
+        let mut app = App::new(TableData::from_rows(rows.clone(), None), rows);
+
+        run(&mut app, "columns name");
+        app.toggle_deleted();
+        run(&mut app, &format!("export {}", path.display()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "name\nb\n");
+
+        run(
+            &mut app,
+            &format!("export {}", dir.path().join("out.txt").display()),
+        );
+        assert!(app.status().unwrap().starts_with("Cannot tell the format"));
+    }
+
+    #[test]
+    fn test_undo_and_redo_view_changes() {
+        let rows: Vec<Value> = (1..=3).map(|id| json!({"id": id})).collect();
+        let mut app = App::new(TableData::from_rows(rows.clone(), None), rows);
+
+        run(&mut app, "sort -id");
+        run(&mut app, "filter id<3");
+        assert_eq!(shown_ids(&app), vec![2, 1]);
+
+        app.undo();
+        assert_eq!(shown_ids(&app), vec![3, 2, 1]);
+        app.undo();
+        assert_eq!(shown_ids(&app), vec![1, 2, 3]);
+        app.undo();
+        assert_eq!(app.status(), Some("Nothing to undo"));
+
+        app.redo();
+        app.redo();
+        assert_eq!(shown_ids(&app), vec![2, 1]);
+
+        // A new change drops what was undone
+        app.undo();
+        run(&mut app, "columns id");
+        app.redo();
+        assert_eq!(app.status(), Some("Nothing to redo"));
+    }
+
+    #[test]
+    fn test_top_values_filter_on_selected_value() {
+        let rows: Vec<Value> = ["b", "a", "b", "c", "b", "a"]
+            .iter()
+            .enumerate()
+            .map(|(id, status)| json!({"id": id, "status": status}))
+            .collect();
+        let mut app = App::new(TableData::from_rows(rows.clone(), None), rows);
+        run(&mut app, "filter id>0");
+        while app.columns()[app.selected_column()] != "status" {
+            app.move_right();
+        }
+
+        app.show_top_values();
+        let state = app.top_values().unwrap();
+        assert_eq!(
+            state.values,
+            vec![(json!("a"), 2), (json!("b"), 2), (json!("c"), 1)]
+        );
+        assert_eq!((state.distinct, state.rows), (3, 5));
+
+        app.top_values_mut().unwrap().move_down();
+        app.filter_top_value();
+        assert_eq!(app.mode, InputMode::Normal);
+        assert_eq!(app.filter_text(), "id>0 status=b");
+        assert_eq!(shown_ids(&app), vec![2, 4]);
+    }
+
+    #[test]
+    fn test_filter_prompt_completes_column_names() {
+        let rows = vec![json!({"name": "a", "nickname": "b", "first name": "c", "age": 1})];
+        let mut app = App::new(TableData::from_rows(rows.clone(), None), rows);
+        app.enter_filter_mode();
+        for c in "age>1 Na".chars() {
+            app.input_char(c);
+        }
+        assert_eq!(app.column_completions(), (vec!["name".to_string()], None));
+
+        app.complete_column();
+        assert_eq!(app.input_buffer, "age>1 name");
+
+        // With no name typed yet, Tab cycles through every column
+        app.input_backspace();
+        app.input_backspace();
+        app.input_backspace();
+        app.input_backspace();
+        app.complete_column();
+        app.complete_column();
+        let (columns, current) = app.column_completions();
+        assert_eq!(columns.len(), 4);
+        assert_eq!(
+            app.input_buffer,
+            format!("age>1 {}", columns[current.unwrap()])
+        );
+        assert!(columns.contains(&"first\\ name".to_string()));
+
+        // Nothing to complete once an operator follows the name
+        app.input_char('=');
+        app.complete_column();
+        assert!(app.input_buffer.ends_with('='));
+        assert!(app.column_completions().0.is_empty());
+    }
+
+    #[test]
+    fn test_column_finder_jumps_and_scrolls_to_column() {
+        let record: serde_json::Map<String, Value> = (0..60)
+            .map(|i| (format!("field_{:02}", i), json!(i)))
+            .collect();
+        let rows = vec![Value::Object(record)];
+        let mut app = App::new(TableData::from_rows(rows.clone(), None), rows);
+
+        app.open_column_finder();
+        assert_eq!(app.mode, InputMode::FindColumn);
+        for c in "f42".chars() {
+            app.find_column_char(c);
+        }
+        app.jump_to_column();
+        assert_eq!(app.mode, InputMode::Normal);
+        assert_eq!(app.columns()[app.selected_column()], "field_42");
+
+        app.ensure_column_visible(10);
+        assert_eq!(app.column_offset(), 33);
+        app.go_to_top();
+        app.open_column_finder();
+        app.find_column_char('0');
+        app.find_column_char('0');
+        app.jump_to_column();
+        app.ensure_column_visible(10);
+        assert_eq!((app.selected_column(), app.column_offset()), (0, 0));
+    }
+
+    #[test]
+    fn test_edit_needs_edit_path() {
+        let rows = vec![json!({"id": 1})];
+        let mut app = App::new(TableData::from_rows(rows.clone(), None), rows);
+        app.enter_edit_mode();
+        assert_eq!(app.mode, InputMode::Normal);
+        assert!(app.status().is_some());
+    }
 
     #[test]
     fn test_quote_if_needed_simple() {
@@ -0,0 +1,386 @@
+use serde_json::Value;
+use std::borrow::Borrow;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+/// Text offered for editing a cell; `None` for arrays and objects, which
+/// cannot be edited in place
+pub fn edit_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Array(_) | Value::Object(_) => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// The value typed over `original`. Strings stay strings; other values are
+/// read as a JSON scalar (a number, `true`, `false`, `null` or a quoted
+/// string), and anything else is taken as a string.
+pub fn parse_edit(text: &str, original: &Value) -> Value {
+    if original.is_string() {
+        return Value::String(text.to_string());
+    }
+    match serde_json::from_str::<Value>(text.trim()) {
+        Ok(value) if !value.is_array() && !value.is_object() => value,
+        _ => Value::String(text.to_string()),
+    }
+}
+
+/// Set top-level `key` of row `row` of the JSONL file at `path`, which must
+/// still hold `before`, to `value`.
+///
+/// Only the value's bytes change: the row keeps its key order and spacing,
+/// a missing key is added at the end of the object, and every other line is
+/// copied byte for byte. The new content is written to a temp file next to
+/// the original and renamed over it, so the file is never left half written.
+pub fn write_back(
+    path: &Path,
+    row: usize,
+    before: &Value,
+    key: &str,
+    value: &Value,
+) -> io::Result<()> {
+    let changed = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} changed since it was loaded", path.display()),
+        )
+    };
+
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut out = NamedTempFile::new_in(parent_dir(path))?;
+    {
+        let mut writer = BufWriter::new(out.as_file_mut());
+        let mut line = Vec::new();
+        let mut rows = 0;
+        let mut replaced = false;
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            // Blank lines are not rows
+            if String::from_utf8_lossy(&line).trim().is_empty() {
+                writer.write_all(&line)?;
+                continue;
+            }
+            if rows == row {
+                if serde_json::from_slice::<Value>(&line).ok().as_ref() != Some(before) {
+                    return Err(changed());
+                }
+                let mut after = before.clone();
+                if let Some(fields) = after.as_object_mut() {
+                    fields.insert(key.to_string(), value.clone());
+                }
+                // The scan is checked against the parse before anything is written
+                let spliced = splice(&line, key, value)
+                    .filter(|spliced| serde_json::from_slice::<Value>(spliced).ok() == Some(after))
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "row {} of {} could not be edited in place",
+                                row + 1,
+                                path.display()
+                            ),
+                        )
+                    })?;
+                writer.write_all(&spliced)?;
+                replaced = true;
+            } else {
+                writer.write_all(&line)?;
+            }
+            rows += 1;
+        }
+        if !replaced {
+            return Err(changed());
+        }
+        writer.flush()?;
+    }
+
+    out.as_file()
+        .set_permissions(fs::metadata(path)?.permissions())?;
+    out.persist(path)?;
+    Ok(())
+}
+
+/// Write `rows` to `path` as JSONL; returns the number of rows written.
+/// An existing file is replaced through a temp file renamed over it.
+pub fn save_rows<V: Borrow<Value>>(
+    path: &Path,
+    rows: impl IntoIterator<Item = V>,
+) -> io::Result<usize> {
+    save_with(path, |out| {
+        let mut count = 0;
+        for row in rows {
+            serde_json::to_writer(&mut *out, row.borrow())?;
+            out.write_all(b"\n")?;
+            count += 1;
+        }
+        Ok(count)
+    })
+}
+
+/// Write `contents` to `path`, replacing an existing file as `save_rows` does
+pub fn save_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    save_with(path, |out| out.write_all(contents))
+}
+
+fn save_with<T>(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<&mut File>) -> io::Result<T>,
+) -> io::Result<T> {
+    let Ok(metadata) = fs::metadata(path) else {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+        let mut out = BufWriter::new(&mut file);
+        let result = write(&mut out)?;
+        out.flush()?;
+        return Ok(result);
+    };
+
+    let mut temp = NamedTempFile::new_in(parent_dir(path))?;
+    let result = {
+        let mut out = BufWriter::new(temp.as_file_mut());
+        let result = write(&mut out)?;
+        out.flush()?;
+        result
+    };
+    temp.as_file().set_permissions(metadata.permissions())?;
+    temp.persist(path)?;
+    Ok(result)
+}
+
+/// Directory holding `path`, where its temp file is created so the rename
+/// stays on one file system
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// Where top-level `key` sits in a line holding one JSON object
+enum Slot {
+    /// The byte range of its value
+    Value(Range<usize>),
+    /// Absent: the offset just past the last value (or the opening brace),
+    /// and whether the object has no keys at all
+    Missing { at: usize, empty: bool },
+}
+
+/// `line` with top-level `key` of its object set to `value` and every other
+/// byte left as it was; `None` if the line is not a single JSON object
+fn splice(line: &[u8], key: &str, value: &Value) -> Option<Vec<u8>> {
+    let text = serde_json::to_vec(value).ok()?;
+    let mut spliced = Vec::with_capacity(line.len() + text.len());
+    match find_slot(line, key)? {
+        Slot::Value(span) => {
+            spliced.extend_from_slice(&line[..span.start]);
+            spliced.extend_from_slice(&text);
+            spliced.extend_from_slice(&line[span.end..]);
+        }
+        Slot::Missing { at, empty } => {
+            spliced.extend_from_slice(&line[..at]);
+            if !empty {
+                spliced.push(b',');
+            }
+            spliced.extend_from_slice(&serde_json::to_vec(key).ok()?);
+            spliced.push(b':');
+            spliced.extend_from_slice(&text);
+            spliced.extend_from_slice(&line[at..]);
+        }
+    }
+    Some(spliced)
+}
+
+/// Walk the keys of the object on `line` looking for `key`
+fn find_slot(line: &[u8], key: &str) -> Option<Slot> {
+    let mut pos = skip_whitespace(line, 0);
+    if line.get(pos) != Some(&b'{') {
+        return None;
+    }
+    let open = pos + 1;
+    pos = skip_whitespace(line, open);
+    if line.get(pos) == Some(&b'}') {
+        return Some(Slot::Missing {
+            at: open,
+            empty: true,
+        });
+    }
+    loop {
+        if line.get(pos) != Some(&b'"') {
+            return None;
+        }
+        let key_end = value_end(line, pos)?;
+        let name: String = serde_json::from_slice(&line[pos..key_end]).ok()?;
+        pos = skip_whitespace(line, key_end);
+        if line.get(pos) != Some(&b':') {
+            return None;
+        }
+        let start = skip_whitespace(line, pos + 1);
+        let end = value_end(line, start)?;
+        if name == key {
+            return Some(Slot::Value(start..end));
+        }
+        pos = skip_whitespace(line, end);
+        match line.get(pos)? {
+            b',' => pos = skip_whitespace(line, pos + 1),
+            b'}' => {
+                return Some(Slot::Missing {
+                    at: end,
+                    empty: false,
+                })
+            }
+            _ => return None,
+        }
+    }
+}
+
+fn skip_whitespace(line: &[u8], pos: usize) -> usize {
+    pos + line[pos..]
+        .iter()
+        .take_while(|b| b.is_ascii_whitespace())
+        .count()
+}
+
+/// End of the JSON value starting at `start`: just past its closing quote or
+/// bracket, or at the byte ending a number or literal
+fn value_end(line: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, &b) in line.iter().enumerate().skip(start) {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => {
+                    in_string = false;
+                    if depth == 0 {
+                        return Some(i + 1);
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            b',' | b'}' | b']' if depth == 0 => return (i > start).then_some(i),
+            _ if depth == 0 && b.is_ascii_whitespace() => return (i > start).then_some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_edit_keeps_strings() {
+        assert_eq!(parse_edit("42", &json!("zip")), json!("42"));
+        assert_eq!(parse_edit("42", &json!(1)), json!(42));
+        assert_eq!(parse_edit("true", &Value::Null), json!(true));
+        assert_eq!(parse_edit("n/a", &json!(1)), json!("n/a"));
+        assert_eq!(parse_edit("[1]", &json!(1)), json!("[1]"));
+    }
+
+    #[test]
+    fn test_write_back_replaces_one_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rows.jsonl");
+        fs::write(
+            &path,
+            "{\"id\":1,  \"name\":\"a\"}\n\n{\"id\":2,\"name\":\"b\"}\r\n{\"id\":3}",
+        )
+        .unwrap();
+
+        write_back(
+            &path,
+            1,
+            &json!({"id": 2, "name": "b"}),
+            "name",
+            &json!("B"),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"id\":1,  \"name\":\"a\"}\n\n{\"id\":2,\"name\":\"B\"}\r\n{\"id\":3}"
+        );
+    }
+
+    #[test]
+    fn test_write_back_keeps_key_order_and_spacing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rows.jsonl");
+        let line = r#"{ "zip": "100", "tags": ["}", {"a": "\""}], "a\"b" : 1.50 }"#;
+        fs::write(&path, format!("{}\n", line)).unwrap();
+        let before: Value = serde_json::from_str(line).unwrap();
+
+        write_back(&path, 0, &before, "a\"b", &json!(2)).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!(
+                "{}\n",
+                r#"{ "zip": "100", "tags": ["}", {"a": "\""}], "a\"b" : 2 }"#
+            )
+        );
+
+        let before: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        write_back(&path, 0, &before, "new", &json!(null)).unwrap();
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .ends_with("\"a\\\"b\" : 2,\"new\":null }\n"));
+    }
+
+    #[test]
+    fn test_splice_into_empty_object() {
+        assert_eq!(
+            splice(b"{ }\n", "id", &json!(1)).unwrap(),
+            b"{\"id\":1 }\n".to_vec()
+        );
+        assert!(splice(b"[1]\n", "id", &json!(1)).is_none());
+    }
+
+    #[test]
+    fn test_save_rows_creates_and_replaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.jsonl");
+
+        let count = save_rows(&path, [json!({"id": 1}), json!({"id": 2})]).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\"id\":1}\n{\"id\":2}\n"
+        );
+
+        save_rows(&path, vec![&json!({"id": 3})]).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"id\":3}\n");
+    }
+
+    #[test]
+    fn test_write_back_refuses_changed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rows.jsonl");
+        fs::write(&path, "{\"id\":1}\n").unwrap();
+
+        let err = write_back(&path, 0, &json!({"id": 2}), "id", &json!(3)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(write_back(&path, 1, &json!({"id": 1}), "id", &json!(3)).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"id\":1}\n");
+    }
+}
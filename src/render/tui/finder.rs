@@ -0,0 +1,135 @@
+/// Fuzzy search over column names, opened with Ctrl-p
+#[derive(Debug, Clone, Default)]
+pub struct ColumnFinder {
+    pub query: String,
+    /// Indices of the columns matching the query, best match first
+    pub matches: Vec<usize>,
+    pub selected: usize,
+}
+
+impl ColumnFinder {
+    pub fn new(columns: &[String]) -> Self {
+        let mut finder = Self::default();
+        finder.update(columns);
+        finder
+    }
+
+    pub fn input_char(&mut self, c: char, columns: &[String]) {
+        self.query.push(c);
+        self.update(columns);
+    }
+
+    pub fn input_backspace(&mut self, columns: &[String]) {
+        self.query.pop();
+        self.update(columns);
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Index of the column chosen
+    pub fn chosen(&self) -> Option<usize> {
+        self.matches.get(self.selected).copied()
+    }
+
+    fn update(&mut self, columns: &[String]) {
+        self.selected = 0;
+        if self.query.is_empty() {
+            self.matches = (0..columns.len()).collect();
+            return;
+        }
+        let mut scored: Vec<(i64, usize)> = columns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, column)| fuzzy_score(&self.query, column).map(|score| (score, i)))
+            .collect();
+        // Best score first, ties in column order
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+    }
+}
+
+/// How well `text` matches `pattern`, whose characters must all appear in it
+/// in order (ignoring case); `None` when they do not.
+///
+/// Runs of consecutive characters and matches at the start of a name part
+/// (after `.`, `_`, `-`, `[` or a space) score higher.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let mut pattern = pattern.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut after_separator = true;
+    for (i, c) in text.chars().flat_map(char::to_lowercase).enumerate() {
+        let Some(&wanted) = pattern.peek() else {
+            break;
+        };
+        if c == wanted {
+            pattern.next();
+            score += 1;
+            if previous == Some(i.wrapping_sub(1)) {
+                score += 5;
+            }
+            if after_separator {
+                score += 3;
+            }
+            previous = Some(i);
+        }
+        after_separator = matches!(c, '.' | '_' | '-' | '[' | ' ');
+    }
+    if pattern.peek().is_some() {
+        return None;
+    }
+    // Shorter names win among equal matches
+    Some(score * 100 - text.chars().count() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(finder: &ColumnFinder, columns: &[String]) -> Vec<String> {
+        finder.matches.iter().map(|&i| columns[i].clone()).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score_needs_characters_in_order() {
+        assert!(fuzzy_score("usrnm", "user.name").is_some());
+        assert!(fuzzy_score("USER", "user.name").is_some());
+        assert!(fuzzy_score("nu", "user.name").is_none());
+        assert!(fuzzy_score("name", "user.name") > fuzzy_score("name", "n.a.m.e"));
+    }
+
+    #[test]
+    fn test_finder_ranks_columns_and_picks_best() {
+        let columns: Vec<String> = ["address.city", "id", "user.city_code", "city"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let mut finder = ColumnFinder::new(&columns);
+        assert_eq!(finder.matches, vec![0, 1, 2, 3]);
+
+        for c in "city".chars() {
+            finder.input_char(c, &columns);
+        }
+        assert_eq!(
+            names(&finder, &columns),
+            vec!["city", "address.city", "user.city_code"]
+        );
+        assert_eq!(finder.chosen(), Some(3));
+
+        finder.move_down();
+        assert_eq!(finder.chosen(), Some(0));
+
+        finder.input_char('z', &columns);
+        assert_eq!(finder.chosen(), None);
+        finder.input_backspace(&columns);
+        assert_eq!(finder.matches.len(), 3);
+    }
+}
@@ -1,4 +1,4 @@
-//! JSON syntax highlighting for the detail view
+//! JSON syntax highlighting for the detail view and `--pretty` output
 
 use ratatui::{
     style::{Color, Style},
@@ -24,6 +24,36 @@ pub fn highlight_json(value: &Value) -> Vec<Line<'static>> {
     pretty.lines().map(highlight_line).collect()
 }
 
+/// Pretty-print a JSON value with ANSI colors, in the detail view's scheme
+pub fn highlight_ansi(value: &Value) -> String {
+    highlight_json(value)
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| match span.style.fg.and_then(ansi_code) {
+                    Some(code) => format!("\x1b[{}m{}\x1b[0m", code, span.content),
+                    None => span.content.to_string(),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// ANSI foreground code for a scheme color; punctuation keeps the terminal's
+/// own foreground, which reads better than white on light backgrounds
+fn ansi_code(color: Color) -> Option<u8> {
+    match color {
+        Color::Cyan => Some(36),
+        Color::Green => Some(32),
+        Color::Yellow => Some(33),
+        Color::Magenta => Some(35),
+        Color::DarkGray => Some(90),
+        _ => None,
+    }
+}
+
 /// Highlight a single line of pretty-printed JSON
 fn highlight_line(line: &str) -> Line<'static> {
     let mut spans: Vec<Span<'static>> = Vec::new();
@@ -175,6 +205,15 @@ mod tests {
         assert!(lines.len() > 3); // Should be multiple lines
     }
 
+    #[test]
+    fn test_highlight_ansi() {
+        let value = json!({"n": 1, "ok": null});
+        assert_eq!(
+            highlight_ansi(&value),
+            "{\n  \x1b[36m\"n\"\x1b[0m: \x1b[33m1\x1b[0m,\n  \x1b[36m\"ok\"\x1b[0m: \x1b[90mnull\x1b[0m\n}"
+        );
+    }
+
     #[test]
     fn test_is_number() {
         assert!(is_number("123"));
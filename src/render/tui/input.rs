@@ -1,16 +1,128 @@
 use super::app::{App, InputMode};
-use crossterm::event::KeyCode;
+use crate::error::{JlcatError, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
 
 pub enum Action {
     Continue,
     Quit,
 }
 
-pub fn handle_key(app: &mut App, key: KeyCode) -> Action {
+/// Actions that can be bound to extra keys, with their built-in key
+const BINDABLE: &[(&str, KeyCode)] = &[
+    ("quit", KeyCode::Char('q')),
+    ("up", KeyCode::Char('k')),
+    ("down", KeyCode::Char('j')),
+    ("left", KeyCode::Char('h')),
+    ("right", KeyCode::Char('l')),
+    ("page-up", KeyCode::Char('b')),
+    ("page-down", KeyCode::Char(' ')),
+    ("top", KeyCode::Char('g')),
+    ("bottom", KeyCode::Char('G')),
+    ("search", KeyCode::Char('/')),
+    ("filter", KeyCode::Char('f')),
+    ("clear", KeyCode::Char('c')),
+    ("whole-word", KeyCode::Char('w')),
+    ("detail", KeyCode::Enter),
+    ("edit", KeyCode::Char('e')),
+    ("delete", KeyCode::Char('d')),
+    ("command", KeyCode::Char(':')),
+    ("undo", KeyCode::Char('u')),
+    ("top-values", KeyCode::Char('t')),
+];
+
+/// Extra key bindings from the `[keys]` config table (e.g. `search = "s"`).
+///
+/// A bound key acts as the action's built-in key, which keeps working.
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    keys: HashMap<KeyCode, KeyCode>,
+}
+
+impl Keymap {
+    /// Build from action names to key names
+    pub fn parse(bindings: &BTreeMap<String, String>) -> Result<Self> {
+        let mut keys = HashMap::new();
+        for (action, key) in bindings {
+            let builtin = BINDABLE
+                .iter()
+                .find(|(name, _)| name == action)
+                .map(|(_, code)| *code)
+                .ok_or_else(|| {
+                    let names: Vec<&str> = BINDABLE.iter().map(|(name, _)| *name).collect();
+                    JlcatError::Config(format!(
+                        "unknown key action '{}' (expected one of: {})",
+                        action,
+                        names.join(", ")
+                    ))
+                })?;
+            keys.insert(parse_key(key)?, builtin);
+        }
+        Ok(Self { keys })
+    }
+
+    /// The built-in key for a pressed key
+    fn resolve(&self, key: KeyCode) -> KeyCode {
+        self.keys.get(&key).copied().unwrap_or(key)
+    }
+}
+
+/// A single character, or a named key such as `enter`, `tab` or `space`
+fn parse_key(name: &str) -> Result<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    Ok(match name.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        _ => return Err(JlcatError::Config(format!("unknown key '{}'", name))),
+    })
+}
+
+pub fn handle_key(app: &mut App, event: KeyEvent) -> Action {
+    app.clear_status();
+    if app.mode == InputMode::Normal && event.modifiers.contains(KeyModifiers::CONTROL) {
+        match event.code {
+            KeyCode::Char('r') => {
+                app.redo();
+                return Action::Continue;
+            }
+            KeyCode::Char('p') => {
+                app.open_column_finder();
+                return Action::Continue;
+            }
+            _ => {}
+        }
+    }
+    let key = event.code;
+    let key = match app.mode {
+        InputMode::Normal | InputMode::Detail | InputMode::TopValues => app.keymap().resolve(key),
+        InputMode::Search
+        | InputMode::Filter
+        | InputMode::Edit
+        | InputMode::Command
+        | InputMode::FindColumn => key,
+    };
     match app.mode {
         InputMode::Normal => handle_normal_mode(app, key),
-        InputMode::Search | InputMode::Filter => handle_input_mode(app, key),
+        InputMode::Search | InputMode::Filter | InputMode::Edit | InputMode::Command => {
+            handle_input_mode(app, key)
+        }
         InputMode::Detail => handle_detail_mode(app, key),
+        InputMode::TopValues => handle_top_values_mode(app, key),
+        InputMode::FindColumn => handle_find_column_mode(app, event),
     }
 }
 
@@ -28,6 +140,14 @@ fn handle_normal_mode(app: &mut App, key: KeyCode) -> Action {
             app.move_down();
             Action::Continue
         }
+        KeyCode::Left | KeyCode::Char('h') => {
+            app.move_left();
+            Action::Continue
+        }
+        KeyCode::Right | KeyCode::Char('l') => {
+            app.move_right();
+            Action::Continue
+        }
         KeyCode::PageUp | KeyCode::Char('b') => {
             app.page_up(10);
             Action::Continue
@@ -63,11 +183,49 @@ fn handle_normal_mode(app: &mut App, key: KeyCode) -> Action {
             Action::Continue
         }
 
+        // Whole-word matching
+        KeyCode::Char('w') => {
+            app.toggle_whole_word();
+            Action::Continue
+        }
+
+        // Edit the selected cell
+        KeyCode::Char('e') => {
+            app.enter_edit_mode();
+            Action::Continue
+        }
+
+        // Mark or unmark the selected row for deletion
+        KeyCode::Char('d') => {
+            app.toggle_deleted();
+            Action::Continue
+        }
+
+        // Most frequent values of the selected column
+        KeyCode::Char('t') => {
+            app.show_top_values();
+            Action::Continue
+        }
+
+        // Undo the last change to the view (redo is Ctrl-r)
+        KeyCode::Char('u') => {
+            app.undo();
+            Action::Continue
+        }
+
+        // Command line (`:write FILE`)
+        KeyCode::Char(':') => {
+            app.enter_command_mode();
+            Action::Continue
+        }
+
         // Detail view
         KeyCode::Enter => {
-            if let Some(source) = app.get_selected_source() {
-                let pretty = serde_json::to_string_pretty(source).unwrap_or_default();
-                let total_lines = pretty.lines().count();
+            let total_lines = app.get_selected_source().map(|source| {
+                let pretty = serde_json::to_string_pretty(&source).unwrap_or_default();
+                pretty.lines().count()
+            });
+            if let Some(total_lines) = total_lines {
                 app.enter_detail_mode(total_lines);
             }
             Action::Continue
@@ -91,6 +249,11 @@ fn handle_input_mode(app: &mut App, key: KeyCode) -> Action {
             app.input_backspace();
             Action::Continue
         }
+        // Complete column names in the filter prompt
+        KeyCode::Tab if app.mode == InputMode::Filter => {
+            app.complete_column();
+            Action::Continue
+        }
         KeyCode::Char(c) => {
             app.input_char(c);
             Action::Continue
@@ -99,6 +262,59 @@ fn handle_input_mode(app: &mut App, key: KeyCode) -> Action {
     }
 }
 
+fn handle_top_values_mode(app: &mut App, key: KeyCode) -> Action {
+    match key {
+        KeyCode::Esc | KeyCode::Char('t') => app.close_top_values(),
+        KeyCode::Char('q') => return Action::Quit,
+        KeyCode::Enter => app.filter_top_value(),
+        KeyCode::Up | KeyCode::Char('k') => {
+            if let Some(state) = app.top_values_mut() {
+                state.move_up();
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            if let Some(state) = app.top_values_mut() {
+                state.move_down();
+            }
+        }
+        _ => {}
+    }
+    Action::Continue
+}
+
+/// Typing narrows the column list; Ctrl-p/Ctrl-n move like Up/Down
+fn handle_find_column_mode(app: &mut App, event: KeyEvent) -> Action {
+    let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+    match event.code {
+        KeyCode::Esc => app.close_column_finder(),
+        KeyCode::Enter => app.jump_to_column(),
+        KeyCode::Backspace => app.find_column_backspace(),
+        KeyCode::Up => {
+            if let Some(finder) = app.column_finder_mut() {
+                finder.move_up();
+            }
+        }
+        KeyCode::Char('p') if ctrl => {
+            if let Some(finder) = app.column_finder_mut() {
+                finder.move_up();
+            }
+        }
+        KeyCode::Down => {
+            if let Some(finder) = app.column_finder_mut() {
+                finder.move_down();
+            }
+        }
+        KeyCode::Char('n') if ctrl => {
+            if let Some(finder) = app.column_finder_mut() {
+                finder.move_down();
+            }
+        }
+        KeyCode::Char(c) if !ctrl => app.find_column_char(c),
+        _ => {}
+    }
+    Action::Continue
+}
+
 fn handle_detail_mode(app: &mut App, key: KeyCode) -> Action {
     match key {
         // Close modal
@@ -161,3 +377,29 @@ fn handle_detail_mode(app: &mut App, key: KeyCode) -> Action {
         _ => Action::Continue,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(a, k)| (a.to_string(), k.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_keymap_resolves_bound_keys() {
+        let keymap = Keymap::parse(&bindings(&[("search", "s"), ("detail", "tab")])).unwrap();
+        assert_eq!(keymap.resolve(KeyCode::Char('s')), KeyCode::Char('/'));
+        assert_eq!(keymap.resolve(KeyCode::Tab), KeyCode::Enter);
+        assert_eq!(keymap.resolve(KeyCode::Char('/')), KeyCode::Char('/'));
+    }
+
+    #[test]
+    fn test_keymap_rejects_unknown_names() {
+        assert!(Keymap::parse(&bindings(&[("fly", "x")])).is_err());
+        assert!(Keymap::parse(&bindings(&[("quit", "hyper")])).is_err());
+    }
+}
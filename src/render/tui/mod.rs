@@ -1,12 +1,17 @@
 mod app;
+mod edit;
+mod finder;
 pub mod highlight;
 mod input;
+mod picker;
 mod view;
 
 pub use app::App;
+pub use input::Keymap;
 
-use crate::core::{FlatTableData, TableData};
+use crate::core::{ColumnSelector, FlatConfig, FlatTableData, Formatter, TableData};
 use crate::error::Result;
+use crate::input::SpilledRows;
 use crossterm::{
     event::{self, Event, KeyEventKind},
     execute,
@@ -16,9 +21,14 @@ use ratatui::prelude::*;
 use serde_json::Value;
 use std::io::{self, stdout, Stdout};
 use std::panic;
+use std::path::PathBuf;
 
 type Tui = Terminal<CrosstermBackend<Stdout>>;
 
+/// Columns and flat mode settings the table was laid out with, which `:`
+/// commands start from
+pub type TableLayout = (Option<ColumnSelector>, Option<FlatConfig>);
+
 /// Initialize the terminal for TUI mode
 fn init_terminal() -> io::Result<Tui> {
     execute!(stdout(), EnterAlternateScreen)?;
@@ -43,26 +53,92 @@ fn install_panic_hook() {
 }
 
 /// Run the TUI application
-pub fn run(table_data: TableData, source_records: Vec<Value>) -> Result<()> {
+pub fn run(
+    table_data: TableData,
+    source_records: Vec<Value>,
+    formatter: Formatter,
+    keymap: Keymap,
+    (selector, flat): TableLayout,
+    edit_path: Option<PathBuf>,
+) -> Result<()> {
+    let app = App::new(table_data, source_records)
+        .with_formatter(formatter)
+        .with_keymap(keymap)
+        .with_layout(selector, flat)
+        .with_edit_path(edit_path);
+    run_app(app)
+}
+
+/// Run the TUI application with flat mode data
+pub fn run_flat(
+    flat_data: FlatTableData,
+    source_records: Vec<Value>,
+    formatter: Formatter,
+    keymap: Keymap,
+    (selector, flat): TableLayout,
+) -> Result<()> {
+    let app = App::from_flat(flat_data, source_records)
+        .with_formatter(formatter)
+        .with_keymap(keymap)
+        .with_layout(selector, flat);
+    run_app(app)
+}
+
+/// Run the TUI application over rows left on disk (`--max-memory`)
+pub fn run_spilled(
+    records: SpilledRows,
+    columns: Vec<String>,
+    selector: Option<ColumnSelector>,
+    formatter: Formatter,
+    keymap: Keymap,
+) -> Result<()> {
+    let app = App::from_spilled(records, columns, selector)
+        .with_formatter(formatter)
+        .with_keymap(keymap);
+    run_app(app)
+}
+
+/// Browse from `dir` for a JSON or JSONL file to open; `None` when the
+/// user quits without choosing one
+pub fn pick_file(dir: PathBuf) -> Result<Option<PathBuf>> {
+    let mut picker = picker::FilePicker::new(dir)?;
+
     install_panic_hook();
+    let mut terminal = init_terminal()?;
 
-    let mut terminal = init_terminal().map_err(crate::error::JlcatError::Io)?;
+    let result = run_picker_loop(&mut terminal, &mut picker);
 
-    let mut app = App::new(table_data, source_records);
-    let result = run_event_loop(&mut terminal, &mut app);
+    restore_terminal()?;
 
-    restore_terminal().map_err(crate::error::JlcatError::Io)?;
+    Ok(result?)
+}
 
-    result
+/// Event loop of the file picker
+fn run_picker_loop(
+    terminal: &mut Tui,
+    picker: &mut picker::FilePicker,
+) -> io::Result<Option<PathBuf>> {
+    loop {
+        terminal.draw(|frame| picker::render(frame, picker))?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                match picker.handle_key(key.code)? {
+                    picker::Pick::Continue => {}
+                    picker::Pick::File(path) => return Ok(Some(path)),
+                    picker::Pick::Cancel => return Ok(None),
+                }
+            }
+        }
+    }
 }
 
-/// Run the TUI application with flat mode data
-pub fn run_flat(flat_data: FlatTableData, source_records: Vec<Value>) -> Result<()> {
+/// Take over the terminal for `app` until the user quits
+fn run_app(mut app: App) -> Result<()> {
     install_panic_hook();
 
     let mut terminal = init_terminal().map_err(crate::error::JlcatError::Io)?;
 
-    let mut app = App::from_flat(flat_data, source_records);
     let result = run_event_loop(&mut terminal, &mut app);
 
     restore_terminal().map_err(crate::error::JlcatError::Io)?;
@@ -79,7 +155,7 @@ fn run_event_loop(terminal: &mut Tui, app: &mut App) -> Result<()> {
 
         if let Event::Key(key) = event::read().map_err(crate::error::JlcatError::Io)? {
             if key.kind == KeyEventKind::Press {
-                match input::handle_key(app, key.code) {
+                match input::handle_key(app, key) {
                     input::Action::Quit => break,
                     input::Action::Continue => {}
                 }
@@ -0,0 +1,275 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File extensions listed unless every file is shown
+const JSON_EXTENSIONS: &[&str] = &["json", "jsonl", "ndjson"];
+
+/// An entry of the directory being browsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// What a key press in the picker leads to
+#[derive(Debug, PartialEq, Eq)]
+pub enum Pick {
+    Continue,
+    /// The user chose this file
+    File(PathBuf),
+    /// The user left without choosing
+    Cancel,
+}
+
+/// State of the file browser shown by `jlcat -i` without input
+pub struct FilePicker {
+    dir: PathBuf,
+    /// Parent link first, then directories, then files, each sorted by name
+    entries: Vec<Entry>,
+    selected: usize,
+    /// List every file rather than JSON files only
+    all_files: bool,
+}
+
+impl FilePicker {
+    pub fn new(dir: PathBuf) -> io::Result<Self> {
+        let mut picker = Self {
+            dir,
+            entries: Vec::new(),
+            selected: 0,
+            all_files: false,
+        };
+        picker.refresh()?;
+        Ok(picker)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn all_files(&self) -> bool {
+        self.all_files
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> io::Result<Pick> {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(Pick::Cancel),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.selected + 1 < self.entries.len() => {
+                self.selected += 1;
+            }
+            KeyCode::Char('g') | KeyCode::Home => self.selected = 0,
+            KeyCode::Char('G') | KeyCode::End => {
+                self.selected = self.entries.len().saturating_sub(1);
+            }
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => self.enter_parent()?,
+            KeyCode::Tab => {
+                self.all_files = !self.all_files;
+                self.selected = 0;
+                self.refresh()?;
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                let Some(entry) = self.entries.get(self.selected).cloned() else {
+                    return Ok(Pick::Continue);
+                };
+                if entry.name == ".." {
+                    self.enter_parent()?;
+                } else if entry.is_dir {
+                    self.dir.push(&entry.name);
+                    self.selected = 0;
+                    self.refresh()?;
+                } else {
+                    return Ok(Pick::File(self.dir.join(&entry.name)));
+                }
+            }
+            _ => {}
+        }
+        Ok(Pick::Continue)
+    }
+
+    /// Move to the parent directory, selecting the one just left
+    fn enter_parent(&mut self) -> io::Result<()> {
+        let Some(parent) = self.dir.parent().map(Path::to_path_buf) else {
+            return Ok(());
+        };
+        let left = self
+            .dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned());
+        self.dir = parent;
+        self.refresh()?;
+        self.selected = left
+            .and_then(|name| self.entries.iter().position(|e| e.is_dir && e.name == name))
+            .unwrap_or(0);
+        Ok(())
+    }
+
+    /// Re-read the directory
+    fn refresh(&mut self) -> io::Result<()> {
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') {
+                continue;
+            }
+            // Follows symlinks, so linked directories can be entered
+            let is_dir = entry.path().is_dir();
+            if is_dir {
+                dirs.push(name);
+            } else if self.all_files || is_json_file(&name) {
+                files.push(name);
+            }
+        }
+        dirs.sort();
+        files.sort();
+
+        self.entries = self
+            .dir
+            .parent()
+            .map(|_| "..".to_string())
+            .into_iter()
+            .chain(dirs)
+            .map(|name| Entry { name, is_dir: true })
+            .chain(files.into_iter().map(|name| Entry {
+                name,
+                is_dir: false,
+            }))
+            .collect();
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+        Ok(())
+    }
+}
+
+fn is_json_file(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            JSON_EXTENSIONS
+                .iter()
+                .any(|json| ext.eq_ignore_ascii_case(json))
+        })
+}
+
+/// Render the file browser
+pub fn render(frame: &mut Frame, picker: &FilePicker) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),    // Entries
+            Constraint::Length(4), // Help
+        ])
+        .split(frame.area());
+
+    let items: Vec<ListItem> = picker
+        .entries()
+        .iter()
+        .map(|entry| {
+            if entry.is_dir {
+                ListItem::new(format!("{}/", entry.name)).style(Style::default().fg(Color::Cyan))
+            } else {
+                ListItem::new(entry.name.as_str())
+            }
+        })
+        .collect();
+
+    let filter = if picker.all_files() {
+        "all files"
+    } else {
+        "*.json, *.jsonl, *.ndjson"
+    };
+    let title = format!(" jlcat - {} ({}) ", picker.dir().display(), filter);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(
+            Style::default()
+                .bg(Color::Blue)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default().with_selected(Some(picker.selected()));
+    frame.render_stateful_widget(list, chunks[0], &mut state);
+
+    let help = Paragraph::new(vec![
+        Line::from("Pick a JSON or JSONL file to open"),
+        Line::from(Span::styled(
+            "Enter:open  Backspace:parent  Tab:all files/JSON only  q:quit",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ])
+    .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(help, chunks[1]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn names(picker: &FilePicker) -> Vec<&str> {
+        picker.entries().iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_lists_directories_then_json_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("logs")).unwrap();
+        for name in ["b.jsonl", "a.json", "notes.txt", ".hidden.json", "c.NDJSON"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let mut picker = FilePicker::new(dir.path().to_path_buf()).unwrap();
+        assert_eq!(
+            names(&picker),
+            vec!["..", "logs", "a.json", "b.jsonl", "c.NDJSON"]
+        );
+
+        picker.handle_key(KeyCode::Tab).unwrap();
+        assert!(names(&picker).contains(&"notes.txt"));
+    }
+
+    #[test]
+    fn test_enter_opens_directories_and_picks_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("logs")).unwrap();
+        fs::write(dir.path().join("logs").join("app.jsonl"), "").unwrap();
+
+        let mut picker = FilePicker::new(dir.path().to_path_buf()).unwrap();
+        picker.handle_key(KeyCode::Down).unwrap();
+        assert_eq!(picker.handle_key(KeyCode::Enter).unwrap(), Pick::Continue);
+        assert_eq!(picker.dir(), dir.path().join("logs"));
+
+        picker.handle_key(KeyCode::Down).unwrap();
+        assert_eq!(
+            picker.handle_key(KeyCode::Enter).unwrap(),
+            Pick::File(dir.path().join("logs").join("app.jsonl"))
+        );
+
+        // Going back up selects the directory just left
+        picker.handle_key(KeyCode::Backspace).unwrap();
+        assert_eq!(picker.dir(), dir.path());
+        assert_eq!(picker.entries()[picker.selected()].name, "logs");
+
+        assert_eq!(picker.handle_key(KeyCode::Char('q')).unwrap(), Pick::Cancel);
+    }
+}
@@ -1,5 +1,6 @@
 use super::app::{App, InputMode};
 use super::highlight::highlight_json;
+use crate::core::{display_width, placeholder, take_width, CellStyle, StyleColor};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -19,6 +20,7 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         ])
         .split(frame.area());
 
+    app.ensure_column_visible(columns_that_fit(chunks[0]));
     render_table(frame, app, chunks[0]);
     render_footer(frame, app, chunks[1]);
 
@@ -30,14 +32,39 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     if app.mode == InputMode::Detail {
         render_detail_modal(frame, app);
     }
+
+    // Render top-values popup on top if in TopValues mode
+    if app.mode == InputMode::TopValues {
+        render_top_values(frame, app);
+    }
+
+    // Render column finder on top if in FindColumn mode
+    if app.mode == InputMode::FindColumn {
+        render_column_finder(frame, app);
+    }
+}
+
+/// Narrowest a column gets before the table scrolls sideways
+const MIN_COLUMN_WIDTH: u16 = 12;
+
+/// How many columns the table shows at once in `area`
+fn columns_that_fit(area: Rect) -> usize {
+    (area.width.saturating_sub(2) / MIN_COLUMN_WIDTH).max(1) as usize
 }
 
 fn render_table(frame: &mut Frame, app: &App, area: Rect) {
+    // Columns shown, scrolled to keep the selected one in view
+    let col_count = app.columns().len();
+    let first = app.column_offset();
+    let shown = columns_that_fit(area).min(col_count.saturating_sub(first));
+
     let header_cells: Vec<Cell> = app
         .columns()
         .iter()
+        .skip(first)
+        .take(shown)
         .map(|h| {
-            Cell::from(h.clone()).style(
+            Cell::from(app.header_text(h)).style(
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
@@ -47,10 +74,16 @@ fn render_table(frame: &mut Frame, app: &App, area: Rect) {
 
     let header = Row::new(header_cells).height(1).bottom_margin(1);
 
-    // Calculate column widths
-    let col_count = app.columns().len();
-    let constraints: Vec<Constraint> = (0..col_count)
-        .map(|_| Constraint::Percentage((100 / col_count.max(1)) as u16))
+    // Calculate column widths; pinned ones (--widths) take priority
+    let constraints: Vec<Constraint> = app
+        .columns()
+        .iter()
+        .skip(first)
+        .take(shown)
+        .map(|c| match app.formatter().pinned_width(c) {
+            Some(width) => Constraint::Length(width.min(u16::MAX as usize) as u16),
+            None => Constraint::Percentage((100 / shown.max(1)) as u16),
+        })
         .collect();
 
     // Build visible rows
@@ -60,10 +93,42 @@ fn render_table(frame: &mut Frame, app: &App, area: Rect) {
 
     let rows: Vec<Row> = (start..end)
         .map(|visible_idx| {
+            // The selected cell stands out within the selected row
+            let cell_style = |col: usize, style: Style| {
+                if visible_idx == app.selected_row() && col == app.selected_column() {
+                    style.add_modifier(Modifier::REVERSED)
+                } else {
+                    style
+                }
+            };
             let row_data = app.get_visible_row(visible_idx);
             let cells: Vec<Cell> = match row_data {
-                Some(values) => values.iter().map(|v| Cell::from(format_value(v))).collect(),
-                None => vec![Cell::from(""); col_count],
+                Some(values) if !app.formatter().is_empty() => {
+                    let styles = app.formatter().cell_styles(app.columns(), &values);
+                    app.columns()
+                        .iter()
+                        .zip(values.iter())
+                        .zip(styles.iter())
+                        .enumerate()
+                        .skip(first)
+                        .take(shown)
+                        .map(|(i, ((c, v), s))| {
+                            Cell::from(cell_text(app, c, v)).style(cell_style(i, to_style(s)))
+                        })
+                        .collect()
+                }
+                Some(values) => app
+                    .columns()
+                    .iter()
+                    .zip(values.iter())
+                    .enumerate()
+                    .skip(first)
+                    .take(shown)
+                    .map(|(i, (c, v))| {
+                        Cell::from(cell_text(app, c, v)).style(cell_style(i, Style::default()))
+                    })
+                    .collect(),
+                None => vec![Cell::from(""); shown],
             };
 
             let style = if visible_idx == app.selected_row() {
@@ -74,16 +139,29 @@ fn render_table(frame: &mut Frame, app: &App, area: Rect) {
             } else {
                 Style::default()
             };
+            let style = if app.is_deleted(visible_idx) {
+                style.add_modifier(Modifier::CROSSED_OUT | Modifier::DIM)
+            } else {
+                style
+            };
 
             Row::new(cells).style(style)
         })
         .collect();
 
-    let title = format!(
+    let mut title = format!(
         " jlcat - {} rows ({} shown) ",
         app.visible_row_count(),
         rows.len()
     );
+    if shown < col_count {
+        title.push_str(&format!(
+            "- columns {}-{} of {} ",
+            first + 1,
+            first + shown,
+            col_count
+        ));
+    }
 
     let table = Table::new(rows, constraints)
         .header(header)
@@ -98,28 +176,39 @@ fn render_table(frame: &mut Frame, app: &App, area: Rect) {
 fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
     let content = match app.mode {
         InputMode::Normal => {
-            // Show selected row details and help
+            // Show the last action's result or selected row details, and help
             let selected = app.get_selected_row();
-            let details = match selected {
-                Some(values) => {
+            let details = match (app.status(), selected) {
+                (Some(status), _) => status.to_string(),
+                (None, Some(values)) => {
                     let cols = app.columns();
                     cols.iter()
                         .zip(values.iter())
-                        .map(|(c, v)| format!("{}={}", c, format_value_short(v)))
+                        .map(|(c, v)| format!("{}={}", c, truncate_short(cell_text(app, c, v))))
                         .collect::<Vec<_>>()
                         .join(" | ")
                 }
-                None => "No data".to_string(),
+                (None, None) => "No data".to_string(),
             };
 
-            let status = if !app.search_query().is_empty() || !app.filter_text().is_empty() {
+            let status = if !app.search_query().is_empty()
+                || !app.filter_text().is_empty()
+                || app.whole_word()
+                || app.deleted_count() > 0
+            {
                 let mut parts = vec![];
+                if app.deleted_count() > 0 {
+                    parts.push(format!("{} marked for deletion", app.deleted_count()));
+                }
                 if !app.search_query().is_empty() {
                     parts.push(format!("search: {}", app.search_query()));
                 }
                 if !app.filter_text().is_empty() {
                     parts.push(format!("filter: {}", app.filter_text()));
                 }
+                if app.whole_word() {
+                    parts.push("whole words".to_string());
+                }
                 format!(" [{}]", parts.join(", "))
             } else {
                 String::new()
@@ -128,7 +217,10 @@ fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
             vec![
                 Line::from(details),
                 Line::from(Span::styled(
-                    format!("q:quit  /:search  f:filter  c:clear{}", status),
+                    format!(
+                        "q:quit  /:search  f:filter  c:clear  w:words  u:undo  e:edit  d:delete  :write{}",
+                        status
+                    ),
                     Style::default().fg(Color::DarkGray),
                 )),
             ]
@@ -147,14 +239,88 @@ fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
             ]
         }
         InputMode::Filter => {
+            let (columns, current) = app.column_completions();
+            let hint = if columns.is_empty() {
+                Line::from(Span::styled(
+                    "Enter:confirm  Esc:cancel  Tab:complete column  (e.g., age>30 name~alice)",
+                    Style::default().fg(Color::DarkGray),
+                ))
+            } else {
+                let mut spans = vec![Span::styled("Tab: ", Style::default().fg(Color::DarkGray))];
+                for (i, column) in columns.into_iter().enumerate() {
+                    let style = if Some(i) == current {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::default().fg(Color::Cyan)
+                    };
+                    spans.push(Span::styled(column, style));
+                    spans.push(Span::raw(" "));
+                }
+                Line::from(spans)
+            };
             vec![
                 Line::from(vec![
                     Span::styled("Filter: ", Style::default().fg(Color::Yellow)),
                     Span::raw(&app.input_buffer),
                     Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
                 ]),
+                hint,
+            ]
+        }
+        InputMode::Edit => {
+            let column = app
+                .columns()
+                .get(app.selected_column())
+                .map(|c| app.formatter().header_text(c))
+                .unwrap_or_default();
+            vec![
+                Line::from(vec![
+                    Span::styled(
+                        format!("Edit {}: ", column),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(&app.input_buffer),
+                    Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+                ]),
+                Line::from(Span::styled(
+                    "Enter:save to file  Esc:cancel",
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ]
+        }
+        InputMode::Command => {
+            vec![
+                Line::from(vec![
+                    Span::styled(":", Style::default().fg(Color::Yellow)),
+                    Span::raw(&app.input_buffer),
+                    Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+                ]),
+                Line::from(Span::styled(
+                    "Enter:run  Esc:cancel  (sort, filter, search, columns, flat, export FILE, write FILE)",
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ]
+        }
+        InputMode::TopValues => {
+            vec![
+                Line::from(Span::styled(
+                    "Top Values",
+                    Style::default().fg(Color::Yellow),
+                )),
+                Line::from(Span::styled(
+                    "Enter:filter on value  Esc:close",
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ]
+        }
+        InputMode::FindColumn => {
+            vec![
                 Line::from(Span::styled(
-                    "Enter:confirm  Esc:cancel  (e.g., age>30 name~alice)",
+                    "Find Column",
+                    Style::default().fg(Color::Yellow),
+                )),
+                Line::from(Span::styled(
+                    "Type to narrow  Enter:go to column  Up/Down:move  Esc:close",
                     Style::default().fg(Color::DarkGray),
                 )),
             ]
@@ -184,17 +350,61 @@ fn format_value(value: &Value) -> String {
         Value::Bool(b) => b.to_string(),
         Value::Number(n) => n.to_string(),
         Value::String(s) => s.clone(),
-        Value::Array(_) => "[...]".to_string(),
-        Value::Object(_) => "{...}".to_string(),
+        Value::Array(_) | Value::Object(_) => placeholder(value),
+    }
+}
+
+/// Convert a formatting rule style into a ratatui style
+fn to_style(style: &CellStyle) -> Style {
+    let mut result = Style::default();
+    if let Some(fg) = style.fg {
+        result = result.fg(to_color(fg));
+    }
+    if let Some(bg) = style.bg {
+        result = result.bg(to_color(bg));
+    }
+    if style.bold {
+        result = result.add_modifier(Modifier::BOLD);
     }
+    if style.italic {
+        result = result.add_modifier(Modifier::ITALIC);
+    }
+    if style.underline {
+        result = result.add_modifier(Modifier::UNDERLINED);
+    }
+    if style.dim {
+        result = result.add_modifier(Modifier::DIM);
+    }
+    result
+}
+
+fn to_color(color: StyleColor) -> Color {
+    match color {
+        StyleColor::Black => Color::Black,
+        StyleColor::Red => Color::Red,
+        StyleColor::Green => Color::Green,
+        StyleColor::Yellow => Color::Yellow,
+        StyleColor::Blue => Color::Blue,
+        StyleColor::Magenta => Color::Magenta,
+        StyleColor::Cyan => Color::Cyan,
+        StyleColor::White => Color::White,
+        StyleColor::Gray => Color::DarkGray,
+    }
+}
+
+/// Cell text after display formats (e.g., `--date-format`, `--max-col-width`) are applied
+fn cell_text(app: &App, column: &str, value: &Value) -> String {
+    let text = app
+        .formatter()
+        .cell_text(column, value)
+        .unwrap_or_else(|| format_value(value));
+    app.formatter()
+        .truncate_cell(column, app.formatter().sanitize(text))
 }
 
-fn format_value_short(value: &Value) -> String {
-    let s = format_value(value);
-    let char_count = s.chars().count();
-    if char_count > 20 {
-        let truncated: String = s.chars().take(17).collect();
-        format!("{}...", truncated)
+fn truncate_short(s: String) -> String {
+    if display_width(&s) > 20 {
+        format!("{}...", take_width(&s, 17))
     } else {
         s
     }
@@ -210,6 +420,111 @@ fn centered_rect(percent: u16, area: Rect) -> Rect {
     Rect::new(x, y, popup_width, popup_height)
 }
 
+/// Render the most frequent values of a column, with their counts
+fn render_top_values(frame: &mut Frame, app: &App) {
+    let Some(state) = app.top_values() else {
+        return;
+    };
+    let area = centered_rect(60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let width = state
+        .values
+        .first()
+        .map(|(_, count)| count.to_string().len())
+        .unwrap_or(1);
+    let lines: Vec<Line> = state
+        .values
+        .iter()
+        .enumerate()
+        .map(|(i, (value, count))| {
+            let share = *count as f64 * 100.0 / state.rows.max(1) as f64;
+            let text = format!(
+                "{:>width$}  {:>5.1}%  {}",
+                count,
+                share,
+                cell_text(app, &state.column, value),
+                width = width
+            );
+            let style = if i == state.selected {
+                Style::default()
+                    .bg(Color::Blue)
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(text, style))
+        })
+        .collect();
+
+    let title = format!(
+        " {}: {} distinct in {} rows ",
+        app.formatter().header_text(&state.column),
+        state.distinct,
+        state.rows
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(title);
+
+    // Keep the selected value in view
+    let height = area.height.saturating_sub(2) as usize;
+    let scroll = (state.selected + 1).saturating_sub(height);
+    let popup = Paragraph::new(lines)
+        .block(block)
+        .scroll((scroll as u16, 0));
+    frame.render_widget(popup, area);
+}
+
+/// Render the column finder: the query and the columns matching it
+fn render_column_finder(frame: &mut Frame, app: &App) {
+    let Some(finder) = app.column_finder() else {
+        return;
+    };
+    let area = centered_rect(60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let lines: Vec<Line> = finder
+        .matches
+        .iter()
+        .enumerate()
+        .map(|(i, &column)| {
+            let name = app.columns().get(column).cloned().unwrap_or_default();
+            let name = app.formatter().sanitize(name);
+            let style = if i == finder.selected {
+                Style::default()
+                    .bg(Color::Blue)
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(name, style))
+        })
+        .collect();
+
+    let title = format!(
+        " Column: {}_ ({} of {}) ",
+        finder.query,
+        finder.matches.len(),
+        app.columns().len()
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan))
+        .title(title);
+
+    // Keep the selected column in view
+    let height = area.height.saturating_sub(2) as usize;
+    let scroll = (finder.selected + 1).saturating_sub(height);
+    let popup = Paragraph::new(lines)
+        .block(block)
+        .scroll((scroll as u16, 0));
+    frame.render_widget(popup, area);
+}
+
 /// Render the detail view modal
 fn render_detail_modal(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
@@ -218,15 +533,12 @@ fn render_detail_modal(frame: &mut Frame, app: &mut App) {
     // Clear the area behind the modal
     frame.render_widget(Clear, modal_area);
 
-    // Get the selected source JSON
-    let source = match app.get_selected_source() {
-        Some(v) => v,
+    // Get highlighted lines of the selected source JSON
+    let lines = match app.get_selected_source() {
+        Some(source) => highlight_json(&source),
         None => return,
     };
 
-    // Get highlighted lines
-    let lines = highlight_json(source);
-
     // Calculate viewport height (modal height minus borders and header/footer)
     let viewport_height = modal_area.height.saturating_sub(4) as usize;
 
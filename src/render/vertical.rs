@@ -0,0 +1,157 @@
+use super::cat::format_value;
+use super::TableRenderer;
+use crate::core::{display_width, FlatTableData, Formatter, SchemaInferrer, TableData};
+use serde_json::Value;
+
+/// Renders each row as a block of `field | value` lines, like psql's `\x` mode
+pub struct VerticalRenderer {
+    formatter: Formatter,
+}
+
+impl VerticalRenderer {
+    pub fn new() -> Self {
+        Self {
+            formatter: Formatter::default(),
+        }
+    }
+
+    /// Apply time formats and truncation to rendered values
+    pub fn with_formatter(mut self, formatter: Formatter) -> Self {
+        self.formatter = formatter;
+        self
+    }
+
+    fn render_records(&self, columns: &[String], rows: &[Vec<Value>]) -> String {
+        let schema = self
+            .formatter
+            .shows_types()
+            .then(|| SchemaInferrer::infer_cells(columns, rows));
+        let names: Vec<String> = columns
+            .iter()
+            .map(|c| self.formatter.typed_header_text(c, schema.as_ref()))
+            .collect();
+        let name_width = names.iter().map(|c| display_width(c)).max().unwrap_or(0);
+
+        let records: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                columns
+                    .iter()
+                    .zip(&names)
+                    .zip(row.iter())
+                    .map(|((col, name), v)| {
+                        let text = self
+                            .formatter
+                            .cell_text(col, v)
+                            .unwrap_or_else(|| format_value(v));
+                        let text = self.formatter.truncate(self.formatter.sanitize(text));
+                        format!("{}{} | {}", name, pad(name, name_width), text)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Dividers span the widest line, as in psql
+        let line_width = records
+            .iter()
+            .flatten()
+            .flat_map(|line| line.lines())
+            .map(display_width)
+            .max()
+            .unwrap_or(0);
+
+        let mut out = Vec::new();
+        for (i, lines) in records.into_iter().enumerate() {
+            out.push(divider(i + 1, line_width));
+            out.extend(lines);
+        }
+        out.join("\n")
+    }
+}
+
+impl Default for VerticalRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TableRenderer for VerticalRenderer {
+    fn render(&self, table_data: &TableData) -> String {
+        self.render_records(table_data.columns(), table_data.rows())
+    }
+
+    fn render_flat(&self, table_data: &FlatTableData) -> String {
+        self.render_records(&table_data.columns(), table_data.rows())
+    }
+}
+
+/// Record divider such as `-[ RECORD 1 ]-----`, padded with dashes to `width`
+pub(super) fn divider(index: usize, width: usize) -> String {
+    let label = format!("-[ RECORD {} ]", index);
+    let dashes = width.saturating_sub(label.len()).max(1);
+    format!("{}{}", label, "-".repeat(dashes))
+}
+
+fn pad(text: &str, width: usize) -> String {
+    " ".repeat(width.saturating_sub(display_width(text)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_vertical() {
+        let rows = vec![
+            json!({"id": 1, "name": "Alice"}),
+            json!({"id": 2, "name": "Bob"}),
+        ];
+        let table_data = TableData::from_rows(rows, None);
+
+        let output = VerticalRenderer::new().render(&table_data);
+
+        assert_eq!(
+            output,
+            "-[ RECORD 1 ]-\n\
+             id   | 1\n\
+             name | Alice\n\
+             -[ RECORD 2 ]-\n\
+             id   | 2\n\
+             name | Bob"
+        );
+    }
+
+    #[test]
+    fn test_render_vertical_pads_divider_to_widest_line() {
+        let rows = vec![json!({"description": "a fairly long value"})];
+        let table_data = TableData::from_rows(rows, None);
+
+        let output = VerticalRenderer::new().render(&table_data);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0].chars().count(), lines[1].chars().count());
+        assert!(lines[0].starts_with("-[ RECORD 1 ]---"));
+    }
+
+    #[test]
+    fn test_render_vertical_aligns_wide_characters() {
+        let rows = vec![json!({"名前": "東京タワー", "id": 1})];
+        let table_data = TableData::from_rows(rows, None);
+
+        let output = VerticalRenderer::new().render(&table_data);
+
+        assert_eq!(
+            output,
+            "-[ RECORD 1 ]----\n\
+             id   | 1\n\
+             名前 | 東京タワー"
+        );
+    }
+
+    #[test]
+    fn test_render_vertical_empty() {
+        let table_data = TableData::from_rows(vec![], None);
+        assert_eq!(VerticalRenderer::new().render(&table_data), "");
+    }
+}
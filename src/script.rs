@@ -0,0 +1,124 @@
+//! Rhai scripting for derived columns and complex predicates.
+//!
+//! Each row is bound to `row` as an object map. Scripts can assign new fields
+//! (`row.total = row.price * row.qty`) and drop rows by evaluating to `false`
+//! (`row.tags.len() > 2 && row.active`).
+
+use crate::error::{JlcatError, Result};
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde_json::Value;
+use std::path::Path;
+
+pub struct RowScript {
+    engine: Engine,
+    ast: AST,
+}
+
+impl RowScript {
+    pub fn compile(source: &str) -> Result<Self> {
+        let engine = Engine::new();
+        let ast = engine
+            .compile(source)
+            .map_err(|e| JlcatError::Script(e.to_string()))?;
+        Ok(Self { engine, ast })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)?;
+        Self::compile(&source)
+    }
+
+    /// Run the script over every row, keeping the (possibly modified) `row`
+    /// unless the script evaluates to `false`
+    pub fn apply(&self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        let mut result = Vec::with_capacity(rows.len());
+
+        for (idx, row) in rows.into_iter().enumerate() {
+            let row_num = idx + 1;
+            let mut scope = Scope::new();
+            scope.push("row", to_dynamic(&row));
+
+            let outcome: Dynamic = self
+                .engine
+                .eval_ast_with_scope(&mut scope, &self.ast)
+                .map_err(|e| script_error(row_num, e))?;
+            if outcome.as_bool() == Ok(false) {
+                continue;
+            }
+
+            let row_dyn = scope.get_value::<Dynamic>("row").unwrap_or(Dynamic::UNIT);
+            let value: Value =
+                rhai::serde::from_dynamic(&row_dyn).map_err(|e| script_error(row_num, e))?;
+            if !value.is_object() {
+                return Err(JlcatError::Script(format!(
+                    "row {}: 'row' must remain an object map",
+                    row_num
+                )));
+            }
+            result.push(value);
+        }
+
+        Ok(result)
+    }
+}
+
+/// Convert a JSON value to a Rhai value.
+/// Done by hand because `arbitrary_precision` numbers don't serialize as plain numbers.
+fn to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::Null => Dynamic::UNIT,
+        Value::Bool(b) => Dynamic::from(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Dynamic::from(i),
+            None => Dynamic::from(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => Dynamic::from(s.clone()),
+        Value::Array(arr) => Dynamic::from_array(arr.iter().map(to_dynamic).collect()),
+        Value::Object(obj) => Dynamic::from_map(
+            obj.iter()
+                .map(|(k, v)| (k.as_str().into(), to_dynamic(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn script_error(row_num: usize, e: impl std::fmt::Display) -> JlcatError {
+    JlcatError::Script(format!("row {}: {}", row_num, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_derived_column() {
+        let script = RowScript::compile("row.total = row.price * row.qty").unwrap();
+        let rows = vec![json!({"price": 3, "qty": 4}), json!({"price": 5, "qty": 0})];
+
+        let result = script.apply(rows).unwrap();
+        assert_eq!(result[0]["total"], json!(12));
+        assert_eq!(result[1]["total"], json!(0));
+    }
+
+    #[test]
+    fn test_predicate_drops_rows() {
+        let script = RowScript::compile("row.age > 30").unwrap();
+        let rows = vec![json!({"age": 25}), json!({"age": 35})];
+
+        let result = script.apply(rows).unwrap();
+        assert_eq!(result, vec![json!({"age": 35})]);
+    }
+
+    #[test]
+    fn test_compile_error() {
+        assert!(RowScript::compile("row.total = ").is_err());
+    }
+
+    #[test]
+    fn test_runtime_error_reports_row() {
+        let script = RowScript::compile(r#"throw "boom""#).unwrap();
+        let err = script.apply(vec![json!({"id": 1})]).unwrap_err();
+        assert!(err.to_string().contains("row 1"));
+    }
+}
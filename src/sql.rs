@@ -0,0 +1,354 @@
+//! SQL queries over the input rows.
+//!
+//! Rows are loaded into an in-memory SQLite table named `t` with one column per
+//! top-level key; arrays and objects are stored as JSON text, so SQLite's JSON
+//! functions (`json_extract(user, '$.name')`) can reach into them. The result
+//! rows of the query replace the input rows.
+//!
+//! [`SqliteExport`] goes the other way, writing the output tables to a
+//! database file.
+
+use crate::error::{JlcatError, Result};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::Connection;
+use serde_json::{Map, Number, Value};
+use std::path::{Path, PathBuf};
+
+/// Name of the table holding the input rows
+const TABLE: &str = "t";
+
+pub struct SqlQuery {
+    sql: String,
+}
+
+impl SqlQuery {
+    pub fn new(sql: &str) -> Self {
+        Self {
+            sql: sql.to_string(),
+        }
+    }
+
+    /// Load the rows into `t` and run the query, one object per result row
+    pub fn apply(&self, rows: Vec<Value>) -> Result<Vec<Value>> {
+        let mut conn = Connection::open_in_memory().map_err(sql_error)?;
+        load(&mut conn, &rows).map_err(sql_error)?;
+
+        let mut stmt = conn.prepare(&self.sql).map_err(sql_error)?;
+        let names: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+        let mut result_rows = stmt.query([]).map_err(sql_error)?;
+
+        let mut result = Vec::new();
+        while let Some(row) = result_rows.next().map_err(sql_error)? {
+            let mut object = Map::new();
+            for (i, name) in names.iter().enumerate() {
+                let value: SqlValue = row.get(i).map_err(sql_error)?;
+                object.insert(name.clone(), from_sql(value));
+            }
+            result.push(Value::Object(object));
+        }
+        Ok(result)
+    }
+}
+
+/// Create `t` with a column per top-level key, in order of first appearance.
+/// SQLite column names ignore case, so a key matching an earlier one but for
+/// case gets a numbered column such as `ID_2`, with a warning.
+fn load(conn: &mut Connection, rows: &[Value]) -> rusqlite::Result<()> {
+    let mut keys: Vec<String> = Vec::new();
+    let mut columns: Vec<String> = Vec::new();
+    for row in rows {
+        for key in row.as_object().into_iter().flat_map(Map::keys) {
+            if keys.contains(key) {
+                continue;
+            }
+            let column = unique_column(key, &columns);
+            if column != *key {
+                crate::color::warn(&format!(
+                    "--sql: key '{}' is column {} in {}, as SQLite column names ignore case",
+                    key, column, TABLE
+                ));
+            }
+            keys.push(key.clone());
+            columns.push(column);
+        }
+    }
+    let values: Vec<Vec<Value>> = rows
+        .iter()
+        .map(|row| {
+            keys.iter()
+                .map(|key| row.get(key).cloned().unwrap_or(Value::Null))
+                .collect()
+        })
+        .collect();
+    create_table(conn, TABLE, &columns, &values)
+}
+
+/// `key`, or `key_2`, `key_3`, ... if the name is taken ignoring case
+fn unique_column(key: &str, columns: &[String]) -> String {
+    let taken = |name: &str| columns.iter().any(|c| c.eq_ignore_ascii_case(name));
+    if !taken(key) {
+        return key.to_string();
+    }
+    let mut n = 2;
+    loop {
+        let name = format!("{}_{}", key, n);
+        if !taken(&name) {
+            return name;
+        }
+        n += 1;
+    }
+}
+
+/// Writes tables to a SQLite database file (`--to-sqlite`)
+pub struct SqliteExport {
+    conn: Connection,
+}
+
+impl SqliteExport {
+    /// Open or create the database at `path`
+    pub fn create(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path).map_err(sql_error)?;
+        Ok(Self { conn })
+    }
+
+    /// Replace table `name` with the rows, typing each column from its values
+    pub fn write_table(
+        &mut self,
+        name: &str,
+        columns: &[String],
+        rows: &[Vec<Value>],
+    ) -> Result<()> {
+        self.conn
+            .execute(&format!("DROP TABLE IF EXISTS {}", quote(name)), [])
+            .map_err(sql_error)?;
+        create_table(&mut self.conn, name, columns, rows).map_err(sql_error)
+    }
+}
+
+/// Split `out.db:table` into the database path and the table name, if given
+pub fn parse_target(spec: &str) -> (PathBuf, Option<String>) {
+    match spec.rsplit_once(':') {
+        Some((path, table))
+            if !path.is_empty() && !table.is_empty() && !table.contains(['/', '\\']) =>
+        {
+            (PathBuf::from(path), Some(table.to_string()))
+        }
+        _ => (PathBuf::from(spec), None),
+    }
+}
+
+/// Create a table and insert the rows in one transaction
+fn create_table(
+    conn: &mut Connection,
+    name: &str,
+    columns: &[String],
+    rows: &[Vec<Value>],
+) -> rusqlite::Result<()> {
+    // SQLite tables need at least one column
+    let definitions = if columns.is_empty() {
+        quote("_")
+    } else {
+        columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let affinity = affinity(rows.iter().filter_map(|row| row.get(i)));
+                format!("{} {}", quote(column), affinity)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let tx = conn.transaction()?;
+    tx.execute(
+        &format!("CREATE TABLE {} ({})", quote(name), definitions),
+        [],
+    )?;
+
+    if !columns.is_empty() {
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let mut insert = tx.prepare(&format!(
+            "INSERT INTO {} VALUES ({})",
+            quote(name),
+            placeholders
+        ))?;
+        for row in rows {
+            let values = (0..columns.len()).map(|i| to_sql(row.get(i).unwrap_or(&Value::Null)));
+            insert.execute(rusqlite::params_from_iter(values))?;
+        }
+    }
+    tx.commit()
+}
+
+/// Column type from the non-null values: INTEGER for integers and booleans,
+/// REAL once any number has a fraction, TEXT for everything else
+fn affinity<'a>(values: impl Iterator<Item = &'a Value>) -> &'static str {
+    let mut affinity = "";
+    for value in values {
+        let next = match value {
+            Value::Null => continue,
+            Value::Bool(_) => "INTEGER",
+            Value::Number(n) if n.as_i64().is_some() => "INTEGER",
+            Value::Number(_) => "REAL",
+            _ => return "TEXT",
+        };
+        if affinity != "REAL" {
+            affinity = next;
+        }
+    }
+    affinity
+}
+
+/// Quote an identifier, doubling embedded quotes
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(i64::from(*b)),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => SqlValue::Integer(i),
+            (None, Some(f)) => SqlValue::Real(f),
+            // Beyond f64 range; keep the digits
+            (None, None) => SqlValue::Text(n.to_string()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Array(_) | Value::Object(_) => SqlValue::Text(value.to_string()),
+    }
+}
+
+fn from_sql(value: SqlValue) -> Value {
+    match value {
+        SqlValue::Null => Value::Null,
+        SqlValue::Integer(i) => Value::from(i),
+        SqlValue::Real(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+        SqlValue::Text(s) => Value::String(s),
+        SqlValue::Blob(b) => Value::String(String::from_utf8_lossy(&b).into_owned()),
+    }
+}
+
+fn sql_error(e: rusqlite::Error) -> JlcatError {
+    JlcatError::Sql(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_group_by() {
+        let rows = vec![
+            json!({"status": "ok", "ms": 10}),
+            json!({"status": "error", "ms": 50}),
+            json!({"status": "ok", "ms": 30}),
+        ];
+        let query = SqlQuery::new(
+            "select status, count(*) as n, avg(ms) as avg_ms from t group by 1 order by 1",
+        );
+        assert_eq!(
+            query.apply(rows).unwrap(),
+            vec![
+                json!({"status": "error", "n": 1, "avg_ms": 50.0}),
+                json!({"status": "ok", "n": 2, "avg_ms": 20.0}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_keys_nested_values_and_quoting() {
+        let rows = vec![
+            json!({"id": 1, "user": {"name": "Alice"}, "odd \"key\"": true}),
+            json!({"id": 2}),
+        ];
+        let query = SqlQuery::new(
+            r#"select id, json_extract(user, '$.name') as name, "odd ""key""" as odd from t"#,
+        );
+        assert_eq!(
+            query.apply(rows).unwrap(),
+            vec![
+                json!({"id": 1, "name": "Alice", "odd": 1}),
+                json!({"id": 2, "name": null, "odd": null}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keys_differing_only_in_case() {
+        // Object keys iterate in sorted order, so "ID" claims the name first
+        let rows = vec![json!({"id": 1, "ID": 2}), json!({"id_2": 3})];
+        let query = SqlQuery::new("select * from t");
+        assert_eq!(
+            query.apply(rows).unwrap(),
+            vec![
+                json!({"ID": 2, "id_2": 1, "id_2_2": null}),
+                json!({"ID": null, "id_2": null, "id_2_2": 3}),
+            ]
+        );
+        assert_eq!(
+            unique_column("Id", &["ID".to_string(), "id_2".to_string()]),
+            "Id_3"
+        );
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let query = SqlQuery::new("select count(*) as n from t");
+        assert_eq!(query.apply(Vec::new()).unwrap(), vec![json!({"n": 0})]);
+    }
+
+    #[test]
+    fn test_export_tables() {
+        let path = std::env::temp_dir().join(format!("jlcat-export-{}.db", std::process::id()));
+        let mut export = SqliteExport::create(&path).unwrap();
+        let columns = vec!["id".to_string(), "score".to_string(), "tags".to_string()];
+        let rows = vec![
+            vec![json!(1), json!(2), json!(["a"])],
+            vec![json!(2), json!(2.5), Value::Null],
+        ];
+        export.write_table("data", &columns, &rows).unwrap();
+        // Writing again replaces the table
+        export.write_table("data", &columns, &rows).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        let sql: String = conn
+            .query_row(
+                "select sql from sqlite_master where name = 'data'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#"CREATE TABLE "data" ("id" INTEGER, "score" REAL, "tags" TEXT)"#
+        );
+        let tags: String = conn
+            .query_row("select tags from data where id = 1", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(tags, r#"["a"]"#);
+        let count: i64 = conn
+            .query_row("select count(*) from data", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(parse_target("out.db"), (PathBuf::from("out.db"), None));
+        assert_eq!(
+            parse_target("out.db:events"),
+            (PathBuf::from("out.db"), Some("events".to_string()))
+        );
+        assert_eq!(parse_target("out.db:"), (PathBuf::from("out.db:"), None));
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        let err = SqlQuery::new("select nope from")
+            .apply(Vec::new())
+            .unwrap_err();
+        assert!(matches!(err, JlcatError::Sql(_)));
+    }
+}
@@ -51,7 +51,7 @@ fn test_flat_mode_depth_limit() {
         .assert()
         .success()
         .stdout(predicate::str::contains("a.b"))
-        .stdout(predicate::str::contains("{...}"));
+        .stdout(predicate::str::contains("{1 key}"));
 }
 
 #[test]
@@ -117,3 +117,935 @@ fn test_head_alias() {
         .stdout(predicate::str::contains("Alice"))
         .stdout(predicate::str::contains("Bob").not());
 }
+
+#[test]
+fn test_config_format_rules() {
+    let config = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        config.path(),
+        "[[format]]\nwhen = \"age>30\"\nstyle = \"bold red\"\ncolumns = [\"age\"]\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.arg("--config")
+        .arg(config.path())
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Charlie"));
+}
+
+#[test]
+fn test_config_invalid_style() {
+    let config = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        config.path(),
+        "[[format]]\nwhen = \"age>30\"\nstyle = \"sparkly\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.arg("--config")
+        .arg(config.path())
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("sparkly"));
+}
+
+#[test]
+fn test_config_defaults_from_env() {
+    let config = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        config.path(),
+        "[defaults]\nstyle = \"markdown\"\n\n[[columns]]\npattern = \"simple.*\"\ncolumns = [\"name\"]\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.env("JLCAT_CONFIG", config.path())
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("| name"))
+        .stdout(predicate::str::contains("age").not());
+
+    // Flags win over config defaults
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.env("JLCAT_CONFIG", config.path())
+        .args(["--style", "plain", "-c", "age"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("age"))
+        .stdout(predicate::str::contains("|").not());
+}
+
+#[test]
+fn test_filter_flag() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-f", "age>26", "-f", "name!=Charlie"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Alice"))
+        .stdout(predicate::str::contains("Bob").not())
+        .stdout(predicate::str::contains("Charlie").not());
+}
+
+#[test]
+fn test_filter_whole_word() {
+    let input = r#"{"msg": "invalid input"}
+{"msg": "missing id"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-w", "-f", "msg~id", "--style", "plain"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("missing id"))
+        .stdout(predicate::str::contains("invalid").not());
+}
+
+#[test]
+fn test_grep_flag() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--grep", "LI", "-f", "age<35", "-c", "name"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Alice"))
+        .stdout(predicate::str::contains("Charlie").not())
+        .stdout(predicate::str::contains("Bob").not())
+        .stdout(predicate::str::contains("age").not());
+}
+
+#[test]
+fn test_distinct_on_keep_last() {
+    let input = r#"{"user": "a", "at": "2024-01-03", "status": "third"}
+{"user": "b", "at": "2024-01-01", "status": "only"}
+{"user": "a", "at": "2024-01-01", "status": "first"}
+{"user": "a", "at": "2024-01-02", "status": "second"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-s", "at:date", "--distinct-on", "user", "--keep", "last"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("third"))
+        .stdout(predicate::str::contains("only"))
+        .stdout(predicate::str::contains("first").not())
+        .stdout(predicate::str::contains("second").not());
+}
+
+#[test]
+fn test_multiple_files_tag_source() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    let output = cmd
+        .args([
+            "--tag-source",
+            "--source-label",
+            "first",
+            "-c",
+            "_source,name",
+        ])
+        .args(["--style", "plain"])
+        .args(["tests/fixtures/simple.jsonl", "tests/fixtures/simple.jsonl"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("Alice").count(), 2);
+    assert_eq!(stdout.matches("first").count(), 3);
+    assert_eq!(stdout.matches("tests/fixtures/simple.jsonl").count(), 3);
+}
+
+#[test]
+fn test_select_reshapes_records() {
+    let input = r#"{"id": 1, "items": [{"sku": "a", "qty": 2}, {"sku": "b", "qty": 5}]}
+{"id": 2, "items": []}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--select", ".items[] | {sku, qty}", "--style", "plain"])
+        .args(["-f", "qty>3"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("b"))
+        .stdout(predicate::str::contains("id").not())
+        .stdout(predicate::str::contains("2").not());
+
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--select", ".items["])
+        .write_stdin(input)
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("select expression"));
+}
+
+#[test]
+fn test_profile() {
+    let config = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        config.path(),
+        "[profiles.seniors]\ncolumns = [\"name\"]\nfilter = \"age>=30\"\nsort = [\"-age\"]\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    let output = cmd
+        .arg("--config")
+        .arg(config.path())
+        .args(["--profile", "seniors"])
+        .arg("tests/fixtures/simple.jsonl")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("Bob"));
+    assert!(!stdout.contains("age"));
+    assert!(stdout.find("Charlie").unwrap() < stdout.find("Alice").unwrap());
+
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.arg("--config")
+        .arg(config.path())
+        .args(["--profile", "juniors"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("available: seniors"));
+}
+
+#[test]
+fn test_env_overrides() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.env("JLCAT_STYLE", "markdown")
+        .env("JLCAT_FLAT", "true")
+        .env("JLCAT_LENIENT", "1")
+        .write_stdin("{\"id\": 1, \"user\": {\"name\": \"Alice\"}}\nnot json\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("| user.name"));
+
+    // Flags win over the environment
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.env("JLCAT_STYLE", "markdown")
+        .args(["--style", "plain"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("|").not());
+
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.env("JLCAT_FLAT", "deep")
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("JLCAT_FLAT"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_transform_cmd() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--transform-cmd", "grep Bob", "tests/fixtures/simple.jsonl"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Bob"))
+        .stdout(predicate::str::contains("Alice").not());
+}
+
+#[test]
+fn test_level_filter() {
+    let input = r#"{"level": "INFO", "msg": "started"}
+{"level": "warning", "msg": "disk low"}
+{"level": "ERROR", "msg": "crashed"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--level", "warn+"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("disk low"))
+        .stdout(predicate::str::contains("crashed"))
+        .stdout(predicate::str::contains("started").not());
+}
+
+#[test]
+fn test_date_format() {
+    let input = r#"{"id": 1, "created_at": 1705314600, "seen": "2024-01-15T10:30:00Z"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--date-format", "%Y/%m/%d"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2024/01/15"))
+        .stdout(predicate::str::contains("1705314600").not());
+}
+
+#[test]
+fn test_date_format_invalid() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--date-format", "%Q"])
+        .write_stdin(r#"{"id": 1}"#)
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_tz_conversion() {
+    let input = r#"{"id": 1, "ts": "2024-01-15T10:30:00Z"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--tz", "Asia/Tokyo"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2024-01-15 19:30:00+09:00"));
+}
+
+#[test]
+fn test_epoch_unit_detection() {
+    let input = r#"{"id": 1, "ts": 1705314600}
+{"id": 2, "ts": 1705314600000}
+{"id": 3, "ts": 1705314600000000}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    let output = cmd
+        .args(["--date-format", "%Y-%m-%d", "--style", "plain"])
+        .write_stdin(input)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("2024-01-15").count(), 3);
+}
+
+#[test]
+fn test_relative_time() {
+    let input = r#"{"id": 1, "ts": "2001-01-01T00:00:00Z"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--relative-time", "ts"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("y ago"));
+}
+
+#[test]
+fn test_max_col_width() {
+    let input = r#"{"id": 1, "message": "this message is far too long to show"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--max-col-width", "12"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("this messag…"))
+        .stdout(predicate::str::contains("too long").not());
+}
+
+#[test]
+fn test_children_summary() {
+    let input = r#"{"id": 1, "orders": [{"price": 10}, {"price": 3}, {"price": 8}]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-r", "--children-summary"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("count"))
+        .stdout(predicate::str::contains("min(price)"))
+        .stdout(predicate::str::contains("max(price)"));
+}
+
+#[test]
+fn test_wrap_keeps_full_value() {
+    let input = r#"{"id": 1, "message": "alpha beta gamma delta epsilon zeta"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--max-col-width", "12", "--wrap"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("zeta"))
+        .stdout(predicate::str::contains("…").not());
+}
+
+#[test]
+fn test_child_limit() {
+    let input = r#"{"id": 1, "items": [{"n": 101}, {"n": 102}, {"n": 103}, {"n": 104}]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-r", "--child-limit", "2"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("102"))
+        .stdout(predicate::str::contains("103").not())
+        .stdout(predicate::str::contains("…and 2 more"));
+}
+
+#[test]
+fn test_child_limit_per_table_and_skip() {
+    let input =
+        r#"{"id": 1, "items": [{"n": 101}, {"n": 102}, {"n": 103}], "tags": ["t1", "t2", "t3"]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-r", "--child-limit", "2,tags=1", "--child-skip", "1"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("101").not())
+        .stdout(predicate::str::contains("103"))
+        .stdout(predicate::str::contains("t2"))
+        .stdout(predicate::str::contains("t3").not())
+        .stdout(predicate::str::contains("…and 1 more"));
+}
+
+#[test]
+fn test_parent_key() {
+    let input = r#"{"id": "ord-7", "items": [{"n": 101}]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-r", "--parent-key", "id"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("_parent_id"))
+        .stdout(predicate::str::contains("_parent_row").not())
+        .stdout(predicate::str::contains("ord-7"));
+}
+
+#[test]
+fn test_children_selects_child_tables() {
+    let input = r#"{"id": 1, "items": [{"n": 101}], "meta": {"blob": "x"}}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-r", "--children", "items"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("## items"))
+        .stdout(predicate::str::contains("## meta").not());
+}
+
+#[test]
+fn test_out_dir_writes_child_tables() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = r#"{"id": 7, "name": "a, b", "items": [{"n": 101}, {"n": 102}]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-r", "--parent-key", "id", "--out-dir"])
+        .arg(dir.path())
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    let root = std::fs::read_to_string(dir.path().join("_root.csv")).unwrap();
+    assert_eq!(root, "id,items,name\n7,[2 items],\"a, b\"\n");
+    let items = std::fs::read_to_string(dir.path().join("items.csv")).unwrap();
+    assert_eq!(items, "_parent_id,n\n7,101\n7,102\n");
+}
+
+#[test]
+fn test_out_dir_jsonl() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = r#"{"id": 1, "items": [{"n": 101}]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-r", "--output", "jsonl", "--out-dir"])
+        .arg(dir.path())
+        .write_stdin(input)
+        .assert()
+        .success();
+
+    let items = std::fs::read_to_string(dir.path().join("items.jsonl")).unwrap();
+    assert_eq!(items, "{\"_parent_row\":0,\"n\":101}\n");
+}
+
+#[test]
+fn test_output_csv_recursive() {
+    let input = r#"{"id": 1, "items": [{"n": 101}]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["-r", "--output", "csv"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("id,items\n1,[1 item]\n\n## items\n\n_parent_row,n\n0,101\n");
+}
+
+#[test]
+fn test_output_markdown() {
+    let input = r#"{"id": 1, "name": "a|b"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--output", "markdown"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("| id | name |\n| --- | --- |\n| 1 | a\\|b |\n");
+}
+
+#[test]
+fn test_output_json_compact() {
+    let input = "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2, \"name\": \"b\"}\n";
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--output", "json", "--compact"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("[{\"id\":1,\"name\":\"a\"},{\"id\":2,\"name\":\"b\"}]\n");
+}
+
+#[test]
+fn test_pretty() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--pretty", "-f", "age>30", "-c", "id,name"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout("-[ RECORD 1 ]------\n{\n  \"id\": 3,\n  \"name\": \"Charlie\"\n}\n");
+}
+
+#[test]
+fn test_nested_inline() {
+    let input = r#"{"id": 1, "address": {"city": "Tokyo", "zip": "100-0001"}}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--nested", "inline:20"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("{\"city\":\"Tokyo\",\"zi…"))
+        .stdout(predicate::str::contains("{...}").not());
+}
+
+#[test]
+fn test_null_and_bool_labels() {
+    let input = "{\"id\": 1, \"ok\": true, \"note\": null}\n{\"id\": 2, \"ok\": false}\n";
+    let output = Command::cargo_bin("jlcat")
+        .unwrap()
+        .args(["--null-label", "-", "--bool-labels", "yes,no"])
+        .write_stdin(input)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(stdout.contains("yes") && stdout.contains("no"));
+    assert_eq!(stdout.matches(" - ").count(), 2);
+    assert!(!stdout.contains("null") && !stdout.contains("true"));
+}
+
+#[test]
+fn test_no_header_and_header_case() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--style", "plain", "--no-header", "-c", "name"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Alice"))
+        .stdout(predicate::str::contains("name").not());
+
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--style", "plain", "--header-case", "upper", "-c", "name"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("NAME"));
+}
+
+#[test]
+fn test_row_numbers() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--row-numbers", "-s", "-age", "-c", "name"])
+        .args(["--output", "json", "--compact"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .success()
+        .stdout(
+            "[{\"#\":3,\"name\":\"Charlie\"},{\"#\":1,\"name\":\"Alice\"},{\"#\":2,\"name\":\"Bob\"}]\n",
+        );
+}
+
+#[test]
+fn test_rows_ranges() {
+    let output = Command::cargo_bin("jlcat")
+        .unwrap()
+        .args([
+            "--rows", "3,1", "-s", "age", "-c", "name", "--output", "csv",
+        ])
+        .arg("tests/fixtures/simple.jsonl")
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "name\nAlice\nCharlie\n"
+    );
+
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--rows", "2..1"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("Invalid row range"));
+}
+
+#[test]
+fn test_reverse() {
+    let output = Command::cargo_bin("jlcat")
+        .unwrap()
+        .args(["--reverse", "--tail", "2", "-c", "name", "--output", "csv"])
+        .arg("tests/fixtures/simple.jsonl")
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "name\nCharlie\nBob\n"
+    );
+}
+
+#[test]
+fn test_max_memory_reads_rows_from_disk() {
+    let run = |args: &[&str], stdin: Option<&str>| {
+        let mut cmd = Command::cargo_bin("jlcat").unwrap();
+        cmd.args(args);
+        match stdin {
+            Some(input) => cmd.write_stdin(input),
+            None => cmd.arg("tests/fixtures/simple.jsonl"),
+        };
+        String::from_utf8(cmd.output().unwrap().stdout).unwrap()
+    };
+    let input = std::fs::read_to_string("tests/fixtures/simple.jsonl").unwrap();
+
+    // 114 bytes of input are over a 100-byte budget, so rows are indexed on disk
+    let loaded = run(&["--tail", "2", "--output", "jsonl"], None);
+    assert!(loaded.contains("Charlie"));
+    let spilled = run(
+        &["--max-memory", "100", "--tail", "2", "--output", "jsonl"],
+        None,
+    );
+    assert_eq!(spilled, loaded);
+    let spilled = run(
+        &["--max-memory", "100", "--tail", "2", "--output", "jsonl"],
+        Some(&input),
+    );
+    assert_eq!(spilled, loaded);
+
+    let table = run(&["--max-memory", "100"], None);
+    for name in ["Alice", "Bob", "Charlie"] {
+        assert!(table.contains(name));
+    }
+
+    // Within budget nothing changes
+    let table = run(&["--max-memory", "1M"], Some(&input));
+    assert_eq!(table, run(&[], None));
+}
+
+#[test]
+fn test_max_memory_files_without_final_newline() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("a.jsonl");
+    let second = dir.path().join("b.jsonl");
+    std::fs::write(&first, "{\"id\": 1}\n{\"id\": 2}").unwrap();
+    std::fs::write(&second, "{\"id\": 3}\n").unwrap();
+    let run = |args: &[&str]| {
+        Command::cargo_bin("jlcat")
+            .unwrap()
+            .args(args)
+            .args(["--output", "jsonl"])
+            .arg(&first)
+            .arg(&second)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone()
+    };
+
+    let spilled = run(&["--max-memory", "1"]);
+    assert_eq!(
+        String::from_utf8(spilled.clone()).unwrap(),
+        "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n"
+    );
+    assert_eq!(spilled, run(&[]));
+}
+
+#[test]
+fn test_max_memory_rejects_stages_that_need_every_row() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--max-memory", "100", "--reverse"])
+        .arg("tests/fixtures/simple.jsonl")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("--max-memory 100"))
+        .stderr(predicate::str::contains("--reverse needs every row loaded"));
+}
+
+#[test]
+fn test_max_memory_sorts_on_disk() {
+    let run = |args: &[&str]| {
+        let output = Command::cargo_bin("jlcat")
+            .unwrap()
+            .args(args)
+            .arg("tests/fixtures/simple.jsonl")
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // One row per run at this budget, so every row goes through the merge
+    let sorted = run(&["--max-memory", "100", "-s", "-age", "--output", "jsonl"]);
+    assert_eq!(sorted, run(&["-s", "-age", "--output", "jsonl"]));
+    let names: Vec<&str> = sorted
+        .lines()
+        .map(|line| line.split("\"name\":").nth(1).unwrap())
+        .collect();
+    assert!(names[0].contains("Charlie") && names[2].contains("Bob"));
+}
+
+#[test]
+fn test_index_cache_sidecar() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("rows.jsonl");
+    std::fs::copy("tests/fixtures/simple.jsonl", &path).unwrap();
+    let run = || {
+        let output = Command::cargo_bin("jlcat")
+            .unwrap()
+            .args(["--max-memory", "100", "--index-cache", "--output", "jsonl"])
+            .arg(&path)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let first = run();
+    assert!(dir.path().join("rows.jsonl.jlcatidx").exists());
+    assert_eq!(run(), first);
+    assert_eq!(first.lines().count(), 3);
+
+    // A changed file is indexed again
+    std::fs::write(&path, "{\"id\": 9}\n{\"id\": 8}\n{\"id\": 7}\n").unwrap();
+    assert_eq!(run(), "{\"id\":9}\n{\"id\":8}\n{\"id\":7}\n");
+}
+
+#[test]
+fn test_hints() {
+    let input = r#"{"id": 1, "orders": [{"sku": "a"}]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.arg("--hints")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "orders: [1 item] (try: -c 'orders' -r)",
+        ));
+}
+
+#[test]
+fn test_elide_middle() {
+    let input = r#"{"id": 1, "token": "abcdefghijklmnopqrstuvwxyz"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--max-col-width", "9", "--elide", "middle"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("abcd…wxyz"));
+}
+
+#[test]
+fn test_auto_layout_flattens_shallow_nesting() {
+    let input = r#"{"id": 1, "user": {"name": "Alice", "age": 30}}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.arg("--auto-layout")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("user.name"))
+        .stdout(predicate::str::contains("Alice"));
+}
+
+#[test]
+fn test_color_always_warning() {
+    let input = "{\"id\": 1}\nnot json\n";
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--lenient", "--color", "always"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("\x1b[33mwarning\x1b[0m"));
+}
+
+#[test]
+fn test_color_auto_respects_no_color() {
+    let input = "{\"id\": 1}\nnot json\n";
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.arg("--lenient")
+        .env("NO_COLOR", "1")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("jlcat: warning: line 2"));
+}
+
+#[test]
+fn test_max_errors_aborts() {
+    let input = "{\"id\": 1}\nnot json\n[1]\n{\"id\": 2}\n";
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--lenient", "--max-errors", "1"])
+        .write_stdin(input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--max-errors 1"))
+        .stderr(predicate::str::contains("1 invalid JSON, 1 non-object"));
+}
+
+#[test]
+fn test_rejects_file() {
+    let rejects = tempfile::NamedTempFile::new().unwrap();
+    let input = "{\"id\": 1}\nnot json\n";
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--lenient", "--rejects"])
+        .arg(rejects.path())
+        .write_stdin(input)
+        .assert()
+        .success();
+
+    let written = std::fs::read_to_string(rejects.path()).unwrap();
+    assert!(written.starts_with("{\"error\":"));
+    assert!(written.contains("\"line\":2"));
+    assert!(written.contains("\"text\":\"not json\""));
+}
+
+#[test]
+fn test_lenient_skip_summary() {
+    let input = "{\"id\": 1}\nnot json\nbad\n42\n";
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.arg("--lenient")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stderr(predicate::str::ends_with(
+            "jlcat: 3 lines skipped (2 invalid JSON, 1 non-object)\n",
+        ));
+}
+
+#[test]
+fn test_quiet_suppresses_line_warnings() {
+    let input = "{\"id\": 1}\nnot json\n";
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--lenient", "--quiet"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stderr("jlcat: 1 line skipped (1 invalid JSON)\n");
+}
+
+#[test]
+fn test_exit_code_parse_error() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.write_stdin("not json\n")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::starts_with(
+            "jlcat: error: JSON parse error",
+        ));
+}
+
+#[test]
+fn test_exit_code_io_error() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.arg("tests/fixtures/does-not-exist.jsonl")
+        .assert()
+        .code(4);
+}
+
+#[test]
+fn test_exit_code_usage_error() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--no-such-flag"]).assert().code(2);
+}
+
+#[test]
+fn test_estimate_counts_small_file() {
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--estimate", "tests/fixtures/simple.jsonl"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("3 rows ("))
+        .stdout(predicate::str::contains("Alice").not());
+}
+
+#[test]
+fn test_transpose() {
+    let input = r#"{"id": 1, "name": "Alice"}
+{"id": 2, "name": "Bob"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--transpose", "--style", "jira"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("|| field || 1 || 2 ||"))
+        .stdout(predicate::str::contains("| name | Alice | Bob |"));
+}
+
+#[test]
+fn test_vertical() {
+    let input = r#"{"id": 1, "name": "Alice"}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.arg("--vertical")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-[ RECORD 1 ]"))
+        .stdout(predicate::str::contains("name | Alice"));
+}
+
+#[test]
+fn test_flat_arrays_index() {
+    let input = r#"{"tags": ["a", "b"]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--flat", "--flat-arrays", "index", "--style", "jira"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("|| tags[0] || tags[1] ||"))
+        .stdout(predicate::str::contains("| a | b |"));
+}
+
+#[test]
+fn test_explode_with_flat() {
+    let input = r#"{"id": 1, "orders": [{"sku": "A"}, {"sku": "B"}]}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args([
+        "--explode",
+        "orders",
+        "--flat",
+        "--style",
+        "jira",
+        "--sort=-orders.sku",
+    ])
+    .write_stdin(input)
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(
+        "|| id || orders.sku ||\n| 1 | B |\n| 1 | A |",
+    ));
+}
+
+#[test]
+fn test_flat_column_selection() {
+    let input = r#"{"id": 1, "user": {"name": "Alice", "age": 30}}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args(["--flat", "-c", "user.name,id", "--style", "jira"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("|| user.name || id ||"))
+        .stdout(predicate::str::contains("user.age").not());
+}
+
+#[test]
+fn test_flat_sort_by_flattened_column() {
+    let input = r#"{"id": 1, "user": {"profile.age": 40}}
+{"id": 2, "user": {"profile.age": 20}}
+{"id": 3, "user": {"profile.age": 30}}"#;
+    let mut cmd = Command::cargo_bin("jlcat").unwrap();
+    cmd.args([
+        "--flat",
+        "-s",
+        "user.profile.age",
+        "-c",
+        "id",
+        "--style",
+        "jira",
+    ])
+    .write_stdin(input)
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("|| id ||\n| 2 |\n| 3 |\n| 1 |"));
+}
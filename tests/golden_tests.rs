@@ -76,10 +76,10 @@ fn golden_nested_jsonl() {
 
     let stdout = String::from_utf8_lossy(&output.stdout);
 
-    // Nested objects should show as {...}
+    // Nested objects should show their key count
     assert!(
-        stdout.contains("{...}"),
-        "Nested objects should show as {{...}}"
+        stdout.contains("{2 keys}"),
+        "Nested objects should show as {{2 keys}}"
     );
     assert!(stdout.contains("id"), "Should contain 'id' column");
     assert!(stdout.contains("user"), "Should contain 'user' column");
@@ -243,8 +243,8 @@ fn golden_recursive_expansion() {
 
     // Should show parent table with placeholder
     assert!(
-        stdout.contains("[...]"),
-        "Should show [...] placeholder for array"
+        stdout.contains("[2 items]"),
+        "Should show [2 items] placeholder for array"
     );
 
     // Should show child table header
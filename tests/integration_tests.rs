@@ -137,7 +137,7 @@ fn test_recursive_nested_object() {
         .write_stdin(input)
         .assert()
         .success()
-        .stdout(predicate::str::contains("{...}"))
+        .stdout(predicate::str::contains("{1 key}"))
         .stdout(predicate::str::contains("## address"))
         .stdout(predicate::str::contains("Tokyo"))
         .stdout(predicate::str::contains("_parent_row"));
@@ -151,7 +151,7 @@ fn test_recursive_array() {
         .write_stdin(input)
         .assert()
         .success()
-        .stdout(predicate::str::contains("[...]"))
+        .stdout(predicate::str::contains("[2 items]"))
         .stdout(predicate::str::contains("## items"))
         .stdout(predicate::str::contains("A"))
         .stdout(predicate::str::contains("B"));
@@ -296,7 +296,7 @@ mod flat_mode_tests {
             .assert()
             .success()
             .stdout(predicate::str::contains("a.b.c"))
-            .stdout(predicate::str::contains("{...}"));
+            .stdout(predicate::str::contains("{1 key}"));
     }
 
     #[test]